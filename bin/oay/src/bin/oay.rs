@@ -20,6 +20,8 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use anyhow::Result;
+use futures::future::BoxFuture;
+use oay::services::HttpService;
 use oay::services::S3Service;
 use oay::services::WebdavService;
 use oay::Config;
@@ -32,11 +34,11 @@ use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let _ = s3().await;
+    let _ = serve().await;
     webdav().await
 }
 
-async fn s3() -> Result<()> {
+async fn serve() -> Result<()> {
     tracing_subscriber::registry()
         .with(fmt::layer().pretty())
         .with(EnvFilter::from_default_env())
@@ -47,9 +49,18 @@ async fn s3() -> Result<()> {
     let scheme = Scheme::from_str(&cfg.backend.typ).context("unsupported scheme")?;
     let op = Operator::via_map(scheme, cfg.backend.map.clone())?;
 
-    let s3 = S3Service::new(Arc::new(cfg), op);
+    let cfg = Arc::new(cfg);
+    let s3 = S3Service::new(cfg.clone(), op.clone());
+    let http = HttpService::new(cfg.clone(), op);
 
-    s3.serve().await?;
+    let mut frontends: Vec<BoxFuture<'_, Result<()>>> = Vec::new();
+    if cfg.frontends.s3.enable {
+        frontends.push(Box::pin(s3.serve()));
+    }
+    if cfg.frontends.http.enable {
+        frontends.push(Box::pin(http.serve()));
+    }
+    futures::future::try_join_all(frontends).await?;
 
     Ok(())
 }
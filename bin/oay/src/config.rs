@@ -38,6 +38,8 @@ pub struct BackendConfig {
 pub struct FrontendsConfig {
     pub s3: S3Config,
     pub webdav: WebdavConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -51,3 +53,15 @@ pub struct WebdavConfig {
     pub enable: bool,
     pub addr: String,
 }
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub enable: bool,
+    pub addr: String,
+    /// Allow uploading files via `PUT`.
+    pub upload: bool,
+    /// Enable basic auth if set.
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
@@ -0,0 +1,671 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use axum::body::boxed;
+use axum::body::Empty;
+use axum::body::StreamBody;
+use axum::extract::BodyStream;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::header;
+use axum::http::HeaderMap;
+use axum::http::Method;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::Uri;
+use axum::middleware;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Redirect;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use opendal::ErrorKind;
+use opendal::Metadata;
+use opendal::Metakey;
+use opendal::Operator;
+use percent_encoding::percent_decode_str;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::AsciiSet;
+use percent_encoding::CONTROLS;
+use serde::Deserialize;
+use serde::Serialize;
+use tower::ServiceBuilder;
+use tower_http::trace::TraceLayer;
+
+use crate::Config;
+
+/// Characters to be encoded in the links of dir listing.
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// HttpService serves the operator as a static file server.
+///
+/// - `GET` and `HEAD` on files support `Range`, `If-None-Match` and `If-Modified-Since`.
+/// - `GET` on dirs (paths end with `/`) returns the listing in HTML, or in JSON
+///   with `?format=json` or `Accept: application/json`.
+/// - `PUT` uploads files if `upload` is enabled.
+pub struct HttpService {
+    cfg: Arc<Config>,
+    op: Operator,
+}
+
+impl HttpService {
+    pub fn new(cfg: Arc<Config>, op: Operator) -> Self {
+        Self { cfg, op }
+    }
+
+    pub async fn serve(&self) -> anyhow::Result<()> {
+        let http_cfg = &self.cfg.frontends.http;
+
+        let state = HttpState {
+            op: self.op.clone(),
+            upload: http_cfg.upload,
+            authorization: http_cfg.username.as_ref().map(|username| {
+                let password = http_cfg.password.as_deref().unwrap_or_default();
+                let credential = STANDARD.encode(format!("{username}:{password}"));
+                Arc::from(format!("Basic {credential}"))
+            }),
+        };
+
+        axum::Server::bind(&http_cfg.addr.parse()?)
+            .serve(router(state).into_make_service())
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn router(state: HttpState) -> Router {
+    let handler = get(handle_get).head(handle_get).put(handle_put);
+    Router::new()
+        .route("/", handler.clone())
+        .route("/*path", handler)
+        .layer(middleware::from_fn_with_state(state.clone(), basic_auth))
+        .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
+        .with_state(state)
+}
+
+#[derive(Clone)]
+pub struct HttpState {
+    op: Operator,
+    upload: bool,
+    /// The expected `Authorization` header, `None` means auth is disabled.
+    authorization: Option<Arc<str>>,
+}
+
+async fn basic_auth<B>(State(state): State<HttpState>, req: Request<B>, next: Next<B>) -> Response {
+    if let Some(expected) = &state.authorization {
+        let actual = req.headers().get(header::AUTHORIZATION);
+        if !actual.map_or(false, |v| {
+            constant_time_eq(v.as_bytes(), expected.as_bytes())
+        }) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Basic realm=\"oay\"")],
+            )
+                .into_response();
+        }
+    }
+
+    next.run(req).await
+}
+
+/// Compare the credentials without returning early on the first mismatched byte,
+/// so that the expected value can't be guessed from the response time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct GetParams {
+    format: Option<String>,
+}
+
+async fn handle_get(
+    State(state): State<HttpState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<GetParams>,
+) -> Result<Response, HttpError> {
+    let path = request_path(&uri)?;
+    if path.is_empty() || path.ends_with('/') {
+        let json = params.format.as_deref() == Some("json")
+            || headers
+                .get(header::ACCEPT)
+                .and_then(|v| v.to_str().ok())
+                .map_or(false, |v| v.contains("application/json"));
+        return list(&state.op, &path, json).await;
+    }
+
+    let meta = state.op.stat(&path).await?;
+    if meta.is_dir() {
+        return Ok(Redirect::permanent(&format!("/{path}/")).into_response());
+    }
+
+    let mut builder = Response::builder()
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(
+            header::CONTENT_TYPE,
+            meta.content_type().unwrap_or("application/octet-stream"),
+        );
+    if let Some(etag) = meta.etag() {
+        builder = builder.header(header::ETAG, format_etag(etag));
+    }
+    if let Some(last_modified) = meta.last_modified() {
+        builder = builder.header(
+            header::LAST_MODIFIED,
+            httpdate::fmt_http_date(SystemTime::from(last_modified)),
+        );
+    }
+
+    if is_not_modified(&headers, &meta) {
+        return Ok(builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(boxed(Empty::new()))?);
+    }
+
+    let size = meta.content_length();
+    let range = match headers.get(header::RANGE).and_then(|v| v.to_str().ok()) {
+        Some(v) => parse_range(v, size),
+        None => ParsedRange::Full,
+    };
+    let (status, start, end) = match range {
+        ParsedRange::Full => (StatusCode::OK, 0, size),
+        ParsedRange::Partial(start, end) => {
+            builder = builder.header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{}/{size}", end - 1),
+            );
+            (StatusCode::PARTIAL_CONTENT, start, end)
+        }
+        ParsedRange::Unsatisfiable => {
+            return Ok(builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{size}"))
+                .body(boxed(Empty::new()))?);
+        }
+    };
+
+    let builder = builder
+        .status(status)
+        .header(header::CONTENT_LENGTH, end - start);
+    if method == Method::HEAD || start == end {
+        return Ok(builder.body(boxed(Empty::new()))?);
+    }
+
+    let reader = state.op.reader_with(&path).range(start..end).await?;
+    Ok(builder.body(boxed(StreamBody::new(reader)))?)
+}
+
+async fn handle_put(
+    State(state): State<HttpState>,
+    uri: Uri,
+    mut body: BodyStream,
+) -> Result<Response, HttpError> {
+    if !state.upload {
+        return Err(HttpError::new(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "upload is not enabled",
+        ));
+    }
+
+    let path = request_path(&uri)?;
+    if path.is_empty() {
+        return Err(HttpError::new(StatusCode::BAD_REQUEST, "path is required"));
+    }
+    if path.ends_with('/') {
+        state.op.create_dir(&path).await?;
+        return Ok(StatusCode::CREATED.into_response());
+    }
+
+    let mut writer = state.op.writer(&path).await?;
+    while let Some(bs) = body.next().await {
+        match bs {
+            Ok(bs) => writer.write(bs).await?,
+            Err(err) => {
+                writer.abort().await?;
+                return Err(HttpError::new(StatusCode::BAD_REQUEST, err.to_string()));
+            }
+        }
+    }
+    writer.close().await?;
+
+    Ok(StatusCode::CREATED.into_response())
+}
+
+#[derive(Serialize)]
+struct ListEntry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    size: u64,
+    last_modified: Option<String>,
+}
+
+async fn list(op: &Operator, path: &str, json: bool) -> Result<Response, HttpError> {
+    let mut entries: Vec<ListEntry> = op
+        .lister_with(path)
+        .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
+        .await?
+        .try_filter(|de| futures::future::ready(de.path() != path))
+        .map_ok(|de| {
+            let meta = de.metadata();
+            ListEntry {
+                name: de.name().to_string(),
+                path: format!("/{}", de.path()),
+                is_dir: meta.is_dir(),
+                size: meta.content_length(),
+                last_modified: meta
+                    .last_modified()
+                    .map(|v| v.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            }
+        })
+        .try_collect()
+        .await?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if json {
+        let body = serde_json::to_vec(&entries)
+            .map_err(|err| HttpError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+        return Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response());
+    }
+
+    let title = escape_html(&format!("/{path}"));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Index of {title}</title></head>\n<body>\n<h1>Index of {title}</h1>\n<table>\n"
+    );
+    if !path.is_empty() {
+        html.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>\n");
+    }
+    for entry in &entries {
+        let size = if entry.is_dir {
+            "-".to_string()
+        } else {
+            entry.size.to_string()
+        };
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            utf8_percent_encode(&entry.path, PATH_ENCODE_SET),
+            escape_html(&entry.name),
+            size,
+            entry.last_modified.as_deref().unwrap_or("-"),
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    Ok(([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response())
+}
+
+/// Get the decoded operator path from request uri.
+///
+/// Paths with `.` or `..` segments are rejected so that requests can't escape
+/// the root of the operator.
+fn request_path(uri: &Uri) -> Result<String, HttpError> {
+    let path = percent_decode_str(uri.path())
+        .decode_utf8()
+        .map_err(|err| HttpError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+    if path.split('/').any(|seg| seg == "." || seg == "..") {
+        return Err(HttpError::new(
+            StatusCode::BAD_REQUEST,
+            "path must not contain `.` or `..` segments",
+        ));
+    }
+    Ok(path.trim_start_matches('/').to_string())
+}
+
+/// Check `If-None-Match` and `If-Modified-Since`, `If-Modified-Since` will be
+/// ignored if `If-None-Match` exists.
+fn is_not_modified(headers: &HeaderMap, meta: &Metadata) -> bool {
+    if let Some(v) = headers.get(header::IF_NONE_MATCH) {
+        let (Ok(v), Some(etag)) = (v.to_str(), meta.etag()) else {
+            return false;
+        };
+        return v
+            .split(',')
+            .map(str::trim)
+            .any(|v| v == "*" || normalize_etag(v) == normalize_etag(etag));
+    }
+
+    if let Some(v) = headers.get(header::IF_MODIFIED_SINCE) {
+        let since = v
+            .to_str()
+            .ok()
+            .and_then(|v| httpdate::parse_http_date(v).ok());
+        let (Some(since), Some(last_modified)) = (since, meta.last_modified()) else {
+            return false;
+        };
+        // HTTP date only has the precision of seconds.
+        return last_modified.timestamp() <= DateTime::<Utc>::from(since).timestamp();
+    }
+
+    false
+}
+
+fn normalize_etag(etag: &str) -> &str {
+    etag.trim_start_matches("W/").trim_matches('"')
+}
+
+fn format_etag(etag: &str) -> String {
+    if etag.ends_with('"') {
+        etag.to_string()
+    } else {
+        format!("\"{etag}\"")
+    }
+}
+
+/// ParsedRange is the result of parsing `Range` header, the end of range is exclusive.
+#[derive(Debug, PartialEq, Eq)]
+enum ParsedRange {
+    Full,
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Parse the `Range` header, invalid or multiple ranges will be ignored and
+/// the full content will be returned as allowed by RFC 9110.
+fn parse_range(value: &str, size: u64) -> ParsedRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ParsedRange::Full;
+    };
+    if spec.contains(',') {
+        return ParsedRange::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return ParsedRange::Full;
+    };
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", "") => return ParsedRange::Full,
+        // Suffix range like `bytes=-100`.
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return ParsedRange::Unsatisfiable,
+            Ok(n) => (size.saturating_sub(n), size),
+            Err(_) => return ParsedRange::Full,
+        },
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) => (start, size),
+            Err(_) => return ParsedRange::Full,
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, (end + 1).min(size)),
+            _ => return ParsedRange::Full,
+        },
+    };
+
+    if start >= size {
+        ParsedRange::Unsatisfiable
+    } else {
+        ParsedRange::Partial(start, end)
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+struct HttpError {
+    code: StatusCode,
+    message: String,
+}
+
+impl HttpError {
+    fn new(code: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        (self.code, self.message).into_response()
+    }
+}
+
+impl From<opendal::Error> for HttpError {
+    fn from(err: opendal::Error) -> Self {
+        let code = match err.kind() {
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+            ErrorKind::ConditionNotMatch => StatusCode::PRECONDITION_FAILED,
+            ErrorKind::Unsupported => StatusCode::NOT_IMPLEMENTED,
+            ErrorKind::IsADirectory | ErrorKind::NotADirectory => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        HttpError::new(code, err.to_string())
+    }
+}
+
+impl From<axum::http::Error> for HttpError {
+    fn from(err: axum::http::Error) -> Self {
+        HttpError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::body::HttpBody;
+    use opendal::services::Memory;
+    use opendal::EntryMode;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn new_state(authorization: Option<&str>) -> HttpState {
+        HttpState {
+            op: Operator::new(Memory::default()).unwrap().finish(),
+            upload: true,
+            authorization: authorization.map(Arc::from),
+        }
+    }
+
+    async fn send(state: &HttpState, req: Request<Body>) -> Response {
+        router(state.clone()).oneshot(req).await.unwrap()
+    }
+
+    async fn read_body(resp: Response) -> Vec<u8> {
+        let mut body = resp.into_body();
+        let mut buf = Vec::new();
+        while let Some(bs) = body.data().await {
+            buf.extend_from_slice(&bs.unwrap());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let cases = [
+            ("bytes=0-4", ParsedRange::Partial(0, 5)),
+            ("bytes=5-", ParsedRange::Partial(5, 10)),
+            ("bytes=-3", ParsedRange::Partial(7, 10)),
+            ("bytes=-20", ParsedRange::Partial(0, 10)),
+            ("bytes=8-20", ParsedRange::Partial(8, 10)),
+            ("bytes=10-", ParsedRange::Unsatisfiable),
+            ("bytes=-0", ParsedRange::Unsatisfiable),
+            ("bytes=4-2", ParsedRange::Full),
+            ("bytes=0-1,3-4", ParsedRange::Full),
+            ("bytes=abc", ParsedRange::Full),
+            ("items=0-4", ParsedRange::Full),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_range(value, 10), expected, "range: {value}");
+        }
+    }
+
+    #[test]
+    fn test_is_not_modified() {
+        let last_modified = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 +0000")
+            .unwrap()
+            .with_timezone(&Utc);
+        let meta = Metadata::new(EntryMode::FILE)
+            .with_etag("\"abc\"".to_string())
+            .with_last_modified(last_modified);
+
+        let cases = [
+            (header::IF_NONE_MATCH, "\"abc\"", true),
+            (header::IF_NONE_MATCH, "W/\"abc\"", true),
+            (header::IF_NONE_MATCH, "\"xyz\", \"abc\"", true),
+            (header::IF_NONE_MATCH, "*", true),
+            (header::IF_NONE_MATCH, "\"xyz\"", false),
+            (
+                header::IF_MODIFIED_SINCE,
+                "Wed, 21 Oct 2015 07:28:00 GMT",
+                true,
+            ),
+            (
+                header::IF_MODIFIED_SINCE,
+                "Wed, 21 Oct 2015 07:27:59 GMT",
+                false,
+            ),
+            (header::IF_MODIFIED_SINCE, "invalid", false),
+        ];
+        for (name, value, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(name.clone(), value.parse().unwrap());
+            assert_eq!(
+                is_not_modified(&headers, &meta),
+                expected,
+                "{name}: {value}"
+            );
+        }
+
+        // `If-Modified-Since` is ignored if `If-None-Match` exists.
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, "\"xyz\"".parse().unwrap());
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert!(!is_not_modified(&headers, &meta));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_range() {
+        let state = new_state(None);
+        state.op.write("file", "0123456789").await.unwrap();
+
+        let req = Request::get("/file")
+            .header(header::RANGE, "bytes=2-5")
+            .body(Body::empty())
+            .unwrap();
+        let resp = send(&state, req).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(resp.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(read_body(resp).await, b"2345");
+
+        let req = Request::get("/file")
+            .header(header::RANGE, "bytes=10-")
+            .body(Body::empty())
+            .unwrap();
+        let resp = send(&state, req).await;
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(resp.headers()[header::CONTENT_RANGE], "bytes */10");
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth() {
+        let expected = format!("Basic {}", STANDARD.encode("user:pass"));
+        let state = new_state(Some(&expected));
+
+        let resp = send(&state, Request::get("/").body(Body::empty()).unwrap()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().contains_key(header::WWW_AUTHENTICATE));
+
+        let wrong = format!("Basic {}", STANDARD.encode("user:word"));
+        let req = Request::get("/")
+            .header(header::AUTHORIZATION, wrong)
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&state, req).await.status(), StatusCode::UNAUTHORIZED);
+
+        let req = Request::get("/")
+            .header(header::AUTHORIZATION, expected)
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&state, req).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_path_traversal() {
+        let state = new_state(None);
+        state.op.write("file", "hello").await.unwrap();
+
+        for uri in [
+            "/dir/../file",
+            "/dir/%2e%2e/file",
+            "/./file",
+            "/dir/%2E/file",
+        ] {
+            let req = Request::get(uri).body(Body::empty()).unwrap();
+            assert_eq!(
+                send(&state, req).await.status(),
+                StatusCode::BAD_REQUEST,
+                "get: {uri}"
+            );
+            let req = Request::put(uri).body(Body::from("evil")).unwrap();
+            assert_eq!(
+                send(&state, req).await.status(),
+                StatusCode::BAD_REQUEST,
+                "put: {uri}"
+            );
+        }
+        assert_eq!(state.op.read("file").await.unwrap(), b"hello");
+
+        // Names that only contain dots are still allowed.
+        let req = Request::get("/...").body(Body::empty()).unwrap();
+        assert_eq!(send(&state, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"Basic abc", b"Basic abc"));
+        assert!(!constant_time_eq(b"Basic abc", b"Basic abd"));
+        assert!(!constant_time_eq(b"Basic abc", b"Basic ab"));
+    }
+}
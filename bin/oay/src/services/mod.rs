@@ -15,6 +15,11 @@
 // specific language governing permissions and limitations
 // under the License.
 
+#[cfg(feature = "frontends-http")]
+mod http;
+#[cfg(feature = "frontends-http")]
+pub use http::HttpService;
+
 #[cfg(feature = "frontends-s3")]
 mod s3;
 #[cfg(feature = "frontends-s3")]
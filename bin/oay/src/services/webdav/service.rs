@@ -22,6 +22,7 @@ use axum::body::Body;
 use axum::http::Request;
 use axum::routing::any_service;
 use axum::Router;
+use dav_server::memls::MemLs;
 use dav_server::DavHandler;
 use dav_server_opendalfs::OpendalFs;
 use opendal::Operator;
@@ -46,6 +47,7 @@ impl WebdavService {
 
         let webdav_handler = DavHandler::builder()
             .filesystem(self.opendalfs.clone())
+            .locksystem(MemLs::new())
             .build_handler();
 
         let webdav_service = tower::service_fn(move |req: Request<Body>| {
@@ -17,6 +17,7 @@
 
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use anyhow::Context;
@@ -47,6 +48,10 @@ struct Config {
     /// example: fs://root=/tmp
     #[arg(env = "OFS_BACKEND", index = 2)]
     backend: String,
+
+    /// seconds that file attributes can be cached, 0 to disable attribute caching
+    #[arg(long, env = "OFS_ATTR_TTL", default_value_t = 1)]
+    attr_ttl: u64,
 }
 
 async fn fuse() -> Result<()> {
@@ -71,7 +76,7 @@ async fn fuse() -> Result<()> {
     mount_option.uid(nix::unistd::getuid().into());
     mount_option.gid(nix::unistd::getgid().into());
 
-    let ofs = Ofs { op };
+    let ofs = Ofs::new(op).with_attr_ttl(Duration::from_secs(cfg.attr_ttl));
 
     let mounthandle = Session::new(mount_option)
         .mount_with_unprivileged(ofs, cfg.mount_path)
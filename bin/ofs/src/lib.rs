@@ -15,23 +15,472 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use std::vec::IntoIter;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use fuse3::path::prelude::*;
+use fuse3::Errno;
 use fuse3::Result;
-use futures_util::stream::Empty;
+use futures_util::stream;
 use futures_util::stream::Iter;
+use futures_util::TryStreamExt;
+use opendal::ErrorKind;
+use opendal::Metadata;
 use opendal::Operator;
+use opendal::Reader;
+use opendal::Writer;
+use tokio::io::AsyncReadExt;
 
+/// Ofs is a fuse filesystem backed by an [`Operator`].
+///
+/// fuse3's path based session maintains the inode table for us, every
+/// callback will be called with the path of the inode, which will be
+/// mapped to OpenDAL's path:
+///
+/// - `/` is mapped to `/`
+/// - `/path/to/dir` is mapped to `path/to/dir/`
+/// - `/path/to/file` is mapped to `path/to/file`
+///
+/// Dirs are listed while they are opened, `readdir` pages through the
+/// listed entries.
+///
+/// Files are read via ranged [`Reader`]. Sequential writes from the start of
+/// an empty or truncated file are streamed into a [`Writer`]. Other writes
+/// will load the whole file into memory and flush it back while the file is
+/// flushed or released.
 pub struct Ofs {
-    pub op: Operator,
+    op: Operator,
+
+    uid: u32,
+    gid: u32,
+    attr_ttl: Duration,
+    attr_cache: Mutex<HashMap<String, (Instant, FileAttr)>>,
+
+    next_fh: AtomicU64,
+    opened_files: Mutex<HashMap<u64, Arc<tokio::sync::Mutex<OpenedFile>>>>,
+    opened_dirs: Mutex<HashMap<u64, Arc<Vec<(FileType, OsString)>>>>,
+}
+
+/// The max count of cached attributes, expired ones will be evicted while
+/// the cache is full.
+const MAX_ATTR_CACHE_ENTRIES: usize = 4096;
+
+/// The size of chunk used to copy content while truncating files.
+const TRUNCATE_CHUNK_SIZE: usize = 256 * 1024;
+
+/// OpenedFile is the state of a file handle returned by `open`.
+struct OpenedFile {
+    path: String,
+    read: bool,
+    write: bool,
+
+    /// Reader and its current position, will be created at the first read.
+    reader: Option<(Reader, u64)>,
+    /// Writer and the count of bytes written, used for sequential writes.
+    writer: Option<(Writer, u64)>,
+    /// Content of the file for random writes, will be flushed while releasing.
+    buffer: Option<Vec<u8>>,
+    /// The content of file is known to be empty, so we can start writing
+    /// without loading it.
+    truncated: bool,
+    /// The buffer or truncation has not been written back.
+    dirty: bool,
+}
+
+impl Ofs {
+    /// Create a new ofs on given operator.
+    pub fn new(op: Operator) -> Self {
+        Self {
+            op,
+
+            uid: nix::unistd::getuid().as_raw(),
+            gid: nix::unistd::getgid().as_raw(),
+            attr_ttl: Duration::from_secs(1),
+            attr_cache: Mutex::default(),
+
+            next_fh: AtomicU64::new(1),
+            opened_files: Mutex::default(),
+            opened_dirs: Mutex::default(),
+        }
+    }
+
+    /// Set the uid of all files.
+    ///
+    /// Default to current user's uid.
+    pub fn with_uid(mut self, uid: u32) -> Self {
+        self.uid = uid;
+        self
+    }
+
+    /// Set the gid of all files.
+    ///
+    /// Default to current user's gid.
+    pub fn with_gid(mut self, gid: u32) -> Self {
+        self.gid = gid;
+        self
+    }
+
+    /// Set how long the attributes of files can be cached by both ofs and kernel.
+    ///
+    /// Default to 1s. Set to `Duration::ZERO` to disable attribute caching.
+    pub fn with_attr_ttl(mut self, ttl: Duration) -> Self {
+        self.attr_ttl = ttl;
+        self
+    }
+
+    fn new_attr(&self, kind: FileType, size: u64, mtime: SystemTime) -> FileAttr {
+        let (perm, nlink) = match kind {
+            FileType::Directory => (0o755, 2),
+            _ => (0o644, 1),
+        };
+
+        FileAttr {
+            size,
+            blocks: (size + 511) / 512,
+            atime: mtime,
+            mtime,
+            ctime: mtime,
+            #[cfg(target_os = "macos")]
+            crtime: mtime,
+            kind,
+            perm,
+            nlink,
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            #[cfg(target_os = "macos")]
+            flags: 0,
+            blksize: 4096,
+        }
+    }
+
+    fn metadata_to_attr(&self, meta: &Metadata) -> FileAttr {
+        let mtime = meta
+            .last_modified()
+            .map(SystemTime::from)
+            .unwrap_or(UNIX_EPOCH);
+
+        if meta.is_dir() {
+            self.new_attr(FileType::Directory, 0, mtime)
+        } else {
+            self.new_attr(FileType::RegularFile, meta.content_length(), mtime)
+        }
+    }
+
+    fn get_cached_attr(&self, path: &str) -> Option<FileAttr> {
+        if self.attr_ttl.is_zero() {
+            return None;
+        }
+
+        let mut cache = self.attr_cache.lock().unwrap();
+        match cache.get(path) {
+            Some((t, attr)) if t.elapsed() < self.attr_ttl => Some(*attr),
+            Some(_) => {
+                cache.remove(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn set_cached_attr(&self, path: &str, attr: FileAttr) {
+        if self.attr_ttl.is_zero() {
+            return;
+        }
+
+        let mut cache = self.attr_cache.lock().unwrap();
+        if cache.len() >= MAX_ATTR_CACHE_ENTRIES && !cache.contains_key(path) {
+            cache.retain(|_, (t, _)| t.elapsed() < self.attr_ttl);
+        }
+        // Evict the oldest one if all cached attributes are still valid.
+        if cache.len() >= MAX_ATTR_CACHE_ENTRIES && !cache.contains_key(path) {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, (t, _))| *t)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(path.to_string(), (Instant::now(), attr));
+    }
+
+    fn invalidate_cached_attr(&self, path: &str) {
+        let mut cache = self.attr_cache.lock().unwrap();
+        cache.remove(path);
+    }
+
+    /// List the entries of given fuse dir, including `.` and `..`.
+    async fn list_dir(&self, path: &OsStr) -> Result<Vec<(FileType, OsString)>> {
+        let dir = dir_path(path);
+        let mut entries = vec![
+            (FileType::Directory, OsString::from(".")),
+            (FileType::Directory, OsString::from("..")),
+        ];
+
+        let mut lister = self.op.lister(&dir).await.map_err(format_opendal_error)?;
+        while let Some(entry) = lister.try_next().await.map_err(format_opendal_error)? {
+            // Some services will return the dir itself.
+            if entry.path() == dir {
+                continue;
+            }
+
+            let kind = if entry.metadata().is_dir() {
+                FileType::Directory
+            } else {
+                FileType::RegularFile
+            };
+            entries.push((kind, entry.name().trim_end_matches('/').into()));
+        }
+
+        Ok(entries)
+    }
+
+    /// Truncate the file at given path to the given size.
+    ///
+    /// The kept content is streamed into a temporary file which will be
+    /// renamed to the path later. Services that can't rename or copy files
+    /// will load the content into memory instead.
+    async fn truncate(&self, path: &str, size: u64) -> Result<()> {
+        if size == 0 {
+            return self
+                .op
+                .write(path, Bytes::new())
+                .await
+                .map_err(format_opendal_error);
+        }
+
+        let len = self
+            .op
+            .stat(path)
+            .await
+            .map_err(format_opendal_error)?
+            .content_length();
+        if len == size {
+            return Ok(());
+        }
+
+        let capability = self.op.info().full_capability();
+        if !capability.rename && !capability.copy {
+            let mut content = self
+                .op
+                .read_with(path)
+                .range(0..size.min(len))
+                .await
+                .map_err(format_opendal_error)?;
+            content.resize(size as usize, 0);
+            return self
+                .op
+                .write(path, content)
+                .await
+                .map_err(format_opendal_error);
+        }
+
+        let tmp = format!(
+            "{path}.ofs-truncate-{}",
+            self.next_fh.fetch_add(1, Ordering::Relaxed)
+        );
+        if let Err(err) = self.write_truncated(path, &tmp, len, size).await {
+            let _ = self.op.delete(&tmp).await;
+            return Err(err);
+        }
+
+        if capability.rename {
+            self.op
+                .rename(&tmp, path)
+                .await
+                .map_err(format_opendal_error)
+        } else {
+            self.op
+                .copy(&tmp, path)
+                .await
+                .map_err(format_opendal_error)?;
+            self.op.delete(&tmp).await.map_err(format_opendal_error)
+        }
+    }
+
+    /// Write the first `size` bytes of `path` into `tmp`, pad with zeros if
+    /// the file is shorter than `size`.
+    async fn write_truncated(&self, path: &str, tmp: &str, len: u64, size: u64) -> Result<()> {
+        let mut w = self.op.writer(tmp).await.map_err(format_opendal_error)?;
+
+        let mut remaining = size.min(len);
+        if remaining > 0 {
+            let mut r = self
+                .op
+                .reader_with(path)
+                .range(0..remaining)
+                .await
+                .map_err(format_opendal_error)?;
+            let mut buf = vec![0; TRUNCATE_CHUNK_SIZE];
+            while remaining > 0 {
+                let n = r.read(&mut buf).await.map_err(format_io_error)?;
+                if n == 0 {
+                    break;
+                }
+                w.write(Bytes::copy_from_slice(&buf[..n]))
+                    .await
+                    .map_err(format_opendal_error)?;
+                remaining -= n as u64;
+            }
+        }
+
+        let mut zeros = size.saturating_sub(len);
+        while zeros > 0 {
+            let n = zeros.min(TRUNCATE_CHUNK_SIZE as u64);
+            w.write(vec![0; n as usize])
+                .await
+                .map_err(format_opendal_error)?;
+            zeros -= n;
+        }
+
+        w.close().await.map_err(format_opendal_error)
+    }
+
+    /// Stat given fuse path and convert the metadata into file attr.
+    async fn stat(&self, path: &OsStr) -> Result<FileAttr> {
+        let file = file_path(path);
+        if file.is_empty() {
+            return Ok(self.new_attr(FileType::Directory, 0, UNIX_EPOCH));
+        }
+        if let Some(attr) = self.get_cached_attr(&file) {
+            return Ok(attr);
+        }
+
+        // Dirs on object storage services are not exist unless we stat
+        // them with the trailing `/`.
+        let meta = match self.op.stat(&file).await {
+            Err(err) if err.kind() == ErrorKind::NotFound => self.op.stat(&dir_path(path)).await,
+            v => v,
+        }
+        .map_err(format_opendal_error)?;
+
+        let attr = self.metadata_to_attr(&meta);
+        self.set_cached_attr(&file, attr);
+        Ok(attr)
+    }
+
+    fn get_opened_file(&self, fh: u64) -> Result<Arc<tokio::sync::Mutex<OpenedFile>>> {
+        let files = self.opened_files.lock().unwrap();
+        files.get(&fh).cloned().ok_or_else(|| libc::EBADF.into())
+    }
+
+    /// Write data into the writer of opened file if it's a sequential write.
+    ///
+    /// Returns `false` if the data should be written into buffer instead.
+    async fn write_sequential(
+        &self,
+        file: &mut OpenedFile,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<bool> {
+        if file.buffer.is_some() {
+            return Ok(false);
+        }
+
+        if file.writer.is_none() && offset == 0 {
+            // Files created by `mknod` are empty, no need to load them.
+            if !file.truncated {
+                let attr = self.stat(OsStr::new(&file.path)).await;
+                file.truncated = matches!(attr, Ok(attr) if attr.size == 0);
+            }
+            if file.truncated {
+                let w = self
+                    .op
+                    .writer(&file.path)
+                    .await
+                    .map_err(format_opendal_error)?;
+                file.writer = Some((w, 0));
+                file.truncated = false;
+                // The writer will overwrite the file while closing.
+                file.dirty = false;
+            }
+        }
+
+        match &mut file.writer {
+            Some((w, written)) if *written == offset => {
+                w.write(Bytes::copy_from_slice(data))
+                    .await
+                    .map_err(format_opendal_error)?;
+                *written += data.len() as u64;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Close the writer of opened file so that the written content is visible.
+    async fn close_writer(&self, file: &mut OpenedFile) -> Result<()> {
+        if let Some((mut w, _)) = file.writer.take() {
+            w.close().await.map_err(format_opendal_error)?;
+
+            // Drop the reader since the content has been changed.
+            file.reader = None;
+            self.invalidate_cached_attr(&file.path);
+        }
+        Ok(())
+    }
+
+    /// Load the content of opened file into buffer before the first random write.
+    async fn load_buffer<'a>(&self, file: &'a mut OpenedFile) -> Result<&'a mut Vec<u8>> {
+        if file.buffer.is_none() {
+            // Content written by writer must be visible before loading.
+            self.close_writer(file).await?;
+
+            let content = if file.truncated {
+                Vec::new()
+            } else {
+                match self.op.read(&file.path).await {
+                    Ok(v) => v,
+                    Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+                    Err(err) => return Err(format_opendal_error(err)),
+                }
+            };
+            file.buffer = Some(content);
+            file.truncated = false;
+        }
+
+        Ok(file.buffer.as_mut().expect("buffer must be loaded"))
+    }
+
+    /// Flush the written content of opened file.
+    async fn flush_file(&self, file: &mut OpenedFile) -> Result<()> {
+        self.close_writer(file).await?;
+        if !file.dirty {
+            return Ok(());
+        }
+
+        let content = file.buffer.clone().unwrap_or_default();
+        self.op
+            .write(&file.path, content)
+            .await
+            .map_err(format_opendal_error)?;
+
+        file.dirty = false;
+        // Drop the reader since the content has been changed.
+        file.reader = None;
+        self.invalidate_cached_attr(&file.path);
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl PathFilesystem for Ofs {
-    type DirEntryStream = Empty<Result<DirectoryEntry>>;
+    type DirEntryStream = Iter<IntoIter<Result<DirectoryEntry>>>;
     type DirEntryPlusStream = Iter<IntoIter<Result<DirectoryEntryPlus>>>;
 
     // Init a fuse filesystem
@@ -42,42 +491,90 @@ impl PathFilesystem for Ofs {
     // Callback when fs is being destroyed
     async fn destroy(&self, _req: Request) {}
 
-    async fn lookup(&self, _req: Request, _parent: &OsStr, _name: &OsStr) -> Result<ReplyEntry> {
-        // TODO
-        Err(libc::ENOSYS.into())
+    async fn lookup(&self, _req: Request, parent: &OsStr, name: &OsStr) -> Result<ReplyEntry> {
+        log::debug!("lookup(parent={:?}, name={:?})", parent, name);
+
+        let attr = self.stat(&join_path(parent, name)).await?;
+        Ok(ReplyEntry {
+            ttl: self.attr_ttl,
+            attr,
+        })
     }
 
     async fn getattr(
         &self,
         _req: Request,
         path: Option<&OsStr>,
-        _fh: Option<u64>,
+        fh: Option<u64>,
         _flags: u32,
     ) -> Result<ReplyAttr> {
-        // TODO
-        log::debug!("getattr(path={:?})", path);
+        log::debug!("getattr(path={:?}, fh={:?})", path, fh);
 
-        Err(libc::ENOSYS.into())
+        let file = match fh {
+            Some(fh) => Some(self.get_opened_file(fh)?),
+            None => None,
+        };
+        let mut attr = match (path, &file) {
+            (Some(path), _) => self.stat(path).await?,
+            (None, Some(file)) => {
+                let path = file.lock().await.path.clone();
+                self.stat(OsStr::new(&path)).await?
+            }
+            (None, None) => return Err(libc::EINVAL.into()),
+        };
+
+        // Use the size of written content if the file is being written.
+        if let Some(file) = file {
+            let file = file.lock().await;
+            let size = match (&file.buffer, &file.writer) {
+                (Some(buffer), _) => Some(buffer.len() as u64),
+                (None, Some((_, written))) => Some(*written),
+                (None, None) if file.truncated => Some(0),
+                (None, None) => None,
+            };
+            if let Some(size) = size {
+                attr.size = size;
+                attr.blocks = (attr.size + 511) / 512;
+            }
+        }
+
+        Ok(ReplyAttr {
+            ttl: self.attr_ttl,
+            attr,
+        })
     }
 
-    async fn read(
+    async fn setattr(
         &self,
-        _req: Request,
+        req: Request,
         path: Option<&OsStr>,
-        fh: u64,
-        offset: u64,
-        size: u32,
-    ) -> Result<ReplyData> {
-        // TODO
-        log::debug!(
-            "read(path={:?}, fh={}, offset={}, size={})",
-            path,
-            fh,
-            offset,
-            size
-        );
+        fh: Option<u64>,
+        set_attr: SetAttr,
+    ) -> Result<ReplyAttr> {
+        log::debug!("setattr(path={:?}, fh={:?})", path, fh);
 
-        Err(libc::ENOSYS.into())
+        // Only truncate is supported, other attributes will be ignored.
+        if let Some(size) = set_attr.size {
+            match (fh, path) {
+                (Some(fh), _) => {
+                    let file = self.get_opened_file(fh)?;
+                    let mut file = file.lock().await;
+                    if !file.write {
+                        return Err(libc::EBADF.into());
+                    }
+                    self.load_buffer(&mut file).await?.resize(size as usize, 0);
+                    file.dirty = true;
+                }
+                (None, Some(path)) => {
+                    let path = file_path(path);
+                    self.truncate(&path, size).await?;
+                    self.invalidate_cached_attr(&path);
+                }
+                (None, None) => return Err(libc::EINVAL.into()),
+            }
+        }
+
+        self.getattr(req, path, fh, 0).await
     }
 
     async fn mkdir(
@@ -88,7 +585,6 @@ impl PathFilesystem for Ofs {
         mode: u32,
         _umask: u32,
     ) -> Result<ReplyEntry> {
-        // TODO
         log::debug!(
             "mkdir(parent={:?}, name={:?}, mode=0o{:o})",
             parent,
@@ -96,20 +592,17 @@ impl PathFilesystem for Ofs {
             mode
         );
 
-        Err(libc::ENOSYS.into())
-    }
+        let path = join_path(parent, name);
+        self.op
+            .create_dir(&dir_path(&path))
+            .await
+            .map_err(format_opendal_error)?;
+        self.invalidate_cached_attr(&file_path(&path));
 
-    async fn readdir(
-        &self,
-        _req: Request,
-        path: &OsStr,
-        fh: u64,
-        offset: i64,
-    ) -> Result<ReplyDirectory<Self::DirEntryStream>> {
-        // TODO
-        log::debug!("readdir(path={:?}, fh={}, offset={})", path, fh, offset);
-
-        Err(libc::ENOSYS.into())
+        Ok(ReplyEntry {
+            ttl: self.attr_ttl,
+            attr: self.new_attr(FileType::Directory, 0, SystemTime::now()),
+        })
     }
 
     async fn mknod(
@@ -120,7 +613,6 @@ impl PathFilesystem for Ofs {
         mode: u32,
         _rdev: u32,
     ) -> Result<ReplyEntry> {
-        // TODO
         log::debug!(
             "mknod(parent={:?}, name={:?}, mode=0o{:o})",
             parent,
@@ -128,27 +620,196 @@ impl PathFilesystem for Ofs {
             mode
         );
 
-        Err(libc::ENOSYS.into())
+        // Only regular files are supported.
+        if (mode & libc::S_IFMT as u32) != libc::S_IFREG as u32 {
+            return Err(libc::ENOTSUP.into());
+        }
+
+        let path = file_path(&join_path(parent, name));
+        self.op
+            .write(&path, Bytes::new())
+            .await
+            .map_err(format_opendal_error)?;
+        self.invalidate_cached_attr(&path);
+
+        Ok(ReplyEntry {
+            ttl: self.attr_ttl,
+            attr: self.new_attr(FileType::RegularFile, 0, SystemTime::now()),
+        })
+    }
+
+    async fn unlink(&self, _req: Request, parent: &OsStr, name: &OsStr) -> Result<()> {
+        log::debug!("unlink(parent={:?}, name={:?})", parent, name);
+
+        let path = file_path(&join_path(parent, name));
+        self.op.delete(&path).await.map_err(format_opendal_error)?;
+        self.invalidate_cached_attr(&path);
+
+        Ok(())
+    }
+
+    async fn rmdir(&self, _req: Request, parent: &OsStr, name: &OsStr) -> Result<()> {
+        log::debug!("rmdir(parent={:?}, name={:?})", parent, name);
+
+        let path = join_path(parent, name);
+        let dir = dir_path(&path);
+
+        let mut lister = self.op.lister(&dir).await.map_err(format_opendal_error)?;
+        while let Some(entry) = lister.try_next().await.map_err(format_opendal_error)? {
+            if entry.path() != dir {
+                return Err(libc::ENOTEMPTY.into());
+            }
+        }
+
+        self.op.delete(&dir).await.map_err(format_opendal_error)?;
+        self.invalidate_cached_attr(&file_path(&path));
+
+        Ok(())
+    }
+
+    async fn rename(
+        &self,
+        _req: Request,
+        origin_parent: &OsStr,
+        origin_name: &OsStr,
+        parent: &OsStr,
+        name: &OsStr,
+    ) -> Result<()> {
+        log::debug!(
+            "rename(p={:?}, name={:?}, newp={:?}, newname={:?})",
+            origin_parent,
+            origin_name,
+            parent,
+            name
+        );
+
+        let from = join_path(origin_parent, origin_name);
+        if matches!(self.stat(&from).await?.kind, FileType::Directory) {
+            return Err(libc::ENOTSUP.into());
+        }
+
+        let from = file_path(&from);
+        let to = file_path(&join_path(parent, name));
+
+        let capability = self.op.info().full_capability();
+        if capability.rename {
+            self.op
+                .rename(&from, &to)
+                .await
+                .map_err(format_opendal_error)?;
+        } else if capability.copy {
+            self.op
+                .copy(&from, &to)
+                .await
+                .map_err(format_opendal_error)?;
+            self.op.delete(&from).await.map_err(format_opendal_error)?;
+        } else {
+            return Err(libc::ENOTSUP.into());
+        }
+
+        self.invalidate_cached_attr(&from);
+        self.invalidate_cached_attr(&to);
+        Ok(())
     }
 
     async fn open(&self, _req: Request, path: &OsStr, flags: u32) -> Result<ReplyOpen> {
-        // TODO
         log::debug!("open(path={:?}, flags=0x{:x})", path, flags);
 
-        Err(libc::ENOSYS.into())
+        let flags = flags as i32;
+        let (read, write) = match flags & libc::O_ACCMODE {
+            libc::O_RDONLY => (true, false),
+            libc::O_WRONLY => (false, true),
+            libc::O_RDWR => (true, true),
+            _ => return Err(libc::EINVAL.into()),
+        };
+
+        let mut file = OpenedFile {
+            path: file_path(path),
+            read,
+            write,
+            reader: None,
+            writer: None,
+            buffer: None,
+            truncated: false,
+            dirty: false,
+        };
+        if write && flags & libc::O_TRUNC != 0 {
+            file.truncated = true;
+            file.dirty = true;
+        }
+
+        let fh = self.next_fh.fetch_add(1, Ordering::Relaxed);
+        self.opened_files
+            .lock()
+            .unwrap()
+            .insert(fh, Arc::new(tokio::sync::Mutex::new(file)));
+
+        Ok(ReplyOpen { fh, flags: 0 })
     }
 
-    async fn setattr(
+    async fn read(
         &self,
         _req: Request,
         path: Option<&OsStr>,
-        _fh: Option<u64>,
-        _set_attr: SetAttr,
-    ) -> Result<ReplyAttr> {
-        // TODO
-        log::debug!("setattr(path={:?})", path);
+        fh: u64,
+        offset: u64,
+        size: u32,
+    ) -> Result<ReplyData> {
+        log::debug!(
+            "read(path={:?}, fh={}, offset={}, size={})",
+            path,
+            fh,
+            offset,
+            size
+        );
+
+        let file = self.get_opened_file(fh)?;
+        let mut file = file.lock().await;
+        if !file.read {
+            return Err(libc::EBADF.into());
+        }
 
-        Err(libc::ENOSYS.into())
+        // Make sure content written by writer is visible.
+        self.close_writer(&mut file).await?;
+        if file.truncated {
+            return Ok(ReplyData { data: Bytes::new() });
+        }
+        // Read from buffer directly if the file is being written.
+        if let Some(buffer) = &file.buffer {
+            let start = (offset as usize).min(buffer.len());
+            let end = (start + size as usize).min(buffer.len());
+            return Ok(ReplyData {
+                data: Bytes::copy_from_slice(&buffer[start..end]),
+            });
+        }
+
+        // Reuse current reader for sequential read, or start a new
+        // ranged reader from the offset.
+        let file = &mut *file;
+        if !matches!(&file.reader, Some((_, pos)) if *pos == offset) {
+            let r = self
+                .op
+                .reader_with(&file.path)
+                .range(offset..)
+                .await
+                .map_err(format_opendal_error)?;
+            file.reader = Some((r, offset));
+        }
+        let (reader, pos) = file.reader.as_mut().expect("reader must be valid");
+
+        let mut buf = vec![0; size as usize];
+        let mut n = 0;
+        while n < buf.len() {
+            let m = reader.read(&mut buf[n..]).await.map_err(format_io_error)?;
+            if m == 0 {
+                break;
+            }
+            n += m;
+        }
+        buf.truncate(n);
+        *pos += n as u64;
+
+        Ok(ReplyData { data: buf.into() })
     }
 
     async fn write(
@@ -160,7 +821,6 @@ impl PathFilesystem for Ofs {
         data: &[u8],
         flags: u32,
     ) -> Result<ReplyWrite> {
-        // TODO
         log::debug!(
             "write(path={:?}, fh={}, offset={}, len={}, flags=0x{:x})",
             path,
@@ -170,7 +830,43 @@ impl PathFilesystem for Ofs {
             flags
         );
 
-        Err(libc::ENOSYS.into())
+        let file = self.get_opened_file(fh)?;
+        let mut file = file.lock().await;
+        if !file.write {
+            return Err(libc::EBADF.into());
+        }
+
+        if self.write_sequential(&mut file, offset, data).await? {
+            return Ok(ReplyWrite {
+                written: data.len() as u32,
+            });
+        }
+
+        let buffer = self.load_buffer(&mut file).await?;
+        let (start, end) = (offset as usize, offset as usize + data.len());
+        if buffer.len() < end {
+            buffer.resize(end, 0);
+        }
+        buffer[start..end].copy_from_slice(data);
+        file.dirty = true;
+
+        Ok(ReplyWrite {
+            written: data.len() as u32,
+        })
+    }
+
+    async fn flush(
+        &self,
+        _req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        _lock_owner: u64,
+    ) -> Result<()> {
+        log::debug!("flush(path={:?}, fh={})", path, fh);
+
+        let file = self.get_opened_file(fh)?;
+        let mut file = file.lock().await;
+        self.flush_file(&mut file).await
     }
 
     async fn release(
@@ -182,7 +878,6 @@ impl PathFilesystem for Ofs {
         _lock_owner: u64,
         flush: bool,
     ) -> Result<()> {
-        // TODO
         log::debug!(
             "release(path={:?}, fh={}, flags={}, flush={})",
             path,
@@ -191,33 +886,501 @@ impl PathFilesystem for Ofs {
             flush
         );
 
-        Err(libc::ENOSYS.into())
+        let file = self.opened_files.lock().unwrap().remove(&fh);
+        match file {
+            Some(file) => self.flush_file(&mut *file.lock().await).await,
+            None => Err(libc::EBADF.into()),
+        }
     }
 
-    async fn rename(
+    async fn opendir(&self, _req: Request, path: &OsStr, flags: u32) -> Result<ReplyOpen> {
+        log::debug!("opendir(path={:?}, flags=0x{:x})", path, flags);
+
+        let entries = self.list_dir(path).await?;
+        let fh = self.next_fh.fetch_add(1, Ordering::Relaxed);
+        self.opened_dirs
+            .lock()
+            .unwrap()
+            .insert(fh, Arc::new(entries));
+
+        Ok(ReplyOpen { fh, flags: 0 })
+    }
+
+    async fn readdir(
         &self,
         _req: Request,
-        origin_parent: &OsStr,
-        origin_name: &OsStr,
-        parent: &OsStr,
-        name: &OsStr,
-    ) -> Result<()> {
-        // TODO
+        path: &OsStr,
+        fh: u64,
+        offset: i64,
+    ) -> Result<ReplyDirectory<Self::DirEntryStream>> {
+        log::debug!("readdir(path={:?}, fh={}, offset={})", path, fh, offset);
+
+        let entries = self
+            .opened_dirs
+            .lock()
+            .unwrap()
+            .get(&fh)
+            .cloned()
+            .ok_or_else(|| Errno::from(libc::EBADF))?;
+
+        let entries = entries
+            .iter()
+            .enumerate()
+            .skip(offset as usize)
+            .map(|(i, (kind, name))| {
+                Ok(DirectoryEntry {
+                    kind: *kind,
+                    name: name.clone(),
+                    offset: i as i64 + 1,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(ReplyDirectory {
+            entries: stream::iter(entries),
+        })
+    }
+
+    async fn releasedir(&self, _req: Request, path: &OsStr, fh: u64, flags: u32) -> Result<()> {
         log::debug!(
-            "rename(p={:?}, name={:?}, newp={:?}, newname={:?})",
-            origin_parent,
-            origin_name,
-            parent,
-            name
+            "releasedir(path={:?}, fh={}, flags=0x{:x})",
+            path,
+            fh,
+            flags
         );
 
-        Err(libc::ENOSYS.into())
+        self.opened_dirs.lock().unwrap().remove(&fh);
+        Ok(())
     }
+}
 
-    async fn unlink(&self, _req: Request, parent: &OsStr, name: &OsStr) -> Result<()> {
-        // TODO
-        log::debug!("unlink(parent={:?}, name={:?})", parent, name);
+/// Join fuse path with the name of its child.
+fn join_path(parent: &OsStr, name: &OsStr) -> OsString {
+    PathBuf::from(parent).join(name).into_os_string()
+}
+
+/// Convert fuse path into OpenDAL's file path.
+///
+/// The root will be converted into an empty string.
+fn file_path(path: &OsStr) -> String {
+    path.to_string_lossy().trim_matches('/').to_string()
+}
+
+/// Convert fuse path into OpenDAL's dir path.
+fn dir_path(path: &OsStr) -> String {
+    match file_path(path) {
+        v if v.is_empty() => "/".to_string(),
+        v => format!("{v}/"),
+    }
+}
+
+fn format_opendal_error(err: opendal::Error) -> Errno {
+    log::debug!("opendal error: {err:?}");
+
+    let errno = match err.kind() {
+        ErrorKind::NotFound => libc::ENOENT,
+        ErrorKind::PermissionDenied => libc::EACCES,
+        ErrorKind::AlreadyExists => libc::EEXIST,
+        ErrorKind::IsADirectory => libc::EISDIR,
+        ErrorKind::NotADirectory => libc::ENOTDIR,
+        ErrorKind::Unsupported => libc::ENOTSUP,
+        ErrorKind::RateLimited => libc::EBUSY,
+        _ => libc::EIO,
+    };
+    errno.into()
+}
+
+fn format_io_error(err: io::Error) -> Errno {
+    log::debug!("io error: {err:?}");
+
+    match err.kind() {
+        io::ErrorKind::NotFound => libc::ENOENT.into(),
+        io::ErrorKind::PermissionDenied => libc::EACCES.into(),
+        _ => libc::EIO.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services;
+
+    use super::*;
+
+    fn new_request() -> Request {
+        Request {
+            unique: 0,
+            uid: 0,
+            gid: 0,
+            pid: 0,
+        }
+    }
+
+    #[test]
+    fn test_path_conversion() {
+        assert_eq!(file_path(OsStr::new("/")), "");
+        assert_eq!(dir_path(OsStr::new("/")), "/");
+        assert_eq!(file_path(OsStr::new("/a/b")), "a/b");
+        assert_eq!(dir_path(OsStr::new("/a/b")), "a/b/");
+        assert_eq!(
+            join_path(OsStr::new("/a"), OsStr::new("b")),
+            OsString::from("/a/b")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_and_read() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::ZERO);
+
+        ofs.mkdir(new_request(), OsStr::new("/"), OsStr::new("dir"), 0o755, 0)
+            .await
+            .unwrap();
+        ofs.mknod(
+            new_request(),
+            OsStr::new("/dir"),
+            OsStr::new("file"),
+            libc::S_IFREG as u32 | 0o644,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let path = OsStr::new("/dir/file");
+        let fh = ofs
+            .open(new_request(), path, (libc::O_WRONLY | libc::O_TRUNC) as u32)
+            .await
+            .unwrap()
+            .fh;
+        ofs.write(new_request(), Some(path), fh, 0, b"Hello, ", 0)
+            .await
+            .unwrap();
+        ofs.write(new_request(), Some(path), fh, 7, b"World!", 0)
+            .await
+            .unwrap();
+        ofs.release(new_request(), Some(path), fh, 0, 0, true)
+            .await
+            .unwrap();
+        assert_eq!(op.read("dir/file").await.unwrap(), b"Hello, World!");
+
+        let entry = ofs
+            .lookup(new_request(), OsStr::new("/dir"), OsStr::new("file"))
+            .await
+            .unwrap();
+        assert!(matches!(entry.attr.kind, FileType::RegularFile));
+        assert_eq!(entry.attr.size, 13);
+
+        let fh = ofs
+            .open(new_request(), path, libc::O_RDONLY as u32)
+            .await
+            .unwrap()
+            .fh;
+        let data = ofs
+            .read(new_request(), Some(path), fh, 7, 5)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(data.as_ref(), b"World");
+        ofs.release(new_request(), Some(path), fh, 0, 0, false)
+            .await
+            .unwrap();
+
+        let dir = OsStr::new("/dir");
+        let fh = ofs.opendir(new_request(), dir, 0).await.unwrap().fh;
+        // Entries created after opendir are not visible to this handle.
+        create_file(&ofs, "/dir", "other").await;
+        let entries: Vec<_> = ofs
+            .readdir(new_request(), dir, fh, 0)
+            .await
+            .unwrap()
+            .entries
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.clone()).collect();
+        assert_eq!(names, vec![".", "..", "file"]);
+        // Continue from the offset of the last returned entry.
+        let entries: Vec<_> = ofs
+            .readdir(new_request(), dir, fh, entries[1].offset)
+            .await
+            .unwrap()
+            .entries
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.clone()).collect();
+        assert_eq!(names, vec!["file"]);
+        ofs.releasedir(new_request(), dir, fh, 0).await.unwrap();
+        ofs.readdir(new_request(), dir, fh, 0).await.unwrap_err();
+
+        ofs.unlink(new_request(), OsStr::new("/dir"), OsStr::new("file"))
+            .await
+            .unwrap();
+        let err = ofs
+            .lookup(new_request(), OsStr::new("/dir"), OsStr::new("file"))
+            .await
+            .unwrap_err();
+        assert!(err.is_not_exist());
+    }
+
+    async fn create_file(ofs: &Ofs, parent: &str, name: &str) {
+        ofs.mknod(
+            new_request(),
+            OsStr::new(parent),
+            OsStr::new(name),
+            libc::S_IFREG as u32 | 0o644,
+            0,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sequential_and_random_write() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::ZERO);
+        create_file(&ofs, "/", "file").await;
+
+        let path = OsStr::new("/file");
+        let fh = ofs
+            .open(new_request(), path, libc::O_RDWR as u32)
+            .await
+            .unwrap()
+            .fh;
+
+        // Sequential writes to an empty file are streamed into writer.
+        ofs.write(new_request(), Some(path), fh, 0, b"Hello, ", 0)
+            .await
+            .unwrap();
+        ofs.write(new_request(), Some(path), fh, 7, b"World!", 0)
+            .await
+            .unwrap();
+        {
+            let file = ofs.get_opened_file(fh).unwrap();
+            let file = file.lock().await;
+            assert!(file.buffer.is_none());
+            assert!(matches!(file.writer, Some((_, 13))));
+        }
+        let attr = ofs
+            .getattr(new_request(), Some(path), Some(fh), 0)
+            .await
+            .unwrap()
+            .attr;
+        assert_eq!(attr.size, 13);
+
+        // Random writes fall back to buffer with streamed content.
+        ofs.write(new_request(), Some(path), fh, 0, b"J", 0)
+            .await
+            .unwrap();
+        let data = ofs
+            .read(new_request(), Some(path), fh, 0, 5)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(data.as_ref(), b"Jello");
+
+        ofs.release(new_request(), Some(path), fh, 0, 0, true)
+            .await
+            .unwrap();
+        assert_eq!(op.read("file").await.unwrap(), b"Jello, World!");
+
+        // Writes to a non-empty file without truncating keep the rest content.
+        let fh = ofs
+            .open(new_request(), path, libc::O_WRONLY as u32)
+            .await
+            .unwrap()
+            .fh;
+        ofs.write(new_request(), Some(path), fh, 0, b"H", 0)
+            .await
+            .unwrap();
+        ofs.release(new_request(), Some(path), fh, 0, 0, true)
+            .await
+            .unwrap();
+        assert_eq!(op.read("file").await.unwrap(), b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_readdir() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let ofs = Ofs::new(op.clone());
+        ofs.mkdir(new_request(), OsStr::new("/"), OsStr::new("dir"), 0o755, 0)
+            .await
+            .unwrap();
+        ofs.mkdir(
+            new_request(),
+            OsStr::new("/dir"),
+            OsStr::new("sub"),
+            0o755,
+            0,
+        )
+        .await
+        .unwrap();
+        create_file(&ofs, "/dir", "file").await;
+
+        let fh = ofs
+            .opendir(new_request(), OsStr::new("/dir"), 0)
+            .await
+            .unwrap()
+            .fh;
+        let entries: Vec<_> = ofs
+            .readdir(new_request(), OsStr::new("/dir"), fh, 0)
+            .await
+            .unwrap()
+            .entries
+            .try_collect()
+            .await
+            .unwrap();
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .map(|e| (e.name, matches!(e.kind, FileType::Directory), e.offset))
+            .collect();
+        entries[2..].sort();
+        assert_eq!(
+            entries,
+            vec![
+                (".".into(), true, 1),
+                ("..".into(), true, 2),
+                ("file".into(), false, 3),
+                ("sub".into(), true, 4),
+            ]
+        );
+
+        // Entries before offset should be skipped.
+        let entries: Vec<_> = ofs
+            .readdir(new_request(), OsStr::new("/dir"), fh, 2)
+            .await
+            .unwrap()
+            .entries
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].offset, 3);
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_unlink() {
+        let root = std::env::temp_dir().join(format!("ofs-test-rename-{}", std::process::id()));
+        let mut builder = services::Fs::default();
+        builder.root(root.to_str().unwrap());
+        let op = Operator::new(builder).unwrap().finish();
+        let ofs = Ofs::new(op.clone());
+
+        ofs.mkdir(new_request(), OsStr::new("/"), OsStr::new("dir"), 0o755, 0)
+            .await
+            .unwrap();
+        op.write("from", "Hello, World!").await.unwrap();
+
+        ofs.rename(
+            new_request(),
+            OsStr::new("/"),
+            OsStr::new("from"),
+            OsStr::new("/dir"),
+            OsStr::new("to"),
+        )
+        .await
+        .unwrap();
+        assert!(ofs
+            .lookup(new_request(), OsStr::new("/"), OsStr::new("from"))
+            .await
+            .unwrap_err()
+            .is_not_exist());
+        let entry = ofs
+            .lookup(new_request(), OsStr::new("/dir"), OsStr::new("to"))
+            .await
+            .unwrap();
+        assert_eq!(entry.attr.size, 13);
+        assert_eq!(op.read("dir/to").await.unwrap(), b"Hello, World!");
+
+        // Rename dirs is not supported.
+        let err = ofs
+            .rename(
+                new_request(),
+                OsStr::new("/"),
+                OsStr::new("dir"),
+                OsStr::new("/"),
+                OsStr::new("other"),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err, Errno::from(libc::ENOTSUP));
+
+        ofs.unlink(new_request(), OsStr::new("/dir"), OsStr::new("to"))
+            .await
+            .unwrap();
+        assert!(!op.is_exist("dir/to").await.unwrap());
+        ofs.rmdir(new_request(), OsStr::new("/"), OsStr::new("dir"))
+            .await
+            .unwrap();
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_attr_cache() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        op.write("file", "Hello, World!").await.unwrap();
+
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::from_secs(3600));
+        let lookup = || ofs.lookup(new_request(), OsStr::new("/"), OsStr::new("file"));
+        assert_eq!(lookup().await.unwrap().attr.size, 13);
+
+        // Changes outside ofs are not visible until the cache expired.
+        op.write("file", "Hello").await.unwrap();
+        assert_eq!(lookup().await.unwrap().attr.size, 13);
+
+        // Changes via ofs will invalidate the cache.
+        ofs.unlink(new_request(), OsStr::new("/"), OsStr::new("file"))
+            .await
+            .unwrap();
+        assert!(lookup().await.unwrap_err().is_not_exist());
+
+        // Cache is disabled while ttl is zero.
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::ZERO);
+        op.write("file", "Hello").await.unwrap();
+        let lookup = || ofs.lookup(new_request(), OsStr::new("/"), OsStr::new("file"));
+        assert_eq!(lookup().await.unwrap().attr.size, 5);
+        op.write("file", "Hello, World!").await.unwrap();
+        assert_eq!(lookup().await.unwrap().attr.size, 13);
+
+        // Cached attributes are bounded.
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::from_secs(3600));
+        let attr = ofs.new_attr(FileType::RegularFile, 0, UNIX_EPOCH);
+        for i in 0..MAX_ATTR_CACHE_ENTRIES + 10 {
+            ofs.set_cached_attr(&format!("file-{i}"), attr);
+        }
+        assert_eq!(ofs.attr_cache.lock().unwrap().len(), MAX_ATTR_CACHE_ENTRIES);
+        assert!(ofs
+            .get_cached_attr(&format!("file-{}", MAX_ATTR_CACHE_ENTRIES + 9))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_truncate_by_path() {
+        let root = std::env::temp_dir().join(format!("ofs-test-truncate-{}", std::process::id()));
+        let mut builder = services::Fs::default();
+        builder.root(root.to_str().unwrap());
+        let op = Operator::new(builder).unwrap().finish();
+        let ofs = Ofs::new(op.clone()).with_attr_ttl(Duration::ZERO);
+        op.write("file", "Hello, World!").await.unwrap();
+
+        let truncate = |size| {
+            let set_attr = SetAttr {
+                size: Some(size),
+                ..Default::default()
+            };
+            ofs.setattr(new_request(), Some(OsStr::new("/file")), None, set_attr)
+        };
+        assert_eq!(truncate(5).await.unwrap().attr.size, 5);
+        assert_eq!(op.read("file").await.unwrap(), b"Hello");
+        assert_eq!(truncate(8).await.unwrap().attr.size, 8);
+        assert_eq!(op.read("file").await.unwrap(), b"Hello\0\0\0");
+        assert_eq!(truncate(0).await.unwrap().attr.size, 0);
+        assert_eq!(op.read("file").await.unwrap(), b"");
+
+        // Temporary files must be cleaned up.
+        let entries: Vec<_> = op.list("/").await.unwrap();
+        assert_eq!(entries.len(), 1);
 
-        Err(libc::ENOSYS.into())
+        std::fs::remove_dir_all(root).unwrap();
     }
 }
@@ -77,10 +77,22 @@ async fn main() -> Result<()> {
             let cmd = oli::commands::cp::cli(new_cmd("ocp")?);
             oli::commands::cp::main(&cmd.get_matches()).await?;
         }
+        Some("odu") => {
+            let cmd = oli::commands::du::cli(new_cmd("odu")?);
+            oli::commands::du::main(&cmd.get_matches()).await?;
+        }
+        Some("ofind") => {
+            let cmd = oli::commands::find::cli(new_cmd("ofind")?);
+            oli::commands::find::main(&cmd.get_matches()).await?;
+        }
         Some("ols") => {
             let cmd = oli::commands::ls::cli(new_cmd("ols")?);
             oli::commands::ls::main(&cmd.get_matches()).await?;
         }
+        Some("omv") => {
+            let cmd = oli::commands::mv::cli(new_cmd("omv")?);
+            oli::commands::mv::main(&cmd.get_matches()).await?;
+        }
         Some("orm") => {
             let cmd = oli::commands::rm::cli(new_cmd("orm")?);
             oli::commands::rm::main(&cmd.get_matches()).await?;
@@ -89,6 +101,14 @@ async fn main() -> Result<()> {
             let cmd = oli::commands::stat::cli(new_cmd("ostat")?);
             oli::commands::stat::main(&cmd.get_matches()).await?;
         }
+        Some("osync") => {
+            let cmd = oli::commands::sync::cli(new_cmd("osync")?);
+            oli::commands::sync::main(&cmd.get_matches()).await?;
+        }
+        Some("otree") => {
+            let cmd = oli::commands::tree::cli(new_cmd("otree")?);
+            oli::commands::tree::main(&cmd.get_matches()).await?;
+        }
         Some(v) => {
             println!("{v} is not supported")
         }
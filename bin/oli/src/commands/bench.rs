@@ -0,0 +1,327 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::value_parser;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use opendal::Operator;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+use crate::utils::format_size;
+use crate::utils::parse_size;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+
+    let opts = BenchOptions {
+        size: *args
+            .get_one::<u64>("size")
+            .ok_or_else(|| anyhow!("missing size"))?,
+        count: *args
+            .get_one::<usize>("count")
+            .ok_or_else(|| anyhow!("missing count"))?,
+        small_size: *args
+            .get_one::<u64>("small-size")
+            .ok_or_else(|| anyhow!("missing small size"))?,
+        small_count: *args
+            .get_one::<usize>("small-count")
+            .ok_or_else(|| anyhow!("missing small count"))?,
+        list_count: *args
+            .get_one::<usize>("list-count")
+            .ok_or_else(|| anyhow!("missing list count"))?,
+        concurrent: *args
+            .get_one::<usize>("concurrent")
+            .ok_or_else(|| anyhow!("missing concurrent"))?,
+    };
+
+    // Run in a dedicated dir so that the cleanup won't touch existing files.
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let dir = format!("{}oli-bench-{nanos}/", dir_path(&path));
+
+    println!("Benchmarking {target} in {dir}");
+    Report::print_header();
+    let result = bench(&op, &dir, &opts).await;
+    let cleanup = op.remove_all(&dir).await;
+    result?;
+    cleanup?;
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("benchmark the throughput and latency of the service")
+        .arg(Arg::new("target").required(true))
+        .arg(
+            Arg::new("size")
+                .required(false)
+                .long("size")
+                .help("Size of each file in read and write benchmarks")
+                .default_value("4MiB")
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new("count")
+                .required(false)
+                .long("count")
+                .help("Number of files in read and write benchmarks")
+                .default_value("16")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("small-size")
+                .required(false)
+                .long("small-size")
+                .help("Size of each file in small object benchmarks")
+                .default_value("4KiB")
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new("small-count")
+                .required(false)
+                .long("small-count")
+                .help("Number of files in small object benchmarks")
+                .default_value("256")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("list-count")
+                .required(false)
+                .long("list-count")
+                .help("Number of times to list the small objects")
+                .default_value("10")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("concurrent")
+                .required(false)
+                .long("concurrent")
+                .short('j')
+                .help("Number of concurrent operations in concurrent benchmarks")
+                .default_value("8")
+                .value_parser(value_parser!(usize)),
+        )
+}
+
+struct BenchOptions {
+    size: u64,
+    count: usize,
+    small_size: u64,
+    small_count: usize,
+    list_count: usize,
+    concurrent: usize,
+}
+
+async fn bench(op: &Operator, dir: &str, opts: &BenchOptions) -> Result<()> {
+    let data = gen_data(opts.size as usize);
+    let path = |i: usize| format!("{dir}large/{i}");
+
+    for (name, concurrent) in [("write", 1), ("write (concurrent)", opts.concurrent)] {
+        run(name, opts.count, concurrent, |i| {
+            let (op, path, data) = (op.clone(), path(i), data.clone());
+            async move {
+                op.write(&path, data).await?;
+                Ok::<_, anyhow::Error>(opts.size)
+            }
+        })
+        .await?
+        .print();
+    }
+    for (name, concurrent) in [("read", 1), ("read (concurrent)", opts.concurrent)] {
+        run(name, opts.count, concurrent, |i| {
+            let (op, path) = (op.clone(), path(i));
+            async move { Ok::<_, anyhow::Error>(op.read(&path).await?.len() as u64) }
+        })
+        .await?
+        .print();
+    }
+
+    let small_data = gen_data(opts.small_size as usize);
+    let small_dir = format!("{dir}small/");
+    let small_path = |i: usize| format!("{small_dir}{i}");
+    run("small write", opts.small_count, opts.concurrent, |i| {
+        let (op, path, data) = (op.clone(), small_path(i), small_data.clone());
+        async move {
+            op.write(&path, data).await?;
+            Ok::<_, anyhow::Error>(opts.small_size)
+        }
+    })
+    .await?
+    .print();
+    run("small read", opts.small_count, opts.concurrent, |i| {
+        let (op, path) = (op.clone(), small_path(i));
+        async move { Ok::<_, anyhow::Error>(op.read(&path).await?.len() as u64) }
+    })
+    .await?
+    .print();
+    run("small stat", opts.small_count, opts.concurrent, |i| {
+        let (op, path) = (op.clone(), small_path(i));
+        async move {
+            op.stat(&path).await?;
+            Ok::<_, anyhow::Error>(0)
+        }
+    })
+    .await?
+    .print();
+
+    run("list", opts.list_count, 1, |_| {
+        let (op, path) = (op.clone(), small_dir.clone());
+        async move {
+            op.list(&path).await?;
+            Ok::<_, anyhow::Error>(0)
+        }
+    })
+    .await?
+    .print();
+
+    Ok(())
+}
+
+/// Run the operation `count` times with given concurrency, the operation
+/// returns the bytes it transferred.
+async fn run<F, Fut>(name: &str, count: usize, concurrent: usize, f: F) -> Result<Report>
+where
+    F: Fn(usize) -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    let start = Instant::now();
+    let results: Vec<(Duration, u64)> = futures::stream::iter(0..count)
+        .map(|i| {
+            let fut = f(i);
+            async move {
+                let start = Instant::now();
+                let bytes = fut.await?;
+                Ok::<_, anyhow::Error>((start.elapsed(), bytes))
+            }
+        })
+        .buffer_unordered(concurrent.max(1))
+        .try_collect()
+        .await?;
+
+    Ok(Report::new(name, start.elapsed(), results))
+}
+
+/// Generate pseudo-random data which can't be compressed easily.
+fn gen_data(size: usize) -> Vec<u8> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+struct Report {
+    name: String,
+    elapsed: Duration,
+    bytes: u64,
+    /// Latencies of all operations in ascending order.
+    latencies: Vec<Duration>,
+}
+
+impl Report {
+    fn new(name: &str, elapsed: Duration, results: Vec<(Duration, u64)>) -> Self {
+        let bytes = results.iter().map(|(_, bytes)| bytes).sum();
+        let mut latencies: Vec<Duration> = results.into_iter().map(|(d, _)| d).collect();
+        latencies.sort_unstable();
+
+        Self {
+            name: name.to_string(),
+            elapsed,
+            bytes,
+            latencies,
+        }
+    }
+
+    /// Get the latency at given percentile like `0.99`.
+    fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let idx = ((self.latencies.len() as f64 * p).ceil() as usize).max(1) - 1;
+        self.latencies[idx.min(self.latencies.len() - 1)]
+    }
+
+    fn print_header() {
+        println!(
+            "{:<20} {:>8} {:>10} {:>14} {:>10} {:>10} {:>10} {:>10}",
+            "workload", "ops", "ops/s", "throughput", "p50", "p90", "p99", "max"
+        );
+    }
+
+    fn print(&self) {
+        let secs = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        let ops = self.latencies.len();
+        let throughput = if self.bytes == 0 {
+            "-".to_string()
+        } else {
+            format!("{}/s", format_size((self.bytes as f64 / secs) as u64))
+        };
+
+        println!(
+            "{:<20} {:>8} {:>10.1} {:>14} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+            self.name,
+            ops,
+            ops as f64 / secs,
+            throughput,
+            self.percentile(0.5),
+            self.percentile(0.9),
+            self.percentile(0.99),
+            self.percentile(1.0),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let results = (1..=100)
+            .map(|i| (Duration::from_millis(i), 0))
+            .rev()
+            .collect();
+        let report = Report::new("test", Duration::from_secs(1), results);
+
+        assert_eq!(report.percentile(0.5), Duration::from_millis(50));
+        assert_eq!(report.percentile(0.99), Duration::from_millis(99));
+        assert_eq!(report.percentile(1.0), Duration::from_millis(100));
+    }
+}
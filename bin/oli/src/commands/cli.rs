@@ -22,11 +22,21 @@ use clap::Command;
 
 pub async fn main(args: &ArgMatches) -> Result<()> {
     match args.subcommand() {
+        Some(("bench", sub_args)) => super::bench::main(sub_args).await?,
         Some(("cat", sub_args)) => super::cat::main(sub_args).await?,
         Some(("cp", sub_args)) => super::cp::main(sub_args).await?,
+        Some(("du", sub_args)) => super::du::main(sub_args).await?,
+        Some(("find", sub_args)) => super::find::main(sub_args).await?,
         Some(("ls", sub_args)) => super::ls::main(sub_args).await?,
+        Some(("mv", sub_args)) => super::mv::main(sub_args).await?,
+        Some(("presign", sub_args)) => super::presign::main(sub_args).await?,
         Some(("rm", sub_args)) => super::rm::main(sub_args).await?,
+        Some(("share", sub_args)) => super::share::main(sub_args).await?,
+        Some(("shell", sub_args)) => super::shell::main(sub_args).await?,
         Some(("stat", sub_args)) => super::stat::main(sub_args).await?,
+        Some(("sync", sub_args)) => super::sync::main(sub_args).await?,
+        Some(("tree", sub_args)) => super::tree::main(sub_args).await?,
+        Some(("watch", sub_args)) => super::watch::main(sub_args).await?,
         _ => return Err(anyhow!("not handled")),
     }
 
@@ -39,9 +49,19 @@ fn new_cmd(name: &'static str) -> Command {
 
 pub fn cli(cmd: Command) -> Command {
     cmd.about("OpenDAL Command Line Interface")
+        .subcommand(super::bench::cli(new_cmd("bench")))
         .subcommand(super::cat::cli(new_cmd("cat")))
         .subcommand(super::cp::cli(new_cmd("cp")))
+        .subcommand(super::du::cli(new_cmd("du")))
+        .subcommand(super::find::cli(new_cmd("find")))
         .subcommand(super::ls::cli(new_cmd("ls")))
+        .subcommand(super::mv::cli(new_cmd("mv")))
+        .subcommand(super::presign::cli(new_cmd("presign")))
         .subcommand(super::rm::cli(new_cmd("rm")))
+        .subcommand(super::share::cli(new_cmd("share")))
+        .subcommand(super::shell::cli(new_cmd("shell")))
         .subcommand(super::stat::cli(new_cmd("stat")))
+        .subcommand(super::sync::cli(new_cmd("sync")))
+        .subcommand(super::tree::cli(new_cmd("tree")))
+        .subcommand(super::watch::cli(new_cmd("watch")))
 }
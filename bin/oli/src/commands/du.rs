@@ -0,0 +1,108 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use futures::TryStreamExt;
+use opendal::Metakey;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+use crate::utils::format_size;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let summarize = args.get_flag("summarize");
+    let bytes = args.get_flag("bytes");
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+    let root = dir_path(&path);
+
+    // Size of every sub dir, keyed by its path relative to root.
+    let mut dirs: BTreeMap<String, u64> = BTreeMap::new();
+    let mut total = 0;
+    let mut ds = op
+        .lister_with(&root)
+        .recursive(true)
+        .metakey(Metakey::Mode | Metakey::ContentLength)
+        .await?;
+    while let Some(de) = ds.try_next().await? {
+        let meta = de.metadata();
+        if meta.mode().is_dir() {
+            continue;
+        }
+        let size = meta.content_length();
+        total += size;
+
+        let rel = de.path().trim_start_matches('/').strip_prefix(&root);
+        let rel = rel.ok_or_else(|| anyhow!("invalid path: {}", de.path()))?;
+        // Add the size to all ancestors of this file.
+        for (idx, _) in rel.match_indices('/') {
+            *dirs.entry(rel[..=idx].to_string()).or_default() += size;
+        }
+    }
+
+    let format = |size: u64| {
+        if bytes {
+            size.to_string()
+        } else {
+            format_size(size)
+        }
+    };
+    if !summarize {
+        for (dir, size) in &dirs {
+            println!("{}\t{root}{dir}", format(*size));
+        }
+    }
+    println!("{}\t{target}", format(total));
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("show disk usage")
+        .arg(Arg::new("target").required(true))
+        .arg(
+            Arg::new("summarize")
+                .required(false)
+                .long("summarize")
+                .short('s')
+                .help("Display only the total size of target")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("bytes")
+                .required(false)
+                .long("bytes")
+                .short('b')
+                .help("Display sizes in bytes instead of human-readable units")
+                .action(ArgAction::SetTrue),
+        )
+}
@@ -0,0 +1,241 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::value_parser;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use futures::TryStreamExt;
+use globset::Glob;
+use globset::GlobMatcher;
+use opendal::Entry;
+use opendal::Metakey;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+use crate::utils::parse_size;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+    let root = dir_path(&path);
+
+    let filter = Filter {
+        name: args
+            .get_one::<String>("name")
+            .map(|v| Glob::new(v).map(|g| g.compile_matcher()))
+            .transpose()?,
+        file_type: args.get_one::<FileType>("type").copied(),
+        size: args
+            .get_one::<String>("size")
+            .map(|v| Cmp::parse(v, parse_size))
+            .transpose()?,
+        mtime: args
+            .get_one::<String>("mtime")
+            .map(|v| Cmp::parse(v, |s| Ok(s.parse()?)))
+            .transpose()?,
+        now: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+    };
+    let delete = args.get_flag("delete");
+
+    let mut matched = Vec::new();
+    let mut ds = op
+        .lister_with(&root)
+        .recursive(true)
+        .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
+        .await?;
+    while let Some(de) = ds.try_next().await? {
+        if !filter.is_match(&de) {
+            continue;
+        }
+        println!("{}", de.path());
+        if delete {
+            matched.push(de.path().to_string());
+        }
+    }
+
+    // Delete children before their parents so that dirs are empty while deleting.
+    matched.sort_unstable();
+    matched.reverse();
+    for path in matched {
+        op.delete(&path).await?;
+    }
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("find entries matching the filters")
+        .arg(Arg::new("target").required(true))
+        .arg(
+            Arg::new("name")
+                .required(false)
+                .long("name")
+                .help("Match the entry name against the glob, like `*.txt`"),
+        )
+        .arg(
+            Arg::new("type")
+                .required(false)
+                .long("type")
+                .help("Match the entry type, `f` for files and `d` for dirs")
+                .value_parser(value_parser!(FileType)),
+        )
+        .arg(
+            Arg::new("size")
+                .required(false)
+                .long("size")
+                .allow_hyphen_values(true)
+                .help("Match files larger (`+N`), smaller (`-N`) or equal (`N`) to the size, like `+10M`"),
+        )
+        .arg(
+            Arg::new("mtime")
+                .required(false)
+                .long("mtime")
+                .allow_hyphen_values(true)
+                .help("Match entries modified more (`+N`), less (`-N`) or exactly (`N`) days ago"),
+        )
+        .arg(
+            Arg::new("delete")
+                .required(false)
+                .long("delete")
+                .help("Delete the matched entries")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileType {
+    File,
+    Dir,
+}
+
+impl FromStr for FileType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "f" => Ok(FileType::File),
+            "d" => Ok(FileType::Dir),
+            v => Err(anyhow!("invalid type: {v}, expect `f` or `d`")),
+        }
+    }
+}
+
+/// Cmp compares values in the way of `find`: `+N` means greater than `N`,
+/// `-N` means less than `N` and `N` means exactly `N`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Greater(u64),
+    Less(u64),
+    Equal(u64),
+}
+
+impl Cmp {
+    fn parse(s: &str, parse: impl Fn(&str) -> Result<u64>) -> Result<Self> {
+        if let Some(v) = s.strip_prefix('+') {
+            Ok(Cmp::Greater(parse(v)?))
+        } else if let Some(v) = s.strip_prefix('-') {
+            Ok(Cmp::Less(parse(v)?))
+        } else {
+            Ok(Cmp::Equal(parse(s)?))
+        }
+    }
+
+    fn is_match(&self, v: u64) -> bool {
+        match *self {
+            Cmp::Greater(n) => v > n,
+            Cmp::Less(n) => v < n,
+            Cmp::Equal(n) => v == n,
+        }
+    }
+}
+
+struct Filter {
+    name: Option<GlobMatcher>,
+    file_type: Option<FileType>,
+    size: Option<Cmp>,
+    /// Compared with the days since last modified.
+    mtime: Option<Cmp>,
+    /// Current unix timestamp in seconds.
+    now: i64,
+}
+
+impl Filter {
+    fn is_match(&self, de: &Entry) -> bool {
+        let meta = de.metadata();
+        let is_dir = meta.mode().is_dir();
+
+        if let Some(name) = &self.name {
+            if !name.is_match(de.name().trim_end_matches('/')) {
+                return false;
+            }
+        }
+        match self.file_type {
+            Some(FileType::File) if is_dir => return false,
+            Some(FileType::Dir) if !is_dir => return false,
+            _ => {}
+        }
+        if let Some(size) = &self.size {
+            // Dirs don't have size.
+            if is_dir || !size.is_match(meta.content_length()) {
+                return false;
+            }
+        }
+        if let Some(mtime) = &self.mtime {
+            let Some(last_modified) = meta.last_modified() else {
+                return false;
+            };
+            let days = (self.now - last_modified.timestamp()).max(0) as u64 / 86400;
+            if !mtime.is_match(days) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmp() -> Result<()> {
+        assert_eq!(Cmp::parse("+1K", parse_size)?, Cmp::Greater(1024));
+        assert_eq!(Cmp::parse("-3", parse_size)?, Cmp::Less(3));
+        assert_eq!(Cmp::parse("7", parse_size)?, Cmp::Equal(7));
+
+        assert!(Cmp::Greater(1).is_match(2));
+        assert!(!Cmp::Greater(1).is_match(1));
+        assert!(Cmp::Less(1).is_match(0));
+        assert!(Cmp::Equal(1).is_match(1));
+        Ok(())
+    }
+}
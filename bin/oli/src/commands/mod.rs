@@ -33,9 +33,19 @@
 //! }
 //! ```
 
+pub mod bench;
 pub mod cat;
 pub mod cli;
 pub mod cp;
+pub mod du;
+pub mod find;
 pub mod ls;
+pub mod mv;
+pub mod presign;
 pub mod rm;
+pub mod share;
+pub mod shell;
 pub mod stat;
+pub mod sync;
+pub mod tree;
+pub mod watch;
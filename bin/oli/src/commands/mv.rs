@@ -0,0 +1,151 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use futures::TryStreamExt;
+use opendal::Metakey;
+use opendal::Operator;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+use crate::utils::is_same_operator;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+    let recursive = args.get_flag("recursive");
+
+    let src = args
+        .get_one::<String>("source")
+        .ok_or_else(|| anyhow!("missing source"))?;
+    let (src_op, src_path) = cfg.parse_location(src)?;
+
+    let dst = args
+        .get_one::<String>("destination")
+        .ok_or_else(|| anyhow!("missing destination"))?;
+    let (dst_op, dst_path) = cfg.parse_location(dst)?;
+
+    let same_operator = is_same_operator(&src_op, &dst_op);
+
+    if !recursive {
+        if same_operator && src_path.trim_start_matches('/') == dst_path.trim_start_matches('/') {
+            return Err(anyhow!("cannot move {src_path} to itself"));
+        }
+        println!("Moving {src_path}");
+        return move_file(&src_op, &src_path, &dst_op, &dst_path).await;
+    }
+
+    let src_root = dir_path(&src_path);
+    let dst_root = dir_path(&dst_path);
+    if same_operator && (dst_root.starts_with(&src_root) || src_root.starts_with(&dst_root)) {
+        return Err(anyhow!(
+            "cannot move {src_path} to {dst_path}: destination overlaps source"
+        ));
+    }
+
+    let mut dirs = vec![src_root.clone()];
+    let mut ds = src_op
+        .lister_with(&src_root)
+        .recursive(true)
+        .metakey(Metakey::Mode)
+        .await?;
+    while let Some(de) = ds.try_next().await? {
+        if de.metadata().mode().is_dir() {
+            dirs.push(de.path().trim_start_matches('/').to_string());
+            continue;
+        }
+        let fp = de
+            .path()
+            .trim_start_matches('/')
+            .strip_prefix(&src_root)
+            .ok_or_else(|| anyhow!("invalid path: {}", de.path()))?;
+
+        println!("Moving {}", de.path());
+        move_file(&src_op, de.path(), &dst_op, &format!("{dst_root}{fp}")).await?;
+    }
+    // Clean up the dirs left in source, children first. Dirs that are not
+    // empty (e.g. files were added while moving) are kept.
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.len()));
+    for dir in dirs {
+        remove_empty_dir(&src_op, &dir).await?;
+    }
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("move")
+        .arg(Arg::new("source").required(true))
+        .arg(Arg::new("destination").required(true))
+        .arg(
+            Arg::new("recursive")
+                .required(false)
+                .long("recursive")
+                .short('r')
+                .help("Move files under source recursively to destination")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// Move a single file, `rename` will be used if source and destination are
+/// the same service and it's supported, otherwise fallback to copy + delete.
+async fn move_file(src_op: &Operator, src: &str, dst_op: &Operator, dst: &str) -> Result<()> {
+    if is_same_operator(src_op, dst_op) {
+        let cap = src_op.info().full_capability();
+        if cap.rename {
+            src_op.rename(src, dst).await?;
+            return Ok(());
+        }
+        if cap.copy {
+            src_op.copy(src, dst).await?;
+            src_op.delete(src).await?;
+            return Ok(());
+        }
+    }
+
+    let reader = src_op.reader(src).await?;
+    let buf_reader = futures::io::BufReader::with_capacity(8 * 1024 * 1024, reader);
+    let mut writer = dst_op.writer(dst).await?;
+    futures::io::copy_buf(buf_reader, &mut writer).await?;
+    writer.close().await?;
+
+    src_op.delete(src).await?;
+    Ok(())
+}
+
+/// Remove the dir only if there is no entry left in it.
+async fn remove_empty_dir(op: &Operator, dir: &str) -> Result<()> {
+    if dir.is_empty() {
+        return Ok(());
+    }
+    let mut ds = op.lister(dir).await?;
+    while let Some(de) = ds.try_next().await? {
+        if de.path().trim_start_matches('/') != dir {
+            return Ok(());
+        }
+    }
+    op.delete(dir).await?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::value_parser;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use opendal::raw::PresignedRequest;
+use opendal::Operator;
+
+use crate::config::Config;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+
+    let method = *args
+        .get_one::<Method>("method")
+        .ok_or_else(|| anyhow!("missing method"))?;
+    let expire = *args
+        .get_one::<Duration>("expire")
+        .ok_or_else(|| anyhow!("missing expire"))?;
+
+    let req = presign(&op, &path, method, expire).await?;
+    if args.get_flag("curl") {
+        println!("{}", to_curl(&req, method));
+        return Ok(());
+    }
+
+    println!("method: {}", req.method());
+    println!("url: {}", req.uri());
+    for (name, value) in req.header() {
+        println!("header: {name}: {}", value.to_str()?);
+    }
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("generate a presigned request for object")
+        .arg(Arg::new("target").required(true))
+        .arg(
+            Arg::new("method")
+                .required(false)
+                .long("method")
+                .short('m')
+                .help("Method of the request, `get`, `put` or `head`")
+                .default_value("get")
+                .value_parser(value_parser!(Method)),
+        )
+        .arg(expire_arg())
+        .arg(
+            Arg::new("curl")
+                .required(false)
+                .long("curl")
+                .help("Print a ready-to-run curl command line instead")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// The `--expire` arg which accepts durations like `30m`, `1h` or `7days`.
+pub(crate) fn expire_arg() -> Arg {
+    Arg::new("expire")
+        .required(false)
+        .long("expire")
+        .short('e')
+        .help("Expire duration of the request, like `30m`, `1h` or `7days`")
+        .default_value("1h")
+        .value_parser(humantime::parse_duration)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Method {
+    Get,
+    Put,
+    Head,
+}
+
+impl FromStr for Method {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "get" => Ok(Method::Get),
+            "put" => Ok(Method::Put),
+            "head" => Ok(Method::Head),
+            v => Err(anyhow!(
+                "invalid method: {v}, expect `get`, `put` or `head`"
+            )),
+        }
+    }
+}
+
+/// Presign the request, returns error with the capability name if the
+/// service doesn't support it.
+pub(crate) async fn presign(
+    op: &Operator,
+    path: &str,
+    method: Method,
+    expire: Duration,
+) -> Result<PresignedRequest> {
+    let cap = op.info().full_capability();
+    let (name, supported) = match method {
+        Method::Get => ("presign_read", cap.presign_read),
+        Method::Put => ("presign_write", cap.presign_write),
+        Method::Head => ("presign_stat", cap.presign_stat),
+    };
+    if !cap.presign || !supported {
+        return Err(anyhow!(
+            "service {} doesn't support capability {name}",
+            op.info().scheme()
+        ));
+    }
+
+    let req = match method {
+        Method::Get => op.presign_read(path, expire).await?,
+        Method::Put => op.presign_write(path, expire).await?,
+        Method::Head => op.presign_stat(path, expire).await?,
+    };
+    Ok(req)
+}
+
+fn to_curl(req: &PresignedRequest, method: Method) -> String {
+    let mut cmd = match method {
+        Method::Get => "curl".to_string(),
+        Method::Put => format!("curl -X {}", req.method()),
+        // Use `-I` so that curl won't wait for the body.
+        Method::Head => "curl -I".to_string(),
+    };
+    for (name, value) in req.header() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        cmd.push_str(&format!(" -H {}", quote(&format!("{name}: {value}"))));
+    }
+    if method == Method::Put {
+        cmd.push_str(" --upload-file <FILE>");
+    }
+    cmd.push_str(&format!(" {}", quote(&req.uri().to_string())));
+    cmd
+}
+
+/// Quote the string for shells.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_curl() -> Result<()> {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-amz-acl", "private".parse()?);
+        let req = PresignedRequest::new(
+            http::Method::PUT,
+            "https://example.com/a?b=c".parse()?,
+            headers,
+        );
+
+        assert_eq!(
+            to_curl(&req, Method::Put),
+            "curl -X PUT -H 'x-amz-acl: private' --upload-file <FILE> 'https://example.com/a?b=c'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,62 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+
+use super::presign::expire_arg;
+use super::presign::presign;
+use super::presign::Method;
+use crate::config::Config;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+
+    let expire = *args
+        .get_one::<Duration>("expire")
+        .ok_or_else(|| anyhow!("missing expire"))?;
+
+    let req = presign(&op, &path, Method::Get, expire).await?;
+    if !req.header().is_empty() {
+        return Err(anyhow!(
+            "presigned url of service {} requires headers, use `oli presign` instead",
+            op.info().scheme()
+        ));
+    }
+    println!("{}", req.uri());
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("generate a url to share object for download")
+        .arg(Arg::new("target").required(true))
+        .arg(expire_arg())
+}
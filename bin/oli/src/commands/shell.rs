@@ -0,0 +1,428 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+use futures::io::AllowStdIo;
+use futures::TryStreamExt;
+use opendal::Operator;
+use rustyline::completion::Completer;
+use rustyline::completion::Pair;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::Context;
+use rustyline::Editor;
+use rustyline::Helper;
+use tokio::runtime::Handle;
+
+use crate::config::Config;
+
+const COMMANDS: [&str; 12] = [
+    "cat", "cd", "exit", "get", "help", "ls", "profile", "put", "pwd", "quit", "rm", "stat",
+];
+
+const HELP: &str = "\
+cd [dir]                change the current dir, `/` by default
+ls [dir]                list entries in dir, the current dir by default
+cat <path>...           print the content of files
+stat <path>             show metadata of the path
+get <remote> [local]    download file to local, use the file name by default
+put <local> [remote]    upload local file, use the file name by default
+rm [-r] <path>          remove file, or dir recursively with `-r`
+pwd                     print the current dir
+profile [name]          switch to another profile, or list all profiles
+help                    show this help
+exit, quit              leave the shell";
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let profile = args
+        .get_one::<String>("profile")
+        .ok_or_else(|| anyhow!("missing profile"))?;
+    let mut shell = Shell::new(cfg, profile)?;
+
+    let mut rl = Editor::<ShellHelper, DefaultHistory>::new()?;
+    rl.set_helper(Some(ShellHelper {
+        op: shell.op.clone(),
+        cwd: shell.cwd.clone(),
+    }));
+    let history_path = config_path.with_file_name("shell_history");
+    // History file may not exist yet.
+    let _ = rl.load_history(&history_path);
+
+    loop {
+        let prompt = format!("{}:{}> ", shell.profile, shell.cwd);
+        // Readline blocks the current thread, and the completion inside
+        // will block on listing via the runtime handle.
+        let line = match tokio::task::block_in_place(|| rl.readline(&prompt)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        rl.add_history_entry(line)?;
+
+        match shell.run(line).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => eprintln!("error: {err}"),
+        }
+        if let Some(helper) = rl.helper_mut() {
+            helper.op = shell.op.clone();
+            helper.cwd = shell.cwd.clone();
+        }
+    }
+
+    if let Some(dir) = history_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    rl.save_history(&history_path)?;
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("start an interactive shell on the profile")
+        .arg(Arg::new("profile").required(true))
+}
+
+/// Shell keeps the state of an interactive session.
+struct Shell {
+    cfg: Config,
+    profile: String,
+    op: Operator,
+    /// The current dir, always starts and ends with `/`.
+    cwd: String,
+}
+
+impl Shell {
+    fn new(cfg: Config, profile: &str) -> Result<Self> {
+        let (op, _) = cfg.parse_location(&format!("{profile}:///"))?;
+        Ok(Self {
+            cfg,
+            profile: profile.to_string(),
+            op,
+            cwd: "/".to_string(),
+        })
+    }
+
+    /// Run the command line, returns `false` if the shell should exit.
+    async fn run(&mut self, line: &str) -> Result<bool> {
+        let mut args = line.split_whitespace();
+        let cmd = args.next().unwrap_or_default();
+        let args: Vec<&str> = args.collect();
+
+        match cmd {
+            "exit" | "quit" => return Ok(false),
+            "help" => println!("{HELP}"),
+            "pwd" => println!("{}", self.cwd),
+            "profile" => match args.first() {
+                Some(profile) => self.switch(profile)?,
+                None => {
+                    for name in self.cfg.profile_names() {
+                        let mark = if name == self.profile { "*" } else { " " };
+                        println!("{mark} {name}");
+                    }
+                }
+            },
+            "cd" => self.cd(args.first().copied().unwrap_or("/")).await?,
+            "ls" => self.ls(args.first().copied().unwrap_or(".")).await?,
+            "cat" => {
+                if args.is_empty() {
+                    return Err(anyhow!("missing path"));
+                }
+                for path in args {
+                    self.cat(path).await?;
+                }
+            }
+            "stat" => self.stat(arg(&args, 0, "path")?).await?,
+            "get" => {
+                self.get(arg(&args, 0, "remote path")?, args.get(1).copied())
+                    .await?
+            }
+            "put" => {
+                self.put(arg(&args, 0, "local path")?, args.get(1).copied())
+                    .await?
+            }
+            "rm" => match args.as_slice() {
+                ["-r", path] => self.op.remove_all(&self.resolve(path)).await?,
+                [path] => self.op.delete(&self.resolve(path)).await?,
+                _ => return Err(anyhow!("usage: rm [-r] <path>")),
+            },
+            v => return Err(anyhow!("unknown command: {v}, type `help` for usage")),
+        }
+
+        Ok(true)
+    }
+
+    fn resolve(&self, path: &str) -> String {
+        resolve_path(&self.cwd, path)
+    }
+
+    fn switch(&mut self, profile: &str) -> Result<()> {
+        let (op, _) = self.cfg.parse_location(&format!("{profile}:///"))?;
+        self.profile = profile.to_string();
+        self.op = op;
+        self.cwd = "/".to_string();
+        Ok(())
+    }
+
+    async fn cd(&mut self, path: &str) -> Result<()> {
+        let mut dir = self.resolve(path);
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+
+        // Dirs on object storage may only exist as the prefix of files.
+        if dir != "/"
+            && !self.op.is_exist(&dir).await?
+            && self.op.lister(&dir).await?.try_next().await?.is_none()
+        {
+            return Err(anyhow!("no such dir: {dir}"));
+        }
+        self.cwd = dir;
+        Ok(())
+    }
+
+    async fn ls(&self, path: &str) -> Result<()> {
+        let mut dir = self.resolve(path);
+        if !dir.ends_with('/') {
+            dir.push('/');
+        }
+
+        let mut ds = self.op.lister(&dir).await?;
+        while let Some(de) = ds.try_next().await? {
+            // Some services will return the dir itself.
+            if de.path().trim_start_matches('/') == dir.trim_start_matches('/') {
+                continue;
+            }
+            println!("{}", de.name());
+        }
+        Ok(())
+    }
+
+    async fn cat(&self, path: &str) -> Result<()> {
+        let reader = self.op.reader(&self.resolve(path)).await?;
+        let mut stdout = AllowStdIo::new(std::io::stdout());
+        futures::io::copy(reader, &mut stdout).await?;
+        Ok(())
+    }
+
+    async fn stat(&self, path: &str) -> Result<()> {
+        let path = self.resolve(path);
+        let meta = self.op.stat(&path).await?;
+        println!("path: {path}");
+        println!("size: {}", meta.content_length());
+        if let Some(etag) = meta.etag() {
+            println!("etag: {etag}");
+        }
+        println!("type: {}", meta.mode());
+        if let Some(content_type) = meta.content_type() {
+            println!("content-type: {content_type}");
+        }
+        if let Some(last_modified) = meta.last_modified() {
+            println!("last-modified: {last_modified}");
+        }
+        Ok(())
+    }
+
+    async fn get(&self, remote: &str, local: Option<&str>) -> Result<()> {
+        let remote = self.resolve(remote);
+        let local = match local {
+            Some(v) => PathBuf::from(v),
+            None => PathBuf::from(file_name(&remote)?),
+        };
+
+        let reader = self.op.reader(&remote).await?;
+        let mut file = AllowStdIo::new(fs::File::create(&local)?);
+        let size = futures::io::copy(reader, &mut file).await?;
+        println!("Downloaded {remote} to {} ({size} bytes)", local.display());
+        Ok(())
+    }
+
+    async fn put(&self, local: &str, remote: Option<&str>) -> Result<()> {
+        let remote = match remote {
+            Some(v) => self.resolve(v),
+            None => {
+                let name = Path::new(local)
+                    .file_name()
+                    .ok_or_else(|| anyhow!("invalid local path: {local}"))?;
+                self.resolve(&name.to_string_lossy())
+            }
+        };
+
+        let file = AllowStdIo::new(fs::File::open(local)?);
+        let mut writer = self.op.writer(&remote).await?;
+        let size = futures::io::copy(file, &mut writer).await?;
+        writer.close().await?;
+        println!("Uploaded {local} to {remote} ({size} bytes)");
+        Ok(())
+    }
+}
+
+fn arg<'a>(args: &[&'a str], idx: usize, name: &str) -> Result<&'a str> {
+    args.get(idx)
+        .copied()
+        .ok_or_else(|| anyhow!("missing {name}"))
+}
+
+fn file_name(path: &str) -> Result<&str> {
+    path.rsplit('/')
+        .next()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("invalid file path: {path}"))
+}
+
+/// Resolve the path against the current dir, `.` and `..` will be handled
+/// and the trailing `/` of dirs will be kept.
+fn resolve_path(cwd: &str, path: &str) -> String {
+    let full = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{cwd}{path}")
+    };
+    let is_dir = full.ends_with('/') || full.ends_with("/.") || full.ends_with("/..");
+
+    let mut parts = Vec::new();
+    for part in full.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            v => parts.push(v),
+        }
+    }
+
+    let mut resolved = format!("/{}", parts.join("/"));
+    if is_dir && !parts.is_empty() {
+        resolved.push('/');
+    }
+    resolved
+}
+
+/// ShellHelper completes command names and remote paths.
+struct ShellHelper {
+    op: Operator,
+    cwd: String,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let word = &line[start..];
+
+        if line[..start].trim().is_empty() {
+            let pairs = COMMANDS
+                .iter()
+                .filter(|cmd| cmd.starts_with(word))
+                .map(|cmd| Pair {
+                    display: cmd.to_string(),
+                    replacement: format!("{cmd} "),
+                })
+                .collect();
+            return Ok((start, pairs));
+        }
+
+        let (dir, prefix) = match word.rfind('/') {
+            Some(idx) => word.split_at(idx + 1),
+            None => ("", word),
+        };
+        let list_path = resolve_path(&self.cwd, if dir.is_empty() { "." } else { dir });
+        // Completion is called inside `block_in_place`, so it's safe to block here.
+        let entries = match Handle::current().block_on(self.op.list(&list_path)) {
+            Ok(entries) => entries,
+            // Listing failure should not break the input.
+            Err(_) => return Ok((start, vec![])),
+        };
+
+        let pairs = entries
+            .iter()
+            .filter(|de| de.path().trim_start_matches('/') != list_path.trim_start_matches('/'))
+            .filter(|de| de.name().starts_with(prefix))
+            .map(|de| Pair {
+                display: de.name().to_string(),
+                replacement: format!("{dir}{}", de.name()),
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_path() {
+        let cases = vec![
+            ("/", ".", "/"),
+            ("/", "a", "/a"),
+            ("/", "a/", "/a/"),
+            ("/a/", "b/c", "/a/b/c"),
+            ("/a/", "..", "/"),
+            ("/a/b/", "../c/", "/a/c/"),
+            ("/a/b/", "/d", "/d"),
+            ("/a/", "./b/../c", "/a/c"),
+            ("/", "../..", "/"),
+        ];
+        for (cwd, path, expected) in cases {
+            assert_eq!(resolve_path(cwd, path), expected, "{cwd} + {path}");
+        }
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("/a/b.txt").unwrap(), "b.txt");
+        assert!(file_name("/a/").is_err());
+    }
+}
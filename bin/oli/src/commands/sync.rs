@@ -0,0 +1,349 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::value_parser;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use opendal::ErrorKind;
+use opendal::Metadata;
+use opendal::Metakey;
+use opendal::Operator;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+use crate::utils::is_same_operator;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let src = args
+        .get_one::<String>("source")
+        .ok_or_else(|| anyhow!("missing source"))?;
+    let (src_op, src_path) = cfg.parse_location(src)?;
+    let src_path = dir_path(&src_path);
+
+    let dst = args
+        .get_one::<String>("destination")
+        .ok_or_else(|| anyhow!("missing destination"))?;
+    let (dst_op, dst_path) = cfg.parse_location(dst)?;
+    let dst_path = dir_path(&dst_path);
+
+    let filter = Filter::new(
+        args.get_many::<String>("include").unwrap_or_default(),
+        args.get_many::<String>("exclude").unwrap_or_default(),
+    )?;
+    let delete = args.get_flag("delete");
+    let dry_run = args.get_flag("dry-run");
+    let concurrent = *args
+        .get_one::<usize>("concurrent")
+        .ok_or_else(|| anyhow!("missing concurrent"))?;
+
+    let src_files = list_files(&src_op, &src_path, &filter).await?;
+    let dst_files = list_files(&dst_op, &dst_path, &filter).await?;
+
+    // Etags are only comparable inside the same bucket of the same service.
+    let same_operator = is_same_operator(&src_op, &dst_op);
+    let mut tasks = Vec::new();
+    for (rel, src_meta) in &src_files {
+        match dst_files.get(rel) {
+            Some(dst_meta) if !is_changed(src_meta, dst_meta, same_operator) => {}
+            _ => tasks.push(Task::Copy {
+                path: rel.clone(),
+                size: src_meta.content_length(),
+            }),
+        }
+    }
+    if delete {
+        for rel in dst_files.keys() {
+            if !src_files.contains_key(rel) {
+                tasks.push(Task::Delete { path: rel.clone() })
+            }
+        }
+    }
+
+    if dry_run {
+        for task in &tasks {
+            match task {
+                Task::Copy { path, size } => println!("Would copy {path} ({size} bytes)"),
+                Task::Delete { path } => println!("Would delete {path}"),
+            }
+        }
+        println!("{} files would be changed", tasks.len());
+        return Ok(());
+    }
+
+    let total = tasks.len();
+    let mut done = 0;
+    let mut bytes = 0;
+    let mut results = futures::stream::iter(tasks)
+        .map(|task| {
+            let (src_op, dst_op) = (&src_op, &dst_op);
+            let (src_path, dst_path) = (&src_path, &dst_path);
+            async move {
+                match &task {
+                    Task::Copy { path, .. } => {
+                        copy_file(
+                            src_op,
+                            &format!("{src_path}{path}"),
+                            dst_op,
+                            &format!("{dst_path}{path}"),
+                        )
+                        .await?
+                    }
+                    Task::Delete { path } => dst_op.delete(&format!("{dst_path}{path}")).await?,
+                }
+                Ok::<_, anyhow::Error>(task)
+            }
+        })
+        .buffer_unordered(concurrent.max(1));
+
+    while let Some(task) = results.try_next().await? {
+        done += 1;
+        match task {
+            Task::Copy { path, size } => {
+                bytes += size;
+                println!("[{done}/{total}] Copied {path} ({size} bytes)");
+            }
+            Task::Delete { path } => println!("[{done}/{total}] Deleted {path}"),
+        }
+    }
+    println!("Synced {total} files, {bytes} bytes transferred");
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("sync files under source to destination, only transferring the differences")
+        .arg(Arg::new("source").required(true))
+        .arg(Arg::new("destination").required(true))
+        .arg(
+            Arg::new("delete")
+                .required(false)
+                .long("delete")
+                .help("Delete files in destination that don't exist in source")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("include")
+                .required(false)
+                .long("include")
+                .help("Only sync files matching the glob, can be specified multiple times")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("exclude")
+                .required(false)
+                .long("exclude")
+                .help("Skip files matching the glob, can be specified multiple times")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .required(false)
+                .long("dry-run")
+                .help("Print the changes without performing them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("concurrent")
+                .required(false)
+                .long("concurrent")
+                .short('j')
+                .help("Number of files transferred concurrently")
+                .default_value("8")
+                .value_parser(value_parser!(usize)),
+        )
+}
+
+/// Task is a change to be applied on destination.
+enum Task {
+    Copy { path: String, size: u64 },
+    Delete { path: String },
+}
+
+/// Filter decides whether a file should be synced by its path relative to
+/// the sync root.
+struct Filter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Filter {
+    fn new<'a>(
+        include: impl Iterator<Item = &'a String>,
+        exclude: impl Iterator<Item = &'a String>,
+    ) -> Result<Self> {
+        let include = build_globset(include)?;
+        let exclude = build_globset(exclude)?;
+
+        Ok(Self {
+            include: if include.is_empty() {
+                None
+            } else {
+                Some(include)
+            },
+            exclude,
+        })
+    }
+
+    fn is_match(&self, path: &str) -> bool {
+        if let Some(include) = &self.include {
+            if !include.is_match(path) {
+                return false;
+            }
+        }
+        !self.exclude.is_match(path)
+    }
+}
+
+fn build_globset<'a>(globs: impl Iterator<Item = &'a String>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// List all files under given dir, returns their paths relative to the dir.
+async fn list_files(
+    op: &Operator,
+    path: &str,
+    filter: &Filter,
+) -> Result<BTreeMap<String, Metadata>> {
+    let mut files = BTreeMap::new();
+
+    let mut ds = match op
+        .lister_with(path)
+        .recursive(true)
+        .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::Etag | Metakey::LastModified)
+        .await
+    {
+        Ok(ds) => ds,
+        // Destination that doesn't exist yet is treated as empty.
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(files),
+        Err(err) => return Err(err.into()),
+    };
+    while let Some(de) = ds.try_next().await? {
+        if de.metadata().mode().is_dir() {
+            continue;
+        }
+        let rel = de
+            .path()
+            .trim_start_matches('/')
+            .strip_prefix(path)
+            .ok_or_else(|| anyhow!("invalid path: {}", de.path()))?;
+        if !filter.is_match(rel) {
+            continue;
+        }
+        files.insert(rel.to_string(), de.metadata().clone());
+    }
+
+    Ok(files)
+}
+
+/// Check whether the source file is different from the destination one.
+///
+/// Files with different size are always changed. Otherwise, etag will be
+/// compared if both sides are the same operator and have it, and then last
+/// modified time: source that is newer than destination is considered changed.
+fn is_changed(src: &Metadata, dst: &Metadata, same_operator: bool) -> bool {
+    if src.content_length() != dst.content_length() {
+        return true;
+    }
+    if same_operator && has_metakey(src, dst, Metakey::Etag) {
+        if let (Some(src_etag), Some(dst_etag)) = (src.etag(), dst.etag()) {
+            return src_etag != dst_etag;
+        }
+    }
+    if has_metakey(src, dst, Metakey::LastModified) {
+        if let (Some(src_mtime), Some(dst_mtime)) = (src.last_modified(), dst.last_modified()) {
+            return src_mtime > dst_mtime;
+        }
+    }
+    false
+}
+
+/// Check whether both metadata have given metakey.
+fn has_metakey(src: &Metadata, dst: &Metadata, key: Metakey) -> bool {
+    let has = |m: &Metadata| m.metakey().contains(key) || m.metakey().contains(Metakey::Complete);
+    has(src) && has(dst)
+}
+
+async fn copy_file(src_op: &Operator, src: &str, dst_op: &Operator, dst: &str) -> Result<()> {
+    let reader = src_op.reader(src).await?;
+    let buf_reader = futures::io::BufReader::with_capacity(8 * 1024 * 1024, reader);
+
+    let mut writer = dst_op.writer(dst).await?;
+    futures::io::copy_buf(buf_reader, &mut writer).await?;
+    writer.close().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() -> Result<()> {
+        let include = ["**/*.txt".to_string()];
+        let exclude = ["tmp/**".to_string()];
+        let filter = Filter::new(include.iter(), exclude.iter())?;
+
+        assert!(filter.is_match("a.txt"));
+        assert!(filter.is_match("dir/a.txt"));
+        assert!(!filter.is_match("a.csv"));
+        assert!(!filter.is_match("tmp/a.txt"));
+
+        let filter = Filter::new(Vec::<String>::new().iter(), exclude.iter())?;
+        assert!(filter.is_match("a.csv"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_changed() {
+        let meta = |size: u64, etag: Option<&str>| {
+            let mut m = Metadata::new(opendal::EntryMode::FILE).with_content_length(size);
+            if let Some(etag) = etag {
+                m = m.with_etag(etag.to_string());
+            }
+            m
+        };
+
+        assert!(is_changed(&meta(1, None), &meta(2, None), true));
+        assert!(!is_changed(&meta(1, None), &meta(1, None), true));
+        assert!(is_changed(&meta(1, Some("a")), &meta(1, Some("b")), true));
+        assert!(!is_changed(&meta(1, Some("a")), &meta(1, Some("a")), true));
+
+        // Etags of different services are not comparable.
+        assert!(!is_changed(&meta(1, Some("a")), &meta(1, Some("b")), false));
+        assert!(is_changed(&meta(1, Some("a")), &meta(2, Some("a")), false));
+    }
+}
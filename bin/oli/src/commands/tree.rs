@@ -0,0 +1,127 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::value_parser;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+use futures::TryStreamExt;
+use opendal::Metakey;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let level = args.get_one::<usize>("level").copied();
+
+    let target = args
+        .get_one::<String>("target")
+        .ok_or_else(|| anyhow!("missing target"))?;
+    let (op, path) = cfg.parse_location(target)?;
+    let root = dir_path(&path);
+
+    let mut tree = Node::default();
+    let mut ds = op
+        .lister_with(&root)
+        .recursive(true)
+        .metakey(Metakey::Mode)
+        .await?;
+    while let Some(de) = ds.try_next().await? {
+        let rel = de.path().trim_start_matches('/').strip_prefix(&root);
+        let rel = rel.ok_or_else(|| anyhow!("invalid path: {}", de.path()))?;
+        if rel.is_empty() {
+            continue;
+        }
+        tree.insert(rel);
+    }
+
+    println!("{target}");
+    let (mut dirs, mut files) = (0, 0);
+    tree.print("", 1, level, &mut dirs, &mut files);
+    println!("\n{dirs} directories, {files} files");
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("list contents in a tree-like format")
+        .arg(Arg::new("target").required(true))
+        .arg(
+            Arg::new("level")
+                .required(false)
+                .long("level")
+                .short('L')
+                .help("Max display depth of the tree")
+                .value_parser(value_parser!(usize)),
+        )
+}
+
+/// Node is an entry in the tree, dirs have `/` at the end of their names.
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Insert the path relative to this node, all missing parents will be created.
+    fn insert(&mut self, path: &str) {
+        let (name, rest) = match path.find('/') {
+            Some(idx) => path.split_at(idx + 1),
+            None => (path, ""),
+        };
+        let child = self.children.entry(name.to_string()).or_default();
+        if !rest.is_empty() {
+            child.insert(rest);
+        }
+    }
+
+    fn print(
+        &self,
+        prefix: &str,
+        depth: usize,
+        level: Option<usize>,
+        dirs: &mut usize,
+        files: &mut usize,
+    ) {
+        if matches!(level, Some(level) if depth > level) {
+            return;
+        }
+
+        let count = self.children.len();
+        for (idx, (name, child)) in self.children.iter().enumerate() {
+            let last = idx + 1 == count;
+            println!("{prefix}{}{name}", if last { "└── " } else { "├── " });
+
+            if name.ends_with('/') {
+                *dirs += 1;
+                let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                child.print(&prefix, depth + 1, level, dirs, files);
+            } else {
+                *files += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use futures::TryStreamExt;
+use notify::RecursiveMode;
+use notify::Watcher;
+use opendal::services;
+use opendal::ErrorKind;
+use opendal::Metadata;
+use opendal::Metakey;
+use opendal::Operator;
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::utils::dir_path;
+
+pub async fn main(args: &ArgMatches) -> Result<()> {
+    let config_path = args
+        .get_one::<PathBuf>("config")
+        .ok_or_else(|| anyhow!("missing config path"))?;
+    let cfg = Config::load(config_path)?;
+
+    let src = args
+        .get_one::<String>("source")
+        .ok_or_else(|| anyhow!("missing source"))?;
+    let root = fs::canonicalize(src)?;
+    if !root.is_dir() {
+        return Err(anyhow!("source must be a local dir: {src}"));
+    }
+
+    let dst = args
+        .get_one::<String>("destination")
+        .ok_or_else(|| anyhow!("missing destination"))?;
+    let (remote, remote_path) = cfg.parse_location(dst)?;
+
+    let state_path = match args.get_one::<PathBuf>("state") {
+        Some(v) => v.clone(),
+        None => config_path.with_file_name("watch"),
+    };
+    fs::create_dir_all(&state_path)?;
+    if fs::canonicalize(&state_path)?.starts_with(&root) {
+        return Err(anyhow!("state must not be stored inside the source dir"));
+    }
+    let debounce = *args
+        .get_one::<Duration>("debounce")
+        .ok_or_else(|| anyhow!("missing debounce"))?;
+
+    let syncer = Syncer {
+        local: local_operator(&root)?,
+        remote,
+        remote_root: dir_path(&remote_path),
+        state: state_operator(&state_path, &format!("{}|{dst}", root.display()))?,
+    };
+
+    // Start watching before the full scan so that no changes will be missed.
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let _ = tx.send(res);
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+
+    syncer.scan("").await?;
+    if args.get_flag("once") {
+        return Ok(());
+    }
+    println!("Watching {} for changes", root.display());
+
+    loop {
+        let mut changed = BTreeSet::new();
+        tokio::select! {
+            res = rx.recv() => match res {
+                Some(res) => collect_event(&root, res, &mut changed),
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        // Debounce: wait until there is no event for a while.
+        while let Ok(Some(res)) = tokio::time::timeout(debounce, rx.recv()).await {
+            collect_event(&root, res, &mut changed);
+        }
+
+        for path in changed {
+            if let Err(err) = syncer.sync(&path).await {
+                eprintln!("failed to sync {path}: {err}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn cli(cmd: Command) -> Command {
+    cmd.about("watch local dir and mirror the changes to destination continuously")
+        .arg(Arg::new("source").required(true))
+        .arg(Arg::new("destination").required(true))
+        .arg(
+            Arg::new("state")
+                .required(false)
+                .long("state")
+                .help("Path to the state dir, `watch` beside the config file by default")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("debounce")
+                .required(false)
+                .long("debounce")
+                .help("Wait for no more changes in this duration before syncing, like `500ms`")
+                .default_value("1s")
+                .value_parser(humantime::parse_duration),
+        )
+        .arg(
+            Arg::new("once")
+                .required(false)
+                .long("once")
+                .help("Sync the changes since last run and exit without watching")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn local_operator(root: &Path) -> Result<Operator> {
+    let mut builder = services::Fs::default();
+    builder.root(&root.to_string_lossy());
+    Ok(Operator::new(builder)?.finish())
+}
+
+/// The state records the local file's size and last modified time at the time
+/// it was uploaded in a plain file at the path relative to source.
+///
+/// Every pair of source and destination has its own dir under the state path.
+fn state_operator(path: &Path, pair: &str) -> Result<Operator> {
+    let mut builder = services::Fs::default();
+    builder.root(&path.join(state_dir_name(pair)).to_string_lossy());
+    Ok(Operator::new(builder)?.finish())
+}
+
+/// Name the state dir by the FNV-1a hash of the pair, which is stable across
+/// runs and builds.
+fn state_dir_name(pair: &str) -> String {
+    let hash = pair.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Collect the changed paths relative to root from the event.
+fn collect_event(root: &Path, res: notify::Result<notify::Event>, changed: &mut BTreeSet<String>) {
+    let event = match res {
+        Ok(event) => event,
+        Err(err) => {
+            eprintln!("failed to watch: {err}");
+            return;
+        }
+    };
+    for path in event.paths {
+        if let Ok(rel) = path.strip_prefix(root) {
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if !rel.is_empty() {
+                changed.insert(rel);
+            }
+        }
+    }
+}
+
+struct Syncer {
+    local: Operator,
+    remote: Operator,
+    remote_root: String,
+    state: Operator,
+}
+
+impl Syncer {
+    /// Sync the changed path, which could be a file or dir, created or removed.
+    async fn sync(&self, path: &str) -> Result<()> {
+        match self.local.stat(path).await {
+            Ok(meta) if meta.is_dir() => self.scan(&format!("{path}/")).await,
+            Ok(meta) => self.upload(path, &meta).await,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                // The removed path could be a file or a dir.
+                if self.state.is_exist(path).await? {
+                    self.delete(path).await?;
+                }
+                self.scan(&format!("{path}/")).await
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Scan all files under dir, upload the changed ones and delete the
+    /// removed ones since last sync.
+    async fn scan(&self, dir: &str) -> Result<()> {
+        let mut seen = HashSet::new();
+        let ds = self
+            .local
+            .lister_with(dir)
+            .recursive(true)
+            .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
+            .await;
+        match ds {
+            Ok(mut ds) => {
+                while let Some(de) = ds.try_next().await? {
+                    if de.metadata().is_dir() {
+                        continue;
+                    }
+                    self.upload(de.path(), de.metadata()).await?;
+                    seen.insert(de.path().to_string());
+                }
+            }
+            // The dir has been removed.
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut ds = self.state.lister_with(dir).recursive(true).await?;
+        while let Some(de) = ds.try_next().await? {
+            if de.path().ends_with('/') || seen.contains(de.path()) {
+                continue;
+            }
+            self.delete(de.path()).await?;
+        }
+        Ok(())
+    }
+
+    async fn upload(&self, path: &str, meta: &Metadata) -> Result<()> {
+        let stamp = stamp(meta);
+        match self.state.read(path).await {
+            Ok(v) if v == stamp.as_bytes() => return Ok(()),
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let reader = self.local.reader(path).await?;
+        let mut writer = self
+            .remote
+            .writer(&format!("{}{path}", self.remote_root))
+            .await?;
+        futures::io::copy(reader, &mut writer).await?;
+        writer.close().await?;
+
+        self.state.write(path, stamp).await?;
+        println!("Uploaded {path}");
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        self.remote
+            .delete(&format!("{}{path}", self.remote_root))
+            .await?;
+        self.state.delete(path).await?;
+        println!("Deleted {path}");
+        Ok(())
+    }
+}
+
+/// Stamp identifies the version of local file by its size and last modified time.
+fn stamp(meta: &Metadata) -> String {
+    let mtime = meta
+        .last_modified()
+        .map(|v| v.timestamp_micros())
+        .unwrap_or_default();
+    format!("{}:{mtime}", meta.content_length())
+}
@@ -125,6 +125,13 @@ impl Config {
         Config { profiles }
     }
 
+    /// Get the names of all loaded profiles in sorted order.
+    pub fn profile_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.profiles.keys().map(|v| v.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
     /// Parse `<profile>://abc/def` into `op` and `location`.
     pub fn parse_location(&self, s: &str) -> Result<(Operator, String)> {
         if !s.contains(":/") {
@@ -0,0 +1,115 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Utils provides the helpers shared by commands.
+
+/// Make sure the path is a dir path so that it can be joined with relative paths.
+pub(crate) fn dir_path(path: &str) -> String {
+    let path = path.trim_start_matches('/');
+    if path.is_empty() || path.ends_with('/') {
+        path.to_string()
+    } else {
+        format!("{path}/")
+    }
+}
+
+/// Check whether both operators point to the same location of the same service.
+pub(crate) fn is_same_operator(a: &opendal::Operator, b: &opendal::Operator) -> bool {
+    let (a, b) = (a.info(), b.info());
+    a.scheme() == b.scheme() && a.root() == b.root() && a.name() == b.name()
+}
+
+/// Format the size in human-readable units like `1.5 MiB`.
+pub(crate) fn format_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Parse the size with optional units like `10`, `4K` or `1.5M`.
+pub(crate) fn parse_size(s: &str) -> anyhow::Result<u64> {
+    let s = s.trim();
+    let (num, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, ""),
+    };
+    let num: f64 = num
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid size: {s}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(anyhow::anyhow!("invalid size unit: {s}")),
+    };
+    Ok((num * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_path() {
+        assert_eq!(dir_path(""), "");
+        assert_eq!(dir_path("/"), "");
+        assert_eq!(dir_path("abc"), "abc/");
+        assert_eq!(dir_path("/abc/def/"), "abc/def/");
+    }
+
+    #[test]
+    fn test_is_same_operator() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let new_op = |root: &std::path::Path| {
+            let mut builder = opendal::services::Fs::default();
+            builder.root(&root.to_string_lossy());
+            opendal::Operator::new(builder).unwrap().finish()
+        };
+        assert!(is_same_operator(&new_op(a.path()), &new_op(a.path())));
+        assert!(!is_same_operator(&new_op(a.path()), &new_op(b.path())));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 << 30), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("10").unwrap(), 10);
+        assert_eq!(parse_size("4K").unwrap(), 4096);
+        assert_eq!(parse_size("1.5MiB").unwrap(), 3 << 19);
+        assert!(parse_size("abc").is_err());
+        assert!(parse_size("1X").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fs;
+use std::process::Command;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+
+#[tokio::test]
+async fn test_basic_bench() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("bench")
+        .arg(dir.path().as_os_str())
+        .args(["--size", "4KiB", "--count", "4"])
+        .args(["--small-count", "8", "--list-count", "2"]);
+    let res = cmd.assert().success();
+    let output = String::from_utf8(res.get_output().stdout.clone())?;
+
+    assert!(output.contains("write (concurrent)"), "{output}");
+    assert!(output.contains("list"), "{output}");
+    // All files created by bench should be removed.
+    assert_eq!(fs::read_dir(dir.path())?.count(), 0);
+    Ok(())
+}
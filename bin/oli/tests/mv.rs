@@ -0,0 +1,82 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fs;
+use std::process::Command;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+
+#[tokio::test]
+async fn test_basic_mv() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_path = dir.path().join("src.txt");
+    let dst_path = dir.path().join("dst.txt");
+    let expect = "hello";
+    fs::write(&src_path, expect)?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("mv")
+        .arg(src_path.as_os_str())
+        .arg(dst_path.as_os_str());
+    cmd.assert().success();
+
+    assert!(!src_path.exists());
+    assert_eq!(expect, fs::read_to_string(&dst_path)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recursive_mv() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_dir = dir.path().join("src");
+    let dst_dir = dir.path().join("dst");
+    fs::create_dir_all(src_dir.join("sub"))?;
+    fs::write(src_dir.join("a.txt"), "a")?;
+    fs::write(src_dir.join("sub/b.txt"), "b")?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("mv")
+        .arg("-r")
+        .arg(src_dir.as_os_str())
+        .arg(dst_dir.as_os_str());
+    cmd.assert().success();
+
+    assert!(!src_dir.exists());
+    assert_eq!("a", fs::read_to_string(dst_dir.join("a.txt"))?);
+    assert_eq!("b", fs::read_to_string(dst_dir.join("sub/b.txt"))?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recursive_mv_into_itself() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_dir = dir.path().join("src");
+    fs::create_dir_all(&src_dir)?;
+    fs::write(src_dir.join("a.txt"), "a")?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("mv")
+        .arg("-r")
+        .arg(src_dir.as_os_str())
+        .arg(src_dir.join("sub").as_os_str());
+    cmd.assert().failure();
+
+    assert_eq!("a", fs::read_to_string(src_dir.join("a.txt"))?);
+    assert!(!src_dir.join("sub").exists());
+    Ok(())
+}
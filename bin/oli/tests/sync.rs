@@ -0,0 +1,74 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fs;
+use std::process::Command;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+
+#[tokio::test]
+async fn test_basic_sync() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_dir = dir.path().join("src");
+    let dst_dir = dir.path().join("dst");
+    fs::create_dir_all(src_dir.join("sub"))?;
+    fs::create_dir_all(&dst_dir)?;
+    fs::write(src_dir.join("a.txt"), "hello")?;
+    fs::write(src_dir.join("sub/b.txt"), "world")?;
+    fs::write(src_dir.join("c.tmp"), "tmp")?;
+    fs::write(dst_dir.join("a.txt"), "old")?;
+    fs::write(dst_dir.join("extra.txt"), "extra")?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("sync")
+        .arg("--delete")
+        .arg("--exclude")
+        .arg("*.tmp")
+        .arg(src_dir.as_os_str())
+        .arg(dst_dir.as_os_str());
+    cmd.assert().success();
+
+    assert_eq!(fs::read_to_string(dst_dir.join("a.txt"))?, "hello");
+    assert_eq!(fs::read_to_string(dst_dir.join("sub/b.txt"))?, "world");
+    assert!(!dst_dir.join("c.tmp").exists());
+    assert!(!dst_dir.join("extra.txt").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sync_dry_run() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_dir = dir.path().join("src");
+    let dst_dir = dir.path().join("dst");
+    fs::create_dir_all(&src_dir)?;
+    fs::create_dir_all(&dst_dir)?;
+    fs::write(src_dir.join("a.txt"), "hello")?;
+    fs::write(dst_dir.join("extra.txt"), "extra")?;
+
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("sync")
+        .arg("--delete")
+        .arg("--dry-run")
+        .arg(src_dir.as_os_str())
+        .arg(dst_dir.as_os_str());
+    cmd.assert().success();
+
+    assert!(!dst_dir.join("a.txt").exists());
+    assert!(dst_dir.join("extra.txt").exists());
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+
+fn watch_once(src: &Path, dst: &Path, state: &Path) -> Result<()> {
+    let mut cmd = Command::cargo_bin("oli")?;
+    cmd.arg("watch")
+        .arg("--once")
+        .arg("--state")
+        .arg(state.as_os_str())
+        .arg(src.as_os_str())
+        .arg(dst.as_os_str());
+    cmd.assert().success();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_once() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let src_dir = dir.path().join("src");
+    let dst_dir = dir.path().join("dst");
+    let state_dir = dir.path().join("state");
+    fs::create_dir_all(src_dir.join("sub"))?;
+    fs::write(src_dir.join("a.txt"), "hello")?;
+    fs::write(src_dir.join("sub/b.txt"), "world")?;
+
+    watch_once(&src_dir, &dst_dir, &state_dir)?;
+    assert_eq!(fs::read_to_string(dst_dir.join("a.txt"))?, "hello");
+    assert_eq!(fs::read_to_string(dst_dir.join("sub/b.txt"))?, "world");
+
+    // Unchanged files should not be uploaded again after restart.
+    fs::write(dst_dir.join("a.txt"), "remote")?;
+    fs::remove_file(src_dir.join("sub/b.txt"))?;
+
+    watch_once(&src_dir, &dst_dir, &state_dir)?;
+    assert_eq!(fs::read_to_string(dst_dir.join("a.txt"))?, "remote");
+    assert!(!dst_dir.join("sub/b.txt").exists());
+    Ok(())
+}
@@ -0,0 +1,1091 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::DateTime;
+use chrono::Utc;
+use tokio::sync::mpsc;
+use tokio::sync::Notify;
+
+use crate::raw::*;
+use crate::*;
+
+/// Emit a structured audit record for every operation.
+///
+/// Every access, no matter reading or mutating, will produce an
+/// [`AuditRecord`] which contains who, when, path, operation, bytes, result
+/// and latency. Records are buffered in memory and flushed to an
+/// [`AuditSink`] in batches, so auditing won't add latency to the wrapped
+/// operations.
+///
+/// # Flush
+///
+/// Records will be flushed while:
+///
+/// - [`AuditLayer::run`] is spawned on runtime: pending records will be
+///   flushed once `batch_size` is reached or every `flush_interval`.
+/// - [`AuditLayer::flush`] is called: all pending records will be flushed.
+///   Users should call it before shutdown to make sure no records are lost.
+///
+/// Failed batches will be kept and retried in next flush.
+///
+/// At most `max_pending` records will be kept in memory, including the ones
+/// of in-flight operations. Once exceeded, new operations will fail with
+/// [`ErrorKind::RateLimited`] until records are flushed. Users can choose to
+/// drop the oldest records instead by [`AuditOverflow::DropOldest`].
+///
+/// # Sinks
+///
+/// - [`OperatorAuditSink`]: Write JSON lines into another [`Operator`].
+/// - [`ChannelAuditSink`]: Send records into a channel.
+/// - [`LogAuditSink`]: Emit JSON lines via the `log` crate.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::layers::AuditLayer;
+/// use opendal::layers::OperatorAuditSink;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let mut builder = services::Fs::default();
+/// builder.root("/var/log/opendal");
+/// let audit = Operator::new(builder)?.finish();
+/// let layer = AuditLayer::new(OperatorAuditSink::new(audit, "audit.jsonl")).with_actor("alice");
+/// tokio::spawn(layer.clone().run());
+///
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(layer.clone())
+///     .finish();
+/// op.write("test", "Hello, World!").await?;
+///
+/// layer.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AuditLayer {
+    sink: Arc<dyn AuditSink>,
+    actor: Option<String>,
+    batch_size: usize,
+    flush_interval: Duration,
+    max_pending: usize,
+    overflow: AuditOverflow,
+    buffer: Arc<AuditBuffer>,
+}
+
+impl AuditLayer {
+    /// Create a new `AuditLayer` which flushes records into given sink.
+    pub fn new(sink: impl AuditSink) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            actor: None,
+            batch_size: 128,
+            flush_interval: Duration::from_secs(1),
+            max_pending: 10240,
+            overflow: AuditOverflow::default(),
+            buffer: Arc::default(),
+        }
+    }
+
+    /// Set the actor that performs the operations, will be recorded as `actor`.
+    pub fn with_actor(mut self, actor: &str) -> Self {
+        self.actor = Some(actor.to_string());
+        self
+    }
+
+    /// Set the count of records that triggers a flush.
+    ///
+    /// Default to 128.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the max interval between two flushes.
+    ///
+    /// Default to 1s.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Set the max count of records that can be kept in memory.
+    ///
+    /// Default to 10240.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    /// Set how to handle new records once `max_pending` is reached.
+    ///
+    /// Default to [`AuditOverflow::Reject`].
+    pub fn with_overflow(mut self, overflow: AuditOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Get the count of records that have not been flushed.
+    pub fn pending(&self) -> usize {
+        self.buffer.records().len()
+    }
+
+    /// Get the count of records that have been dropped because too many
+    /// records are pending, only happens with [`AuditOverflow::DropOldest`].
+    pub fn dropped(&self) -> u64 {
+        self.buffer.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Flush all pending records into sink.
+    pub async fn flush(&self) -> Result<()> {
+        let _guard = self.buffer.flushing.lock().await;
+
+        let mut records = std::mem::take(&mut *self.buffer.records());
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(err) = self.sink.write(records.make_contiguous()).await {
+            // Put records back so that they can be flushed next time.
+            let mut buf = self.buffer.records();
+            let newer = std::mem::replace(&mut *buf, records);
+            buf.extend(newer);
+            if self.overflow == AuditOverflow::DropOldest {
+                self.buffer.drop_oldest(&mut buf, self.max_pending);
+            }
+            return Err(err);
+        }
+
+        self.buffer.release(records.len());
+        Ok(())
+    }
+
+    /// Run the background flusher.
+    ///
+    /// This future never ends, users should spawn it on their runtime.
+    pub async fn run(self) {
+        loop {
+            let _ = tokio::time::timeout(self.flush_interval, self.buffer.notify.notified()).await;
+
+            if let Err(err) = self.flush().await {
+                log::warn!(target: LOG_TARGET, "flush audit records failed: {err}");
+            }
+        }
+    }
+}
+
+impl<A: Accessor> Layer<A> for AuditLayer {
+    type LayeredAccessor = AuditAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let scheme = inner.info().scheme();
+
+        AuditAccessor {
+            inner,
+            ctx: Arc::new(AuditContext {
+                scheme,
+                actor: self.actor.clone(),
+                batch_size: self.batch_size,
+                max_pending: self.max_pending,
+                overflow: self.overflow,
+                buffer: self.buffer.clone(),
+            }),
+        }
+    }
+}
+
+const LOG_TARGET: &str = "opendal::audit";
+
+/// AuditOverflow controls how [`AuditLayer`] handles new records once too
+/// many records are pending.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuditOverflow {
+    /// Fail new operations with a temporary [`ErrorKind::RateLimited`] error
+    /// until pending records are flushed, so that no record will be lost.
+    #[default]
+    Reject,
+    /// Accept new operations and drop the oldest records, dropped records are
+    /// counted by [`AuditLayer::dropped`].
+    DropOldest,
+}
+
+/// AuditRecord is a structured record of one access.
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    timestamp: DateTime<Utc>,
+    actor: Option<String>,
+    scheme: Scheme,
+    operation: &'static str,
+    path: String,
+    target: Option<String>,
+    bytes: Option<u64>,
+    error: Option<(ErrorKind, String)>,
+    latency: Duration,
+}
+
+impl AuditRecord {
+    /// The time that the operation started.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    /// The actor who performed the operation.
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    /// The scheme of the audited storage.
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// The operation that has been performed.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// The path that has been accessed.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The target path of `copy` and `rename`.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// The bytes that have been read or written.
+    ///
+    /// Only available for `read` and `write`.
+    pub fn bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    /// Check if the operation succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The error kind that the operation failed with.
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        self.error.as_ref().map(|(kind, _)| *kind)
+    }
+
+    /// The latency of the operation.
+    ///
+    /// For `read` and `write`, latency is counted until the reader or writer
+    /// is finished.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Serialize this record into a JSON line without trailing newline.
+    pub fn to_json(&self) -> String {
+        let (result, error) = match &self.error {
+            None => ("ok", None),
+            Some((kind, msg)) => (kind.into_static(), Some(msg.as_str())),
+        };
+
+        serde_json::json!({
+            "timestamp": self.timestamp.to_rfc3339(),
+            "actor": self.actor,
+            "scheme": self.scheme.into_static(),
+            "operation": self.operation,
+            "path": self.path,
+            "target": self.target,
+            "bytes": self.bytes,
+            "result": result,
+            "error": error,
+            "latency_us": self.latency.as_micros() as u64,
+        })
+        .to_string()
+    }
+}
+
+/// AuditSink is the destination of audit records.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait AuditSink: Debug + Send + Sync + 'static {
+    /// Write a batch of records into sink.
+    ///
+    /// The whole batch will be retried if an error is returned.
+    async fn write(&self, records: &[AuditRecord]) -> Result<()>;
+}
+
+/// Write audit records as JSON lines into an [`Operator`].
+///
+/// Records will be appended to `path` if the operator supports append.
+/// Otherwise, every batch will be written into a new file named
+/// `{path}.{timestamp}-{seq}`.
+#[derive(Debug)]
+pub struct OperatorAuditSink {
+    op: Operator,
+    path: String,
+    seq: AtomicU64,
+}
+
+impl OperatorAuditSink {
+    /// Create a new sink which writes records into `path` of given operator.
+    pub fn new(op: Operator, path: &str) -> Self {
+        Self {
+            op,
+            path: path.to_string(),
+            seq: AtomicU64::new(0),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AuditSink for OperatorAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        let mut buf = String::new();
+        for record in records {
+            buf.push_str(&record.to_json());
+            buf.push('\n');
+        }
+
+        if self.op.info().full_capability().write_can_append {
+            self.op.write_with(&self.path, buf).append(true).await
+        } else {
+            let path = format!(
+                "{}.{}-{}",
+                self.path,
+                Utc::now().timestamp_millis(),
+                self.seq.fetch_add(1, Ordering::Relaxed)
+            );
+            self.op.write(&path, buf).await
+        }
+    }
+}
+
+/// Send audit records into a channel.
+#[derive(Debug)]
+pub struct ChannelAuditSink {
+    tx: mpsc::Sender<AuditRecord>,
+}
+
+impl ChannelAuditSink {
+    /// Create a new sink which sends records into given sender.
+    pub fn new(tx: mpsc::Sender<AuditRecord>) -> Self {
+        Self { tx }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AuditSink for ChannelAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        for record in records {
+            self.tx
+                .send(record.clone())
+                .await
+                .map_err(|_| Error::new(ErrorKind::Unexpected, "audit channel has been closed"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Emit audit records as JSON lines via the `log` crate.
+///
+/// Records are emitted under target `opendal::audit`.
+#[derive(Debug)]
+pub struct LogAuditSink {
+    level: log::Level,
+}
+
+impl Default for LogAuditSink {
+    fn default() -> Self {
+        Self {
+            level: log::Level::Info,
+        }
+    }
+}
+
+impl LogAuditSink {
+    /// Set the level of emitted records.
+    ///
+    /// Default to [`log::Level::Info`].
+    pub fn with_level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AuditSink for LogAuditSink {
+    async fn write(&self, records: &[AuditRecord]) -> Result<()> {
+        for record in records {
+            log::log!(target: LOG_TARGET, self.level, "{}", record.to_json());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct AuditBuffer {
+    records: Mutex<VecDeque<AuditRecord>>,
+    /// The count of records that are buffered, being flushed or belong to
+    /// in-flight operations, only used by [`AuditOverflow::Reject`].
+    reserved: AtomicUsize,
+    /// The count of records dropped because too many records are pending.
+    dropped: AtomicU64,
+    notify: Notify,
+    /// Make sure only one flush is running at the same time.
+    flushing: tokio::sync::Mutex<()>,
+}
+
+impl AuditBuffer {
+    fn records(&self) -> std::sync::MutexGuard<'_, VecDeque<AuditRecord>> {
+        self.records.lock().expect("lock must be acquired")
+    }
+
+    /// Reserve `n` records for new operations, returns `false` if there is
+    /// no enough room.
+    fn reserve(&self, n: usize, max_pending: usize) -> bool {
+        self.reserved
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                (v + n <= max_pending).then_some(v + n)
+            })
+            .is_ok()
+    }
+
+    /// Release the reserved records after they have been flushed.
+    fn release(&self, n: usize) {
+        let _ = self
+            .reserved
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                Some(v.saturating_sub(n))
+            });
+    }
+
+    /// Drop the oldest records until at most `max_pending` records are left.
+    fn drop_oldest(&self, records: &mut VecDeque<AuditRecord>, max_pending: usize) {
+        let excess = records.len().saturating_sub(max_pending);
+        if excess > 0 {
+            records.drain(..excess);
+            self.dropped.fetch_add(excess as u64, Ordering::Relaxed);
+            log::warn!(target: LOG_TARGET, "too many pending audit records, {excess} dropped");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AuditContext {
+    scheme: Scheme,
+    actor: Option<String>,
+    batch_size: usize,
+    max_pending: usize,
+    overflow: AuditOverflow,
+    buffer: Arc<AuditBuffer>,
+}
+
+impl AuditContext {
+    /// Reserve room for `n` records before starting operations.
+    fn reserve(&self, op: Operation, n: usize) -> Result<()> {
+        if self.overflow == AuditOverflow::DropOldest || self.buffer.reserve(n, self.max_pending) {
+            return Ok(());
+        }
+
+        // Wake up the flusher since we are full.
+        self.buffer.notify.notify_one();
+        Err(
+            Error::new(ErrorKind::RateLimited, "too many pending audit records")
+                .with_operation(op)
+                .with_context("max_pending", self.max_pending.to_string())
+                .set_temporary(),
+        )
+    }
+
+    /// Start an operation that will be recorded once finished.
+    fn start(self: &Arc<Self>, op: Operation, path: &str) -> Result<AuditEntry> {
+        self.reserve(op, 1)?;
+        Ok(self.entry(op, path))
+    }
+
+    /// Create an entry whose record has been reserved.
+    fn entry(self: &Arc<Self>, op: Operation, path: &str) -> AuditEntry {
+        AuditEntry {
+            ctx: self.clone(),
+            timestamp: Utc::now(),
+            start: Instant::now(),
+            operation: op.into_static(),
+            path: path.to_string(),
+            target: None,
+        }
+    }
+
+    fn push(&self, record: AuditRecord) {
+        let mut records = self.buffer.records();
+        records.push_back(record);
+        if self.overflow == AuditOverflow::DropOldest {
+            self.buffer.drop_oldest(&mut records, self.max_pending);
+        }
+        if records.len() >= self.batch_size {
+            self.buffer.notify.notify_one();
+        }
+    }
+}
+
+/// AuditEntry is an in-flight operation that will be recorded once finished.
+struct AuditEntry {
+    ctx: Arc<AuditContext>,
+    timestamp: DateTime<Utc>,
+    start: Instant,
+    operation: &'static str,
+    path: String,
+    target: Option<String>,
+}
+
+impl AuditEntry {
+    fn with_target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    fn finish<T>(self, bytes: Option<u64>, res: &Result<T>) {
+        self.finish_with(
+            bytes,
+            res.as_ref().err().map(|err| (err.kind(), err.to_string())),
+        )
+    }
+
+    fn finish_with(self, bytes: Option<u64>, error: Option<(ErrorKind, String)>) {
+        let record = AuditRecord {
+            timestamp: self.timestamp,
+            actor: self.ctx.actor.clone(),
+            scheme: self.ctx.scheme,
+            operation: self.operation,
+            path: self.path,
+            target: self.target,
+            bytes,
+            error,
+            latency: self.start.elapsed(),
+        };
+        self.ctx.push(record);
+    }
+}
+
+#[derive(Debug)]
+pub struct AuditAccessor<A: Accessor> {
+    inner: A,
+    ctx: Arc<AuditContext>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for AuditAccessor<A> {
+    type Inner = A;
+    type Reader = AuditReader<A::Reader>;
+    type BlockingReader = AuditReader<A::BlockingReader>;
+    type Writer = AuditWriter<A::Writer>;
+    type BlockingWriter = AuditWriter<A::BlockingWriter>;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let entry = self.ctx.start(Operation::CreateDir, path)?;
+        let res = self.inner.create_dir(path, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let entry = self.ctx.start(Operation::Read, path)?;
+        match self.inner.read(path, args).await {
+            Ok((rp, r)) => Ok((rp, AuditReader::new(r, entry))),
+            Err(err) => {
+                entry.finish_with(Some(0), Some((err.kind(), err.to_string())));
+                Err(err)
+            }
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let entry = self.ctx.start(Operation::Write, path)?;
+        match self.inner.write(path, args).await {
+            Ok((rp, w)) => Ok((rp, AuditWriter::new(w, entry))),
+            Err(err) => {
+                entry.finish_with(Some(0), Some((err.kind(), err.to_string())));
+                Err(err)
+            }
+        }
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let entry = self.ctx.start(Operation::Copy, from)?.with_target(to);
+        let res = self.inner.copy(from, to, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let entry = self.ctx.start(Operation::Rename, from)?.with_target(to);
+        let res = self.inner.rename(from, to, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let entry = self.ctx.start(Operation::Stat, path)?;
+        let res = self.inner.stat(path, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let entry = self.ctx.start(Operation::Delete, path)?;
+        let res = self.inner.delete(path, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let entry = self.ctx.start(Operation::List, path)?;
+        let res = self.inner.list(path, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let timestamp = Utc::now();
+        let start = Instant::now();
+        let ops = args
+            .operation()
+            .iter()
+            .map(|(path, op)| (path.clone(), op.operation()))
+            .collect::<Vec<_>>();
+        self.ctx.reserve(Operation::Batch, ops.len())?;
+
+        let res = self.inner.batch(args).await;
+        // Every path in batch will be recorded separately, sharing the latency
+        // of the whole batch.
+        for (path, op) in ops {
+            let error = match &res {
+                Ok(rp) => rp
+                    .results()
+                    .iter()
+                    .find(|(p, _)| p == &path)
+                    .and_then(|(_, res)| res.as_ref().err())
+                    .map(|err| (err.kind(), err.to_string())),
+                Err(err) => Some((err.kind(), err.to_string())),
+            };
+            let mut entry = self.ctx.entry(op, &path);
+            entry.timestamp = timestamp;
+            entry.start = start;
+            entry.finish_with(None, error);
+        }
+        res
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let entry = self.ctx.start(Operation::Presign, path)?;
+        let res = self.inner.presign(path, args).await;
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let entry = self.ctx.start(Operation::BlockingCreateDir, path)?;
+        let res = self.inner.blocking_create_dir(path, args);
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let entry = self.ctx.start(Operation::BlockingRead, path)?;
+        match self.inner.blocking_read(path, args) {
+            Ok((rp, r)) => Ok((rp, AuditReader::new(r, entry))),
+            Err(err) => {
+                entry.finish_with(Some(0), Some((err.kind(), err.to_string())));
+                Err(err)
+            }
+        }
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let entry = self.ctx.start(Operation::BlockingWrite, path)?;
+        match self.inner.blocking_write(path, args) {
+            Ok((rp, w)) => Ok((rp, AuditWriter::new(w, entry))),
+            Err(err) => {
+                entry.finish_with(Some(0), Some((err.kind(), err.to_string())));
+                Err(err)
+            }
+        }
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let entry = self
+            .ctx
+            .start(Operation::BlockingCopy, from)?
+            .with_target(to);
+        let res = self.inner.blocking_copy(from, to, args);
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let entry = self
+            .ctx
+            .start(Operation::BlockingRename, from)?
+            .with_target(to);
+        let res = self.inner.blocking_rename(from, to, args);
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let entry = self.ctx.start(Operation::BlockingStat, path)?;
+        let res = self.inner.blocking_stat(path, args);
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let entry = self.ctx.start(Operation::BlockingDelete, path)?;
+        let res = self.inner.blocking_delete(path, args);
+        entry.finish(None, &res);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        let entry = self.ctx.start(Operation::BlockingList, path)?;
+        let res = self.inner.blocking_list(path, args);
+        entry.finish(None, &res);
+        res
+    }
+}
+
+/// AuditReader counts the bytes that have been read.
+///
+/// The record will be emitted once reader reaches the end, meets an error or
+/// is dropped.
+pub struct AuditReader<R> {
+    inner: R,
+    entry: Option<AuditEntry>,
+    bytes: u64,
+}
+
+impl<R> AuditReader<R> {
+    fn new(inner: R, entry: AuditEntry) -> Self {
+        Self {
+            inner,
+            entry: Some(entry),
+            bytes: 0,
+        }
+    }
+
+    fn finish(&mut self, error: Option<&Error>) {
+        if let Some(entry) = self.entry.take() {
+            entry.finish_with(
+                Some(self.bytes),
+                error.map(|err| (err.kind(), err.to_string())),
+            );
+        }
+    }
+
+    fn handle_read(&mut self, res: &Result<usize>, buf: &[u8]) {
+        match res {
+            Ok(0) if !buf.is_empty() => self.finish(None),
+            Ok(n) => self.bytes += *n as u64,
+            Err(err) => self.finish(Some(err)),
+        }
+    }
+
+    fn handle_next(&mut self, res: &Option<Result<Bytes>>) {
+        match res {
+            None => self.finish(None),
+            Some(Ok(bs)) => self.bytes += bs.len() as u64,
+            Some(Err(err)) => self.finish(Some(err)),
+        }
+    }
+}
+
+impl<R> Drop for AuditReader<R> {
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
+
+impl<R: oio::Read> oio::Read for AuditReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_read(cx, buf));
+        self.handle_read(&res, buf);
+        Poll::Ready(res)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = ready!(self.inner.poll_next(cx));
+        self.handle_next(&res);
+        Poll::Ready(res)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for AuditReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let res = self.inner.read(buf);
+        self.handle_read(&res, buf);
+        res
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let res = self.inner.next();
+        self.handle_next(&res);
+        res
+    }
+}
+
+/// AuditWriter counts the bytes that have been written.
+///
+/// The record will be emitted once writer is closed, aborted, meets an error
+/// or is dropped.
+pub struct AuditWriter<W> {
+    inner: W,
+    entry: Option<AuditEntry>,
+    bytes: u64,
+}
+
+impl<W> AuditWriter<W> {
+    fn new(inner: W, entry: AuditEntry) -> Self {
+        Self {
+            inner,
+            entry: Some(entry),
+            bytes: 0,
+        }
+    }
+
+    fn finish(&mut self, error: Option<(ErrorKind, String)>) {
+        if let Some(entry) = self.entry.take() {
+            entry.finish_with(Some(self.bytes), error);
+        }
+    }
+
+    fn handle_write(&mut self, res: &Result<usize>) {
+        match res {
+            Ok(n) => self.bytes += *n as u64,
+            Err(err) => self.finish(Some((err.kind(), err.to_string()))),
+        }
+    }
+
+    fn handle_close(&mut self, res: &Result<()>) {
+        self.finish(res.as_ref().err().map(|err| (err.kind(), err.to_string())));
+    }
+}
+
+impl<W> Drop for AuditWriter<W> {
+    fn drop(&mut self) {
+        // Writer dropped without close will not be committed.
+        self.finish(Some((
+            ErrorKind::Unexpected,
+            "writer has been dropped without close".to_string(),
+        )));
+    }
+}
+
+impl<W: oio::Write> oio::Write for AuditWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_write(cx, bs));
+        self.handle_write(&res);
+        Poll::Ready(res)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_close(cx));
+        self.handle_close(&res);
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_abort(cx));
+        self.finish(Some((
+            ErrorKind::Unexpected,
+            "writer has been aborted".to_string(),
+        )));
+        Poll::Ready(res)
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for AuditWriter<W> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let res = self.inner.write(bs);
+        self.handle_write(&res);
+        res
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close();
+        self.handle_close(&res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_audit_records() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let layer = AuditLayer::new(ChannelAuditSink::new(tx)).with_actor("alice");
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        op.write("test", "Hello, World!").await.unwrap();
+        op.read("test").await.unwrap();
+        op.stat("not_exist").await.unwrap_err();
+        assert_eq!(layer.pending(), 4);
+
+        layer.flush().await.unwrap();
+        assert_eq!(layer.pending(), 0);
+
+        let write = rx.recv().await.unwrap();
+        assert_eq!(write.operation(), "write");
+        assert_eq!(write.actor(), Some("alice"));
+        assert_eq!(write.bytes(), Some(13));
+        assert!(write.is_ok());
+
+        // Operator::read will stat first.
+        assert_eq!(rx.recv().await.unwrap().operation(), "stat");
+        let read = rx.recv().await.unwrap();
+        assert_eq!(read.operation(), "read");
+        assert_eq!(read.bytes(), Some(13));
+
+        let stat = rx.recv().await.unwrap();
+        assert_eq!(stat.path(), "not_exist");
+        assert_eq!(stat.error_kind(), Some(ErrorKind::NotFound));
+
+        let json: serde_json::Value = serde_json::from_str(&stat.to_json()).unwrap();
+        assert_eq!(json["result"], "NotFound");
+        assert_eq!(json["scheme"], "memory");
+    }
+
+    #[tokio::test]
+    async fn test_audit_drop_oldest() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let layer = AuditLayer::new(ChannelAuditSink::new(tx))
+            .with_max_pending(2)
+            .with_overflow(AuditOverflow::DropOldest);
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        for path in ["a", "b", "c"] {
+            op.stat(path).await.unwrap_err();
+        }
+        assert_eq!(layer.pending(), 2);
+        assert_eq!(layer.dropped(), 1);
+
+        layer.flush().await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().path(), "b");
+        assert_eq!(rx.recv().await.unwrap().path(), "c");
+    }
+
+    #[tokio::test]
+    async fn test_audit_reject() {
+        let (tx, mut rx) = mpsc::channel(16);
+        let layer = AuditLayer::new(ChannelAuditSink::new(tx)).with_max_pending(2);
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        op.stat("a").await.unwrap_err();
+        op.stat("b").await.unwrap_err();
+        let err = op.stat("c").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(layer.pending(), 2);
+        assert_eq!(layer.dropped(), 0);
+
+        // New operations are accepted after flushed.
+        layer.flush().await.unwrap();
+        op.stat("d").await.unwrap_err();
+        layer.flush().await.unwrap();
+        for path in ["a", "b", "d"] {
+            assert_eq!(rx.recv().await.unwrap().path(), path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_operator_sink() {
+        let audit = Operator::new(Memory::default()).unwrap().finish();
+        let layer = AuditLayer::new(OperatorAuditSink::new(audit.clone(), "audit/log.jsonl"))
+            .with_batch_size(1)
+            .with_flush_interval(Duration::from_secs(3600));
+        let flusher = tokio::spawn(layer.clone().run());
+
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+        op.create_dir("dir/").await.unwrap();
+
+        // Records should be flushed in background.
+        let mut entries = vec![];
+        for _ in 0..100 {
+            entries = audit.list("audit/").await.unwrap();
+            if !entries.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        flusher.abort();
+
+        assert_eq!(entries.len(), 1);
+        let content = audit.read(entries[0].path()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(json["operation"], "create_dir");
+        assert_eq!(json["path"], "dir/");
+        assert_eq!(json["result"], "ok");
+    }
+}
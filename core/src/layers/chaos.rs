@@ -16,15 +16,25 @@
 // under the License.
 
 use std::io;
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::FutureExt;
+use flagset::flags;
+use flagset::FlagSet;
+use futures::Future;
 use rand::prelude::*;
 use rand::rngs::StdRng;
 
+use crate::layers::PathPattern;
 use crate::raw::*;
 use crate::*;
 
@@ -40,10 +50,17 @@ use crate::*;
 /// For example: If we specify an error rate of 0.5, there is a 50% chance
 /// of an EOF error for every read operation.
 ///
-/// # Note
+/// # Rules
 ///
-/// For now, ChaosLayer only injects read operations. More operations may
-/// be added in the future.
+/// More faults can be injected by [`ChaosRule`]. Every rule decides which
+/// [`ChaosFault`] to inject, on which [`ChaosOperation`] and paths, and how often.
+/// Rules are checked in order, and the first rule that fires wins.
+///
+/// # Replay
+///
+/// All random decisions are made by a rng seeded with [`ChaosLayer::seed`].
+/// Setting the same seed by [`ChaosLayer::with_seed`] will replay the same
+/// faults as long as the operations are issued in the same order.
 ///
 /// # Examples
 ///
@@ -59,14 +76,58 @@ use crate::*;
 ///     .layer(ChaosLayer::new(0.1))
 ///     .finish();
 /// ```
+///
+/// Inject more faults with rules:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::ChaosFault;
+/// use opendal::layers::ChaosLayer;
+/// use opendal::layers::ChaosOperation;
+/// use opendal::layers::ChaosRule;
+/// use opendal::services;
+/// use opendal::ErrorKind;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         ChaosLayer::new(0.0)
+///             .with_seed(42)
+///             .with_rule(
+///                 ChaosRule::new(ChaosFault::TemporaryError(ErrorKind::RateLimited))
+///                     .with_operations(ChaosOperation::Stat | ChaosOperation::List)
+///                     .with_ratio(0.1),
+///             )
+///             .with_rule(
+///                 ChaosRule::new(ChaosFault::Latency(Duration::from_millis(100)))
+///                     .with_path("logs/**/*.json"),
+///             )
+///             .with_rule(
+///                 // Fail the 3rd write call of every writer under `upload/`.
+///                 ChaosRule::new(ChaosFault::Error(ErrorKind::Unexpected))
+///                     .with_operations(ChaosOperation::WriterWrite)
+///                     .with_path("upload/")
+///                     .with_after(2)
+///                     .with_times(1),
+///             ),
+///     )
+///     .finish();
+/// ```
 #[derive(Debug, Clone)]
 pub struct ChaosLayer {
-    error_ratio: f64,
+    rules: Vec<ChaosRule>,
+    seed: u64,
 }
 
 impl ChaosLayer {
     /// Create a new chaos layer with specified error ratio.
     ///
+    /// Temporary errors will be injected into read operations at this ratio.
+    /// Use `0.0` to only inject the faults of rules.
+    ///
     /// # Panics
     ///
     /// Input error_ratio must in [0.0..=1.0]
@@ -75,7 +136,39 @@ impl ChaosLayer {
             (0.0..=1.0).contains(&error_ratio),
             "error_ratio must between 0.0 and 1.0"
         );
-        Self { error_ratio }
+
+        let mut rules = Vec::new();
+        if error_ratio > 0.0 {
+            rules.push(
+                ChaosRule::new(ChaosFault::TemporaryError(ErrorKind::Unexpected))
+                    .with_operations(ChaosOperation::ReaderRead)
+                    .with_ratio(error_ratio),
+            );
+        }
+
+        Self {
+            rules,
+            seed: thread_rng().gen(),
+        }
+    }
+
+    /// Set the seed of the rng, so that the faults can be replayed.
+    ///
+    /// Default to a random seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The seed used by this layer, print it while test failed to replay the faults.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Append a rule.
+    pub fn with_rule(mut self, rule: ChaosRule) -> Self {
+        self.rules.push(rule);
+        self
     }
 }
 
@@ -85,8 +178,274 @@ impl<A: Accessor> Layer<A> for ChaosLayer {
     fn layer(&self, inner: A) -> Self::LayeredAccessor {
         ChaosAccessor {
             inner,
-            rng: StdRng::from_entropy(),
-            error_ratio: self.error_ratio,
+            chaos: Arc::new(Chaos {
+                counters: RuleCounter::new_vec(self.rules.len()),
+                rules: self.rules.clone(),
+                rng: Mutex::new(StdRng::seed_from_u64(self.seed)),
+            }),
+        }
+    }
+}
+
+flags! {
+    /// ChaosOperation is the operation that [`ChaosRule`] applies to.
+    ///
+    /// Blocking operations share the same kind with async ones.
+    pub enum ChaosOperation: u16 {
+        /// `create_dir`
+        CreateDir,
+        /// `read`, the call that opens a reader.
+        Read,
+        /// `write`, the call that opens a writer.
+        Write,
+        /// `copy`
+        Copy,
+        /// `rename`
+        Rename,
+        /// `stat`
+        Stat,
+        /// `delete`
+        Delete,
+        /// `list`, the call that opens a lister.
+        List,
+        /// `batch`, faults are injected into every operation in the batch.
+        Batch,
+        /// `presign`
+        Presign,
+        /// `read`, `seek` and `next` of a reader.
+        ReaderRead,
+        /// `write` of a writer.
+        WriterWrite,
+        /// `close` of a writer.
+        WriterClose,
+        /// `next` of a lister.
+        ListerNext,
+    }
+}
+
+/// ChaosFault is the fault injected by [`ChaosRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChaosFault {
+    /// Return a permanent error with given kind.
+    Error(ErrorKind),
+    /// Return a temporary error with given kind, which could be retried.
+    TemporaryError(ErrorKind),
+    /// Sleep for given duration before the operation.
+    Latency(Duration),
+    /// Reader returns EOF from now on.
+    ///
+    /// Only applies to [`ChaosOperation::ReaderRead`].
+    TruncatedRead,
+    /// Flip a random bit in the data returned by reader.
+    ///
+    /// Only applies to [`ChaosOperation::ReaderRead`].
+    CorruptedRead,
+    /// Writer only accepts part of the given data.
+    ///
+    /// Only applies to [`ChaosOperation::WriterWrite`].
+    ShortWrite,
+}
+
+impl ChaosFault {
+    fn applies_to(&self, op: ChaosOperation) -> bool {
+        match self {
+            ChaosFault::Error(_) | ChaosFault::TemporaryError(_) | ChaosFault::Latency(_) => true,
+            ChaosFault::TruncatedRead | ChaosFault::CorruptedRead => {
+                op == ChaosOperation::ReaderRead
+            }
+            ChaosFault::ShortWrite => op == ChaosOperation::WriterWrite,
+        }
+    }
+}
+
+/// ChaosRule decides when to inject a [`ChaosFault`].
+#[derive(Debug, Clone)]
+pub struct ChaosRule {
+    fault: ChaosFault,
+    operations: FlagSet<ChaosOperation>,
+    pattern: Option<PathPattern>,
+    ratio: f64,
+    after: usize,
+    times: Option<usize>,
+}
+
+impl ChaosRule {
+    /// Create a new rule which injects the fault into all operations it applies to.
+    pub fn new(fault: ChaosFault) -> Self {
+        Self {
+            fault,
+            operations: FlagSet::full(),
+            pattern: None,
+            ratio: 1.0,
+            after: 0,
+            times: None,
+        }
+    }
+
+    /// Only inject into given operations.
+    pub fn with_operations(mut self, operations: impl Into<FlagSet<ChaosOperation>>) -> Self {
+        self.operations = operations.into();
+        self
+    }
+
+    /// Only inject into paths matching the pattern.
+    ///
+    /// Patterns contain `*` or `?` are globs, `*` and `?` match any characters
+    /// except `/`, `**` matches any characters including `/`. Other patterns
+    /// are prefixes.
+    pub fn with_path(mut self, pattern: &str) -> Self {
+        self.pattern = Some(PathPattern::new(pattern));
+        self
+    }
+
+    /// Set the ratio to inject the fault when the rule matches.
+    ///
+    /// Default to `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// Input ratio must in [0.0..=1.0]
+    pub fn with_ratio(mut self, ratio: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "ratio must between 0.0 and 1.0"
+        );
+        self.ratio = ratio;
+        self
+    }
+
+    /// Skip the first `n` matched calls.
+    ///
+    /// Calls of [`ChaosOperation::ReaderRead`], [`ChaosOperation::WriterWrite`],
+    /// [`ChaosOperation::WriterClose`] and [`ChaosOperation::ListerNext`] are
+    /// counted per reader, writer or lister, other calls are counted across the
+    /// whole layer.
+    ///
+    /// For example, `with_after(2)` on [`ChaosOperation::WriterWrite`] fails
+    /// from the 3rd write call of every writer, which can be used to fail in the
+    /// middle of multipart uploads.
+    pub fn with_after(mut self, n: usize) -> Self {
+        self.after = n;
+        self
+    }
+
+    /// Inject the fault at most `n` times.
+    ///
+    /// Injected faults are counted in the same way as [`ChaosRule::with_after`].
+    pub fn with_times(mut self, n: usize) -> Self {
+        self.times = Some(n);
+        self
+    }
+
+    fn matches(&self, op: ChaosOperation, path: &str) -> bool {
+        self.operations.contains(op)
+            && self.fault.applies_to(op)
+            && self.pattern.as_ref().map_or(true, |v| v.matches(path))
+    }
+}
+
+/// RuleCounter counts the matched calls and injected faults of a rule.
+#[derive(Debug, Default)]
+struct RuleCounter {
+    matched: AtomicUsize,
+    injected: AtomicUsize,
+}
+
+impl RuleCounter {
+    fn new_vec(n: usize) -> Vec<Self> {
+        (0..n).map(|_| Self::default()).collect()
+    }
+}
+
+/// Chaos holds the shared state of all rules.
+#[derive(Debug)]
+struct Chaos {
+    rules: Vec<ChaosRule>,
+    /// counters of operations on accessor, IO operations have their own.
+    counters: Vec<RuleCounter>,
+    rng: Mutex<StdRng>,
+}
+
+impl Chaos {
+    /// Pick the fault to inject for this call.
+    fn pick(&self, op: ChaosOperation, path: &str) -> Option<ChaosFault> {
+        self.pick_with(op, path, &self.counters)
+    }
+
+    /// Pick the fault to inject for this call with given counters.
+    fn pick_with(
+        &self,
+        op: ChaosOperation,
+        path: &str,
+        counters: &[RuleCounter],
+    ) -> Option<ChaosFault> {
+        let mut rng = self.rng.lock().expect("lock must succeed");
+        for (rule, counter) in self.rules.iter().zip(counters) {
+            if !rule.matches(op, path) {
+                continue;
+            }
+            if counter.matched.fetch_add(1, Ordering::Relaxed) < rule.after {
+                continue;
+            }
+            if rule
+                .times
+                .map_or(false, |v| counter.injected.load(Ordering::Relaxed) >= v)
+            {
+                continue;
+            }
+            if rng.gen_bool(rule.ratio) {
+                counter.injected.fetch_add(1, Ordering::Relaxed);
+                return Some(rule.fault);
+            }
+        }
+        None
+    }
+
+    fn gen_range(&self, end: usize) -> usize {
+        self.rng
+            .lock()
+            .expect("lock must succeed")
+            .gen_range(0..end)
+    }
+
+    fn error(fault: ChaosFault, operation: &'static str, path: &str) -> Option<Error> {
+        let (kind, temporary) = match fault {
+            ChaosFault::Error(kind) => (kind, false),
+            ChaosFault::TemporaryError(kind) => (kind, true),
+            _ => return None,
+        };
+
+        let err = Error::new(kind, "I am your chaos!")
+            .with_operation(operation)
+            .with_context("path", path);
+        Some(if temporary { err.set_temporary() } else { err })
+    }
+
+    async fn inject(&self, op: ChaosOperation, operation: &'static str, path: &str) -> Result<()> {
+        match self.pick(op, path) {
+            Some(ChaosFault::Latency(d)) => {
+                tokio::time::sleep(d).await;
+                Ok(())
+            }
+            Some(fault) => Self::error(fault, operation, path).map_or(Ok(()), Err),
+            None => Ok(()),
+        }
+    }
+
+    fn blocking_inject(
+        &self,
+        op: ChaosOperation,
+        operation: &'static str,
+        path: &str,
+    ) -> Result<()> {
+        match self.pick(op, path) {
+            Some(ChaosFault::Latency(d)) => {
+                std::thread::sleep(d);
+                Ok(())
+            }
+            Some(fault) => Self::error(fault, operation, path).map_or(Ok(()), Err),
+            None => Ok(()),
         }
     }
 }
@@ -94,9 +453,7 @@ impl<A: Accessor> Layer<A> for ChaosLayer {
 #[derive(Debug)]
 pub struct ChaosAccessor<A> {
     inner: A,
-    rng: StdRng,
-
-    error_ratio: f64,
+    chaos: Arc<Chaos>,
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -105,124 +462,635 @@ impl<A: Accessor> LayeredAccessor for ChaosAccessor<A> {
     type Inner = A;
     type Reader = ChaosReader<A::Reader>;
     type BlockingReader = ChaosReader<A::BlockingReader>;
-    type Writer = A::Writer;
-    type BlockingWriter = A::BlockingWriter;
-    type Lister = A::Lister;
-    type BlockingLister = A::BlockingLister;
+    type Writer = ChaosWriter<A::Writer>;
+    type BlockingWriter = ChaosWriter<A::BlockingWriter>;
+    type Lister = ChaosLister<A::Lister>;
+    type BlockingLister = ChaosLister<A::BlockingLister>;
 
     fn inner(&self) -> &Self::Inner {
         &self.inner
     }
 
-    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        self.inner
-            .read(path, args)
-            .map(|v| v.map(|(rp, r)| (rp, ChaosReader::new(r, self.rng.clone(), self.error_ratio))))
-            .await
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.chaos
+            .inject(
+                ChaosOperation::CreateDir,
+                Operation::CreateDir.into_static(),
+                path,
+            )
+            .await?;
+        self.inner.create_dir(path, args).await
     }
 
-    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
-        self.inner
-            .blocking_read(path, args)
-            .map(|(rp, r)| (rp, ChaosReader::new(r, self.rng.clone(), self.error_ratio)))
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.chaos
+            .inject(ChaosOperation::Read, Operation::Read.into_static(), path)
+            .await?;
+        let (rp, r) = self.inner.read(path, args).await?;
+        Ok((rp, ChaosReader::new(r, self.chaos.clone(), path)))
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        self.inner.write(path, args).await
+        self.chaos
+            .inject(ChaosOperation::Write, Operation::Write.into_static(), path)
+            .await?;
+        let (rp, w) = self.inner.write(path, args).await?;
+        Ok((rp, ChaosWriter::new(w, self.chaos.clone(), path)))
     }
 
-    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        self.inner.blocking_write(path, args)
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.chaos
+            .inject(ChaosOperation::Copy, Operation::Copy.into_static(), from)
+            .await?;
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.chaos
+            .inject(
+                ChaosOperation::Rename,
+                Operation::Rename.into_static(),
+                from,
+            )
+            .await?;
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.chaos
+            .inject(ChaosOperation::Stat, Operation::Stat.into_static(), path)
+            .await?;
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.chaos
+            .inject(
+                ChaosOperation::Delete,
+                Operation::Delete.into_static(),
+                path,
+            )
+            .await?;
+        self.inner.delete(path, args).await
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
-        self.inner.list(path, args).await
+        self.chaos
+            .inject(ChaosOperation::List, Operation::List.into_static(), path)
+            .await?;
+        let (rp, l) = self.inner.list(path, args).await?;
+        Ok((rp, ChaosLister::new(l, self.chaos.clone(), path)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let mut ops = Vec::new();
+        let mut results = Vec::new();
+        for (path, op) in args.into_operation() {
+            match self
+                .chaos
+                .inject(ChaosOperation::Batch, Operation::Batch.into_static(), &path)
+                .await
+            {
+                Ok(()) => ops.push((path, op)),
+                Err(err) => results.push((path, Err(err))),
+            }
+        }
+
+        if !ops.is_empty() {
+            let rp = self.inner.batch(OpBatch::new(ops)).await?;
+            results.extend(rp.into_results());
+        }
+        Ok(RpBatch::new(results))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.chaos
+            .inject(
+                ChaosOperation::Presign,
+                Operation::Presign.into_static(),
+                path,
+            )
+            .await?;
+        self.inner.presign(path, args).await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.chaos.blocking_inject(
+            ChaosOperation::CreateDir,
+            Operation::BlockingCreateDir.into_static(),
+            path,
+        )?;
+        self.inner.blocking_create_dir(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Read,
+            Operation::BlockingRead.into_static(),
+            path,
+        )?;
+        let (rp, r) = self.inner.blocking_read(path, args)?;
+        Ok((rp, ChaosReader::new(r, self.chaos.clone(), path)))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Write,
+            Operation::BlockingWrite.into_static(),
+            path,
+        )?;
+        let (rp, w) = self.inner.blocking_write(path, args)?;
+        Ok((rp, ChaosWriter::new(w, self.chaos.clone(), path)))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Copy,
+            Operation::BlockingCopy.into_static(),
+            from,
+        )?;
+        self.inner.blocking_copy(from, to, args)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Rename,
+            Operation::BlockingRename.into_static(),
+            from,
+        )?;
+        self.inner.blocking_rename(from, to, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Stat,
+            Operation::BlockingStat.into_static(),
+            path,
+        )?;
+        self.inner.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.chaos.blocking_inject(
+            ChaosOperation::Delete,
+            Operation::BlockingDelete.into_static(),
+            path,
+        )?;
+        self.inner.blocking_delete(path, args)
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
-        self.inner.blocking_list(path, args)
+        self.chaos.blocking_inject(
+            ChaosOperation::List,
+            Operation::BlockingList.into_static(),
+            path,
+        )?;
+        let (rp, l) = self.inner.blocking_list(path, args)?;
+        Ok((rp, ChaosLister::new(l, self.chaos.clone(), path)))
     }
 }
 
-/// ChaosReader will inject error into read operations.
-pub struct ChaosReader<R> {
-    inner: R,
-    rng: StdRng,
+/// Decision is the fault picked for the on-going IO call.
+///
+/// It's kept until the call returns ready, so that a pending call won't pick
+/// faults again.
+enum Decision {
+    Pass,
+    Corrupt,
+    Short,
+    Latency(Duration),
+    Sleep(Pin<Box<tokio::time::Sleep>>),
+}
 
-    error_ratio: f64,
+/// ChaosIo holds the common state of reader, writer and lister.
+struct ChaosIo {
+    chaos: Arc<Chaos>,
+    path: String,
+    /// calls are counted per reader, writer and lister.
+    counters: Vec<RuleCounter>,
+    decision: Option<Decision>,
+    truncated: bool,
 }
 
-impl<R> ChaosReader<R> {
-    fn new(inner: R, rng: StdRng, error_ratio: f64) -> Self {
+impl ChaosIo {
+    fn new(chaos: Arc<Chaos>, path: &str) -> Self {
         Self {
-            inner,
-            rng,
-            error_ratio,
+            counters: RuleCounter::new_vec(chaos.rules.len()),
+            chaos,
+            path: path.to_string(),
+            decision: None,
+            truncated: false,
+        }
+    }
+
+    fn decide(&mut self, op: ChaosOperation, operation: &'static str) -> Result<Decision> {
+        match self.chaos.pick_with(op, &self.path, &self.counters) {
+            None => Ok(Decision::Pass),
+            Some(ChaosFault::Latency(d)) => Ok(Decision::Latency(d)),
+            Some(ChaosFault::TruncatedRead) => {
+                self.truncated = true;
+                Ok(Decision::Pass)
+            }
+            Some(ChaosFault::CorruptedRead) => Ok(Decision::Corrupt),
+            Some(ChaosFault::ShortWrite) => Ok(Decision::Short),
+            Some(fault) => {
+                Err(Chaos::error(fault, operation, &self.path).expect("fault must be an error"))
+            }
         }
     }
 
-    /// If I feel lucky, we can return the correct response. Otherwise,
-    /// we need to generate an error.
-    fn i_feel_lucky(&mut self) -> bool {
-        let point = self.rng.gen_range(0..=100);
-        point >= (self.error_ratio * 100.0) as i32
+    /// Decide the fault for an async call, latency will be waited here.
+    fn poll_decide(
+        &mut self,
+        cx: &mut Context<'_>,
+        op: ChaosOperation,
+        operation: &'static str,
+    ) -> Poll<Result<&Decision>> {
+        loop {
+            match &mut self.decision {
+                None => self.decision = Some(self.decide(op, operation)?),
+                Some(Decision::Latency(d)) => {
+                    self.decision = Some(Decision::Sleep(Box::pin(tokio::time::sleep(*d))));
+                }
+                Some(Decision::Sleep(sleep)) => {
+                    ready!(sleep.as_mut().poll(cx));
+                    self.decision = Some(Decision::Pass);
+                }
+                Some(_) => break,
+            }
+        }
+        Poll::Ready(Ok(self.decision.as_ref().expect("decision must be set")))
     }
 
-    fn unexpected_eof() -> Error {
-        Error::new(ErrorKind::Unexpected, "I am your chaos!")
-            .with_operation("chaos")
-            .set_temporary()
+    /// Decide the fault for a blocking call, latency will be waited here.
+    fn blocking_decide(&mut self, op: ChaosOperation, operation: &'static str) -> Result<Decision> {
+        match self.decide(op, operation)? {
+            Decision::Latency(d) => {
+                std::thread::sleep(d);
+                Ok(Decision::Pass)
+            }
+            decision => Ok(decision),
+        }
+    }
+
+    /// Flip a random bit in buf.
+    fn corrupt(&self, buf: &mut [u8]) {
+        if !buf.is_empty() {
+            let idx = self.chaos.gen_range(buf.len() * 8);
+            buf[idx / 8] ^= 1 << (idx % 8);
+        }
+    }
+}
+
+/// ChaosReader will inject faults into read operations.
+pub struct ChaosReader<R> {
+    inner: R,
+    io: ChaosIo,
+}
+
+impl<R> ChaosReader<R> {
+    fn new(inner: R, chaos: Arc<Chaos>, path: &str) -> Self {
+        Self {
+            inner,
+            io: ChaosIo::new(chaos, path),
+        }
     }
 }
 
 impl<R: oio::Read> oio::Read for ChaosReader<R> {
     fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
-        if self.i_feel_lucky() {
-            self.inner.poll_read(cx, buf)
-        } else {
-            Poll::Ready(Err(Self::unexpected_eof()))
+        let decision = ready!(self.io.poll_decide(
+            cx,
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::Read.into_static()
+        ));
+        let corrupt = matches!(decision?, Decision::Corrupt);
+        if self.io.truncated {
+            self.io.decision = None;
+            return Poll::Ready(Ok(0));
         }
+
+        let n = ready!(self.inner.poll_read(cx, buf));
+        self.io.decision = None;
+        let n = n?;
+        if corrupt {
+            self.io.corrupt(&mut buf[..n]);
+        }
+        Poll::Ready(Ok(n))
     }
 
     fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
-        if self.i_feel_lucky() {
-            self.inner.poll_seek(cx, pos)
-        } else {
-            Poll::Ready(Err(Self::unexpected_eof()))
-        }
+        ready!(self.io.poll_decide(
+            cx,
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::Seek.into_static()
+        ))?;
+
+        let res = ready!(self.inner.poll_seek(cx, pos));
+        self.io.decision = None;
+        Poll::Ready(res)
     }
 
     fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
-        if self.i_feel_lucky() {
-            self.inner.poll_next(cx)
-        } else {
-            Poll::Ready(Some(Err(Self::unexpected_eof())))
+        let corrupt = match ready!(self.io.poll_decide(
+            cx,
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::Next.into_static()
+        )) {
+            Ok(decision) => matches!(decision, Decision::Corrupt),
+            Err(err) => return Poll::Ready(Some(Err(err))),
+        };
+        if self.io.truncated {
+            self.io.decision = None;
+            return Poll::Ready(None);
+        }
+
+        let res = ready!(self.inner.poll_next(cx));
+        self.io.decision = None;
+        match res {
+            Some(Ok(bs)) if corrupt => {
+                let mut bs = bs.to_vec();
+                self.io.corrupt(&mut bs);
+                Poll::Ready(Some(Ok(Bytes::from(bs))))
+            }
+            res => Poll::Ready(res),
         }
     }
 }
 
 impl<R: oio::BlockingRead> oio::BlockingRead for ChaosReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        if self.i_feel_lucky() {
-            self.inner.read(buf)
-        } else {
-            Err(Self::unexpected_eof())
+        let decision = self.io.blocking_decide(
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::BlockingRead.into_static(),
+        )?;
+        if self.io.truncated {
+            return Ok(0);
+        }
+
+        let n = self.inner.read(buf)?;
+        if matches!(decision, Decision::Corrupt) {
+            self.io.corrupt(&mut buf[..n]);
         }
+        Ok(n)
     }
 
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
-        if self.i_feel_lucky() {
-            self.inner.seek(pos)
+        self.io.blocking_decide(
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::BlockingSeek.into_static(),
+        )?;
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let decision = match self.io.blocking_decide(
+            ChaosOperation::ReaderRead,
+            oio::ReadOperation::BlockingNext.into_static(),
+        ) {
+            Ok(decision) => decision,
+            Err(err) => return Some(Err(err)),
+        };
+        if self.io.truncated {
+            return None;
+        }
+
+        match self.inner.next() {
+            Some(Ok(bs)) if matches!(decision, Decision::Corrupt) => {
+                let mut bs = bs.to_vec();
+                self.io.corrupt(&mut bs);
+                Some(Ok(Bytes::from(bs)))
+            }
+            res => res,
+        }
+    }
+}
+
+/// ChaosWriter will inject faults into write operations.
+pub struct ChaosWriter<W> {
+    inner: W,
+    io: ChaosIo,
+}
+
+impl<W> ChaosWriter<W> {
+    fn new(inner: W, chaos: Arc<Chaos>, path: &str) -> Self {
+        Self {
+            inner,
+            io: ChaosIo::new(chaos, path),
+        }
+    }
+
+    /// Returns the size of data to write in a short write.
+    fn short_size(&self, size: usize) -> usize {
+        if size <= 1 {
+            size
         } else {
-            Err(Self::unexpected_eof())
+            self.io.chaos.gen_range(size - 1) + 1
         }
     }
+}
 
-    fn next(&mut self) -> Option<Result<Bytes>> {
-        if self.i_feel_lucky() {
-            self.inner.next()
+impl<W: oio::Write> oio::Write for ChaosWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let short = matches!(
+            ready!(self.io.poll_decide(
+                cx,
+                ChaosOperation::WriterWrite,
+                oio::WriteOperation::Write.into_static()
+            ))?,
+            Decision::Short
+        );
+
+        let res = if short {
+            let chunk = bs.chunk();
+            let chunk = &chunk[..self.short_size(chunk.len())];
+            ready!(self.inner.poll_write(cx, &chunk))
         } else {
-            Some(Err(Self::unexpected_eof()))
+            ready!(self.inner.poll_write(cx, bs))
+        };
+        self.io.decision = None;
+        Poll::Ready(res)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.io.poll_decide(
+            cx,
+            ChaosOperation::WriterClose,
+            oio::WriteOperation::Close.into_static()
+        ))?;
+
+        let res = ready!(self.inner.poll_close(cx));
+        self.io.decision = None;
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for ChaosWriter<W> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let decision = self.io.blocking_decide(
+            ChaosOperation::WriterWrite,
+            oio::WriteOperation::BlockingWrite.into_static(),
+        )?;
+
+        if matches!(decision, Decision::Short) {
+            let chunk = bs.chunk();
+            let chunk = &chunk[..self.short_size(chunk.len())];
+            self.inner.write(&chunk)
+        } else {
+            self.inner.write(bs)
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.io.blocking_decide(
+            ChaosOperation::WriterClose,
+            oio::WriteOperation::BlockingClose.into_static(),
+        )?;
+        self.inner.close()
+    }
+}
+
+/// ChaosLister will inject faults into list operations.
+pub struct ChaosLister<L> {
+    inner: L,
+    io: ChaosIo,
+}
+
+impl<L> ChaosLister<L> {
+    fn new(inner: L, chaos: Arc<Chaos>, path: &str) -> Self {
+        Self {
+            inner,
+            io: ChaosIo::new(chaos, path),
         }
     }
 }
+
+impl<L: oio::List> oio::List for ChaosLister<L> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        ready!(self.io.poll_decide(
+            cx,
+            ChaosOperation::ListerNext,
+            oio::ListOperation::Next.into_static()
+        ))?;
+
+        let res = ready!(self.inner.poll_next(cx));
+        self.io.decision = None;
+        Poll::Ready(res)
+    }
+}
+
+impl<L: oio::BlockingList> oio::BlockingList for ChaosLister<L> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        self.io.blocking_decide(
+            ChaosOperation::ListerNext,
+            oio::ListOperation::BlockingNext.into_static(),
+        )?;
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    fn new_operator(layer: ChaosLayer) -> Operator {
+        Operator::new(Memory::default())
+            .unwrap()
+            .layer(layer)
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_chaos_rules() {
+        let op = new_operator(
+            ChaosLayer::new(0.0)
+                .with_rule(
+                    ChaosRule::new(ChaosFault::Error(ErrorKind::PermissionDenied))
+                        .with_operations(ChaosOperation::Stat | ChaosOperation::Delete)
+                        .with_path("private/"),
+                )
+                .with_rule(
+                    ChaosRule::new(ChaosFault::TemporaryError(ErrorKind::Unexpected))
+                        .with_operations(ChaosOperation::WriterWrite)
+                        .with_path("upload/**")
+                        .with_after(2)
+                        .with_times(1),
+                ),
+        );
+
+        op.write("private/a", "a").await.unwrap();
+        let err = op.stat("private/a").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!err.is_temporary());
+        let err = op.delete("private/a").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        op.stat("public").await.unwrap_err();
+
+        let mut w = op.writer("upload/x/file").await.unwrap();
+        w.write("1").await.unwrap();
+        w.write("2").await.unwrap();
+        let err = w.write("3").await.unwrap_err();
+        assert!(err.is_temporary());
+        // The rule only fires once.
+        w.write("3").await.unwrap();
+        w.close().await.unwrap();
+        assert_eq!(op.read("upload/x/file").await.unwrap(), b"123");
+
+        // Calls are counted per writer.
+        let mut w = op.writer("upload/y/file").await.unwrap();
+        w.write("1").await.unwrap();
+        w.write("2").await.unwrap();
+        let err = w.write("3").await.unwrap_err();
+        assert!(err.is_temporary());
+    }
+
+    #[tokio::test]
+    async fn test_chaos_read_write_faults() {
+        let content: Vec<u8> = (0..=255).collect();
+
+        let op = new_operator(ChaosLayer::new(0.0).with_rule(
+            ChaosRule::new(ChaosFault::ShortWrite).with_operations(ChaosOperation::WriterWrite),
+        ));
+        op.write("short", content.clone()).await.unwrap();
+        assert_eq!(op.read("short").await.unwrap(), content);
+
+        let op = new_operator(
+            ChaosLayer::new(0.0)
+                .with_rule(ChaosRule::new(ChaosFault::CorruptedRead).with_path("corrupted")),
+        );
+        op.write("corrupted", content.clone()).await.unwrap();
+        let bs = op.read("corrupted").await.unwrap();
+        assert_eq!(bs.len(), content.len());
+        assert_ne!(bs, content);
+
+        let op =
+            new_operator(ChaosLayer::new(0.0).with_rule(ChaosRule::new(ChaosFault::TruncatedRead)));
+        op.write("truncated", content.clone()).await.unwrap();
+        let bs = op.read("truncated").await.unwrap();
+        assert!(bs.len() < content.len());
+    }
+
+    #[test]
+    fn test_chaos_replay() {
+        let layer = ChaosLayer::new(0.0).with_rule(
+            ChaosRule::new(ChaosFault::Error(ErrorKind::Unexpected))
+                .with_operations(ChaosOperation::Stat)
+                .with_ratio(0.5),
+        );
+
+        let run = |layer: ChaosLayer| {
+            let op = new_operator(layer).blocking();
+            op.write("file", "x").unwrap();
+            (0..64).map(|_| op.stat("file").is_ok()).collect::<Vec<_>>()
+        };
+
+        let results = run(layer.clone());
+        assert!(results.contains(&true) && results.contains(&false));
+        assert_eq!(run(layer.clone().with_seed(layer.seed())), results);
+    }
+}
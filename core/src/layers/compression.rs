@@ -0,0 +1,1423 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+
+use crate::raw::oio::RangeReader;
+use crate::raw::oio::ReadExt;
+use crate::raw::*;
+use crate::*;
+
+/// Magic number of zstd and lz4 skippable frame which carries the seek table.
+const SKIPPABLE_MAGIC: u32 = 0x184D2A5E;
+/// Magic number at the end of seek table, defined by zstd seekable format.
+const SEEKABLE_MAGIC: u32 = 0x8F92EAB1;
+/// Size of seek table footer: number of frames (4), descriptor (1), magic (4).
+const FOOTER_SIZE: usize = 9;
+/// Size of seek table entry: compressed size (4), decompressed size (4).
+const ENTRY_SIZE: usize = 8;
+/// Bytes to read from the end of file while loading seek table.
+const TAIL_SIZE: u64 = 16 * 1024;
+/// Max size of a frame, so that frame sizes can be stored in u32.
+const MAX_FRAME_SIZE: usize = 1024 * 1024 * 1024;
+const DEFAULT_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Add transparent compression for underlying storage services.
+///
+/// `CompressionLayer` compresses data on `write` and decompresses data on `read`.
+///
+/// # Format
+///
+/// The codec is recorded as path suffix, for example, `data.json` will be stored
+/// as `data.json.zst` with [`CompressionCodec::Zstd`]. `list` will strip the suffix
+/// so that users can still see `data.json`.
+///
+/// Content is compressed into independent frames, each frame holds at most
+/// `frame_size` bytes of uncompressed data. A seek table that records the size
+/// of every frame is appended at the end of file:
+///
+/// - zstd: the file follows [zstd seekable format](https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md).
+/// - lz4: the seek table is stored in a lz4 skippable frame.
+/// - gzip: the seek table is stored in the extra field of an empty gzip member.
+///
+/// So all files are still valid for standard decompress tools, and range reads
+/// only need to decompress the frames that cover the range.
+///
+/// # Notes
+///
+/// - Compressed frames are written into the underlying writer once they are
+///   complete, so `Writer`'s buffering and multipart upload work as usual.
+/// - `read` and `stat` will load the seek table first, which costs an extra `stat`
+///   and `read` request.
+/// - Files written by other codecs can be read too, the configured codec is tried
+///   first.
+/// - After a file has been written, the files of the same path written by other
+///   codecs will be removed, which costs an extra `delete` request per codec.
+/// - `append` and `presign` are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::CompressionCodec;
+/// use opendal::layers::CompressionLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(CompressionLayer::new(CompressionCodec::Zstd).with_frame_size(1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressionLayer {
+    codec: CompressionCodec,
+    level: Option<i32>,
+    frame_size: usize,
+}
+
+impl CompressionLayer {
+    /// Create a new `CompressionLayer` with given codec.
+    pub fn new(codec: CompressionCodec) -> Self {
+        Self {
+            codec,
+            level: None,
+            frame_size: DEFAULT_FRAME_SIZE,
+        }
+    }
+
+    /// Set the compression level.
+    ///
+    /// Default to the codec's default level. lz4 doesn't support level.
+    pub fn with_level(mut self, level: i32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Set the max size of uncompressed data in every frame.
+    ///
+    /// Smaller frame makes range reads cheaper but compression ratio worse.
+    ///
+    /// Default to 4 MiB.
+    ///
+    /// # Panics
+    ///
+    /// Frame size must be in `1..=1GiB`.
+    pub fn with_frame_size(mut self, frame_size: usize) -> Self {
+        assert!(
+            frame_size > 0 && frame_size <= MAX_FRAME_SIZE,
+            "frame_size must be between 1 and 1GiB"
+        );
+        self.frame_size = frame_size;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for CompressionLayer {
+    type LayeredAccessor = CompressionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        CompressionAccessor {
+            core: Arc::new(CompressionCore {
+                inner,
+                ctx: self.clone(),
+            }),
+        }
+    }
+}
+
+/// CompressionCodec is the codec used by [`CompressionLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompressionCodec {
+    /// Zstandard, stored with `.zst` suffix.
+    Zstd,
+    /// Gzip, stored with `.gz` suffix.
+    Gzip,
+    /// LZ4 frame format, stored with `.lz4` suffix.
+    Lz4,
+}
+
+impl CompressionCodec {
+    const ALL: [CompressionCodec; 3] = [
+        CompressionCodec::Zstd,
+        CompressionCodec::Gzip,
+        CompressionCodec::Lz4,
+    ];
+
+    /// The path suffix of this codec.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            CompressionCodec::Zstd => ".zst",
+            CompressionCodec::Gzip => ".gz",
+            CompressionCodec::Lz4 => ".lz4",
+        }
+    }
+
+    fn compress(&self, level: Option<i32>, bs: &[u8]) -> Result<Vec<u8>> {
+        let res = match self {
+            CompressionCodec::Zstd => zstd::bulk::compress(bs, level.unwrap_or(0)),
+            CompressionCodec::Gzip => {
+                let level = flate2::Compression::new(level.unwrap_or(6).clamp(0, 9) as u32);
+                let mut w = flate2::write::GzEncoder::new(Vec::new(), level);
+                w.write_all(bs).and_then(|_| w.finish())
+            }
+            CompressionCodec::Lz4 => {
+                let mut w = lz4_flex::frame::FrameEncoder::new(Vec::new());
+                w.write_all(bs)
+                    .and_then(|_| w.finish().map_err(std::io::Error::from))
+            }
+        };
+
+        res.map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "compress frame failed")
+                .with_context("codec", self.suffix())
+                .set_source(err)
+        })
+    }
+
+    fn decompress(&self, bs: &[u8], size: usize) -> Result<Vec<u8>> {
+        let res = match self {
+            CompressionCodec::Zstd => zstd::bulk::decompress(bs, size),
+            CompressionCodec::Gzip => {
+                let mut buf = Vec::with_capacity(size);
+                flate2::read::GzDecoder::new(bs)
+                    .read_to_end(&mut buf)
+                    .map(|_| buf)
+            }
+            CompressionCodec::Lz4 => {
+                let mut buf = Vec::with_capacity(size);
+                lz4_flex::frame::FrameDecoder::new(bs)
+                    .read_to_end(&mut buf)
+                    .map(|_| buf)
+            }
+        };
+
+        match res {
+            Ok(buf) if buf.len() == size => Ok(buf),
+            Ok(_) => Err(
+                Error::new(ErrorKind::Unexpected, "decompressed frame size mismatch")
+                    .with_context("codec", self.suffix()),
+            ),
+            Err(err) => Err(Error::new(ErrorKind::Unexpected, "decompress frame failed")
+                .with_context("codec", self.suffix())
+                .set_source(err)),
+        }
+    }
+
+    /// Bytes between the seek table footer and the end of file.
+    fn trailer_tail_size(&self) -> usize {
+        match self {
+            // empty deflate block (2) + crc32 (4) + isize (4)
+            CompressionCodec::Gzip => 10,
+            CompressionCodec::Zstd | CompressionCodec::Lz4 => 0,
+        }
+    }
+
+    /// Bytes before the seek table entries in trailer.
+    fn trailer_head_size(&self) -> usize {
+        match self {
+            // gzip header (10) + xlen (2) + subfield id (2) + subfield len (2)
+            CompressionCodec::Gzip => 16,
+            // magic (4) + frame size (4)
+            CompressionCodec::Zstd | CompressionCodec::Lz4 => 8,
+        }
+    }
+
+    fn trailer_size(&self, frames: usize) -> usize {
+        self.trailer_head_size() + frames * ENTRY_SIZE + FOOTER_SIZE + self.trailer_tail_size()
+    }
+
+    /// Build the trailer which carries the seek table.
+    fn trailer(&self, frames: &[(u32, u32)]) -> Result<Vec<u8>> {
+        let mut table = Vec::with_capacity(frames.len() * ENTRY_SIZE + FOOTER_SIZE);
+        for (c, d) in frames {
+            table.extend_from_slice(&c.to_le_bytes());
+            table.extend_from_slice(&d.to_le_bytes());
+        }
+        table.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+        table.push(0);
+        table.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+
+        let mut bs = Vec::with_capacity(self.trailer_size(frames.len()));
+        match self {
+            CompressionCodec::Zstd | CompressionCodec::Lz4 => {
+                bs.extend_from_slice(&SKIPPABLE_MAGIC.to_le_bytes());
+                bs.extend_from_slice(&(table.len() as u32).to_le_bytes());
+                bs.extend_from_slice(&table);
+            }
+            CompressionCodec::Gzip => {
+                if table.len() + 4 > u16::MAX as usize {
+                    return Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "too many frames for gzip, please use a larger frame size",
+                    ));
+                }
+                // ID1, ID2, CM=deflate, FLG=FEXTRA, MTIME, XFL, OS=unknown
+                bs.extend_from_slice(&[0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 255]);
+                bs.extend_from_slice(&(table.len() as u16 + 4).to_le_bytes());
+                bs.extend_from_slice(b"OD");
+                bs.extend_from_slice(&(table.len() as u16).to_le_bytes());
+                bs.extend_from_slice(&table);
+                // Empty deflate block, crc32 and isize of empty content.
+                bs.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            }
+        }
+        Ok(bs)
+    }
+}
+
+/// SeekTable records the compressed and decompressed size of every frame.
+#[derive(Debug, Clone, Default)]
+struct SeekTable {
+    frames: Vec<(u32, u32)>,
+}
+
+impl SeekTable {
+    /// Parse the number of frames from the tail of file.
+    fn parse_footer(codec: CompressionCodec, tail: &[u8]) -> Result<usize> {
+        let end = tail.len().checked_sub(codec.trailer_tail_size());
+        let footer = match end {
+            Some(end) if end >= FOOTER_SIZE => &tail[end - FOOTER_SIZE..end],
+            _ => return Err(Self::invalid(codec)),
+        };
+
+        let magic = u32::from_le_bytes(footer[5..9].try_into().expect("must be valid"));
+        if magic != SEEKABLE_MAGIC {
+            return Err(Self::invalid(codec));
+        }
+        Ok(u32::from_le_bytes(footer[0..4].try_into().expect("must be valid")) as usize)
+    }
+
+    /// Parse the seek table entries.
+    fn parse(codec: CompressionCodec, frames: usize, entries: &[u8], size: u64) -> Result<Self> {
+        if entries.len() < frames * ENTRY_SIZE {
+            return Err(Self::invalid(codec));
+        }
+
+        let frames: Vec<(u32, u32)> = entries[..frames * ENTRY_SIZE]
+            .chunks_exact(ENTRY_SIZE)
+            .map(|v| {
+                (
+                    u32::from_le_bytes(v[0..4].try_into().expect("must be valid")),
+                    u32::from_le_bytes(v[4..8].try_into().expect("must be valid")),
+                )
+            })
+            .collect();
+
+        let compressed: u64 = frames.iter().map(|(c, _)| *c as u64).sum();
+        if compressed + codec.trailer_size(frames.len()) as u64 != size {
+            return Err(Self::invalid(codec));
+        }
+        Ok(Self { frames })
+    }
+
+    fn invalid(codec: CompressionCodec) -> Error {
+        Error::new(
+            ErrorKind::Unexpected,
+            "file is not compressed by CompressionLayer or corrupted",
+        )
+        .with_context("codec", codec.suffix())
+    }
+
+    fn content_length(&self) -> u64 {
+        self.frames.iter().map(|(_, d)| *d as u64).sum()
+    }
+
+    /// Find frames that cover the given range.
+    ///
+    /// Returns the compressed range, the frames and the bytes to skip in the first frame.
+    fn locate(&self, offset: u64, size: Option<u64>) -> (BytesRange, Vec<(u32, u32)>, usize) {
+        let end = size.map(|v| offset + v).unwrap_or(u64::MAX);
+
+        let (mut c_start, mut d_start) = (0, 0);
+        let mut c_size = 0;
+        let mut skip = 0;
+        let mut frames = Vec::new();
+        for &(c, d) in &self.frames {
+            let d_end = d_start + d as u64;
+            if d_end > offset && d_start < end {
+                if frames.is_empty() {
+                    skip = (offset - d_start) as usize;
+                }
+                frames.push((c, d));
+                c_size += c as u64;
+            } else if frames.is_empty() {
+                c_start += c as u64;
+            }
+            d_start = d_end;
+        }
+
+        (BytesRange::new(Some(c_start), Some(c_size)), frames, skip)
+    }
+}
+
+#[derive(Debug)]
+pub struct CompressionAccessor<A: Accessor> {
+    core: Arc<CompressionCore<A>>,
+}
+
+impl<A: Accessor> CompressionAccessor<A> {
+    fn build_path(&self, path: &str) -> String {
+        format!("{path}{}", self.core.ctx.codec.suffix())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for CompressionAccessor<A> {
+    type Inner = A;
+    type Reader = RangeReader<CompressionCore<A>, DecompressReader<A::Reader>>;
+    type BlockingReader = RangeReader<CompressionCore<A>, DecompressReader<A::BlockingReader>>;
+    type Writer = CompressWriter<A::Writer, A>;
+    type BlockingWriter = CompressWriter<A::BlockingWriter, A>;
+    type Lister = CompressionLister<A::Lister>;
+    type BlockingLister = CompressionLister<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.core.inner
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.core.inner.info();
+
+        let cap = meta.full_capability_mut();
+        cap.write_can_append = false;
+        cap.presign = false;
+        cap.presign_read = false;
+        cap.presign_stat = false;
+        cap.presign_write = false;
+
+        meta
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by CompressionLayer",
+            ));
+        }
+
+        let (rp, w) = self.core.inner.write(&self.build_path(path), args).await?;
+        Ok((rp, CompressWriter::new(w, self.core.clone(), path)))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let (from, codec) = self.core.resolve(from).await?;
+        let rp = self
+            .core
+            .inner
+            .copy(&from, &format!("{to}{}", codec.suffix()), args)
+            .await?;
+        // Files of other codecs would shadow the copied one while resolving.
+        self.core.remove_stale(to, codec).await?;
+        Ok(rp)
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let (from, codec) = self.core.resolve(from).await?;
+        let rp = self
+            .core
+            .inner
+            .rename(&from, &format!("{to}{}", codec.suffix()), args)
+            .await?;
+        self.core.remove_stale(to, codec).await?;
+        Ok(rp)
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        if path.ends_with('/') {
+            return self.core.inner.delete(path, args).await;
+        }
+
+        match self.core.resolve(path).await {
+            Ok((path, _)) => self.core.inner.delete(&path, args).await,
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(RpDelete::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let (rp, l) = self.core.inner.list(path, args).await?;
+        Ok((rp, CompressionLister::new(l)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let ops = args.into_operation();
+
+        // Resolve paths in the same way as `delete` so that files written by
+        // other codecs can be removed too.
+        let mut paths = HashMap::with_capacity(ops.len());
+        let mut resolved = Vec::with_capacity(ops.len());
+        for (path, op) in ops {
+            let p = if path.ends_with('/') {
+                path.clone()
+            } else {
+                match self.core.resolve(&path).await {
+                    Ok((p, _)) => p,
+                    Err(err) if err.kind() == ErrorKind::NotFound => self.build_path(&path),
+                    Err(err) => return Err(err),
+                }
+            };
+            paths.insert(p.clone(), path);
+            resolved.push((p, op));
+        }
+
+        let rp = self.core.inner.batch(OpBatch::new(resolved)).await?;
+        let results = rp
+            .into_results()
+            .into_iter()
+            .map(|(p, res)| match paths.remove(&p) {
+                Some(path) => (path, res),
+                None => (p, res),
+            })
+            .collect();
+        Ok(RpBatch::new(results))
+    }
+
+    async fn presign(&self, _: &str, _: OpPresign) -> Result<RpPresign> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "presign is not supported by CompressionLayer",
+        ))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by CompressionLayer",
+            ));
+        }
+
+        let (rp, w) = self
+            .core
+            .inner
+            .blocking_write(&self.build_path(path), args)?;
+        Ok((rp, CompressWriter::new(w, self.core.clone(), path)))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let (from, codec) = self.core.blocking_resolve(from)?;
+        let rp = self
+            .core
+            .inner
+            .blocking_copy(&from, &format!("{to}{}", codec.suffix()), args)?;
+        self.core.blocking_remove_stale(to, codec)?;
+        Ok(rp)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let (from, codec) = self.core.blocking_resolve(from)?;
+        let rp =
+            self.core
+                .inner
+                .blocking_rename(&from, &format!("{to}{}", codec.suffix()), args)?;
+        self.core.blocking_remove_stale(to, codec)?;
+        Ok(rp)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        if path.ends_with('/') {
+            return self.core.inner.blocking_delete(path, args);
+        }
+
+        match self.core.blocking_resolve(path) {
+            Ok((path, _)) => self.core.inner.blocking_delete(&path, args),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(RpDelete::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        let (rp, l) = self.core.inner.blocking_list(path, args)?;
+        Ok((rp, CompressionLister::new(l)))
+    }
+}
+
+/// CompressionCore returns [`DecompressReader`] for given range.
+///
+/// It will be wrapped by [`RangeReader`] to support seek.
+#[derive(Debug)]
+pub struct CompressionCore<A: Accessor> {
+    inner: A,
+    ctx: CompressionLayer,
+}
+
+impl<A: Accessor> CompressionCore<A> {
+    /// Codecs to try while resolving path, the configured codec goes first.
+    fn codecs(&self) -> impl Iterator<Item = CompressionCodec> + '_ {
+        std::iter::once(self.ctx.codec).chain(
+            CompressionCodec::ALL
+                .into_iter()
+                .filter(|v| *v != self.ctx.codec),
+        )
+    }
+
+    /// Find the compressed file of given path.
+    async fn resolve(&self, path: &str) -> Result<(String, CompressionCodec)> {
+        Ok(self.resolve_with_stat(path).await?.0)
+    }
+
+    async fn resolve_with_stat(
+        &self,
+        path: &str,
+    ) -> Result<((String, CompressionCodec), Metadata)> {
+        let mut not_found = None;
+        for codec in self.codecs() {
+            let p = format!("{path}{}", codec.suffix());
+            match self.inner.stat(&p, OpStat::new()).await {
+                Ok(rp) => return Ok(((p, codec), rp.into_metadata())),
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    not_found.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(not_found.expect("codecs must not be empty"))
+    }
+
+    fn blocking_resolve(&self, path: &str) -> Result<(String, CompressionCodec)> {
+        Ok(self.blocking_resolve_with_stat(path)?.0)
+    }
+
+    fn blocking_resolve_with_stat(
+        &self,
+        path: &str,
+    ) -> Result<((String, CompressionCodec), Metadata)> {
+        let mut not_found = None;
+        for codec in self.codecs() {
+            let p = format!("{path}{}", codec.suffix());
+            match self.inner.blocking_stat(&p, OpStat::new()) {
+                Ok(rp) => return Ok(((p, codec), rp.into_metadata())),
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    not_found.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(not_found.expect("codecs must not be empty"))
+    }
+
+    /// Remove the files of given path written by codecs other than `keep`.
+    async fn remove_stale(&self, path: &str, keep: CompressionCodec) -> Result<()> {
+        for codec in self.codecs().filter(|v| *v != keep) {
+            let p = format!("{path}{}", codec.suffix());
+            match self.inner.delete(&p, OpDelete::new()).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn blocking_remove_stale(&self, path: &str, keep: CompressionCodec) -> Result<()> {
+        for codec in self.codecs().filter(|v| *v != keep) {
+            let p = format!("{path}{}", codec.suffix());
+            match self.inner.blocking_delete(&p, OpDelete::new()) {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the seek table from the end of file.
+    async fn load(&self, path: &str) -> Result<(String, CompressionCodec, Metadata, SeekTable)> {
+        let ((path, codec), meta) = self.resolve_with_stat(path).await?;
+        let size = meta.content_length();
+
+        let tail_offset = size.saturating_sub(TAIL_SIZE);
+        let tail = self
+            .read_range(&path, tail_offset, size - tail_offset)
+            .await?;
+        let frames = SeekTable::parse_footer(codec, &tail)?;
+
+        let entries_offset = size
+            .checked_sub(codec.trailer_size(frames) as u64)
+            .ok_or_else(|| SeekTable::invalid(codec))?
+            + codec.trailer_head_size() as u64;
+        let entries_size = (frames * ENTRY_SIZE) as u64;
+        let table = if entries_offset >= tail_offset {
+            let start = (entries_offset - tail_offset) as usize;
+            SeekTable::parse(codec, frames, &tail[start..], size)?
+        } else {
+            let entries = self.read_range(&path, entries_offset, entries_size).await?;
+            SeekTable::parse(codec, frames, &entries, size)?
+        };
+
+        Ok((path, codec, meta, table))
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, size: u64) -> Result<Vec<u8>> {
+        let (_, mut r) = self
+            .inner
+            .read(
+                path,
+                OpRead::new().with_range(BytesRange::new(Some(offset), Some(size))),
+            )
+            .await?;
+        let mut bs = Vec::with_capacity(size as usize);
+        r.read_to_end(&mut bs).await?;
+        Ok(bs)
+    }
+
+    fn blocking_load(&self, path: &str) -> Result<(String, CompressionCodec, Metadata, SeekTable)> {
+        let ((path, codec), meta) = self.blocking_resolve_with_stat(path)?;
+        let size = meta.content_length();
+
+        let tail_offset = size.saturating_sub(TAIL_SIZE);
+        let tail = self.blocking_read_range(&path, tail_offset, size - tail_offset)?;
+        let frames = SeekTable::parse_footer(codec, &tail)?;
+
+        let entries_offset = size
+            .checked_sub(codec.trailer_size(frames) as u64)
+            .ok_or_else(|| SeekTable::invalid(codec))?
+            + codec.trailer_head_size() as u64;
+        let entries_size = (frames * ENTRY_SIZE) as u64;
+        let table = if entries_offset >= tail_offset {
+            let start = (entries_offset - tail_offset) as usize;
+            SeekTable::parse(codec, frames, &tail[start..], size)?
+        } else {
+            let entries = self.blocking_read_range(&path, entries_offset, entries_size)?;
+            SeekTable::parse(codec, frames, &entries, size)?
+        };
+
+        Ok((path, codec, meta, table))
+    }
+
+    fn blocking_read_range(&self, path: &str, offset: u64, size: u64) -> Result<Vec<u8>> {
+        let (_, mut r) = self.inner.blocking_read(
+            path,
+            OpRead::new().with_range(BytesRange::new(Some(offset), Some(size))),
+        )?;
+        let mut bs = Vec::with_capacity(size as usize);
+        oio::BlockingRead::read_to_end(&mut r, &mut bs)?;
+        Ok(bs)
+    }
+}
+
+/// Build the metadata of decompressed file.
+fn decompressed_metadata(meta: &Metadata, table: &SeekTable) -> Metadata {
+    let mut m = Metadata::new(EntryMode::FILE).with_content_length(table.content_length());
+    if !meta.contains_metakey(Metakey::LastModified) {
+        return m;
+    }
+    if let Some(v) = meta.last_modified() {
+        m.set_last_modified(v);
+    }
+    m
+}
+
+/// Normalize the range with the decompressed content length.
+fn normalize_range(range: BytesRange, total: u64) -> (u64, Option<u64>) {
+    match (range.offset(), range.size()) {
+        (Some(offset), size) => (offset, size),
+        (None, Some(size)) => (total.saturating_sub(size), Some(size.min(total))),
+        (None, None) => (0, None),
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> Accessor for CompressionCore<A> {
+    type Reader = DecompressReader<A::Reader>;
+    type Writer = ();
+    type Lister = ();
+    type BlockingReader = DecompressReader<A::BlockingReader>;
+    type BlockingWriter = ();
+    type BlockingLister = ();
+
+    fn info(&self) -> AccessorInfo {
+        self.inner.info()
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if path.ends_with('/') {
+            return self.inner.stat(path, args).await;
+        }
+
+        let (_, _, meta, table) = self.load(path).await?;
+        Ok(RpStat::new(decompressed_metadata(&meta, &table)))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let (path, codec, _, table) = self.load(path).await?;
+        let (offset, size) = normalize_range(args.range(), table.content_length());
+        let (range, frames, skip) = table.locate(offset, size);
+
+        let remaining = table.content_length().saturating_sub(offset);
+        let remaining = size.map_or(remaining, |v| v.min(remaining));
+        let (_, r) = self.inner.read(&path, args.with_range(range)).await?;
+        Ok((
+            RpRead::new().with_size(Some(remaining)),
+            DecompressReader::new(r, codec, frames, skip, remaining),
+        ))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if path.ends_with('/') {
+            return self.inner.blocking_stat(path, args);
+        }
+
+        let (_, _, meta, table) = self.blocking_load(path)?;
+        Ok(RpStat::new(decompressed_metadata(&meta, &table)))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let (path, codec, _, table) = self.blocking_load(path)?;
+        let (offset, size) = normalize_range(args.range(), table.content_length());
+        let (range, frames, skip) = table.locate(offset, size);
+
+        let remaining = table.content_length().saturating_sub(offset);
+        let remaining = size.map_or(remaining, |v| v.min(remaining));
+        let (_, r) = self.inner.blocking_read(&path, args.with_range(range))?;
+        Ok((
+            RpRead::new().with_size(Some(remaining)),
+            DecompressReader::new(r, codec, frames, skip, remaining),
+        ))
+    }
+}
+
+/// DecompressReader reads compressed frames from inner reader and returns the
+/// decompressed content.
+///
+/// DecompressReader doesn't support seek, use [`RangeReader`] instead.
+pub struct DecompressReader<R> {
+    inner: R,
+    codec: CompressionCodec,
+
+    /// frames to read, in (compressed size, decompressed size).
+    frames: Vec<(u32, u32)>,
+    index: usize,
+    /// bytes to skip in the next frame.
+    skip: usize,
+    /// bytes left to return.
+    remaining: u64,
+
+    buf: Vec<u8>,
+    filled: usize,
+    decompressed: Bytes,
+}
+
+impl<R> DecompressReader<R> {
+    fn new(
+        inner: R,
+        codec: CompressionCodec,
+        frames: Vec<(u32, u32)>,
+        skip: usize,
+        remaining: u64,
+    ) -> Self {
+        Self {
+            inner,
+            codec,
+
+            frames,
+            index: 0,
+            skip,
+            remaining,
+
+            buf: Vec::new(),
+            filled: 0,
+            decompressed: Bytes::new(),
+        }
+    }
+
+    /// Fill the decompressed buffer with next frame.
+    ///
+    /// `read` is used to read data from inner reader, so that async and blocking
+    /// reader can share the same logic.
+    fn poll_fill(
+        &mut self,
+        mut read: impl FnMut(&mut R, &mut [u8]) -> Poll<Result<usize>>,
+    ) -> Poll<Result<()>> {
+        while self.decompressed.is_empty() && self.remaining > 0 {
+            let Some(&(c, d)) = self.frames.get(self.index) else {
+                break;
+            };
+
+            let size = c as usize;
+            if self.buf.len() < size {
+                self.buf.resize(size, 0);
+            }
+            while self.filled < size {
+                let n = ready!(read(&mut self.inner, &mut self.buf[self.filled..size]))?;
+                if n == 0 {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "compressed file is truncated",
+                    )));
+                }
+                self.filled += n;
+            }
+            self.filled = 0;
+            self.index += 1;
+
+            let mut bs = Bytes::from(self.codec.decompress(&self.buf[..size], d as usize)?);
+            bs.advance(self.skip.min(bs.len()));
+            self.skip = 0;
+            bs.truncate(self.remaining.min(bs.len() as u64) as usize);
+            self.remaining -= bs.len() as u64;
+            self.decompressed = bs;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn copy_decompressed(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.decompressed.len());
+        buf[..n].copy_from_slice(&self.decompressed[..n]);
+        self.decompressed.advance(n);
+        n
+    }
+}
+
+impl<R: oio::Read> oio::Read for DecompressReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(self.poll_fill(|r, bs| r.poll_read(cx, bs)))?;
+        Poll::Ready(Ok(self.copy_decompressed(buf)))
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, _: SeekFrom) -> Poll<Result<u64>> {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        )))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Err(err) = ready!(self.poll_fill(|r, bs| r.poll_read(cx, bs))) {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        if self.decompressed.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(std::mem::take(&mut self.decompressed))))
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        match self.poll_fill(|r, bs| Poll::Ready(r.read(bs))) {
+            Poll::Ready(res) => res?,
+            Poll::Pending => unreachable!("blocking reader must not return pending"),
+        }
+        Ok(self.copy_decompressed(buf))
+    }
+
+    fn seek(&mut self, _: SeekFrom) -> Result<u64> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        ))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        match self.poll_fill(|r, bs| Poll::Ready(r.read(bs))) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Some(Err(err)),
+            Poll::Pending => unreachable!("blocking reader must not return pending"),
+        }
+
+        if self.decompressed.is_empty() {
+            None
+        } else {
+            Some(Ok(std::mem::take(&mut self.decompressed)))
+        }
+    }
+}
+
+/// CompressWriter buffers content into frames and writes the compressed frames
+/// into inner writer.
+///
+/// Files of the same path written by other codecs will be removed after inner
+/// writer closed, so that they won't shadow the new content.
+pub struct CompressWriter<W, A: Accessor> {
+    inner: W,
+    core: Arc<CompressionCore<A>>,
+    path: String,
+    codec: CompressionCodec,
+    level: Option<i32>,
+    frame_size: usize,
+
+    buf: BytesMut,
+    frames: Vec<(u32, u32)>,
+    /// compressed bytes that not written into inner writer yet.
+    pending: Bytes,
+    finished: bool,
+    inner_closed: bool,
+    fut: Option<BoxedFuture<Result<()>>>,
+}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for CompressWriter.
+unsafe impl<W: Sync, A: Accessor> Sync for CompressWriter<W, A> {}
+
+impl<W, A: Accessor> CompressWriter<W, A> {
+    fn new(inner: W, core: Arc<CompressionCore<A>>, path: &str) -> Self {
+        let ctx = &core.ctx;
+        Self {
+            codec: ctx.codec,
+            level: ctx.level,
+            frame_size: ctx.frame_size,
+            inner,
+            core,
+            path: path.to_string(),
+
+            buf: BytesMut::new(),
+            frames: Vec::new(),
+            pending: Bytes::new(),
+            finished: false,
+            inner_closed: false,
+            fut: None,
+        }
+    }
+
+    /// Copy given bytes into buffer, returns the bytes consumed.
+    fn fill(&mut self, bs: &dyn oio::WriteBuf) -> usize {
+        let chunk = bs.chunk();
+        let n = chunk.len().min(self.frame_size - self.buf.len());
+        self.buf.extend_from_slice(&chunk[..n]);
+        n
+    }
+
+    /// Compress current buffer into a frame.
+    fn seal(&mut self) -> Result<Vec<u8>> {
+        let frame = self.codec.compress(self.level, &self.buf)?;
+        let compressed = u32::try_from(frame.len()).map_err(|_| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "compressed frame is too large, please use a smaller frame size",
+            )
+        })?;
+        self.frames.push((compressed, self.buf.len() as u32));
+        self.buf.clear();
+        Ok(frame)
+    }
+
+    /// Compress the remaining buffer and build the trailer.
+    fn finish(&mut self) -> Result<Bytes> {
+        let mut bs = if self.buf.is_empty() {
+            Vec::new()
+        } else {
+            self.seal()?
+        };
+        bs.extend_from_slice(&self.codec.trailer(&self.frames)?);
+        Ok(Bytes::from(bs))
+    }
+}
+
+impl<W: oio::Write, A: Accessor> CompressWriter<W, A> {
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.pending.is_empty() {
+            let n = ready!(self.inner.poll_write(cx, &self.pending))?;
+            self.pending.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: oio::Write, A: Accessor> oio::Write for CompressWriter<W, A> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        ready!(self.poll_flush(cx))?;
+
+        let n = self.fill(bs);
+        if self.buf.len() == self.frame_size {
+            self.pending = Bytes::from(self.seal()?);
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_flush(cx))?;
+
+        if !self.finished {
+            self.pending = self.finish()?;
+            self.finished = true;
+            ready!(self.poll_flush(cx))?;
+        }
+
+        if !self.inner_closed {
+            ready!(self.inner.poll_close(cx))?;
+            self.inner_closed = true;
+
+            let core = self.core.clone();
+            let path = self.path.clone();
+            self.fut = Some(Box::pin(async move {
+                core.remove_stale(&path, core.ctx.codec).await
+            }));
+        }
+
+        if let Some(fut) = self.fut.as_mut() {
+            let res = ready!(fut.as_mut().poll(cx));
+            self.fut = None;
+            res?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.pending = Bytes::new();
+        self.buf.clear();
+
+        self.inner.poll_abort(cx)
+    }
+}
+
+impl<W: oio::BlockingWrite, A: Accessor> CompressWriter<W, A> {
+    fn write_pending(&mut self) -> Result<()> {
+        while !self.pending.is_empty() {
+            let n = self.inner.write(&self.pending)?;
+            self.pending.advance(n);
+        }
+        Ok(())
+    }
+}
+
+impl<W: oio::BlockingWrite, A: Accessor> oio::BlockingWrite for CompressWriter<W, A> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.write_pending()?;
+
+        let n = self.fill(bs);
+        if self.buf.len() == self.frame_size {
+            self.pending = Bytes::from(self.seal()?);
+            self.write_pending()?;
+        }
+        Ok(n)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.write_pending()?;
+
+        if !self.finished {
+            self.pending = self.finish()?;
+            self.finished = true;
+            self.write_pending()?;
+        }
+
+        if !self.inner_closed {
+            self.inner.close()?;
+            self.inner_closed = true;
+        }
+        self.core
+            .blocking_remove_stale(&self.path, self.core.ctx.codec)
+    }
+}
+
+/// CompressionLister strips the codec suffix from listed files.
+///
+/// The metadata of compressed files will be reset so that the decompressed size
+/// can be fetched by `stat` lazily.
+pub struct CompressionLister<L> {
+    inner: L,
+}
+
+impl<L> CompressionLister<L> {
+    fn new(inner: L) -> Self {
+        Self { inner }
+    }
+
+    fn map_entry(mut entry: oio::Entry) -> oio::Entry {
+        if !entry.mode().is_file() {
+            return entry;
+        }
+
+        let path = CompressionCodec::ALL
+            .iter()
+            .find_map(|codec| entry.path().strip_suffix(codec.suffix()))
+            .map(|v| v.to_string());
+        if let Some(path) = path {
+            let mut meta = Metadata::new(EntryMode::FILE);
+            if entry.metadata().contains_metakey(Metakey::LastModified) {
+                if let Some(v) = entry.metadata().last_modified() {
+                    meta.set_last_modified(v);
+                }
+            }
+            entry = oio::Entry::with(path, meta);
+        }
+        entry
+    }
+}
+
+impl<L: oio::List> oio::List for CompressionLister<L> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        let entry = ready!(self.inner.poll_next(cx))?;
+        Poll::Ready(Ok(entry.map(Self::map_entry)))
+    }
+}
+
+impl<L: oio::BlockingList> oio::BlockingList for CompressionLister<L> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        let entry = self.inner.next()?;
+        Ok(entry.map(Self::map_entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::services::Fs;
+    use crate::services::Memory;
+
+    fn new_content(size: usize) -> Vec<u8> {
+        // Compressible content with some randomness.
+        let mut rng = thread_rng();
+        (0..size)
+            .map(|_| b"abcdefgh"[rng.gen_range(0..8)])
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_compression_read_write() {
+        for codec in CompressionCodec::ALL {
+            let inner = Operator::new(Memory::default()).unwrap().finish();
+            let op = inner
+                .clone()
+                .layer(CompressionLayer::new(codec).with_frame_size(16));
+
+            for size in [0, 1, 16, 17, 100] {
+                let content = new_content(size);
+                let path = format!("file-{size}");
+
+                let mut w = op.writer(&path).await.unwrap();
+                for chunk in content.chunks(7) {
+                    w.write(chunk.to_vec()).await.unwrap();
+                }
+                w.close().await.unwrap();
+
+                assert_eq!(op.read(&path).await.unwrap(), content, "{codec:?} {size}");
+                assert_eq!(
+                    op.stat(&path).await.unwrap().content_length(),
+                    size as u64,
+                    "{codec:?} {size}"
+                );
+
+                for (offset, len) in [(0, 5), (3, 20), (16, 16), (20, 80)] {
+                    if offset > size {
+                        continue;
+                    }
+                    let expected = &content[offset..(offset + len).min(size)];
+                    let actual = op
+                        .read_with(&path)
+                        .range(offset as u64..(offset + len) as u64)
+                        .await
+                        .unwrap();
+                    assert_eq!(actual, expected, "{codec:?} {size}, range {offset}+{len}");
+                }
+
+                // The stored file must be valid for standard decoders.
+                let raw = inner
+                    .read(&format!("{path}{}", codec.suffix()))
+                    .await
+                    .unwrap();
+                let mut decoded = Vec::new();
+                match codec {
+                    CompressionCodec::Zstd => {
+                        decoded = zstd::stream::decode_all(raw.as_slice()).unwrap();
+                    }
+                    CompressionCodec::Gzip => {
+                        flate2::read::MultiGzDecoder::new(raw.as_slice())
+                            .read_to_end(&mut decoded)
+                            .unwrap();
+                    }
+                    CompressionCodec::Lz4 => {
+                        // lz4_flex decodes one frame at a time and reports the
+                        // skippable frame as error instead of skipping it.
+                        let mut r = raw.as_slice();
+                        while !r.is_empty() {
+                            if let Err(err) =
+                                lz4_flex::frame::FrameDecoder::new(&mut r).read_to_end(&mut decoded)
+                            {
+                                assert!(matches!(
+                                    err.get_ref()
+                                        .and_then(|e| e.downcast_ref::<lz4_flex::frame::Error>()),
+                                    Some(lz4_flex::frame::Error::SkippableFrame(_))
+                                ));
+                                break;
+                            }
+                        }
+                    }
+                }
+                assert_eq!(decoded, content, "{codec:?} {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_blocking_read_write() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(CompressionLayer::new(CompressionCodec::Gzip).with_frame_size(16))
+            .finish()
+            .blocking();
+
+        let content = new_content(50);
+        op.write("test", content.clone()).unwrap();
+
+        assert_eq!(op.read("test").unwrap(), content);
+        assert_eq!(
+            op.read_with("test").range(20..40).call().unwrap(),
+            &content[20..40]
+        );
+        assert_eq!(op.stat("test").unwrap().content_length(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_compression_path() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let zstd = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Zstd));
+        let lz4 = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Lz4));
+
+        zstd.write("dir/a.json", "{}").await.unwrap();
+        lz4.write("dir/b.json", "[]").await.unwrap();
+        assert!(inner.is_exist("dir/a.json.zst").await.unwrap());
+        assert!(inner.is_exist("dir/b.json.lz4").await.unwrap());
+
+        // Files written by other codecs can be read too.
+        assert_eq!(zstd.read("dir/b.json").await.unwrap(), b"[]");
+
+        let mut paths: Vec<_> = zstd
+            .list("dir/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| v.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a.json", "dir/b.json"]);
+
+        zstd.delete("dir/b.json").await.unwrap();
+        assert!(!inner.is_exist("dir/b.json.lz4").await.unwrap());
+        zstd.delete("dir/not_exist").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compression_change_codec() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let zstd = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Zstd));
+        let lz4 = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Lz4));
+
+        zstd.write("a.json", "old").await.unwrap();
+        lz4.write("a.json", "new").await.unwrap();
+
+        // Stale file written by previous codec should be removed.
+        assert!(!inner.is_exist("a.json.zst").await.unwrap());
+        assert!(inner.is_exist("a.json.lz4").await.unwrap());
+        assert_eq!(zstd.read("a.json").await.unwrap(), b"new");
+
+        zstd.blocking().write("a.json", "newer").unwrap();
+        assert!(!inner.is_exist("a.json.lz4").await.unwrap());
+        assert_eq!(lz4.read("a.json").await.unwrap(), b"newer");
+    }
+
+    #[tokio::test]
+    async fn test_compression_copy_rename_across_codecs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = Fs::default();
+        builder.root(&dir.path().to_string_lossy());
+        let inner = Operator::new(builder).unwrap().finish();
+        let zstd = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Zstd));
+        let lz4 = inner
+            .clone()
+            .layer(CompressionLayer::new(CompressionCodec::Lz4));
+
+        zstd.write("to", "stale").await.unwrap();
+        lz4.write("from", "fresh").await.unwrap();
+
+        // The copied lz4 file must not be shadowed by the stale zstd one.
+        zstd.copy("from", "to").await.unwrap();
+        assert!(inner.is_exist("to.lz4").await.unwrap());
+        assert!(!inner.is_exist("to.zst").await.unwrap());
+        assert_eq!(zstd.read("to").await.unwrap(), b"fresh");
+
+        zstd.write("renamed", "stale").await.unwrap();
+        zstd.rename("from", "renamed").await.unwrap();
+        assert!(!inner.is_exist("renamed.zst").await.unwrap());
+        assert_eq!(zstd.read("renamed").await.unwrap(), b"fresh");
+
+        let blocking = zstd.blocking();
+        blocking.write("copied", "stale").unwrap();
+        blocking.copy("to", "copied").unwrap();
+        assert!(!inner.is_exist("copied.zst").await.unwrap());
+        assert_eq!(blocking.read("copied").unwrap(), b"fresh");
+
+        blocking.write("moved", "stale").unwrap();
+        blocking.rename("copied", "moved").unwrap();
+        assert!(!inner.is_exist("moved.zst").await.unwrap());
+        assert_eq!(blocking.read("moved").unwrap(), b"fresh");
+    }
+}
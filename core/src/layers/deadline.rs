@@ -0,0 +1,671 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::future::Future;
+use std::io::SeekFrom;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::select;
+use futures::future::Either;
+use futures::FutureExt;
+use tokio::sync::Notify;
+
+use crate::raw::*;
+use crate::*;
+
+/// CancellationToken is used to cancel in-flight operations.
+///
+/// All clones share the same state, cancel any of them will cancel all
+/// operations that bound to this token.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Create a new CancellationToken.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all operations bound to this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Check if this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait until this token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            // Notified must be created before checking the state, so that
+            // we will not miss the notification between them.
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// OperationContext carries a deadline and a [`CancellationToken`] for
+/// operations.
+///
+/// Use [`DeadlineLayer`] to apply the context to an [`Operator`].
+#[derive(Debug, Clone, Default)]
+pub struct OperationContext {
+    deadline: Option<Instant>,
+    token: CancellationToken,
+}
+
+impl OperationContext {
+    /// Create a new OperationContext without deadline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the deadline of this context.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the deadline of this context to `now + timeout`.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Bind this context to given cancellation token.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// The deadline of this context.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The cancellation token of this context.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Check if this context is still alive, returns an error if the deadline
+    /// exceeded or it has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.token.is_cancelled() {
+            return Err(self.cancelled_error());
+        }
+        if matches!(self.deadline, Some(deadline) if Instant::now() >= deadline) {
+            return Err(self.deadline_error());
+        }
+        Ok(())
+    }
+
+    fn cancelled_error(&self) -> Error {
+        Error::new(ErrorKind::Unexpected, "operation cancelled")
+    }
+
+    fn deadline_error(&self) -> Error {
+        let mut err = Error::new(ErrorKind::Unexpected, "operation deadline exceeded");
+        if let Some(deadline) = self.deadline {
+            err = err.with_context(
+                "deadline",
+                format!(
+                    "{:?} ago",
+                    Instant::now().saturating_duration_since(deadline)
+                ),
+            );
+        }
+        err
+    }
+
+    /// Build a future that resolves to an error once the deadline exceeded or
+    /// the token is cancelled.
+    fn done(&self) -> DoneFuture {
+        let ctx = self.clone();
+        DoneFuture(Box::pin(async move {
+            let cancelled = ctx.token.cancelled();
+            match ctx.deadline {
+                Some(deadline) => {
+                    let sleep = tokio::time::sleep_until(deadline.into());
+                    futures::pin_mut!(cancelled, sleep);
+                    match select(cancelled, sleep).await {
+                        Either::Left(_) => ctx.cancelled_error(),
+                        Either::Right(_) => ctx.deadline_error(),
+                    }
+                }
+                None => {
+                    cancelled.await;
+                    ctx.cancelled_error()
+                }
+            }
+        }))
+    }
+}
+
+/// DoneFuture resolves once the deadline exceeded or the token is cancelled.
+struct DoneFuture(BoxedFuture<Error>);
+
+/// # Safety
+///
+/// wasm32 is a special target that we only have one event-loop for this DoneFuture.
+unsafe impl Send for DoneFuture {}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for DoneFuture.
+unsafe impl Sync for DoneFuture {}
+
+/// Apply an [`OperationContext`] to every operations, so that a group of
+/// operations share the same deadline and can be cancelled together.
+///
+/// # Notes
+///
+/// Unlike [`TimeoutLayer`](crate::layers::TimeoutLayer) which applies a fixed
+/// timeout to each call, `DeadlineLayer` applies an absolute deadline to all
+/// calls going through it, including:
+///
+/// - High level APIs like `remove_all` and recursive listing.
+/// - Every read, write and list on the returned `Reader`, `Writer` and `Lister`.
+/// - Retries and concurrent writes, as long as [`RetryLayer`](crate::layers::RetryLayer)
+///   is added before `DeadlineLayer`.
+///
+/// Once the deadline exceeded or the token cancelled, in-flight calls will be
+/// dropped and return an [`ErrorKind::Unexpected`] error which is persistent,
+/// so that it will not be retried. Writers will be aborted by calling
+/// [`oio::Write::poll_abort`] automatically before returning the error, which
+/// cleans up in-flight multipart uploads.
+///
+/// Blocking operations are checked before every call, but can't be
+/// interrupted during the call.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::CancellationToken;
+/// use opendal::layers::DeadlineLayer;
+/// use opendal::layers::OperationContext;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?.finish();
+///
+/// let token = CancellationToken::new();
+/// let ctx = OperationContext::new()
+///     .with_timeout(Duration::from_secs(30))
+///     .with_cancellation_token(token.clone());
+///
+/// // All calls in `remove_all` share the same deadline, and `token.cancel()`
+/// // from another task will stop it.
+/// op.layer(DeadlineLayer::new(ctx)).remove_all("dir/").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeadlineLayer {
+    ctx: OperationContext,
+}
+
+impl DeadlineLayer {
+    /// Create a new DeadlineLayer with given context.
+    pub fn new(ctx: OperationContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl<A: Accessor> Layer<A> for DeadlineLayer {
+    type LayeredAccessor = DeadlineAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DeadlineAccessor {
+            inner,
+            ctx: self.ctx.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadlineAccessor<A: Accessor> {
+    inner: A,
+    ctx: OperationContext,
+}
+
+impl<A: Accessor> DeadlineAccessor<A> {
+    async fn run<F: Future<Output = Result<T>>, T>(&self, op: Operation, fut: F) -> Result<T> {
+        self.ctx.check().map_err(|err| err.with_operation(op))?;
+
+        let done = self.ctx.done().0;
+        futures::pin_mut!(fut);
+        match select(fut, done).await {
+            Either::Left((res, _)) => res,
+            Either::Right((err, _)) => Err(err.with_operation(op)),
+        }
+    }
+
+    fn check(&self, op: Operation) -> Result<()> {
+        self.ctx.check().map_err(|err| err.with_operation(op))
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for DeadlineAccessor<A> {
+    type Inner = A;
+    type Reader = DeadlineWrapper<A::Reader>;
+    type BlockingReader = DeadlineWrapper<A::BlockingReader>;
+    type Writer = DeadlineWrapper<A::Writer>;
+    type BlockingWriter = DeadlineWrapper<A::BlockingWriter>;
+    type Lister = DeadlineWrapper<A::Lister>;
+    type BlockingLister = DeadlineWrapper<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.run(Operation::CreateDir, self.inner.create_dir(path, args))
+            .await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.run(Operation::Read, self.inner.read(path, args))
+            .await
+            .map(|(rp, r)| (rp, DeadlineWrapper::new(r, self.ctx.clone())))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.run(Operation::Write, self.inner.write(path, args))
+            .await
+            .map(|(rp, w)| (rp, DeadlineWrapper::new(w, self.ctx.clone())))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.run(Operation::Copy, self.inner.copy(from, to, args))
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.run(Operation::Rename, self.inner.rename(from, to, args))
+            .await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.run(Operation::Stat, self.inner.stat(path, args)).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.run(Operation::Delete, self.inner.delete(path, args))
+            .await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.run(Operation::List, self.inner.list(path, args))
+            .await
+            .map(|(rp, l)| (rp, DeadlineWrapper::new(l, self.ctx.clone())))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.run(Operation::Batch, self.inner.batch(args)).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.run(Operation::Presign, self.inner.presign(path, args))
+            .await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.check(Operation::BlockingCreateDir)?;
+        self.inner.blocking_create_dir(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.check(Operation::BlockingRead)?;
+        self.inner
+            .blocking_read(path, args)
+            .map(|(rp, r)| (rp, DeadlineWrapper::new(r, self.ctx.clone())))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.check(Operation::BlockingWrite)?;
+        self.inner
+            .blocking_write(path, args)
+            .map(|(rp, w)| (rp, DeadlineWrapper::new(w, self.ctx.clone())))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.check(Operation::BlockingCopy)?;
+        self.inner.blocking_copy(from, to, args)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.check(Operation::BlockingRename)?;
+        self.inner.blocking_rename(from, to, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.check(Operation::BlockingStat)?;
+        self.inner.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.check(Operation::BlockingDelete)?;
+        self.inner.blocking_delete(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.check(Operation::BlockingList)?;
+        self.inner
+            .blocking_list(path, args)
+            .map(|(rp, l)| (rp, DeadlineWrapper::new(l, self.ctx.clone())))
+    }
+}
+
+pub struct DeadlineWrapper<R> {
+    inner: R,
+    ctx: OperationContext,
+
+    done: Option<DoneFuture>,
+    /// The error that triggered the abort of writer.
+    aborting: Option<Error>,
+    aborted: bool,
+}
+
+impl<R> DeadlineWrapper<R> {
+    fn new(inner: R, ctx: OperationContext) -> Self {
+        Self {
+            inner,
+            ctx,
+            done: None,
+            aborting: None,
+            aborted: false,
+        }
+    }
+
+    /// Poll the deadline and cancellation token, returns an error if any of
+    /// them is reached.
+    #[inline]
+    fn poll_done(&mut self, cx: &mut Context<'_>, op: &'static str) -> Result<()> {
+        let ctx = &self.ctx;
+        let done = self.done.get_or_insert_with(|| ctx.done());
+        match done.0.poll_unpin(cx) {
+            Poll::Pending => Ok(()),
+            Poll::Ready(err) => {
+                // Following calls will build a new future which is ready
+                // immediately since deadline and cancellation are permanent.
+                self.done = None;
+                Err(err.with_operation(op))
+            }
+        }
+    }
+
+    #[inline]
+    fn check(&self, op: &'static str) -> Result<()> {
+        self.ctx.check().map_err(|err| err.with_operation(op))
+    }
+}
+
+impl<R: oio::Read> oio::Read for DeadlineWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.poll_done(cx, oio::ReadOperation::Read.into_static())?;
+        self.inner.poll_read(cx, buf)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        self.poll_done(cx, oio::ReadOperation::Seek.into_static())?;
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Err(err) = self.poll_done(cx, oio::ReadOperation::Next.into_static()) {
+            return Poll::Ready(Some(Err(err)));
+        }
+        self.inner.poll_next(cx)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for DeadlineWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.check(oio::ReadOperation::BlockingRead.into_static())?;
+        self.inner.read(buf)
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.check(oio::ReadOperation::BlockingSeek.into_static())?;
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        if let Err(err) = self.check(oio::ReadOperation::BlockingNext.into_static()) {
+            return Some(Err(err));
+        }
+        self.inner.next()
+    }
+}
+
+impl<R: oio::Write> DeadlineWrapper<R> {
+    /// Abort the inner writer once deadline exceeded or cancelled.
+    ///
+    /// Returns `Ready(Ok(()))` if the writer can go on, `Ready(Err)` after the
+    /// writer has been aborted.
+    fn poll_abort_if_done(&mut self, cx: &mut Context<'_>, op: &'static str) -> Poll<Result<()>> {
+        if self.aborted {
+            return Poll::Ready(self.check(op));
+        }
+        if self.aborting.is_none() {
+            match self.poll_done(cx, op) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(err) => self.aborting = Some(err),
+            }
+        }
+
+        let res = ready!(self.inner.poll_abort(cx));
+        self.aborted = true;
+        let err = self.aborting.take().expect("aborting error must be set");
+        Poll::Ready(Err(match res {
+            Ok(()) => err,
+            Err(abort_err) => err.with_context("abort", abort_err.to_string()),
+        }))
+    }
+}
+
+impl<R: oio::Write> oio::Write for DeadlineWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        ready!(self.poll_abort_if_done(cx, oio::WriteOperation::Write.into_static()))?;
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_abort_if_done(cx, oio::WriteOperation::Close.into_static()))?;
+        self.inner.poll_close(cx)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for DeadlineWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.check(oio::WriteOperation::BlockingWrite.into_static())?;
+        self.inner.write(bs)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.check(oio::WriteOperation::BlockingClose.into_static())?;
+        self.inner.close()
+    }
+}
+
+impl<R: oio::List> oio::List for DeadlineWrapper<R> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        self.poll_done(cx, oio::ListOperation::Next.into_static())?;
+        self.inner.poll_next(cx)
+    }
+}
+
+impl<R: oio::BlockingList> oio::BlockingList for DeadlineWrapper<R> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        self.check(oio::ListOperation::BlockingNext.into_static())?;
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use futures::future::poll_fn;
+
+    use super::*;
+    use crate::services::Memory;
+
+    /// A writer that never finishes until aborted.
+    struct PendingWriter {
+        aborted: Arc<AtomicBool>,
+    }
+
+    impl oio::Write for PendingWriter {
+        fn poll_write(
+            &mut self,
+            _: &mut Context<'_>,
+            _: &dyn oio::WriteBuf,
+        ) -> Poll<Result<usize>> {
+            Poll::Pending
+        }
+
+        fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            Poll::Pending
+        }
+
+        fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            self.aborted.store(true, Ordering::SeqCst);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_operator_with_context() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("test", "Hello, World!").await.unwrap();
+
+        let ctx = OperationContext::new().with_timeout(Duration::from_secs(60));
+        let meta = op
+            .clone()
+            .layer(DeadlineLayer::new(ctx))
+            .stat("test")
+            .await
+            .unwrap();
+        assert_eq!(meta.content_length(), 13);
+
+        let ctx = OperationContext::new().with_deadline(Instant::now());
+        let err = op
+            .clone()
+            .layer(DeadlineLayer::new(ctx))
+            .stat("test")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("deadline exceeded"), "{err}");
+        assert!(!err.is_temporary());
+
+        let ctx = OperationContext::new();
+        ctx.cancellation_token().cancel();
+        let err = op
+            .clone()
+            .layer(DeadlineLayer::new(ctx))
+            .remove_all("/")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+        assert!(op.is_exist("test").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_writer_aborted_on_cancel() {
+        let token = CancellationToken::new();
+        let ctx = OperationContext::new().with_cancellation_token(token.clone());
+        let aborted = Arc::new(AtomicBool::new(false));
+        let mut w = DeadlineWrapper::new(
+            PendingWriter {
+                aborted: aborted.clone(),
+            },
+            ctx,
+        );
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            token.cancel();
+        });
+
+        let bs = Bytes::from("Hello, World!");
+        let err = poll_fn(|cx| oio::Write::poll_write(&mut w, cx, &bs))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+        assert!(aborted.load(Ordering::SeqCst));
+
+        // Following calls should fail directly.
+        let err = poll_fn(|cx| oio::Write::poll_close(&mut w, cx))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_writer_aborted_on_deadline() {
+        let ctx = OperationContext::new().with_timeout(Duration::from_millis(10));
+        let aborted = Arc::new(AtomicBool::new(false));
+        let mut w = DeadlineWrapper::new(
+            PendingWriter {
+                aborted: aborted.clone(),
+            },
+            ctx,
+        );
+
+        let err = poll_fn(|cx| oio::Write::poll_close(&mut w, cx))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("deadline exceeded"), "{err}");
+        assert!(aborted.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,1555 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::raw::oio::ListExt;
+use crate::raw::oio::RangeReader;
+use crate::raw::oio::ReadExt;
+use crate::raw::oio::WriteExt;
+use crate::raw::*;
+use crate::*;
+
+/// Add content-addressed deduplication for underlying storage services.
+///
+/// # Layout
+///
+/// Content is split into chunks, every chunk is stored under its sha256 hash in
+/// the blob area, and the path itself stores a small manifest which lists the
+/// chunks of the file:
+///
+/// - `{prefix}blobs/{hash[..2]}/{hash}`: the chunk content.
+/// - `{prefix}refs/{hash}/{sha256(path)}`: an empty marker which means `path`
+///   references this chunk.
+/// - `{path}`: the manifest in json.
+///
+/// The prefix is `.dedup/` by default, and it will be hidden from `list`.
+///
+/// By default, the whole file is stored as a single chunk. Use
+/// [`DedupLayer::with_chunking`] to enable content-defined chunking, so that
+/// files sharing most of their content can share most of their chunks.
+///
+/// # Notes
+///
+/// - `read` reassembles the chunks transparently and supports range reads.
+/// - `delete` removes the reference markers and deletes chunks that are not
+///   referenced anymore.
+/// - Writers add the reference marker before storing or reusing a chunk, and
+///   a chunk is moved to `{prefix}trash/` before deleting it and checks the
+///   markers again afterwards, so that a chunk won't be lost if a concurrent
+///   writer starts to reference it: it will be moved back in this case.
+/// - `copy` and `rename` only touch the manifest and the reference markers.
+/// - Without chunking, content is buffered in memory until the writer is closed.
+/// - The underlying service must support `list`, so that we can count references.
+/// - `append`, `batch` and `presign` are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::DedupLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(DedupLayer::new().with_chunking(256 * 1024, 1024 * 1024, 4 * 1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DedupLayer {
+    prefix: String,
+    chunker: Option<Chunker>,
+}
+
+impl Default for DedupLayer {
+    fn default() -> Self {
+        Self {
+            prefix: ".dedup/".to_string(),
+            chunker: None,
+        }
+    }
+}
+
+impl DedupLayer {
+    /// Create a new `DedupLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prefix of the blob area.
+    ///
+    /// Default to `.dedup/`.
+    ///
+    /// # Panics
+    ///
+    /// Prefix must not be empty or `/`.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        let prefix = prefix.trim_matches('/');
+        assert!(!prefix.is_empty(), "prefix must not be empty");
+        self.prefix = format!("{prefix}/");
+        self
+    }
+
+    /// Enable content-defined chunking.
+    ///
+    /// Chunk boundaries are decided by a rolling hash of content, so that chunks
+    /// are still shared after bytes are inserted into or removed from a file.
+    /// Chunks are between `min_size` and `max_size`, and about `avg_size` on average.
+    ///
+    /// # Panics
+    ///
+    /// Sizes must satisfy `0 < min_size <= avg_size <= max_size`.
+    pub fn with_chunking(mut self, min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        assert!(
+            0 < min_size && min_size <= avg_size && avg_size <= max_size,
+            "chunk sizes must satisfy 0 < min_size <= avg_size <= max_size"
+        );
+        self.chunker = Some(Chunker::new(min_size, avg_size, max_size));
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for DedupLayer {
+    type LayeredAccessor = DedupAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DedupAccessor {
+            core: Arc::new(DedupCore {
+                inner: Arc::new(inner),
+                prefix: self.prefix.clone(),
+                chunker: self.chunker.clone(),
+            }),
+        }
+    }
+}
+
+/// Buffer size used to move blobs on services without `rename` or `copy`.
+const MOVE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Gear table for content-defined chunking, generated by splitmix64.
+const GEAR: [u64; 256] = {
+    let mut table = [0; 256];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Chunker finds chunk boundaries with gear hash.
+#[derive(Debug, Clone)]
+struct Chunker {
+    min_size: usize,
+    max_size: usize,
+    mask: u64,
+}
+
+impl Chunker {
+    fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        let bits = avg_size.next_power_of_two().trailing_zeros();
+        // Use the high bits so that every bit of the mask depends on a wide window.
+        let mask = if bits == 0 {
+            0
+        } else {
+            (u64::MAX >> (64 - bits)) << (64 - bits)
+        };
+
+        Self {
+            min_size,
+            max_size,
+            mask,
+        }
+    }
+
+    /// Returns the size of the first chunk in data.
+    ///
+    /// Returns `data.len()` if no boundary found and data is shorter than `max_size`.
+    fn cut(&self, data: &[u8]) -> usize {
+        if data.len() <= self.min_size {
+            return data.len();
+        }
+
+        let end = data.len().min(self.max_size);
+        let mut hash: u64 = 0;
+        for (i, b) in data.iter().enumerate().take(end).skip(self.min_size) {
+            hash = (hash << 1).wrapping_add(GEAR[*b as usize]);
+            if hash & self.mask == 0 {
+                return i + 1;
+            }
+        }
+        end
+    }
+}
+
+/// Manifest records the chunks of a file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    size: u64,
+    chunks: Vec<ManifestChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestChunk {
+    hash: String,
+    size: u64,
+}
+
+impl Manifest {
+    fn parse(bs: &[u8]) -> Result<Self> {
+        let manifest: Manifest = serde_json::from_slice(bs).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "file is not a DedupLayer manifest").set_source(err)
+        })?;
+        if manifest.version != 1 {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "DedupLayer manifest version is not supported",
+            )
+            .with_context("version", manifest.version.to_string()));
+        }
+        Ok(manifest)
+    }
+
+    fn push(&mut self, hash: String, size: u64) {
+        self.size += size;
+        self.chunks.push(ManifestChunk { hash, size });
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        Bytes::from(serde_json::to_vec(self).expect("manifest must be serializable"))
+    }
+
+    fn hashes(&self) -> HashSet<&str> {
+        self.chunks.iter().map(|v| v.hash.as_str()).collect()
+    }
+
+    fn metadata(&self, meta: &Metadata) -> Metadata {
+        let mut m = Metadata::new(EntryMode::FILE).with_content_length(self.size);
+        if meta.contains_metakey(Metakey::LastModified) {
+            if let Some(v) = meta.last_modified() {
+                m.set_last_modified(v);
+            }
+        }
+        m
+    }
+}
+
+fn sha256_hex(bs: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bs))
+}
+
+/// Normalize the range with the content length.
+fn normalize_range(range: BytesRange, total: u64) -> (u64, Option<u64>) {
+    match (range.offset(), range.size()) {
+        (Some(offset), size) => (offset, size),
+        (None, Some(size)) => (total.saturating_sub(size), Some(size.min(total))),
+        (None, None) => (0, None),
+    }
+}
+
+#[derive(Debug)]
+pub struct DedupAccessor<A: Accessor> {
+    core: Arc<DedupCore<A>>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for DedupAccessor<A> {
+    type Inner = A;
+    type Reader = RangeReader<DedupCore<A>, DedupReader<A>>;
+    type BlockingReader = RangeReader<DedupCore<A>, BlockingDedupReader<A>>;
+    type Writer = DedupWriter<A>;
+    type BlockingWriter = DedupWriter<A>;
+    type Lister = DedupLister<A::Lister>;
+    type BlockingLister = DedupLister<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        self.core.inner.as_ref()
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.core.inner.info();
+
+        let cap = meta.full_capability_mut();
+        cap.write_can_multi = true;
+        cap.write_can_empty = true;
+        cap.write_can_append = false;
+        cap.copy = cap.write;
+        cap.rename = cap.write;
+        cap.batch = false;
+        cap.batch_delete = false;
+        cap.presign = false;
+        cap.presign_read = false;
+        cap.presign_stat = false;
+        cap.presign_write = false;
+
+        meta
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.core.check_path(path)?;
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by DedupLayer",
+            ));
+        }
+
+        Ok((
+            RpWrite::new(),
+            DedupWriter::new(self.core.clone(), path, args),
+        ))
+    }
+
+    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.core.check_path(to)?;
+        let manifest = self.core.read_manifest(from).await?;
+        self.core.add_refs(to, &manifest).await?;
+        self.core.commit(to, &manifest, OpWrite::new()).await?;
+        Ok(RpCopy::default())
+    }
+
+    async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.core.check_path(from)?;
+        self.core.check_path(to)?;
+        let manifest = self.core.read_manifest(from).await?;
+        self.core.add_refs(to, &manifest).await?;
+        self.core.commit(to, &manifest, OpWrite::new()).await?;
+        self.core.remove(from).await?;
+        Ok(RpRename::default())
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        if path.ends_with('/') {
+            return self.core.inner.delete(path, args).await;
+        }
+
+        self.core.check_path(path)?;
+        self.core.remove(path).await?;
+        Ok(RpDelete::default())
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let (rp, l) = self.core.inner.list(path, args).await?;
+        Ok((rp, DedupLister::new(l, &self.core.prefix)))
+    }
+
+    async fn batch(&self, _: OpBatch) -> Result<RpBatch> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "batch is not supported by DedupLayer",
+        ))
+    }
+
+    async fn presign(&self, _: &str, _: OpPresign) -> Result<RpPresign> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "presign is not supported by DedupLayer",
+        ))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.core.check_path(path)?;
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by DedupLayer",
+            ));
+        }
+
+        Ok((
+            RpWrite::new(),
+            DedupWriter::new(self.core.clone(), path, args),
+        ))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.core.check_path(to)?;
+        let manifest = self.core.blocking_read_manifest(from)?;
+        self.core.blocking_add_refs(to, &manifest)?;
+        self.core.blocking_commit(to, &manifest, OpWrite::new())?;
+        Ok(RpCopy::default())
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.core.check_path(from)?;
+        self.core.check_path(to)?;
+        let manifest = self.core.blocking_read_manifest(from)?;
+        self.core.blocking_add_refs(to, &manifest)?;
+        self.core.blocking_commit(to, &manifest, OpWrite::new())?;
+        self.core.blocking_remove(from)?;
+        Ok(RpRename::default())
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        if path.ends_with('/') {
+            return self.core.inner.blocking_delete(path, args);
+        }
+
+        self.core.check_path(path)?;
+        self.core.blocking_remove(path)?;
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        let (rp, l) = self.core.inner.blocking_list(path, args)?;
+        Ok((rp, DedupLister::new(l, &self.core.prefix)))
+    }
+}
+
+/// DedupCore manages manifests, blobs and references.
+///
+/// It also returns [`DedupReader`] for given range, which will be wrapped by
+/// [`RangeReader`] to support seek.
+#[derive(Debug)]
+pub struct DedupCore<A: Accessor> {
+    inner: Arc<A>,
+    prefix: String,
+    chunker: Option<Chunker>,
+}
+
+impl<A: Accessor> DedupCore<A> {
+    fn check_path(&self, path: &str) -> Result<()> {
+        if path.starts_with(&self.prefix) {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "path is reserved by DedupLayer",
+            )
+            .with_context("path", path));
+        }
+        Ok(())
+    }
+
+    fn blob_path(&self, hash: &str) -> String {
+        format!("{}blobs/{}/{hash}", self.prefix, &hash[..2])
+    }
+
+    fn trash_path(&self, hash: &str) -> String {
+        format!("{}trash/{hash}", self.prefix)
+    }
+
+    fn ref_dir(&self, hash: &str) -> String {
+        format!("{}refs/{hash}/", self.prefix)
+    }
+
+    fn ref_path(&self, hash: &str, path: &str) -> String {
+        format!("{}{}", self.ref_dir(hash), sha256_hex(path.as_bytes()))
+    }
+
+    /// Split data into chunks, the last chunk will be kept in data if `all` is false.
+    fn split(&self, data: &mut BytesMut, all: bool) -> Vec<(String, Bytes)> {
+        let mut chunks = Vec::new();
+        match &self.chunker {
+            None => {
+                if all && !data.is_empty() {
+                    let bs = data.split().freeze();
+                    chunks.push((sha256_hex(&bs), bs));
+                }
+            }
+            Some(chunker) => {
+                while (all && !data.is_empty()) || data.len() >= chunker.max_size {
+                    let n = chunker.cut(data);
+                    let bs = data.split_to(n).freeze();
+                    chunks.push((sha256_hex(&bs), bs));
+                }
+            }
+        }
+        chunks
+    }
+
+    /// Find the blob ranges that cover given range.
+    fn segments(
+        &self,
+        manifest: &Manifest,
+        offset: u64,
+        size: Option<u64>,
+    ) -> VecDeque<(String, BytesRange)> {
+        let end = size.map_or(u64::MAX, |v| offset.saturating_add(v));
+
+        let mut segments = VecDeque::new();
+        let mut start = 0;
+        for chunk in &manifest.chunks {
+            let chunk_end = start + chunk.size;
+            if chunk_end > offset && start < end {
+                let s = offset.saturating_sub(start);
+                let e = end.min(chunk_end) - start;
+                segments.push_back((
+                    self.blob_path(&chunk.hash),
+                    BytesRange::new(Some(s), Some(e - s)),
+                ));
+            }
+            start = chunk_end;
+        }
+        segments
+    }
+
+    async fn read_all(&self, path: &str) -> Result<Vec<u8>> {
+        let (_, mut r) = self.inner.read(path, OpRead::new()).await?;
+        let mut bs = Vec::new();
+        r.read_to_end(&mut bs).await?;
+        Ok(bs)
+    }
+
+    async fn write_all(&self, path: &str, mut bs: Bytes, args: OpWrite) -> Result<()> {
+        let (_, mut w) = self.inner.write(path, args).await?;
+        while bs.has_remaining() {
+            let n = w.write(&bs).await?;
+            bs.advance(n);
+        }
+        w.close().await
+    }
+
+    async fn read_manifest(&self, path: &str) -> Result<Manifest> {
+        Manifest::parse(&self.read_all(path).await?)
+    }
+
+    /// Read the manifest that will be replaced, files that are not manifest are ignored.
+    async fn read_old_manifest(&self, path: &str) -> Result<Option<Manifest>> {
+        match self.read_all(path).await {
+            Ok(bs) => Ok(Manifest::parse(&bs).ok()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn add_ref(&self, hash: &str, path: &str) -> Result<()> {
+        self.write_all(&self.ref_path(hash, path), Bytes::new(), OpWrite::new())
+            .await
+    }
+
+    /// Reference all blobs of the manifest from path.
+    async fn add_refs(&self, path: &str, manifest: &Manifest) -> Result<()> {
+        for hash in manifest.hashes() {
+            self.add_ref(hash, path).await?;
+        }
+        Ok(())
+    }
+
+    /// Reference the blob from path and store it if not exists.
+    ///
+    /// The reference must be added before checking the blob, so that a
+    /// concurrent `collect` will either see the reference or have deleted the
+    /// blob before we check it.
+    async fn store_blob(&self, path: &str, hash: &str, bs: Bytes) -> Result<()> {
+        self.add_ref(hash, path).await?;
+
+        let blob = self.blob_path(hash);
+        match self.inner.stat(&blob, OpStat::new()).await {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.write_all(&blob, bs, OpWrite::new()).await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn store_blobs(&self, path: &str, chunks: Vec<(String, Bytes)>) -> Result<()> {
+        for (hash, bs) in chunks {
+            self.store_blob(path, &hash, bs).await?;
+        }
+        Ok(())
+    }
+
+    /// Point path to given manifest.
+    ///
+    /// References must have been added before and the ones that only used by
+    /// the replaced manifest are released after, so that a failure in the
+    /// middle never leaves a manifest with missing blobs.
+    async fn commit(&self, path: &str, manifest: &Manifest, args: OpWrite) -> Result<()> {
+        let old = self.read_old_manifest(path).await?;
+
+        self.write_all(path, manifest.to_bytes(), args).await?;
+
+        if let Some(old) = old {
+            for hash in old.hashes().difference(&manifest.hashes()) {
+                self.release(hash, path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Release the references added by an aborted writer, references that
+    /// still used by the current manifest of path are kept.
+    async fn abort(&self, path: &str, hashes: Vec<String>) -> Result<()> {
+        let old = self.read_old_manifest(path).await?.unwrap_or_default();
+        let old = old.hashes();
+        for hash in hashes {
+            if !old.contains(hash.as_str()) {
+                self.release(&hash, path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete path and release all its references.
+    async fn remove(&self, path: &str) -> Result<()> {
+        let old = self.read_old_manifest(path).await?;
+
+        self.inner.delete(path, OpDelete::new()).await?;
+
+        if let Some(old) = old {
+            for hash in old.hashes() {
+                self.release(hash, path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn release(&self, hash: &str, path: &str) -> Result<()> {
+        self.inner
+            .delete(&self.ref_path(hash, path), OpDelete::new())
+            .await?;
+        self.collect(hash).await
+    }
+
+    async fn is_referenced(&self, dir: &str) -> Result<bool> {
+        let (_, mut l) = self.inner.list(dir, OpList::new()).await?;
+        while let Some(entry) = l.next().await? {
+            if entry.path() != dir {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Delete the blob if no one references it.
+    ///
+    /// A writer could reference the blob after we checked, and skip storing it
+    /// since it still exists. So references are checked again after deleting,
+    /// and the blob will be restored if anyone references it now.
+    async fn collect(&self, hash: &str) -> Result<()> {
+        let dir = self.ref_dir(hash);
+        if self.is_referenced(&dir).await? {
+            return Ok(());
+        }
+
+        let (path, trash) = (self.blob_path(hash), self.trash_path(hash));
+        match self.move_blob(&path, &trash).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        if self.is_referenced(&dir).await? {
+            return self.move_blob(&trash, &path).await;
+        }
+        self.inner.delete(&trash, OpDelete::new()).await?;
+        // The empty ref dir is left on services like fs, it's fine to fail.
+        let _ = self.inner.delete(&dir, OpDelete::new()).await;
+        Ok(())
+    }
+
+    /// Move the blob with `rename` or `copy` if supported, otherwise stream
+    /// it to the new path.
+    async fn move_blob(&self, from: &str, to: &str) -> Result<()> {
+        let cap = self.inner.info().full_capability();
+        if cap.rename {
+            self.inner.rename(from, to, OpRename::new()).await?;
+            return Ok(());
+        }
+
+        if cap.copy {
+            self.inner.copy(from, to, OpCopy::new()).await?;
+        } else {
+            let (_, mut r) = self.inner.read(from, OpRead::new()).await?;
+            let (_, mut w) = self.inner.write(to, OpWrite::new()).await?;
+            let mut buf = vec![0; MOVE_BUFFER_SIZE];
+            loop {
+                let n = r.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                let mut bs = &buf[..n];
+                while !bs.is_empty() {
+                    let written = w.write(&bs).await?;
+                    bs = &bs[written..];
+                }
+            }
+            w.close().await?;
+        }
+        self.inner.delete(from, OpDelete::new()).await?;
+        Ok(())
+    }
+
+    fn blocking_read_all(&self, path: &str) -> Result<Vec<u8>> {
+        let (_, mut r) = self.inner.blocking_read(path, OpRead::new())?;
+        let mut bs = Vec::new();
+        oio::BlockingRead::read_to_end(&mut r, &mut bs)?;
+        Ok(bs)
+    }
+
+    fn blocking_write_all(&self, path: &str, mut bs: Bytes, args: OpWrite) -> Result<()> {
+        let (_, mut w) = self.inner.blocking_write(path, args)?;
+        while bs.has_remaining() {
+            let n = oio::BlockingWrite::write(&mut w, &bs)?;
+            bs.advance(n);
+        }
+        oio::BlockingWrite::close(&mut w)
+    }
+
+    fn blocking_read_manifest(&self, path: &str) -> Result<Manifest> {
+        Manifest::parse(&self.blocking_read_all(path)?)
+    }
+
+    fn blocking_read_old_manifest(&self, path: &str) -> Result<Option<Manifest>> {
+        match self.blocking_read_all(path) {
+            Ok(bs) => Ok(Manifest::parse(&bs).ok()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn blocking_add_ref(&self, hash: &str, path: &str) -> Result<()> {
+        self.blocking_write_all(&self.ref_path(hash, path), Bytes::new(), OpWrite::new())
+    }
+
+    fn blocking_add_refs(&self, path: &str, manifest: &Manifest) -> Result<()> {
+        for hash in manifest.hashes() {
+            self.blocking_add_ref(hash, path)?;
+        }
+        Ok(())
+    }
+
+    fn blocking_store_blobs(&self, path: &str, chunks: Vec<(String, Bytes)>) -> Result<()> {
+        for (hash, bs) in chunks {
+            self.blocking_add_ref(&hash, path)?;
+
+            let blob = self.blob_path(&hash);
+            match self.inner.blocking_stat(&blob, OpStat::new()) {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    self.blocking_write_all(&blob, bs, OpWrite::new())?
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn blocking_commit(&self, path: &str, manifest: &Manifest, args: OpWrite) -> Result<()> {
+        let old = self.blocking_read_old_manifest(path)?;
+
+        self.blocking_write_all(path, manifest.to_bytes(), args)?;
+
+        if let Some(old) = old {
+            for hash in old.hashes().difference(&manifest.hashes()) {
+                self.blocking_release(hash, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn blocking_remove(&self, path: &str) -> Result<()> {
+        let old = self.blocking_read_old_manifest(path)?;
+
+        self.inner.blocking_delete(path, OpDelete::new())?;
+
+        if let Some(old) = old {
+            for hash in old.hashes() {
+                self.blocking_release(hash, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn blocking_release(&self, hash: &str, path: &str) -> Result<()> {
+        self.inner
+            .blocking_delete(&self.ref_path(hash, path), OpDelete::new())?;
+        self.blocking_collect(hash)
+    }
+
+    fn blocking_is_referenced(&self, dir: &str) -> Result<bool> {
+        let (_, mut l) = self.inner.blocking_list(dir, OpList::new())?;
+        while let Some(entry) = oio::BlockingList::next(&mut l)? {
+            if entry.path() != dir {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn blocking_collect(&self, hash: &str) -> Result<()> {
+        let dir = self.ref_dir(hash);
+        if self.blocking_is_referenced(&dir)? {
+            return Ok(());
+        }
+
+        let (path, trash) = (self.blob_path(hash), self.trash_path(hash));
+        match self.blocking_move_blob(&path, &trash) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        if self.blocking_is_referenced(&dir)? {
+            return self.blocking_move_blob(&trash, &path);
+        }
+        self.inner.blocking_delete(&trash, OpDelete::new())?;
+        // The empty ref dir is left on services like fs, it's fine to fail.
+        let _ = self.inner.blocking_delete(&dir, OpDelete::new());
+        Ok(())
+    }
+
+    fn blocking_move_blob(&self, from: &str, to: &str) -> Result<()> {
+        let cap = self.inner.info().full_capability();
+        if cap.rename {
+            self.inner.blocking_rename(from, to, OpRename::new())?;
+            return Ok(());
+        }
+
+        if cap.copy {
+            self.inner.blocking_copy(from, to, OpCopy::new())?;
+        } else {
+            let (_, mut r) = self.inner.blocking_read(from, OpRead::new())?;
+            let (_, mut w) = self.inner.blocking_write(to, OpWrite::new())?;
+            let mut buf = vec![0; MOVE_BUFFER_SIZE];
+            loop {
+                let n = oio::BlockingRead::read(&mut r, &mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                let mut bs = &buf[..n];
+                while !bs.is_empty() {
+                    let written = oio::BlockingWrite::write(&mut w, &bs)?;
+                    bs = &bs[written..];
+                }
+            }
+            oio::BlockingWrite::close(&mut w)?;
+        }
+        self.inner.blocking_delete(from, OpDelete::new())?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> Accessor for DedupCore<A> {
+    type Reader = DedupReader<A>;
+    type Writer = ();
+    type Lister = ();
+    type BlockingReader = BlockingDedupReader<A>;
+    type BlockingWriter = ();
+    type BlockingLister = ();
+
+    fn info(&self) -> AccessorInfo {
+        self.inner.info()
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let meta = self.inner.stat(path, args).await?.into_metadata();
+        if meta.is_dir() {
+            return Ok(RpStat::new(meta));
+        }
+
+        let manifest = self.read_manifest(path).await?;
+        Ok(RpStat::new(manifest.metadata(&meta)))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let manifest = self.read_manifest(path).await?;
+        let (offset, size) = normalize_range(args.range(), manifest.size);
+
+        let remaining = manifest.size.saturating_sub(offset);
+        let remaining = size.map_or(remaining, |v| v.min(remaining));
+        Ok((
+            RpRead::new().with_size(Some(remaining)),
+            DedupReader::new(self.inner.clone(), self.segments(&manifest, offset, size)),
+        ))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let meta = self.inner.blocking_stat(path, args)?.into_metadata();
+        if meta.is_dir() {
+            return Ok(RpStat::new(meta));
+        }
+
+        let manifest = self.blocking_read_manifest(path)?;
+        Ok(RpStat::new(manifest.metadata(&meta)))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let manifest = self.blocking_read_manifest(path)?;
+        let (offset, size) = normalize_range(args.range(), manifest.size);
+
+        let remaining = manifest.size.saturating_sub(offset);
+        let remaining = size.map_or(remaining, |v| v.min(remaining));
+        Ok((
+            RpRead::new().with_size(Some(remaining)),
+            BlockingDedupReader::new(self.inner.clone(), self.segments(&manifest, offset, size)),
+        ))
+    }
+}
+
+/// DedupReader reads blobs one by one.
+///
+/// DedupReader doesn't support seek, use [`RangeReader`] instead.
+pub struct DedupReader<A: Accessor> {
+    inner: Arc<A>,
+    segments: VecDeque<(String, BytesRange)>,
+    state: ReadState<A::Reader>,
+}
+
+enum ReadState<R> {
+    Idle,
+    Open(BoxedFuture<Result<R>>),
+    Read(R),
+}
+
+/// # Safety
+///
+/// wasm32 is a special target that we only have one event-loop for this state.
+unsafe impl<R: Send> Send for ReadState<R> {}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for ReadState.
+unsafe impl<R: Sync> Sync for ReadState<R> {}
+
+impl<A: Accessor> DedupReader<A> {
+    fn new(inner: Arc<A>, segments: VecDeque<(String, BytesRange)>) -> Self {
+        Self {
+            inner,
+            segments,
+            state: ReadState::Idle,
+        }
+    }
+
+    /// Make sure there is a reader available, returns false if all blobs are read.
+    fn poll_open(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        loop {
+            match &mut self.state {
+                ReadState::Idle => {
+                    let Some((path, range)) = self.segments.pop_front() else {
+                        return Poll::Ready(Ok(false));
+                    };
+
+                    let inner = self.inner.clone();
+                    self.state = ReadState::Open(Box::pin(async move {
+                        let (_, r) = inner.read(&path, OpRead::new().with_range(range)).await?;
+                        Ok(r)
+                    }));
+                }
+                ReadState::Open(fut) => {
+                    let r = ready!(fut.as_mut().poll(cx));
+                    self.state = match r {
+                        Ok(r) => ReadState::Read(r),
+                        Err(err) => {
+                            self.state = ReadState::Idle;
+                            return Poll::Ready(Err(err));
+                        }
+                    };
+                }
+                ReadState::Read(_) => return Poll::Ready(Ok(true)),
+            }
+        }
+    }
+}
+
+impl<A: Accessor> oio::Read for DedupReader<A> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        while ready!(self.poll_open(cx))? {
+            let ReadState::Read(r) = &mut self.state else {
+                unreachable!("reader must be opened")
+            };
+            match ready!(r.poll_read(cx, buf))? {
+                0 => self.state = ReadState::Idle,
+                n => return Poll::Ready(Ok(n)),
+            }
+        }
+        Poll::Ready(Ok(0))
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, _: SeekFrom) -> Poll<Result<u64>> {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        )))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        loop {
+            match ready!(self.poll_open(cx)) {
+                Ok(true) => {}
+                Ok(false) => return Poll::Ready(None),
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+
+            let ReadState::Read(r) = &mut self.state else {
+                unreachable!("reader must be opened")
+            };
+            match ready!(r.poll_next(cx)) {
+                None => self.state = ReadState::Idle,
+                Some(res) => return Poll::Ready(Some(res)),
+            }
+        }
+    }
+}
+
+/// BlockingDedupReader reads blobs one by one.
+///
+/// BlockingDedupReader doesn't support seek, use [`RangeReader`] instead.
+pub struct BlockingDedupReader<A: Accessor> {
+    inner: Arc<A>,
+    segments: VecDeque<(String, BytesRange)>,
+    reader: Option<A::BlockingReader>,
+}
+
+impl<A: Accessor> BlockingDedupReader<A> {
+    fn new(inner: Arc<A>, segments: VecDeque<(String, BytesRange)>) -> Self {
+        Self {
+            inner,
+            segments,
+            reader: None,
+        }
+    }
+
+    fn open(&mut self) -> Result<Option<&mut A::BlockingReader>> {
+        if self.reader.is_none() {
+            let Some((path, range)) = self.segments.pop_front() else {
+                return Ok(None);
+            };
+            let (_, r) = self
+                .inner
+                .blocking_read(&path, OpRead::new().with_range(range))?;
+            self.reader = Some(r);
+        }
+        Ok(self.reader.as_mut())
+    }
+}
+
+impl<A: Accessor> oio::BlockingRead for BlockingDedupReader<A> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while let Some(r) = self.open()? {
+            match r.read(buf)? {
+                0 => self.reader = None,
+                n => return Ok(n),
+            }
+        }
+        Ok(0)
+    }
+
+    fn seek(&mut self, _: SeekFrom) -> Result<u64> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        ))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        loop {
+            let r = match self.open() {
+                Ok(Some(r)) => r,
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            };
+            match r.next() {
+                None => self.reader = None,
+                Some(res) => return Some(res),
+            }
+        }
+    }
+}
+
+/// DedupWriter splits content into chunks and stores them as blobs, the
+/// manifest will be written while closing.
+pub struct DedupWriter<A: Accessor> {
+    core: Arc<DedupCore<A>>,
+    path: String,
+    args: OpWrite,
+
+    buf: BytesMut,
+    manifest: Manifest,
+    state: WriteState,
+}
+
+enum WriteState {
+    Idle,
+    Store(BoxedFuture<Result<()>>),
+    Finish(BoxedFuture<Result<()>>),
+}
+
+/// # Safety
+///
+/// wasm32 is a special target that we only have one event-loop for this state.
+unsafe impl Send for WriteState {}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for WriteState.
+unsafe impl Sync for WriteState {}
+
+impl<A: Accessor> DedupWriter<A> {
+    fn new(core: Arc<DedupCore<A>>, path: &str, args: OpWrite) -> Self {
+        Self {
+            core,
+            path: path.to_string(),
+            args,
+
+            buf: BytesMut::new(),
+            manifest: Manifest {
+                version: 1,
+                ..Default::default()
+            },
+            state: WriteState::Idle,
+        }
+    }
+
+    /// Copy given bytes into buffer and split out the complete chunks.
+    fn fill(&mut self, bs: &dyn oio::WriteBuf) -> (usize, Vec<(String, Bytes)>) {
+        let chunk = bs.chunk();
+        let n = match &self.core.chunker {
+            Some(chunker) => chunk.len().min(chunker.max_size - self.buf.len()),
+            None => chunk.len(),
+        };
+        self.buf.extend_from_slice(&chunk[..n]);
+
+        let chunks = self.core.split(&mut self.buf, false);
+        for (hash, bs) in &chunks {
+            self.manifest.push(hash.clone(), bs.len() as u64);
+        }
+        (n, chunks)
+    }
+
+    fn finish(&mut self) -> Vec<(String, Bytes)> {
+        let chunks = self.core.split(&mut self.buf, true);
+        for (hash, bs) in &chunks {
+            self.manifest.push(hash.clone(), bs.len() as u64);
+        }
+        chunks
+    }
+}
+
+impl<A: Accessor> oio::Write for DedupWriter<A> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        if let WriteState::Store(fut) = &mut self.state {
+            let res = ready!(fut.as_mut().poll(cx));
+            self.state = WriteState::Idle;
+            res?;
+        }
+
+        let (n, chunks) = self.fill(bs);
+        if !chunks.is_empty() {
+            let core = self.core.clone();
+            let path = self.path.clone();
+            self.state =
+                WriteState::Store(Box::pin(
+                    async move { core.store_blobs(&path, chunks).await },
+                ));
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            match &mut self.state {
+                WriteState::Idle => {
+                    let chunks = self.finish();
+                    let core = self.core.clone();
+                    let path = self.path.clone();
+                    let manifest = self.manifest.clone();
+                    let args = self.args.clone();
+                    self.state = WriteState::Finish(Box::pin(async move {
+                        core.store_blobs(&path, chunks).await?;
+                        core.commit(&path, &manifest, args).await
+                    }));
+                }
+                WriteState::Store(fut) => {
+                    let res = ready!(fut.as_mut().poll(cx));
+                    self.state = WriteState::Idle;
+                    res?;
+                }
+                WriteState::Finish(fut) => {
+                    let res = ready!(fut.as_mut().poll(cx));
+                    self.state = WriteState::Idle;
+                    return Poll::Ready(res);
+                }
+            }
+        }
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            match &mut self.state {
+                WriteState::Idle | WriteState::Store(_) => {
+                    // Release the references added by this writer, blobs that
+                    // still referenced by others will be kept.
+                    self.buf.clear();
+                    let hashes = self
+                        .manifest
+                        .hashes()
+                        .into_iter()
+                        .map(|v| v.to_string())
+                        .collect();
+                    let core = self.core.clone();
+                    let path = self.path.clone();
+                    self.state =
+                        WriteState::Finish(Box::pin(
+                            async move { core.abort(&path, hashes).await },
+                        ));
+                }
+                WriteState::Finish(fut) => {
+                    let res = ready!(fut.as_mut().poll(cx));
+                    self.state = WriteState::Idle;
+                    return Poll::Ready(res);
+                }
+            }
+        }
+    }
+}
+
+impl<A: Accessor> oio::BlockingWrite for DedupWriter<A> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let (n, chunks) = self.fill(bs);
+        self.core.blocking_store_blobs(&self.path, chunks)?;
+        Ok(n)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let chunks = self.finish();
+        self.core.blocking_store_blobs(&self.path, chunks)?;
+        self.core
+            .blocking_commit(&self.path, &self.manifest, self.args.clone())
+    }
+}
+
+/// DedupLister hides the blob area and resets the metadata of files.
+///
+/// The metadata of files will be reset so that the real size can be fetched
+/// by `stat` lazily.
+pub struct DedupLister<L> {
+    inner: L,
+    prefix: String,
+}
+
+impl<L> DedupLister<L> {
+    fn new(inner: L, prefix: &str) -> Self {
+        Self {
+            inner,
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn map_entry(&self, entry: oio::Entry) -> Option<oio::Entry> {
+        if entry.path().starts_with(&self.prefix) {
+            return None;
+        }
+        if !entry.mode().is_file() {
+            return Some(entry);
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        if entry.metadata().contains_metakey(Metakey::LastModified) {
+            if let Some(v) = entry.metadata().last_modified() {
+                meta.set_last_modified(v);
+            }
+        }
+        Some(oio::Entry::new(entry.path(), meta))
+    }
+}
+
+impl<L: oio::List> oio::List for DedupLister<L> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        loop {
+            match ready!(self.inner.poll_next(cx))? {
+                None => return Poll::Ready(Ok(None)),
+                Some(entry) => {
+                    if let Some(entry) = self.map_entry(entry) {
+                        return Poll::Ready(Ok(Some(entry)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<L: oio::BlockingList> oio::BlockingList for DedupLister<L> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        while let Some(entry) = self.inner.next()? {
+            if let Some(entry) = self.map_entry(entry) {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::services::Fs;
+    use crate::services::Memory;
+
+    fn new_content(size: usize) -> Vec<u8> {
+        let mut rng = thread_rng();
+        let mut content = vec![0; size];
+        rng.fill_bytes(&mut content);
+        content
+    }
+
+    async fn count_files(op: &Operator, dir: &str) -> usize {
+        op.list_with(dir)
+            .recursive(true)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.metadata().is_file())
+            .count()
+    }
+
+    async fn count_blobs(op: &Operator) -> usize {
+        count_files(op, ".dedup/blobs/").await
+    }
+
+    #[tokio::test]
+    async fn test_dedup_read_write() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let op = inner.clone().layer(DedupLayer::new());
+
+        let content = new_content(1000);
+        op.write("a", content.clone()).await.unwrap();
+        op.write("dir/b", content.clone()).await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 1);
+
+        assert_eq!(op.read("dir/b").await.unwrap(), content);
+        assert_eq!(op.stat("a").await.unwrap().content_length(), 1000);
+        assert_eq!(
+            op.read_with("a").range(100..300).await.unwrap(),
+            &content[100..300]
+        );
+
+        let mut paths: Vec<_> = op
+            .list_with("")
+            .recursive(true)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| v.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["a", "dir/b"]);
+
+        op.delete("a").await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 1);
+        assert_eq!(op.read("dir/b").await.unwrap(), content);
+
+        // Overwrite releases the previous content.
+        op.write("dir/b", "hello").await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 1);
+        assert_eq!(op.read("dir/b").await.unwrap(), b"hello");
+
+        op.delete("dir/b").await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 0);
+        op.delete("not_exist").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dedup_chunking() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let op = inner
+            .clone()
+            .layer(DedupLayer::new().with_chunking(64, 256, 1024));
+
+        let content = new_content(64 * 1024);
+        let mut w = op.writer("a").await.unwrap();
+        for chunk in content.chunks(1000) {
+            w.write(chunk.to_vec()).await.unwrap();
+        }
+        w.close().await.unwrap();
+        let blobs = count_blobs(&inner).await;
+        assert!(blobs > 1);
+
+        // Insert some bytes, most of the chunks should be shared.
+        let mut modified = content.clone();
+        modified.splice(30000..30000, new_content(10));
+        op.write("b", modified.clone()).await.unwrap();
+        let added = count_blobs(&inner).await - blobs;
+        assert!(added < blobs / 4, "added {added} blobs of {blobs}");
+
+        assert_eq!(op.read("a").await.unwrap(), content);
+        assert_eq!(op.read("b").await.unwrap(), modified);
+        assert_eq!(
+            op.read_with("b").range(1000..40000).await.unwrap(),
+            &modified[1000..40000]
+        );
+
+        op.rename("b", "c").await.unwrap();
+        assert_eq!(op.read("c").await.unwrap(), modified);
+        op.delete("a").await.unwrap();
+        op.delete("c").await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_abort() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let op = inner
+            .clone()
+            .layer(DedupLayer::new().with_chunking(16, 32, 64));
+
+        let content = new_content(256);
+        let mut w1 = op.writer("a").await.unwrap();
+        let mut w2 = op.writer("b").await.unwrap();
+        for chunk in content.chunks(64) {
+            w1.write(chunk.to_vec()).await.unwrap();
+            w2.write(chunk.to_vec()).await.unwrap();
+        }
+
+        // Blobs shared with the in-flight writer must be kept.
+        w1.abort().await.unwrap();
+        w2.close().await.unwrap();
+        assert!(!op.is_exist("a").await.unwrap());
+        assert_eq!(op.read("b").await.unwrap(), content);
+
+        op.delete("b").await.unwrap();
+        assert_eq!(count_blobs(&inner).await, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_dedup_concurrent() {
+        // Memory moves blobs by streaming, and fs moves them by rename.
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = Fs::default();
+        builder.root(&dir.path().to_string_lossy());
+        for inner in [
+            Operator::new(Memory::default()).unwrap().finish(),
+            Operator::new(builder).unwrap().finish(),
+        ] {
+            test_dedup_concurrent_with(inner).await;
+        }
+    }
+
+    async fn test_dedup_concurrent_with(inner: Operator) {
+        let op = inner.clone().layer(DedupLayer::new());
+
+        let content = new_content(1000);
+        for round in 0..20 {
+            op.write("base", content.clone()).await.unwrap();
+
+            // Writers reuse the blob while delete is collecting it.
+            let mut tasks = Vec::new();
+            for i in 0..4 {
+                let (op, content) = (op.clone(), content.clone());
+                tasks.push(tokio::spawn(async move {
+                    op.write(&format!("{round}-{i}"), content).await
+                }));
+            }
+            let deleter = {
+                let op = op.clone();
+                tokio::spawn(async move { op.delete("base").await })
+            };
+            for task in tasks {
+                task.await.unwrap().unwrap();
+            }
+            deleter.await.unwrap().unwrap();
+
+            for i in 0..4 {
+                let path = format!("{round}-{i}");
+                assert_eq!(op.read(&path).await.unwrap(), content, "{path}");
+                op.delete(&path).await.unwrap();
+            }
+            assert_eq!(count_blobs(&inner).await, 0);
+            assert_eq!(count_files(&inner, ".dedup/trash/").await, 0);
+        }
+    }
+
+    #[test]
+    fn test_dedup_blocking_fs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = Fs::default();
+        builder.root(&dir.path().to_string_lossy());
+        let inner = Operator::new(builder).unwrap().finish();
+        let op = inner
+            .clone()
+            .layer(DedupLayer::new().with_chunking(16, 32, 64))
+            .blocking();
+
+        let content = new_content(1000);
+        op.write("a", content.clone()).unwrap();
+        op.copy("a", "b").unwrap();
+        assert_eq!(op.read("b").unwrap(), content);
+        assert_eq!(
+            op.read_with("b").range(10..500).call().unwrap(),
+            &content[10..500]
+        );
+        assert_eq!(op.stat("b").unwrap().content_length(), 1000);
+
+        op.delete("a").unwrap();
+        op.delete("b").unwrap();
+        assert!(inner
+            .blocking()
+            .list_with(".dedup/blobs/")
+            .recursive(true)
+            .call()
+            .unwrap()
+            .into_iter()
+            .all(|v| v.metadata().is_dir()));
+    }
+}
@@ -0,0 +1,1202 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::KeyInit;
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::Payload;
+use aes_gcm::Aes256Gcm;
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use chacha20poly1305::ChaCha20Poly1305;
+
+use crate::raw::oio::RangeReader;
+use crate::raw::oio::ReadExt;
+use crate::raw::*;
+use crate::*;
+
+/// Magic bytes at the start of every encrypted file.
+const MAGIC: &[u8; 4] = b"OENC";
+/// Version of the encrypted file format.
+const VERSION: u8 = 1;
+/// Size of the encrypted file header.
+const HEADER_SIZE: usize = 64;
+/// Max length of key id that can be stored in header.
+const MAX_KEY_ID_SIZE: usize = HEADER_SIZE - 28;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+/// Extra bytes added to every chunk: the nonce and the authentication tag.
+const CHUNK_OVERHEAD: usize = NONCE_SIZE + TAG_SIZE;
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Add client side encryption for underlying storage services.
+///
+/// `EncryptionLayer` encrypts data on `write` and decrypts data on `read`
+/// transparently, so that the underlying storage never sees the plaintext.
+///
+/// # Format
+///
+/// Every file starts with a fixed size header which records the algorithm,
+/// chunk size, key id and a random file id. The content is split into chunks
+/// which are encrypted and authenticated independently with a random nonce.
+/// The header and the chunk index are used as associated data, so chunks can't
+/// be reordered, truncated or moved between files without being detected.
+///
+/// Thanks to independent chunks, `read_with(range)` only needs to fetch and
+/// decrypt the chunks that cover the range.
+///
+/// # Keys
+///
+/// Keys are provided by [`KeyProvider`]. New files are always encrypted by the
+/// current key, and existing files are decrypted by the key recorded in their
+/// header. So keys can be rotated without rewriting existing data.
+///
+/// # Notes
+///
+/// - `stat` and `list` report the plaintext size, which is calculated by the
+///   configured chunk size. Please don't change the chunk size for existing data.
+/// - Reading a range that not starts from the beginning of file will send an extra
+///   request to fetch the header.
+/// - Reading a range that ends at the end of a chunk will fetch one more chunk to
+///   make sure the file is not truncated.
+/// - `append` and `presign` are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::EncryptionLayer;
+/// use opendal::layers::StaticKeyProvider;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let keys = StaticKeyProvider::new("key-2024", [0; 32]).with_key("key-2023", [1; 32]);
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(EncryptionLayer::new(keys))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncryptionLayer {
+    keys: Arc<dyn KeyProvider>,
+    algorithm: EncryptionAlgorithm,
+    chunk_size: usize,
+}
+
+impl EncryptionLayer {
+    /// Create a new `EncryptionLayer` with given key provider.
+    pub fn new(keys: impl KeyProvider) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            algorithm: EncryptionAlgorithm::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Set the algorithm used to encrypt new files.
+    ///
+    /// Default to [`EncryptionAlgorithm::Aes256Gcm`].
+    pub fn with_algorithm(mut self, algorithm: EncryptionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the size of plaintext in every encrypted chunk.
+    ///
+    /// Default to 64 KiB.
+    ///
+    /// # Panics
+    ///
+    /// Chunk size must be in `1..=u32::MAX`.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(
+            chunk_size > 0 && chunk_size <= u32::MAX as usize,
+            "chunk_size must be between 1 and u32::MAX"
+        );
+        self.chunk_size = chunk_size;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for EncryptionLayer {
+    type LayeredAccessor = EncryptionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        EncryptionAccessor {
+            core: Arc::new(EncryptionCore {
+                inner,
+                ctx: Arc::new(self.clone()),
+            }),
+        }
+    }
+}
+
+/// EncryptionAlgorithm is the AEAD algorithm used by [`EncryptionLayer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncryptionAlgorithm {
+    /// AES-256 in Galois/Counter Mode.
+    #[default]
+    Aes256Gcm,
+    /// ChaCha20 with Poly1305 authenticator.
+    ChaCha20Poly1305,
+}
+
+impl EncryptionAlgorithm {
+    fn to_u8(self) -> u8 {
+        match self {
+            EncryptionAlgorithm::Aes256Gcm => 1,
+            EncryptionAlgorithm::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    fn from_u8(v: u8) -> Result<Self> {
+        match v {
+            1 => Ok(EncryptionAlgorithm::Aes256Gcm),
+            2 => Ok(EncryptionAlgorithm::ChaCha20Poly1305),
+            v => Err(Error::new(
+                ErrorKind::Unexpected,
+                "encrypted file uses unknown algorithm",
+            )
+            .with_context("algorithm", v.to_string())),
+        }
+    }
+}
+
+/// KeyProvider provides keys for [`EncryptionLayer`].
+///
+/// All keys are 256 bits, and identified by a key id which is stored in the
+/// header of encrypted files. Key id must not be longer than 36 bytes.
+pub trait KeyProvider: Send + Sync + Debug + 'static {
+    /// Return the id and value of the key used to encrypt new files.
+    fn current_key(&self) -> Result<(String, [u8; 32])>;
+
+    /// Return the key of given id to decrypt existing files.
+    fn get_key(&self, key_id: &str) -> Result<[u8; 32]>;
+}
+
+/// StaticKeyProvider is a [`KeyProvider`] with in-memory keys.
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: HashMap<String, [u8; 32]>,
+}
+
+impl StaticKeyProvider {
+    /// Create a new `StaticKeyProvider` with the key used to encrypt new files.
+    pub fn new(key_id: &str, key: [u8; 32]) -> Self {
+        Self {
+            current: key_id.to_string(),
+            keys: HashMap::from([(key_id.to_string(), key)]),
+        }
+    }
+
+    /// Add a key that only used to decrypt existing files.
+    pub fn with_key(mut self, key_id: &str, key: [u8; 32]) -> Self {
+        self.keys.entry(key_id.to_string()).or_insert(key);
+        self
+    }
+}
+
+impl Debug for StaticKeyProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Never print the keys.
+        f.debug_struct("StaticKeyProvider")
+            .field("current", &self.current)
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key(&self) -> Result<(String, [u8; 32])> {
+        Ok((self.current.clone(), self.get_key(&self.current)?))
+    }
+
+    fn get_key(&self, key_id: &str) -> Result<[u8; 32]> {
+        self.keys.get(key_id).copied().ok_or_else(|| {
+            Error::new(ErrorKind::PermissionDenied, "encryption key is not found")
+                .with_context("key_id", key_id)
+        })
+    }
+}
+
+enum Cipher {
+    Aes256Gcm(Box<Aes256Gcm>),
+    ChaCha20Poly1305(Box<ChaCha20Poly1305>),
+}
+
+impl Cipher {
+    fn new(algorithm: EncryptionAlgorithm, key: &[u8; 32]) -> Self {
+        match algorithm {
+            EncryptionAlgorithm::Aes256Gcm => {
+                Cipher::Aes256Gcm(Box::new(Aes256Gcm::new(key.into())))
+            }
+            EncryptionAlgorithm::ChaCha20Poly1305 => {
+                Cipher::ChaCha20Poly1305(Box::new(ChaCha20Poly1305::new(key.into())))
+            }
+        }
+    }
+
+    /// Encrypt given plaintext into `nonce || ciphertext || tag`.
+    fn encrypt(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let ciphertext = match self {
+            Cipher::Aes256Gcm(c) => c.encrypt(&nonce.into(), payload),
+            Cipher::ChaCha20Poly1305(c) => c.encrypt(&nonce.into(), payload),
+        }
+        .map_err(|_| Error::new(ErrorKind::Unexpected, "encrypt chunk failed"))?;
+
+        let mut frame = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        frame.extend_from_slice(&nonce);
+        frame.extend_from_slice(&ciphertext);
+        Ok(frame)
+    }
+
+    /// Decrypt given `nonce || ciphertext || tag` into plaintext.
+    fn decrypt(&self, aad: &[u8], frame: &[u8]) -> Result<Vec<u8>> {
+        if frame.len() < CHUNK_OVERHEAD {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "encrypted chunk is truncated",
+            ));
+        }
+
+        let (nonce, msg) = frame.split_at(NONCE_SIZE);
+        let nonce: [u8; NONCE_SIZE] = nonce.try_into().expect("nonce size must be valid");
+        let payload = Payload { msg, aad };
+        match self {
+            Cipher::Aes256Gcm(c) => c.decrypt(&nonce.into(), payload),
+            Cipher::ChaCha20Poly1305(c) => c.decrypt(&nonce.into(), payload),
+        }
+        .map_err(|_| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "decrypt chunk failed, the content may be corrupted or tampered",
+            )
+        })
+    }
+}
+
+/// Header of an encrypted file.
+///
+/// ```text
+/// | magic (4) | version (1) | algorithm (1) | key id size (1) | reserved (1) |
+/// | chunk size (4, little endian) | file id (16) | key id (36, zero padded) |
+/// ```
+struct Header {
+    raw: [u8; HEADER_SIZE],
+    cipher: Cipher,
+}
+
+impl Header {
+    /// Build a new header for file that will be written.
+    fn new(ctx: &EncryptionLayer) -> Result<Self> {
+        let (key_id, key) = ctx.keys.current_key()?;
+        if key_id.len() > MAX_KEY_ID_SIZE {
+            return Err(
+                Error::new(ErrorKind::ConfigInvalid, "encryption key id is too long")
+                    .with_context("key_id", key_id),
+            );
+        }
+
+        let mut raw = [0; HEADER_SIZE];
+        raw[..4].copy_from_slice(MAGIC);
+        raw[4] = VERSION;
+        raw[5] = ctx.algorithm.to_u8();
+        raw[6] = key_id.len() as u8;
+        raw[8..12].copy_from_slice(&(ctx.chunk_size as u32).to_le_bytes());
+        OsRng.fill_bytes(&mut raw[12..28]);
+        raw[28..28 + key_id.len()].copy_from_slice(key_id.as_bytes());
+
+        Ok(Self {
+            raw,
+            cipher: Cipher::new(ctx.algorithm, &key),
+        })
+    }
+
+    /// Parse the header of existing file.
+    fn parse(bs: &[u8], ctx: &EncryptionLayer) -> Result<Self> {
+        if bs.len() < HEADER_SIZE || &bs[..4] != MAGIC {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "file is not encrypted by EncryptionLayer",
+            ));
+        }
+        if bs[4] != VERSION {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "encrypted file version is not supported",
+            )
+            .with_context("version", bs[4].to_string()));
+        }
+
+        let algorithm = EncryptionAlgorithm::from_u8(bs[5])?;
+        let chunk_size = u32::from_le_bytes(bs[8..12].try_into().expect("must be valid"));
+        if chunk_size as usize != ctx.chunk_size {
+            return Err(
+                Error::new(ErrorKind::Unexpected, "encrypted file chunk size mismatch")
+                    .with_context("expected", ctx.chunk_size.to_string())
+                    .with_context("actual", chunk_size.to_string()),
+            );
+        }
+        let key_id_size = (bs[6] as usize).min(MAX_KEY_ID_SIZE);
+        let key_id = String::from_utf8_lossy(&bs[28..28 + key_id_size]);
+        let key = ctx.keys.get_key(&key_id)?;
+
+        let mut raw = [0; HEADER_SIZE];
+        raw.copy_from_slice(&bs[..HEADER_SIZE]);
+        Ok(Self {
+            raw,
+            cipher: Cipher::new(algorithm, &key),
+        })
+    }
+
+    fn aad(&self, index: u64) -> [u8; HEADER_SIZE + 8] {
+        let mut aad = [0; HEADER_SIZE + 8];
+        aad[..HEADER_SIZE].copy_from_slice(&self.raw);
+        aad[HEADER_SIZE..].copy_from_slice(&index.to_be_bytes());
+        aad
+    }
+
+    fn encrypt_chunk(&self, index: u64, plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.encrypt(&self.aad(index), plaintext)
+    }
+
+    fn decrypt_chunk(&self, index: u64, frame: &[u8]) -> Result<Vec<u8>> {
+        self.cipher.decrypt(&self.aad(index), frame)
+    }
+}
+
+/// Calculate the plaintext size by given ciphertext size.
+///
+/// The last chunk of a file is always shorter than chunk size (maybe empty),
+/// so the size of plaintext can be calculated without reading the file.
+fn plaintext_size(size: u64, chunk_size: usize) -> u64 {
+    let frame_size = (chunk_size + CHUNK_OVERHEAD) as u64;
+    let body = size.saturating_sub(HEADER_SIZE as u64);
+
+    (body / frame_size) * chunk_size as u64
+        + (body % frame_size).saturating_sub(CHUNK_OVERHEAD as u64)
+}
+
+fn set_plaintext_size(meta: &mut Metadata, chunk_size: usize) {
+    if meta.is_file() && meta.contains_metakey(Metakey::ContentLength) {
+        let size = meta.content_length();
+        meta.set_content_length(plaintext_size(size, chunk_size));
+    }
+}
+
+#[derive(Debug)]
+pub struct EncryptionAccessor<A: Accessor> {
+    core: Arc<EncryptionCore<A>>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for EncryptionAccessor<A> {
+    type Inner = A;
+    type Reader = RangeReader<EncryptionCore<A>, DecryptReader<A::Reader>>;
+    type BlockingReader = RangeReader<EncryptionCore<A>, DecryptReader<A::BlockingReader>>;
+    type Writer = EncryptWriter<A::Writer>;
+    type BlockingWriter = EncryptWriter<A::BlockingWriter>;
+    type Lister = EncryptionLister<A::Lister>;
+    type BlockingLister = EncryptionLister<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.core.inner
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.core.inner.info();
+
+        let cap = meta.full_capability_mut();
+        cap.write_can_append = false;
+        cap.presign = false;
+        cap.presign_read = false;
+        cap.presign_stat = false;
+        cap.presign_write = false;
+
+        meta
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by EncryptionLayer",
+            ));
+        }
+
+        let header = Header::new(&self.core.ctx)?;
+        let (rp, w) = self.core.inner.write(path, args).await?;
+        Ok((rp, EncryptWriter::new(w, header, self.core.ctx.chunk_size)))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.stat(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let (rp, l) = self.core.inner.list(path, args).await?;
+        Ok((rp, EncryptionLister::new(l, self.core.ctx.chunk_size)))
+    }
+
+    async fn presign(&self, _: &str, _: OpPresign) -> Result<RpPresign> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "presign is not supported by EncryptionLayer",
+        ))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        Ok((
+            RpRead::new(),
+            RangeReader::new(self.core.clone(), path, args),
+        ))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by EncryptionLayer",
+            ));
+        }
+
+        let header = Header::new(&self.core.ctx)?;
+        let (rp, w) = self.core.inner.blocking_write(path, args)?;
+        Ok((rp, EncryptWriter::new(w, header, self.core.ctx.chunk_size)))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.core.blocking_stat(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        let (rp, l) = self.core.inner.blocking_list(path, args)?;
+        Ok((rp, EncryptionLister::new(l, self.core.ctx.chunk_size)))
+    }
+}
+
+/// EncryptionCore returns [`DecryptReader`] for given plaintext range.
+///
+/// It will be wrapped by [`RangeReader`] to support seek.
+#[derive(Debug)]
+pub struct EncryptionCore<A: Accessor> {
+    inner: A,
+    ctx: Arc<EncryptionLayer>,
+}
+
+impl<A: Accessor> EncryptionCore<A> {
+    /// Calculate the ciphertext range to read for given plaintext range.
+    ///
+    /// Returns the ciphertext range, the index of first chunk and the plaintext
+    /// bytes to skip in the first chunk.
+    fn ciphertext_range(&self, range: BytesRange) -> (BytesRange, u64, usize) {
+        let chunk_size = self.ctx.chunk_size as u64;
+        let frame_size = chunk_size + CHUNK_OVERHEAD as u64;
+
+        let offset = range.offset().expect("offset must be normalized");
+        let first = offset / chunk_size;
+        let skip = (offset % chunk_size) as usize;
+        let size = match range.size() {
+            Some(size) if size > 0 => {
+                let last = (offset + size - 1) / chunk_size;
+                let mut frames = last - first + 1;
+                // Range ends at the end of a chunk, read the next chunk too so
+                // that truncated files can be detected.
+                if (offset + size) % chunk_size == 0 {
+                    frames += 1;
+                }
+                Some(frames * frame_size)
+            }
+            _ => None,
+        };
+
+        if first == 0 {
+            // Read header with the first chunk in the same request.
+            (
+                BytesRange::new(Some(0), size.map(|v| v + HEADER_SIZE as u64)),
+                first,
+                skip,
+            )
+        } else {
+            (
+                BytesRange::new(Some(HEADER_SIZE as u64 + first * frame_size), size),
+                first,
+                skip,
+            )
+        }
+    }
+
+    async fn normalize_range(&self, path: &str, args: &OpRead) -> Result<BytesRange> {
+        let range = args.range();
+        if range.offset().is_some() {
+            return Ok(range);
+        }
+
+        let rp = self.stat(path, OpStat::new()).await?;
+        Ok(range.complete(rp.into_metadata().content_length()))
+    }
+
+    fn blocking_normalize_range(&self, path: &str, args: &OpRead) -> Result<BytesRange> {
+        let range = args.range();
+        if range.offset().is_some() {
+            return Ok(range);
+        }
+
+        let rp = self.blocking_stat(path, OpStat::new())?;
+        Ok(range.complete(rp.into_metadata().content_length()))
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> Accessor for EncryptionCore<A> {
+    type Reader = DecryptReader<A::Reader>;
+    type Writer = ();
+    type Lister = ();
+    type BlockingReader = DecryptReader<A::BlockingReader>;
+    type BlockingWriter = ();
+    type BlockingLister = ();
+
+    fn info(&self) -> AccessorInfo {
+        self.inner.info()
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let chunk_size = self.ctx.chunk_size;
+        self.inner.stat(path, args).await.map(|rp| {
+            rp.map_metadata(|mut meta| {
+                set_plaintext_size(&mut meta, chunk_size);
+                meta
+            })
+        })
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = self.normalize_range(path, &args).await?;
+        let (ciphertext_range, first, skip) = self.ciphertext_range(range);
+
+        let header = if first == 0 {
+            None
+        } else {
+            let (_, mut r) = self
+                .inner
+                .read(
+                    path,
+                    args.clone()
+                        .with_range(BytesRange::new(Some(0), Some(HEADER_SIZE as u64))),
+                )
+                .await?;
+            let mut bs = Vec::with_capacity(HEADER_SIZE);
+            r.read_to_end(&mut bs).await?;
+            Some(Header::parse(&bs, &self.ctx)?)
+        };
+
+        let (_, r) = self
+            .inner
+            .read(path, args.with_range(ciphertext_range))
+            .await?;
+        Ok((
+            RpRead::new(),
+            DecryptReader::new(r, self.ctx.clone(), header, first, skip, range.size()),
+        ))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let chunk_size = self.ctx.chunk_size;
+        self.inner.blocking_stat(path, args).map(|rp| {
+            rp.map_metadata(|mut meta| {
+                set_plaintext_size(&mut meta, chunk_size);
+                meta
+            })
+        })
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let range = self.blocking_normalize_range(path, &args)?;
+        let (ciphertext_range, first, skip) = self.ciphertext_range(range);
+
+        let header = if first == 0 {
+            None
+        } else {
+            let (_, mut r) = self.inner.blocking_read(
+                path,
+                args.clone()
+                    .with_range(BytesRange::new(Some(0), Some(HEADER_SIZE as u64))),
+            )?;
+            let mut bs = Vec::with_capacity(HEADER_SIZE);
+            oio::BlockingRead::read_to_end(&mut r, &mut bs)?;
+            Some(Header::parse(&bs, &self.ctx)?)
+        };
+
+        let (_, r) = self
+            .inner
+            .blocking_read(path, args.with_range(ciphertext_range))?;
+        Ok((
+            RpRead::new(),
+            DecryptReader::new(r, self.ctx.clone(), header, first, skip, range.size()),
+        ))
+    }
+}
+
+/// DecryptReader reads encrypted chunks from inner reader and returns the plaintext.
+///
+/// DecryptReader doesn't support seek, use [`RangeReader`] instead.
+pub struct DecryptReader<R> {
+    inner: R,
+    ctx: Arc<EncryptionLayer>,
+    /// header will be `None` if it should be read from inner reader.
+    header: Option<Header>,
+
+    /// index of the next chunk.
+    index: u64,
+    /// plaintext bytes to skip in the next chunk.
+    skip: usize,
+    /// plaintext bytes left to return, `None` means read until the end.
+    remaining: Option<u64>,
+    /// whether we have decrypted any chunk in this reader.
+    started: bool,
+    done: bool,
+
+    buf: Vec<u8>,
+    filled: usize,
+    plaintext: Bytes,
+}
+
+impl<R> DecryptReader<R> {
+    fn new(
+        inner: R,
+        ctx: Arc<EncryptionLayer>,
+        header: Option<Header>,
+        index: u64,
+        skip: usize,
+        remaining: Option<u64>,
+    ) -> Self {
+        Self {
+            inner,
+            ctx,
+            header,
+
+            index,
+            skip,
+            remaining,
+            started: false,
+            done: matches!(remaining, Some(0)),
+
+            buf: Vec::new(),
+            filled: 0,
+            plaintext: Bytes::new(),
+        }
+    }
+
+    /// Fill the plaintext buffer with next chunk.
+    ///
+    /// `read` is used to read data from inner reader, so that async and blocking
+    /// reader can share the same logic.
+    fn poll_fill(
+        &mut self,
+        mut read: impl FnMut(&mut R, &mut [u8]) -> Poll<Result<usize>>,
+    ) -> Poll<Result<()>> {
+        // If range ends at the end of a full chunk, keep going to verify the next
+        // chunk before returning the plaintext.
+        while !self.done && (self.plaintext.is_empty() || self.remaining == Some(0)) {
+            let frame_size = match &self.header {
+                None => HEADER_SIZE,
+                Some(_) => self.ctx.chunk_size + CHUNK_OVERHEAD,
+            };
+            if self.buf.len() < frame_size {
+                self.buf.resize(frame_size, 0);
+            }
+
+            while self.filled < frame_size {
+                let n = ready!(read(
+                    &mut self.inner,
+                    &mut self.buf[self.filled..frame_size]
+                ))?;
+                if n == 0 {
+                    break;
+                }
+                self.filled += n;
+            }
+            let frame = &self.buf[..self.filled];
+            self.filled = 0;
+
+            if self.header.is_none() {
+                self.header = Some(Header::parse(frame, &self.ctx)?);
+                continue;
+            }
+            let header = self.header.as_ref().expect("header must be valid");
+
+            if frame.is_empty() {
+                if self.started {
+                    // The last chunk of a file must be shorter than chunk size.
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "encrypted file is truncated",
+                    )));
+                }
+                // Reading out of the end of file.
+                self.done = true;
+                break;
+            }
+
+            let plaintext = header.decrypt_chunk(self.index, frame)?;
+            self.index += 1;
+            self.started = true;
+            if plaintext.len() < self.ctx.chunk_size {
+                // The last chunk of a file is always shorter than chunk size.
+                self.done = true;
+            }
+            if self.remaining == Some(0) {
+                // This chunk is only read to verify that the file is not truncated.
+                self.done = true;
+                continue;
+            }
+
+            let mut plaintext = Bytes::from(plaintext);
+            plaintext.advance(self.skip.min(plaintext.len()));
+            self.skip = 0;
+            if let Some(remaining) = self.remaining.as_mut() {
+                if plaintext.len() as u64 > *remaining {
+                    self.done = true;
+                }
+                plaintext.truncate(*remaining as usize);
+                *remaining -= plaintext.len() as u64;
+            }
+            self.plaintext = plaintext;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn copy_plaintext(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.plaintext.len());
+        buf[..n].copy_from_slice(&self.plaintext[..n]);
+        self.plaintext.advance(n);
+        n
+    }
+}
+
+impl<R: oio::Read> oio::Read for DecryptReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        ready!(self.poll_fill(|r, bs| r.poll_read(cx, bs)))?;
+        Poll::Ready(Ok(self.copy_plaintext(buf)))
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, _: SeekFrom) -> Poll<Result<u64>> {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        )))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Err(err) = ready!(self.poll_fill(|r, bs| r.poll_read(cx, bs))) {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        if self.plaintext.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(std::mem::take(&mut self.plaintext))))
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        match self.poll_fill(|r, bs| Poll::Ready(r.read(bs))) {
+            Poll::Ready(res) => res?,
+            Poll::Pending => unreachable!("blocking reader must not return pending"),
+        }
+        Ok(self.copy_plaintext(buf))
+    }
+
+    fn seek(&mut self, _: SeekFrom) -> Result<u64> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output reader doesn't support seeking",
+        ))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        match self.poll_fill(|r, bs| Poll::Ready(r.read(bs))) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(err)) => return Some(Err(err)),
+            Poll::Pending => unreachable!("blocking reader must not return pending"),
+        }
+
+        if self.plaintext.is_empty() {
+            None
+        } else {
+            Some(Ok(std::mem::take(&mut self.plaintext)))
+        }
+    }
+}
+
+/// EncryptWriter buffers plaintext into chunks and writes the encrypted chunks
+/// into inner writer.
+pub struct EncryptWriter<W> {
+    inner: W,
+    header: Header,
+    chunk_size: usize,
+
+    index: u64,
+    buf: BytesMut,
+    /// encrypted bytes that not written into inner writer yet.
+    pending: Bytes,
+    header_written: bool,
+    finished: bool,
+}
+
+impl<W> EncryptWriter<W> {
+    fn new(inner: W, header: Header, chunk_size: usize) -> Self {
+        Self {
+            inner,
+            header,
+            chunk_size,
+
+            index: 0,
+            buf: BytesMut::new(),
+            pending: Bytes::new(),
+            header_written: false,
+            finished: false,
+        }
+    }
+
+    /// Copy given bytes into buffer, returns the bytes consumed.
+    fn fill(&mut self, bs: &dyn oio::WriteBuf) -> usize {
+        let chunk = bs.chunk();
+        let n = chunk.len().min(self.chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&chunk[..n]);
+        n
+    }
+
+    /// Encrypt current buffer into a chunk.
+    fn seal(&mut self) -> Result<Bytes> {
+        let frame = self.header.encrypt_chunk(self.index, &self.buf)?;
+        self.index += 1;
+        self.buf.clear();
+
+        if self.header_written {
+            return Ok(Bytes::from(frame));
+        }
+        self.header_written = true;
+
+        let mut bs = Vec::with_capacity(HEADER_SIZE + frame.len());
+        bs.extend_from_slice(&self.header.raw);
+        bs.extend_from_slice(&frame);
+        Ok(Bytes::from(bs))
+    }
+}
+
+impl<W: oio::Write> EncryptWriter<W> {
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.pending.is_empty() {
+            let n = ready!(self.inner.poll_write(cx, &self.pending))?;
+            self.pending.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: oio::Write> oio::Write for EncryptWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        ready!(self.poll_flush(cx))?;
+
+        let n = self.fill(bs);
+        if self.buf.len() == self.chunk_size {
+            self.pending = self.seal()?;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_flush(cx))?;
+
+        if !self.finished {
+            // The last chunk is always shorter than chunk size, maybe empty.
+            self.pending = self.seal()?;
+            self.finished = true;
+            ready!(self.poll_flush(cx))?;
+        }
+
+        self.inner.poll_close(cx)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.pending = Bytes::new();
+        self.buf.clear();
+
+        self.inner.poll_abort(cx)
+    }
+}
+
+impl<W: oio::BlockingWrite> EncryptWriter<W> {
+    fn write_pending(&mut self) -> Result<()> {
+        while !self.pending.is_empty() {
+            let n = self.inner.write(&self.pending)?;
+            self.pending.advance(n);
+        }
+        Ok(())
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for EncryptWriter<W> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.write_pending()?;
+
+        let n = self.fill(bs);
+        if self.buf.len() == self.chunk_size {
+            self.pending = self.seal()?;
+            self.write_pending()?;
+        }
+        Ok(n)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.write_pending()?;
+
+        if !self.finished {
+            self.pending = self.seal()?;
+            self.finished = true;
+            self.write_pending()?;
+        }
+
+        self.inner.close()
+    }
+}
+
+/// EncryptionLister reports the plaintext size of listed entries.
+pub struct EncryptionLister<L> {
+    inner: L,
+    chunk_size: usize,
+}
+
+impl<L> EncryptionLister<L> {
+    fn new(inner: L, chunk_size: usize) -> Self {
+        Self { inner, chunk_size }
+    }
+
+    fn map_entry(&self, mut entry: oio::Entry) -> oio::Entry {
+        set_plaintext_size(entry.metadata_mut(), self.chunk_size);
+        entry
+    }
+}
+
+impl<L: oio::List> oio::List for EncryptionLister<L> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        let entry = ready!(self.inner.poll_next(cx))?;
+        Poll::Ready(Ok(entry.map(|e| self.map_entry(e))))
+    }
+}
+
+impl<L: oio::BlockingList> oio::BlockingList for EncryptionLister<L> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        let entry = self.inner.next()?;
+        Ok(entry.map(|e| self.map_entry(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::services::Memory;
+
+    fn new_operator(keys: StaticKeyProvider, algorithm: EncryptionAlgorithm) -> Operator {
+        Operator::new(Memory::default())
+            .unwrap()
+            .layer(
+                EncryptionLayer::new(keys)
+                    .with_algorithm(algorithm)
+                    .with_chunk_size(16),
+            )
+            .finish()
+    }
+
+    #[test]
+    fn test_plaintext_size() {
+        let cases = vec![
+            ("empty", 0, HEADER_SIZE + CHUNK_OVERHEAD),
+            ("short", 10, HEADER_SIZE + 10 + CHUNK_OVERHEAD),
+            ("aligned", 32, HEADER_SIZE + 3 * CHUNK_OVERHEAD + 32),
+            ("unaligned", 40, HEADER_SIZE + 3 * CHUNK_OVERHEAD + 40),
+        ];
+
+        for (name, expected, input) in cases {
+            assert_eq!(plaintext_size(input as u64, 16), expected, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encryption_read_write() {
+        for algorithm in [
+            EncryptionAlgorithm::Aes256Gcm,
+            EncryptionAlgorithm::ChaCha20Poly1305,
+        ] {
+            let op = new_operator(StaticKeyProvider::new("key", [7; 32]), algorithm);
+
+            for size in [0, 1, 15, 16, 17, 100] {
+                let mut content = vec![0; size];
+                thread_rng().fill_bytes(&mut content);
+                let path = format!("file-{size}");
+
+                let mut w = op.writer(&path).await.unwrap();
+                for chunk in content.chunks(7) {
+                    w.write(chunk.to_vec()).await.unwrap();
+                }
+                w.close().await.unwrap();
+
+                assert_eq!(op.read(&path).await.unwrap(), content, "size {size}");
+                assert_eq!(
+                    op.stat(&path).await.unwrap().content_length(),
+                    size as u64,
+                    "size {size}"
+                );
+
+                for (offset, len) in [(0, 5), (3, 20), (16, 16), (20, 80), (size, 10)] {
+                    // Reading out of the end of file is not covered here.
+                    if offset > size {
+                        continue;
+                    }
+                    let expected = &content[offset.min(size)..(offset + len).min(size)];
+                    let actual = op
+                        .read_with(&path)
+                        .range(offset as u64..(offset + len) as u64)
+                        .await
+                        .unwrap();
+                    assert_eq!(actual, expected, "size {size}, range {offset}+{len}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encryption_blocking_read_write() {
+        let op = new_operator(
+            StaticKeyProvider::new("key", [7; 32]),
+            EncryptionAlgorithm::default(),
+        )
+        .blocking();
+
+        let mut content = vec![0; 50];
+        thread_rng().fill_bytes(&mut content);
+        op.write("test", content.clone()).unwrap();
+
+        assert_eq!(op.read("test").unwrap(), content);
+        assert_eq!(
+            op.read_with("test").range(20..40).call().unwrap(),
+            &content[20..40]
+        );
+        assert_eq!(op.stat("test").unwrap().content_length(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_encryption_ciphertext() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        let op = inner.clone().layer(
+            EncryptionLayer::new(StaticKeyProvider::new("key", [7; 32])).with_chunk_size(16),
+        );
+
+        op.write("test", "hello, world! hello, world!")
+            .await
+            .unwrap();
+
+        let raw = inner.read("test").await.unwrap();
+        assert!(!raw.windows(5).any(|w| w == b"hello"));
+
+        let entries = op.list("").await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            op.stat(entries[0].path()).await.unwrap().content_length(),
+            27
+        );
+
+        // Tamper the last byte of the first chunk.
+        let mut tampered = raw.clone();
+        tampered[HEADER_SIZE + CHUNK_OVERHEAD + 15] ^= 1;
+        inner.write("test", tampered).await.unwrap();
+        assert!(op.read("test").await.is_err());
+
+        // Drop the last chunk.
+        inner
+            .write("test", raw[..HEADER_SIZE + CHUNK_OVERHEAD + 16].to_vec())
+            .await
+            .unwrap();
+        assert!(op.read("test").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encryption_key_rotation() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+
+        let old = inner
+            .clone()
+            .layer(EncryptionLayer::new(StaticKeyProvider::new("old", [1; 32])));
+        old.write("old", "old content").await.unwrap();
+
+        let new = inner.clone().layer(EncryptionLayer::new(
+            StaticKeyProvider::new("new", [2; 32]).with_key("old", [1; 32]),
+        ));
+        new.write("new", "new content").await.unwrap();
+
+        assert_eq!(new.read("old").await.unwrap(), b"old content");
+        assert_eq!(new.read("new").await.unwrap(), b"new content");
+
+        let err = old.read("new").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
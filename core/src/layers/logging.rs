@@ -15,21 +15,35 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::cell::RefCell;
 use std::fmt::Debug;
+use std::fmt::Display;
+use std::future::Future;
 use std::io;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::FutureExt;
 use futures::TryFutureExt;
 use log::debug;
+use log::info;
 use log::log;
 use log::trace;
+use log::warn;
 use log::Level;
 
+use super::retry::DefaultRetryInterceptor;
+use crate::layers::RetryInterceptor;
 use crate::raw::oio::ReadOperation;
 use crate::raw::oio::WriteOperation;
 use crate::raw::*;
@@ -48,6 +62,45 @@ use crate::*;
 /// - The default log level while expected error happened is `Warn`.
 /// - The default log level while unexpected failure happened is `Error`.
 ///
+/// # Slow Operations
+///
+/// Operations that are slower than the configured threshold will be logged at
+/// `Warn` with a `-> slow` suffix, including path, bytes, duration breakdown
+/// and retry count:
+///
+/// - [`LoggingLayer::with_slow_threshold`]: threshold of the total duration.
+///   For readers and writers, the duration starts from `read`/`write` call
+///   until the reader is dropped or the writer is closed.
+/// - [`LoggingLayer::with_first_byte_threshold`]: threshold of the time to
+///   the first byte read or written.
+/// - [`LoggingLayer::with_sample_rate`]: successful operations that are not
+///   slow can be sampled and logged at `Info` with a `-> sampled` suffix.
+///
+/// Retry count is only available if [`LoggingLayer::retry_interceptor`] is
+/// registered to a [`RetryLayer`](crate::layers::RetryLayer) that placed
+/// under this layer.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::LoggingLayer;
+/// use opendal::layers::RetryLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let mut logging = LoggingLayer::default()
+///     .with_slow_threshold(Duration::from_secs(1))
+///     .with_first_byte_threshold(Duration::from_millis(200))
+///     .with_sample_rate(0.01);
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(RetryLayer::new().with_notify(logging.retry_interceptor()))
+///     .layer(logging)
+///     .finish();
+/// ```
+///
 /// # Todo
 ///
 /// We should migrate to log's kv api after it's ready.
@@ -89,6 +142,11 @@ pub struct LoggingLayer {
     error_level: Option<Level>,
     failure_level: Option<Level>,
     backtrace_output: bool,
+
+    slow_threshold: Option<Duration>,
+    first_byte_threshold: Option<Duration>,
+    sample_interval: u64,
+    retry_scope: Option<u64>,
 }
 
 impl Default for LoggingLayer {
@@ -97,6 +155,11 @@ impl Default for LoggingLayer {
             error_level: Some(Level::Warn),
             failure_level: Some(Level::Error),
             backtrace_output: false,
+
+            slow_threshold: None,
+            first_byte_threshold: None,
+            sample_interval: 0,
+            retry_scope: None,
         }
     }
 }
@@ -148,6 +211,138 @@ impl LoggingLayer {
         self.backtrace_output = enable;
         self
     }
+
+    /// Log operations whose total duration exceeds given threshold at `Warn`.
+    ///
+    /// Slow logging is disabled by default.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    /// Log readers and writers whose time to first byte exceeds given
+    /// threshold at `Warn`.
+    ///
+    /// Slow logging is disabled by default.
+    pub fn with_first_byte_threshold(mut self, threshold: Duration) -> Self {
+        self.first_byte_threshold = Some(threshold);
+        self
+    }
+
+    /// Sample successful operations at given rate and log them at `Info`.
+    ///
+    /// The rate should be in `[0.0, 1.0]`, sampling is done by logging one of
+    /// every `1 / rate` operations. `0.0` means disable sampling which is the
+    /// default behavior.
+    pub fn with_sample_rate(mut self, rate: f64) -> Self {
+        self.sample_interval = if rate > 0.0 {
+            (1.0 / rate.min(1.0)).round() as u64
+        } else {
+            0
+        };
+        self
+    }
+
+    /// Build a [`RetryInterceptor`] which counts retries for the slow
+    /// operations log.
+    ///
+    /// Retries are counted for every operation, including the retries while
+    /// reading or writing by its reader or writer. Retries are logged at
+    /// `Warn` in the same way as [`RetryLayer`](crate::layers::RetryLayer)'s
+    /// default interceptor.
+    pub fn retry_interceptor(&mut self) -> LoggingRetryInterceptor {
+        let scope = *self
+            .retry_scope
+            .get_or_insert_with(|| NEXT_RETRY_SCOPE.fetch_add(1, Ordering::Relaxed));
+        LoggingRetryInterceptor { scope }
+    }
+}
+
+/// LoggingRetryInterceptor counts retries for [`LoggingLayer`].
+///
+/// Created by [`LoggingLayer::retry_interceptor`].
+pub struct LoggingRetryInterceptor {
+    scope: u64,
+}
+
+impl RetryInterceptor for LoggingRetryInterceptor {
+    fn intercept(&self, err: &Error, dur: Duration, ctx: &[(&str, &str)]) {
+        RETRY_COUNTERS.with(|counters| {
+            let counters = counters.borrow();
+            // Retries belong to the innermost operation of the same scope.
+            if let Some((_, count)) = counters.iter().rev().find(|(v, _)| *v == self.scope) {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        DefaultRetryInterceptor.intercept(err, dur, ctx)
+    }
+}
+
+/// The scope of retries counted by every [`LoggingRetryInterceptor`].
+static NEXT_RETRY_SCOPE: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Retry counters of the operations that are running on current thread,
+    /// [`LoggingRetryInterceptor`] will count retries into the last one of
+    /// its scope.
+    static RETRY_COUNTERS: RefCell<Vec<(u64, Arc<AtomicUsize>)>> = RefCell::new(Vec::new());
+}
+
+/// RetryCounter counts the retries of an operation.
+#[derive(Clone, Debug, Default)]
+struct RetryCounter {
+    scope: Option<u64>,
+    count: Arc<AtomicUsize>,
+}
+
+impl RetryCounter {
+    /// Retry count of the operation.
+    fn get(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Run given function with retries counted by this counter.
+    fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let Some(scope) = self.scope else {
+            return f();
+        };
+
+        RETRY_COUNTERS.with(|v| v.borrow_mut().push((scope, self.count.clone())));
+        let _guard = RetryCounterGuard;
+        f()
+    }
+
+    /// Wrap given future with retries counted by this counter.
+    fn wrap<F: Future + Unpin>(&self, fut: F) -> RetryCounterFuture<F> {
+        RetryCounterFuture {
+            counter: self.clone(),
+            fut,
+        }
+    }
+}
+
+/// Pop the counter pushed by [`RetryCounter::scope`] even if panicked.
+struct RetryCounterGuard;
+
+impl Drop for RetryCounterGuard {
+    fn drop(&mut self) {
+        RETRY_COUNTERS.with(|v| v.borrow_mut().pop());
+    }
+}
+
+struct RetryCounterFuture<F> {
+    counter: RetryCounter,
+    fut: F,
+}
+
+impl<F: Future + Unpin> Future for RetryCounterFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        this.counter.scope(|| this.fut.poll_unpin(cx))
+    }
 }
 
 impl<A: Accessor> Layer<A> for LoggingLayer {
@@ -163,6 +358,12 @@ impl<A: Accessor> Layer<A> for LoggingLayer {
                 error_level: self.error_level,
                 failure_level: self.failure_level,
                 backtrace_output: self.backtrace_output,
+
+                slow_threshold: self.slow_threshold,
+                first_byte_threshold: self.first_byte_threshold,
+                sample_interval: self.sample_interval,
+                sampled: Arc::default(),
+                retry_scope: self.retry_scope,
             },
         }
     }
@@ -174,6 +375,12 @@ pub struct LoggingContext {
     error_level: Option<Level>,
     failure_level: Option<Level>,
     backtrace_output: bool,
+
+    slow_threshold: Option<Duration>,
+    first_byte_threshold: Option<Duration>,
+    sample_interval: u64,
+    sampled: Arc<AtomicU64>,
+    retry_scope: Option<u64>,
 }
 
 impl LoggingContext {
@@ -200,6 +407,96 @@ impl LoggingContext {
             format!("{err}")
         }
     }
+
+    /// Check whether slow logging or sampling is enabled.
+    #[inline]
+    fn observe_enabled(&self) -> bool {
+        self.slow_threshold.is_some()
+            || self.first_byte_threshold.is_some()
+            || self.sample_interval > 0
+    }
+
+    /// Observe a finished operation call.
+    #[inline]
+    fn observe_call<T>(
+        &self,
+        op: Operation,
+        path: &str,
+        start: Instant,
+        retries: &RetryCounter,
+        res: &Result<T>,
+    ) {
+        self.observe(op, path, start, None, None, retries.get(), res.is_ok())
+    }
+
+    /// Create a retry counter for a new operation.
+    #[inline]
+    fn retry_counter(&self) -> RetryCounter {
+        RetryCounter {
+            scope: self.retry_scope,
+            count: Arc::default(),
+        }
+    }
+
+    /// Log the operation at `Warn` if it's slow, or at `Info` if it's
+    /// successful and sampled.
+    #[allow(clippy::too_many_arguments)]
+    fn observe(
+        &self,
+        op: impl Display,
+        path: &str,
+        start: Instant,
+        first_byte: Option<Duration>,
+        bytes: Option<u64>,
+        retries: usize,
+        ok: bool,
+    ) {
+        if !self.observe_enabled() {
+            return;
+        }
+
+        let duration = start.elapsed();
+        let slow = self.slow_threshold.map_or(false, |v| duration > v)
+            || matches!((self.first_byte_threshold, first_byte), (Some(t), Some(v)) if v > t);
+        let sampled = !slow
+            && ok
+            && self.sample_interval > 0
+            && self.sampled.fetch_add(1, Ordering::Relaxed) % self.sample_interval == 0;
+        if !slow && !sampled {
+            return;
+        }
+
+        let bytes = bytes.map(|v| format!(" bytes={v}")).unwrap_or_default();
+        let first_byte = first_byte
+            .map(|v| format!(" first_byte={v:?}"))
+            .unwrap_or_default();
+        if slow {
+            warn!(
+                target: LOGGING_TARGET,
+                "service={} operation={} path={}{} duration={:?}{} retries={} ok={} -> slow",
+                self.scheme,
+                op,
+                path,
+                bytes,
+                duration,
+                first_byte,
+                retries,
+                ok
+            );
+        } else {
+            info!(
+                target: LOGGING_TARGET,
+                "service={} operation={} path={}{} duration={:?}{} retries={} -> sampled",
+                self.scheme,
+                op,
+                path,
+                bytes,
+                duration,
+                first_byte,
+                retries
+            );
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -254,8 +551,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .create_dir(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.create_dir(path, args))
             .await
             .map(|v| {
                 debug!(
@@ -280,7 +579,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     )
                 };
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::CreateDir, path, start, &retries, &result);
+        result
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
@@ -295,8 +597,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
 
         let range = args.range();
 
-        self.inner
-            .read(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.read(path, args))
             .await
             .map(|(rp, r)| {
                 debug!(
@@ -309,7 +613,14 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                 );
                 (
                     rp,
-                    LoggingReader::new(self.ctx.clone(), Operation::Read, path, r),
+                    LoggingReader::new(
+                        self.ctx.clone(),
+                        Operation::Read,
+                        path,
+                        start,
+                        retries.clone(),
+                        r,
+                    ),
                 )
             })
             .map_err(|err| {
@@ -326,7 +637,12 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     )
                 }
                 err
-            })
+            });
+        if result.is_err() {
+            self.ctx
+                .observe_call(Operation::Read, path, start, &retries, &result);
+        }
+        result
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
@@ -338,8 +654,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .write(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.write(path, args))
             .await
             .map(|(rp, w)| {
                 debug!(
@@ -349,7 +667,14 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     Operation::Write,
                     path,
                 );
-                let w = LoggingWriter::new(self.ctx.clone(), Operation::Write, path, w);
+                let w = LoggingWriter::new(
+                    self.ctx.clone(),
+                    Operation::Write,
+                    path,
+                    start,
+                    retries.clone(),
+                    w,
+                );
                 (rp, w)
             })
             .map_err(|err| {
@@ -365,7 +690,12 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     )
                 };
                 err
-            })
+            });
+        if result.is_err() {
+            self.ctx
+                .observe_call(Operation::Write, path, start, &retries, &result);
+        }
+        result
     }
 
     async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
@@ -378,8 +708,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             to
         );
 
-        self.inner
-            .copy(from, to, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.copy(from, to, args))
             .await
             .map(|v| {
                 debug!(
@@ -406,7 +738,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     )
                 };
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::Copy, from, start, &retries, &result);
+        result
     }
 
     async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
@@ -419,8 +754,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             to
         );
 
-        self.inner
-            .rename(from, to, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.rename(from, to, args))
             .await
             .map(|v| {
                 debug!(
@@ -447,7 +784,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     )
                 };
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::Rename, from, start, &retries, &result);
+        result
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
@@ -459,8 +799,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .stat(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.stat(path, args))
             .await
             .map(|v| {
                 debug!(
@@ -485,7 +827,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 };
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::Stat, path, start, &retries, &result);
+        result
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
@@ -497,8 +842,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .delete(path, args.clone())
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.delete(path, args.clone()))
             .inspect(|v| match v {
                 Ok(_) => {
                     debug!(
@@ -523,7 +870,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     }
                 }
             })
-            .await
+            .await;
+        self.ctx
+            .observe_call(Operation::Delete, path, start, &retries, &result);
+        result
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
@@ -535,8 +885,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .list(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.list(path, args))
             .map(|v| match v {
                 Ok((rp, v)) => {
                     debug!(
@@ -564,7 +916,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     Err(err)
                 }
             })
-            .await
+            .await;
+        self.ctx
+            .observe_call(Operation::List, path, start, &retries, &result);
+        result
     }
 
     async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
@@ -576,8 +931,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .presign(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.presign(path, args))
             .await
             .map(|v| {
                 debug!(
@@ -602,7 +959,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::Presign, path, start, &retries, &result);
+        result
     }
 
     async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
@@ -615,8 +975,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             Operation::Batch,
         );
 
-        self.inner
-            .batch(args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .wrap(self.inner.batch(args))
             .map_ok(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -642,7 +1004,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                 }
                 err
             })
-            .await
+            .await;
+        self.ctx
+            .observe_call(Operation::Batch, "", start, &retries, &result);
+        result
     }
 
     fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
@@ -654,8 +1019,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .blocking_create_dir(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_create_dir(path, args))
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -679,7 +1046,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingCreateDir, path, start, &retries, &result);
+        result
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
@@ -692,8 +1062,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             args.range(),
         );
 
-        self.inner
-            .blocking_read(path, args.clone())
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_read(path, args.clone()))
             .map(|(rp, r)| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -703,7 +1075,14 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     path,
                     args.range(),
                 );
-                let r = LoggingReader::new(self.ctx.clone(), Operation::BlockingRead, path, r);
+                let r = LoggingReader::new(
+                    self.ctx.clone(),
+                    Operation::BlockingRead,
+                    path,
+                    start,
+                    retries.clone(),
+                    r,
+                );
                 (rp, r)
             })
             .map_err(|err| {
@@ -720,7 +1099,12 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        if result.is_err() {
+            self.ctx
+                .observe_call(Operation::BlockingRead, path, start, &retries, &result);
+        }
+        result
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
@@ -732,8 +1116,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path,
         );
 
-        self.inner
-            .blocking_write(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_write(path, args))
             .map(|(rp, w)| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -742,7 +1128,14 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     Operation::BlockingWrite,
                     path,
                 );
-                let w = LoggingWriter::new(self.ctx.clone(), Operation::BlockingWrite, path, w);
+                let w = LoggingWriter::new(
+                    self.ctx.clone(),
+                    Operation::BlockingWrite,
+                    path,
+                    start,
+                    retries.clone(),
+                    w,
+                );
                 (rp, w)
             })
             .map_err(|err| {
@@ -758,7 +1151,12 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        if result.is_err() {
+            self.ctx
+                .observe_call(Operation::BlockingWrite, path, start, &retries, &result);
+        }
+        result
     }
 
     fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
@@ -771,8 +1169,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             to,
         );
 
-        self.inner
-            .blocking_copy(from, to, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_copy(from, to, args))
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -798,7 +1198,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingCopy, from, start, &retries, &result);
+        result
     }
 
     fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
@@ -811,8 +1214,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             to,
         );
 
-        self.inner
-            .blocking_rename(from, to, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_rename(from, to, args))
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -838,7 +1243,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingRename, from, start, &retries, &result);
+        result
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
@@ -850,8 +1258,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .blocking_stat(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_stat(path, args))
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -875,7 +1285,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingStat, path, start, &retries, &result);
+        result
     }
 
     fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
@@ -887,8 +1300,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .blocking_delete(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_delete(path, args))
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -912,7 +1327,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingDelete, path, start, &retries, &result);
+        result
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
@@ -924,8 +1342,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             path
         );
 
-        self.inner
-            .blocking_list(path, args)
+        let start = Instant::now();
+        let retries = self.ctx.retry_counter();
+        let result = retries
+            .scope(|| self.inner.blocking_list(path, args))
             .map(|(rp, v)| {
                 debug!(
                     target: LOGGING_TARGET,
@@ -950,7 +1370,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
                     );
                 }
                 err
-            })
+            });
+        self.ctx
+            .observe_call(Operation::BlockingList, path, start, &retries, &result);
+        result
     }
 }
 
@@ -960,17 +1383,34 @@ pub struct LoggingReader<R> {
     path: String,
     op: Operation,
 
+    start: Instant,
+    first_byte: Option<Duration>,
+    failed: bool,
+    retries: RetryCounter,
+
     read: u64,
     inner: R,
 }
 
 impl<R> LoggingReader<R> {
-    fn new(ctx: LoggingContext, op: Operation, path: &str, reader: R) -> Self {
+    fn new(
+        ctx: LoggingContext,
+        op: Operation,
+        path: &str,
+        start: Instant,
+        retries: RetryCounter,
+        reader: R,
+    ) -> Self {
         Self {
             ctx,
             op,
             path: path.to_string(),
 
+            start,
+            first_byte: None,
+            failed: false,
+            retries,
+
             read: 0,
             inner: reader,
         }
@@ -987,6 +1427,15 @@ impl<R> Drop for LoggingReader<R> {
             self.path,
             self.read
         );
+        self.ctx.observe(
+            self.op,
+            &self.path,
+            self.start,
+            self.first_byte,
+            Some(self.read),
+            self.retries.get(),
+            !self.failed,
+        );
     }
 }
 
@@ -994,10 +1443,11 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
     fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
         let buf_size = buf.len();
 
-        match self.inner.poll_read(cx, buf) {
+        match self.retries.scope(|| self.inner.poll_read(cx, buf)) {
             Poll::Ready(res) => match res {
                 Ok(n) => {
                     self.read += n as u64;
+                    self.first_byte.get_or_insert_with(|| self.start.elapsed());
                     trace!(
                         target: LOGGING_TARGET,
                         "service={} operation={} path={} read={} -> buf size: {}B, read {}B ",
@@ -1011,6 +1461,7 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
                     Poll::Ready(Ok(n))
                 }
                 Err(err) => {
+                    self.failed = true;
                     if let Some(lvl) = self.ctx.error_level(&err) {
                         log!(
                             target: LOGGING_TARGET,
@@ -1042,7 +1493,7 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
     }
 
     fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
-        match self.inner.poll_seek(cx, pos) {
+        match self.retries.scope(|| self.inner.poll_seek(cx, pos)) {
             Poll::Ready(res) => match res {
                 Ok(n) => {
                     trace!(
@@ -1056,6 +1507,7 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
                     Poll::Ready(Ok(n))
                 }
                 Err(err) => {
+                    self.failed = true;
                     if let Some(lvl) = self.ctx.error_level(&err) {
                         log!(
                             target: LOGGING_TARGET,
@@ -1086,10 +1538,11 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
     }
 
     fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
-        match self.inner.poll_next(cx) {
+        match self.retries.scope(|| self.inner.poll_next(cx)) {
             Poll::Ready(res) => match res {
                 Some(Ok(bs)) => {
                     self.read += bs.len() as u64;
+                    self.first_byte.get_or_insert_with(|| self.start.elapsed());
                     trace!(
                         target: LOGGING_TARGET,
                         "service={} operation={} path={} read={} -> next returns {}B",
@@ -1102,6 +1555,7 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
                     Poll::Ready(Some(Ok(bs)))
                 }
                 Some(Err(err)) => {
+                    self.failed = true;
                     if let Some(lvl) = self.ctx.error_level(&err) {
                         log!(
                             target: LOGGING_TARGET,
@@ -1145,9 +1599,10 @@ impl<R: oio::Read> oio::Read for LoggingReader<R> {
 
 impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        match self.inner.read(buf) {
+        match self.retries.scope(|| self.inner.read(buf)) {
             Ok(n) => {
                 self.read += n as u64;
+                self.first_byte.get_or_insert_with(|| self.start.elapsed());
                 trace!(
                     target: LOGGING_TARGET,
                     "service={} operation={} path={} read={} -> data read {}B",
@@ -1160,6 +1615,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
                 Ok(n)
             }
             Err(err) => {
+                self.failed = true;
                 if let Some(lvl) = self.ctx.error_level(&err) {
                     log!(
                         target: LOGGING_TARGET,
@@ -1179,7 +1635,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
 
     #[inline]
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
-        match self.inner.seek(pos) {
+        match self.retries.scope(|| self.inner.seek(pos)) {
             Ok(n) => {
                 trace!(
                     target: LOGGING_TARGET,
@@ -1192,6 +1648,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
                 Ok(n)
             }
             Err(err) => {
+                self.failed = true;
                 if let Some(lvl) = self.ctx.error_level(&err) {
                     log!(
                         target: LOGGING_TARGET,
@@ -1210,9 +1667,10 @@ impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
     }
 
     fn next(&mut self) -> Option<Result<Bytes>> {
-        match self.inner.next() {
+        match self.retries.scope(|| self.inner.next()) {
             Some(Ok(bs)) => {
                 self.read += bs.len() as u64;
+                self.first_byte.get_or_insert_with(|| self.start.elapsed());
                 trace!(
                     target: LOGGING_TARGET,
                     "service={} operation={} path={} read={} -> data read {}B",
@@ -1225,6 +1683,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for LoggingReader<R> {
                 Some(Ok(bs))
             }
             Some(Err(err)) => {
+                self.failed = true;
                 if let Some(lvl) = self.ctx.error_level(&err) {
                     log!(
                         target: LOGGING_TARGET,
@@ -1249,28 +1708,57 @@ pub struct LoggingWriter<W> {
     op: Operation,
     path: String,
 
+    start: Instant,
+    first_byte: Option<Duration>,
+    retries: RetryCounter,
+
     written: u64,
     inner: W,
 }
 
 impl<W> LoggingWriter<W> {
-    fn new(ctx: LoggingContext, op: Operation, path: &str, writer: W) -> Self {
+    fn new(
+        ctx: LoggingContext,
+        op: Operation,
+        path: &str,
+        start: Instant,
+        retries: RetryCounter,
+        writer: W,
+    ) -> Self {
         Self {
             ctx,
             op,
             path: path.to_string(),
 
+            start,
+            first_byte: None,
+            retries,
+
             written: 0,
             inner: writer,
         }
     }
+
+    /// Observe the writer while it's closed.
+    fn observe(&self, ok: bool) {
+        self.ctx.observe(
+            self.op,
+            &self.path,
+            self.start,
+            self.first_byte,
+            Some(self.written),
+            self.retries.get(),
+            ok,
+        );
+    }
 }
 
 impl<W: oio::Write> oio::Write for LoggingWriter<W> {
     fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
-        match ready!(self.inner.poll_write(cx, bs)) {
+        match ready!(self.retries.scope(|| self.inner.poll_write(cx, bs))) {
             Ok(n) => {
                 self.written += n as u64;
+                self.first_byte.get_or_insert_with(|| self.start.elapsed());
                 trace!(
                     target: LOGGING_TARGET,
                     "service={} operation={} path={} written={}B -> input data {}B, write {}B",
@@ -1302,7 +1790,7 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
     }
 
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        match ready!(self.inner.poll_abort(cx)) {
+        match ready!(self.retries.scope(|| self.inner.poll_abort(cx))) {
             Ok(_) => {
                 trace!(
                     target: LOGGING_TARGET,
@@ -1333,7 +1821,7 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        match ready!(self.inner.poll_close(cx)) {
+        match ready!(self.retries.scope(|| self.inner.poll_close(cx))) {
             Ok(_) => {
                 debug!(
                     target: LOGGING_TARGET,
@@ -1343,6 +1831,7 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
                     self.path,
                     self.written
                 );
+                self.observe(true);
                 Poll::Ready(Ok(()))
             }
             Err(err) => {
@@ -1358,6 +1847,7 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
                         self.ctx.error_print(&err),
                     )
                 }
+                self.observe(false);
                 Poll::Ready(Err(err))
             }
         }
@@ -1366,9 +1856,10 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
 
 impl<W: oio::BlockingWrite> oio::BlockingWrite for LoggingWriter<W> {
     fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
-        match self.inner.write(bs) {
+        match self.retries.scope(|| self.inner.write(bs)) {
             Ok(n) => {
                 self.written += n as u64;
+                self.first_byte.get_or_insert_with(|| self.start.elapsed());
                 trace!(
                     target: LOGGING_TARGET,
                     "service={} operation={} path={} written={}B -> input data {}B, write {}B",
@@ -1400,7 +1891,7 @@ impl<W: oio::BlockingWrite> oio::BlockingWrite for LoggingWriter<W> {
     }
 
     fn close(&mut self) -> Result<()> {
-        match self.inner.close() {
+        match self.retries.scope(|| self.inner.close()) {
             Ok(_) => {
                 debug!(
                     target: LOGGING_TARGET,
@@ -1410,6 +1901,7 @@ impl<W: oio::BlockingWrite> oio::BlockingWrite for LoggingWriter<W> {
                     self.path,
                     self.written
                 );
+                self.observe(true);
                 Ok(())
             }
             Err(err) => {
@@ -1425,6 +1917,7 @@ impl<W: oio::BlockingWrite> oio::BlockingWrite for LoggingWriter<W> {
                         self.ctx.error_print(&err),
                     )
                 }
+                self.observe(false);
                 Err(err)
             }
         }
@@ -1563,3 +2056,95 @@ impl<P: oio::BlockingList> oio::BlockingList for LoggingLister<P> {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_interceptor() {
+        let mut layer = LoggingLayer::default();
+        let ctx = layer.layer(()).ctx;
+        assert!(ctx.retry_scope.is_none());
+
+        let interceptor = layer.retry_interceptor();
+        let ctx = layer.layer(()).ctx;
+        let err = Error::new(ErrorKind::Unexpected, "retry").set_temporary();
+        let retry = || interceptor.intercept(&err, Duration::ZERO, &[("path", "test")]);
+
+        // Retries out of any operation are not counted.
+        retry();
+
+        let outer = ctx.retry_counter();
+        let inner = ctx.retry_counter();
+        outer.scope(|| {
+            retry();
+            // Retries belong to the innermost operation.
+            inner.scope(|| {
+                retry();
+                retry();
+            });
+            retry();
+        });
+        assert_eq!(outer.get(), 2);
+        assert_eq!(inner.get(), 2);
+
+        // Operations of other layers are not affected.
+        let other = LoggingLayer::default().retry_interceptor();
+        outer.scope(|| other.intercept(&err, Duration::ZERO, &[("path", "test")]));
+        assert_eq!(outer.get(), 2);
+
+        // Copies share the same scope.
+        let copied = layer;
+        let counter = copied.layer(()).ctx.retry_counter();
+        counter.scope(retry);
+        assert_eq!(counter.get(), 1);
+        RETRY_COUNTERS.with(|v| assert!(v.borrow().is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_retry_count_of_future() {
+        let mut logging = LoggingLayer::default();
+        let interceptor = logging.retry_interceptor();
+        let ctx = logging.layer(()).ctx;
+        let err = Error::new(ErrorKind::Unexpected, "retry").set_temporary();
+
+        let counter = ctx.retry_counter();
+        let fut = Box::pin(async {
+            interceptor.intercept(&err, Duration::ZERO, &[]);
+            tokio::task::yield_now().await;
+            interceptor.intercept(&err, Duration::ZERO, &[]);
+        });
+        counter.wrap(fut).await;
+        assert_eq!(counter.get(), 2);
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        assert_eq!(LoggingLayer::default().sample_interval, 0);
+        assert_eq!(
+            LoggingLayer::default()
+                .with_sample_rate(1.0)
+                .sample_interval,
+            1
+        );
+        assert_eq!(
+            LoggingLayer::default()
+                .with_sample_rate(0.1)
+                .sample_interval,
+            10
+        );
+        assert_eq!(
+            LoggingLayer::default()
+                .with_sample_rate(2.0)
+                .sample_interval,
+            1
+        );
+        assert_eq!(
+            LoggingLayer::default()
+                .with_sample_rate(-1.0)
+                .sample_interval,
+            0
+        );
+    }
+}
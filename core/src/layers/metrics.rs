@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::io;
@@ -27,12 +28,17 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures::FutureExt;
 use futures::TryFutureExt;
+use metrics::counter;
+use metrics::histogram;
 use metrics::increment_counter;
 use metrics::register_counter;
 use metrics::register_histogram;
 use metrics::Counter;
 use metrics::Histogram;
+use metrics::Label;
 
+use super::metrics_config::LABEL_PATH;
+use crate::layers::MetricsConfig;
 use crate::raw::*;
 use crate::*;
 
@@ -77,9 +83,14 @@ static LABEL_ERROR: &str = "error";
 /// - `service`: Service name from [`Scheme`]
 /// - `operation`: Operation name from [`Operation`]
 /// - `error`: [`ErrorKind`] received by requests
+/// - `path`: Prefix of the path, only available if it's enabled via
+///   [`MetricsLayer::with_config`]
 ///
 /// # Notes
 ///
+/// Histogram buckets are decided by the exporter in `metrics`, so the buckets in
+/// [`MetricsConfig`] will not take effect for this layer.
+///
 /// Please make sure the exporter has been pulled in regular time.
 /// Otherwise, the histogram data collected by `requests_duration_seconds`
 /// could result in OOM.
@@ -98,6 +109,21 @@ static LABEL_ERROR: &str = "error";
 ///     .finish();
 /// ```
 ///
+/// Label metrics with the first segment of path:
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::MetricsConfig;
+/// use opendal::layers::MetricsLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(MetricsLayer.with_config(MetricsConfig::default().with_path_segments(1)))
+///     .finish();
+/// ```
+///
 /// # Output
 ///
 /// OpenDAL is using [`metrics`](https://docs.rs/metrics/latest/metrics/) for metrics internally.
@@ -113,281 +139,167 @@ static LABEL_ERROR: &str = "error";
 /// let (recorder, exporter) = builder.build().expect("failed to build recorder/exporter");
 /// let recorder = builder.build_recorder().expect("failed to build recorder");
 /// ```
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct MetricsLayer;
 
+impl MetricsLayer {
+    /// Create a layer with the [`MetricsConfig`] which controls the path label.
+    pub fn with_config(self, config: MetricsConfig) -> ConfiguredMetricsLayer {
+        ConfiguredMetricsLayer { config }
+    }
+}
+
 impl<A: Accessor> Layer<A> for MetricsLayer {
     type LayeredAccessor = MetricsAccessor<A>;
 
     fn layer(&self, inner: A) -> Self::LayeredAccessor {
-        let meta = inner.info();
+        MetricsAccessor::new(inner, MetricsConfig::default())
+    }
+}
 
-        MetricsAccessor {
-            inner,
-            handle: Arc::new(MetricsHandler::new(meta.scheme().into_static())),
-        }
+/// [`MetricsLayer`] with a [`MetricsConfig`], created by [`MetricsLayer::with_config`].
+#[derive(Debug, Clone)]
+pub struct ConfiguredMetricsLayer {
+    config: MetricsConfig,
+}
+
+impl<A: Accessor> Layer<A> for ConfiguredMetricsLayer {
+    type LayeredAccessor = MetricsAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        MetricsAccessor::new(inner, self.config.clone())
     }
 }
 
-/// metrics will hold all metrics handlers in a `RwLock<HashMap>`.
+/// MetricsHandler records metrics via the global recorder.
 ///
-/// By holding all metrics handlers we needed, we can reduce the lock
-/// cost on fetching them. All metrics update will be atomic operations.
+/// Without the path label, handles of metrics are registered in advance so that
+/// all metrics update will be atomic operations. Otherwise, labels are built for
+/// every request.
 struct MetricsHandler {
     service: &'static str,
-
-    requests_total_metadata: Counter,
-    requests_duration_seconds_metadata: Histogram,
-
-    requests_total_create: Counter,
-    requests_duration_seconds_create: Histogram,
-
-    requests_total_read: Counter,
-    requests_duration_seconds_read: Histogram,
-    bytes_total_read: Counter,
-
-    requests_total_write: Counter,
-    requests_duration_seconds_write: Histogram,
-    bytes_total_write: Counter,
-
-    requests_total_stat: Counter,
-    requests_duration_seconds_stat: Histogram,
-
-    requests_total_delete: Counter,
-    requests_duration_seconds_delete: Histogram,
-
-    requests_total_list: Counter,
-    requests_duration_seconds_list: Histogram,
-
-    requests_total_presign: Counter,
-    requests_duration_seconds_presign: Histogram,
-
-    requests_total_batch: Counter,
-    requests_duration_seconds_batch: Histogram,
-
-    requests_total_blocking_create: Counter,
-    requests_duration_seconds_blocking_create: Histogram,
-
-    requests_total_blocking_read: Counter,
-    requests_duration_seconds_blocking_read: Histogram,
-    bytes_total_blocking_read: Counter,
-
-    requests_total_blocking_write: Counter,
-    requests_duration_seconds_blocking_write: Histogram,
-    #[allow(dead_code)]
-    bytes_total_blocking_write: Counter,
-
-    requests_total_blocking_stat: Counter,
-    requests_duration_seconds_blocking_stat: Histogram,
-
-    requests_total_blocking_delete: Counter,
-    requests_duration_seconds_blocking_delete: Histogram,
-
-    requests_total_blocking_list: Counter,
-    requests_duration_seconds_blocking_list: Histogram,
+    config: MetricsConfig,
+    handles: HashMap<Operation, OperationHandles>,
 }
 
-impl MetricsHandler {
-    fn new(service: &'static str) -> Self {
-        Self {
-            service,
-
-            requests_total_metadata: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Info.into_static(),
-            ),
-            requests_duration_seconds_metadata: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Info.into_static(),
-            ),
-
-            requests_total_create: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::CreateDir.into_static(),
-            ),
-            requests_duration_seconds_create: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::CreateDir.into_static(),
-            ),
-
-            requests_total_read: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Read.into_static(),
-            ),
-            requests_duration_seconds_read: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Read.into_static(),
-            ),
-            bytes_total_read: register_counter!(
-                METRIC_BYTES_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Read.into_static(),
-            ),
+/// Metrics handles of an operation.
+struct OperationHandles {
+    requests_total: Counter,
+    requests_duration_seconds: Histogram,
+    /// Only available for read and write.
+    bytes_total: Option<Counter>,
+}
 
-            requests_total_write: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Write.into_static(),
-            ),
-            requests_duration_seconds_write: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Write.into_static(),
-            ),
-            bytes_total_write: register_counter!(
+impl OperationHandles {
+    fn register(service: &'static str, op: Operation) -> Self {
+        let bytes_total = matches!(
+            op,
+            Operation::Read | Operation::Write | Operation::BlockingRead | Operation::BlockingWrite
+        )
+        .then(|| {
+            register_counter!(
                 METRIC_BYTES_TOTAL,
                 LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Write.into_static(),
-            ),
-
-            requests_total_stat: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Stat.into_static(),
-            ),
-            requests_duration_seconds_stat: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Stat.into_static(),
-            ),
-
-            requests_total_delete: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Delete.into_static(),
-            ),
-            requests_duration_seconds_delete: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Delete.into_static(),
-            ),
-
-            requests_total_list: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::List.into_static(),
-            ),
-            requests_duration_seconds_list: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::List.into_static(),
-            ),
-
-            requests_total_presign: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Presign.into_static(),
-            ),
-            requests_duration_seconds_presign: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Presign.into_static(),
-            ),
+                LABEL_OPERATION => op.into_static(),
+            )
+        });
 
-            requests_total_batch: register_counter!(
+        Self {
+            requests_total: register_counter!(
                 METRIC_REQUESTS_TOTAL,
                 LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Batch.into_static(),
+                LABEL_OPERATION => op.into_static(),
             ),
-            requests_duration_seconds_batch: register_histogram!(
+            requests_duration_seconds: register_histogram!(
                 METRIC_REQUESTS_DURATION_SECONDS,
                 LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::Batch.into_static(),
+                LABEL_OPERATION => op.into_static(),
             ),
+            bytes_total,
+        }
+    }
+}
 
-            requests_total_blocking_create: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingCreateDir.into_static(),
-            ),
-            requests_duration_seconds_blocking_create: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingCreateDir.into_static(),
-            ),
+impl MetricsHandler {
+    fn new(service: &'static str, config: MetricsConfig) -> Self {
+        let mut handles = HashMap::new();
+        if !config.path_label_enabled() {
+            for op in [
+                Operation::Info,
+                Operation::CreateDir,
+                Operation::Read,
+                Operation::Write,
+                Operation::Stat,
+                Operation::Delete,
+                Operation::List,
+                Operation::Presign,
+                Operation::Batch,
+                Operation::BlockingCreateDir,
+                Operation::BlockingRead,
+                Operation::BlockingWrite,
+                Operation::BlockingStat,
+                Operation::BlockingDelete,
+                Operation::BlockingList,
+            ] {
+                handles.insert(op, OperationHandles::register(service, op));
+            }
+        }
 
-            requests_total_blocking_read: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingRead.into_static(),
-            ),
-            requests_duration_seconds_blocking_read: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingRead.into_static(),
-            ),
-            bytes_total_blocking_read: register_counter!(
-                METRIC_BYTES_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingRead.into_static(),
-            ),
+        Self {
+            service,
+            config,
+            handles,
+        }
+    }
 
-            requests_total_blocking_write: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingWrite.into_static(),
-            ),
-            requests_duration_seconds_blocking_write: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingWrite.into_static(),
-            ),
-            bytes_total_blocking_write: register_counter!(
-                METRIC_BYTES_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingWrite.into_static(),
-            ),
+    fn labels(&self, op: Operation, path: &str) -> Vec<Label> {
+        let mut labels = vec![
+            Label::new(LABEL_SERVICE, self.service),
+            Label::new(LABEL_OPERATION, op.into_static()),
+        ];
+        if let Some(path) = self.config.path_label(path) {
+            labels.push(Label::new(LABEL_PATH, path.to_string()));
+        }
+        labels
+    }
 
-            requests_total_blocking_stat: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingStat.into_static(),
-            ),
-            requests_duration_seconds_blocking_stat: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingStat.into_static(),
-            ),
+    fn increment_requests_total(&self, op: Operation, path: &str) {
+        match self.handles.get(&op) {
+            Some(h) => h.requests_total.increment(1),
+            None => increment_counter!(METRIC_REQUESTS_TOTAL, self.labels(op, path)),
+        }
+    }
 
-            requests_total_blocking_delete: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingDelete.into_static(),
-            ),
-            requests_duration_seconds_blocking_delete: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingDelete.into_static(),
-            ),
+    fn observe_requests_duration(&self, op: Operation, path: &str, start: Instant) {
+        let dur = start.elapsed().as_secs_f64();
+        match self.handles.get(&op) {
+            Some(h) => h.requests_duration_seconds.record(dur),
+            None => histogram!(METRIC_REQUESTS_DURATION_SECONDS, dur, self.labels(op, path)),
+        }
+    }
 
-            requests_total_blocking_list: register_counter!(
-                METRIC_REQUESTS_TOTAL,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingList.into_static(),
-            ),
-            requests_duration_seconds_blocking_list: register_histogram!(
-                METRIC_REQUESTS_DURATION_SECONDS,
-                LABEL_SERVICE => service,
-                LABEL_OPERATION => Operation::BlockingList.into_static(),
-            ),
+    fn increment_bytes_total(&self, op: Operation, path: &str, bytes: u64) {
+        match self.handles.get(&op).and_then(|h| h.bytes_total.as_ref()) {
+            Some(c) => c.increment(bytes),
+            None => counter!(METRIC_BYTES_TOTAL, bytes, self.labels(op, path)),
         }
     }
 
     /// error handling is the cold path, so we will not init error counters
     /// in advance.
     #[inline]
-    fn increment_errors_total(&self, op: Operation, kind: ErrorKind) {
-        increment_counter!(METRICS_ERRORS_TOTAL,
-            LABEL_SERVICE => self.service,
-            LABEL_OPERATION => op.into_static(),
-            LABEL_ERROR => kind.into_static(),
-        )
+    fn increment_errors_total(&self, op: Operation, path: &str, kind: ErrorKind) {
+        let mut labels = self.labels(op, path);
+        labels.push(Label::new(LABEL_ERROR, kind.into_static()));
+        increment_counter!(METRICS_ERRORS_TOTAL, labels)
+    }
+
+    /// Record the request and its duration, and the error if failed.
+    fn observe<T>(&self, op: Operation, path: &str, start: Instant, res: &Result<T>) {
+        self.observe_requests_duration(op, path, start);
+        if let Err(err) = res {
+            self.increment_errors_total(op, path, err.kind());
+        }
     }
 }
 
@@ -397,6 +309,16 @@ pub struct MetricsAccessor<A: Accessor> {
     handle: Arc<MetricsHandler>,
 }
 
+impl<A: Accessor> MetricsAccessor<A> {
+    fn new(inner: A, config: MetricsConfig) -> Self {
+        let service = inner.info().scheme().into_static();
+        Self {
+            inner,
+            handle: Arc::new(MetricsHandler::new(service, config)),
+        }
+    }
+}
+
 impl<A: Accessor> Debug for MetricsAccessor<A> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("MetricsAccessor")
@@ -421,62 +343,43 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
     }
 
     fn metadata(&self) -> AccessorInfo {
-        self.handle.requests_total_metadata.increment(1);
+        self.handle.increment_requests_total(Operation::Info, "");
 
         let start = Instant::now();
         let result = self.inner.info();
-        let dur = start.elapsed().as_secs_f64();
-
-        self.handle.requests_duration_seconds_metadata.record(dur);
+        self.handle
+            .observe_requests_duration(Operation::Info, "", start);
 
         result
     }
 
     async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
-        self.handle.requests_total_create.increment(1);
+        self.handle
+            .increment_requests_total(Operation::CreateDir, path);
 
         let start = Instant::now();
-
-        self.inner
-            .create_dir(path, args)
-            .map(|v| {
-                let dur = start.elapsed().as_secs_f64();
-
-                self.handle.requests_duration_seconds_create.record(dur);
-
-                v.map_err(|e| {
-                    self.handle
-                        .increment_errors_total(Operation::CreateDir, e.kind());
-                    e
-                })
-            })
-            .await
+        let result = self.inner.create_dir(path, args).await;
+        self.handle
+            .observe(Operation::CreateDir, path, start, &result);
+        result
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        self.handle.requests_total_read.increment(1);
+        self.handle.increment_requests_total(Operation::Read, path);
 
         let start = Instant::now();
-
         self.inner
             .read(path, args)
             .map(|v| {
                 v.map(|(rp, r)| {
                     (
                         rp,
-                        MetricWrapper::new(
-                            r,
-                            Operation::Read,
-                            self.handle.clone(),
-                            self.handle.bytes_total_read.clone(),
-                            self.handle.requests_duration_seconds_read.clone(),
-                            Some(start),
-                        ),
+                        MetricWrapper::new(r, Operation::Read, path, self.handle.clone(), start),
                     )
                 })
                 .map_err(|err| {
                     self.handle
-                        .increment_errors_total(Operation::Read, err.kind());
+                        .increment_errors_total(Operation::Read, path, err.kind());
                     err
                 })
             })
@@ -484,248 +387,158 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        self.handle.requests_total_write.increment(1);
+        self.handle.increment_requests_total(Operation::Write, path);
 
         let start = Instant::now();
-
         self.inner
             .write(path, args)
             .map_ok(|(rp, w)| {
                 (
                     rp,
-                    MetricWrapper::new(
-                        w,
-                        Operation::Write,
-                        self.handle.clone(),
-                        self.handle.bytes_total_write.clone(),
-                        self.handle.requests_duration_seconds_write.clone(),
-                        Some(start),
-                    ),
+                    MetricWrapper::new(w, Operation::Write, path, self.handle.clone(), start),
                 )
             })
             .inspect_err(|e| {
                 self.handle
-                    .increment_errors_total(Operation::Write, e.kind());
+                    .increment_errors_total(Operation::Write, path, e.kind());
             })
             .await
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        self.handle.requests_total_stat.increment(1);
+        self.handle.increment_requests_total(Operation::Stat, path);
 
         let start = Instant::now();
-
-        self.inner
-            .stat(path, args)
-            .inspect_ok(|_| {
-                let dur = start.elapsed().as_secs_f64();
-
-                self.handle.requests_duration_seconds_stat.record(dur);
-            })
-            .inspect_err(|e| {
-                self.handle
-                    .increment_errors_total(Operation::Stat, e.kind());
-            })
-            .await
+        let result = self.inner.stat(path, args).await;
+        self.handle.observe(Operation::Stat, path, start, &result);
+        result
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
-        self.handle.requests_total_delete.increment(1);
+        self.handle
+            .increment_requests_total(Operation::Delete, path);
 
         let start = Instant::now();
-
-        self.inner
-            .delete(path, args)
-            .inspect_ok(|_| {
-                let dur = start.elapsed().as_secs_f64();
-
-                self.handle.requests_duration_seconds_delete.record(dur);
-            })
-            .inspect_err(|e| {
-                self.handle
-                    .increment_errors_total(Operation::Delete, e.kind());
-            })
-            .await
+        let result = self.inner.delete(path, args).await;
+        self.handle.observe(Operation::Delete, path, start, &result);
+        result
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
-        self.handle.requests_total_list.increment(1);
+        self.handle.increment_requests_total(Operation::List, path);
 
         let start = Instant::now();
-
-        self.inner
-            .list(path, args)
-            .inspect_ok(|_| {
-                let dur = start.elapsed().as_secs_f64();
-
-                self.handle.requests_duration_seconds_list.record(dur);
-            })
-            .inspect_err(|e| {
-                self.handle
-                    .increment_errors_total(Operation::List, e.kind());
-            })
-            .await
+        let result = self.inner.list(path, args).await;
+        self.handle.observe(Operation::List, path, start, &result);
+        result
     }
 
     async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
-        self.handle.requests_total_batch.increment(1);
+        self.handle.increment_requests_total(Operation::Batch, "");
 
         let start = Instant::now();
         let result = self.inner.batch(args).await;
-        let dur = start.elapsed().as_secs_f64();
-
-        self.handle.requests_duration_seconds_batch.record(dur);
-
-        result.map_err(|e| {
-            self.handle
-                .increment_errors_total(Operation::Batch, e.kind());
-            e
-        })
+        self.handle.observe(Operation::Batch, "", start, &result);
+        result
     }
 
     async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
-        self.handle.requests_total_presign.increment(1);
+        self.handle
+            .increment_requests_total(Operation::Presign, path);
 
         let start = Instant::now();
         let result = self.inner.presign(path, args).await;
-        let dur = start.elapsed().as_secs_f64();
-
-        self.handle.requests_duration_seconds_presign.record(dur);
-
-        result.map_err(|e| {
-            self.handle
-                .increment_errors_total(Operation::Presign, e.kind());
-            e
-        })
+        self.handle
+            .observe(Operation::Presign, path, start, &result);
+        result
     }
 
     fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
-        self.handle.requests_total_blocking_create.increment(1);
+        self.handle
+            .increment_requests_total(Operation::BlockingCreateDir, path);
 
         let start = Instant::now();
         let result = self.inner.blocking_create_dir(path, args);
-        let dur = start.elapsed().as_secs_f64();
+        self.handle
+            .observe(Operation::BlockingCreateDir, path, start, &result);
+        result
+    }
 
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
         self.handle
-            .requests_duration_seconds_blocking_create
-            .record(dur);
+            .increment_requests_total(Operation::BlockingRead, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_read(path, args).map(|(rp, r)| {
+            (
+                rp,
+                MetricWrapper::new(r, Operation::BlockingRead, path, self.handle.clone(), start),
+            )
+        });
 
         result.map_err(|e| {
             self.handle
-                .increment_errors_total(Operation::BlockingCreateDir, e.kind());
+                .increment_errors_total(Operation::BlockingRead, path, e.kind());
             e
         })
     }
 
-    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
-        self.handle.requests_total_blocking_read.increment(1);
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.handle
+            .increment_requests_total(Operation::BlockingWrite, path);
 
         let start = Instant::now();
-        let result = self.inner.blocking_read(path, args).map(|(rp, r)| {
+        let result = self.inner.blocking_write(path, args).map(|(rp, w)| {
             (
                 rp,
                 MetricWrapper::new(
-                    r,
-                    Operation::BlockingRead,
+                    w,
+                    Operation::BlockingWrite,
+                    path,
                     self.handle.clone(),
-                    self.handle.bytes_total_blocking_read.clone(),
-                    self.handle.requests_duration_seconds_blocking_read.clone(),
-                    Some(start),
+                    start,
                 ),
             )
         });
 
         result.map_err(|e| {
             self.handle
-                .increment_errors_total(Operation::BlockingRead, e.kind());
+                .increment_errors_total(Operation::BlockingWrite, path, e.kind());
             e
         })
     }
 
-    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        self.handle.requests_total_blocking_write.increment(1);
-
-        let start = Instant::now();
-        let result = self.inner.blocking_write(path, args);
-        let dur = start.elapsed().as_secs_f64();
-
-        self.handle
-            .requests_duration_seconds_blocking_write
-            .record(dur);
-
-        result
-            .map(|(rp, w)| {
-                (
-                    rp,
-                    MetricWrapper::new(
-                        w,
-                        Operation::BlockingWrite,
-                        self.handle.clone(),
-                        self.handle.bytes_total_write.clone(),
-                        self.handle.requests_duration_seconds_write.clone(),
-                        Some(start),
-                    ),
-                )
-            })
-            .map_err(|e| {
-                self.handle
-                    .increment_errors_total(Operation::BlockingWrite, e.kind());
-                e
-            })
-    }
-
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        self.handle.requests_total_blocking_stat.increment(1);
+        self.handle
+            .increment_requests_total(Operation::BlockingStat, path);
 
         let start = Instant::now();
         let result = self.inner.blocking_stat(path, args);
-        let dur = start.elapsed().as_secs_f64();
-
         self.handle
-            .requests_duration_seconds_blocking_stat
-            .record(dur);
-
-        result.map_err(|e| {
-            self.handle
-                .increment_errors_total(Operation::BlockingStat, e.kind());
-            e
-        })
+            .observe(Operation::BlockingStat, path, start, &result);
+        result
     }
 
     fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
-        self.handle.requests_total_blocking_delete.increment(1);
+        self.handle
+            .increment_requests_total(Operation::BlockingDelete, path);
 
         let start = Instant::now();
         let result = self.inner.blocking_delete(path, args);
-        let dur = start.elapsed().as_secs_f64();
-
         self.handle
-            .requests_duration_seconds_blocking_delete
-            .record(dur);
-
-        result.map_err(|e| {
-            self.handle
-                .increment_errors_total(Operation::BlockingDelete, e.kind());
-            e
-        })
+            .observe(Operation::BlockingDelete, path, start, &result);
+        result
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
-        self.handle.requests_total_blocking_list.increment(1);
+        self.handle
+            .increment_requests_total(Operation::BlockingList, path);
 
         let start = Instant::now();
         let result = self.inner.blocking_list(path, args);
-        let dur = start.elapsed().as_secs_f64();
-
         self.handle
-            .requests_duration_seconds_blocking_list
-            .record(dur);
-
-        result.map_err(|e| {
-            self.handle
-                .increment_errors_total(Operation::BlockingList, e.kind());
-            e
-        })
+            .observe(Operation::BlockingList, path, start, &result);
+        result
     }
 }
 
@@ -733,11 +546,10 @@ pub struct MetricWrapper<R> {
     inner: R,
 
     op: Operation,
-    bytes_counter: Counter,
-    requests_duration_seconds: Histogram,
+    path: String,
     handle: Arc<MetricsHandler>,
 
-    start: Option<Instant>,
+    start: Instant,
     bytes: u64,
 }
 
@@ -745,17 +557,21 @@ impl<R> MetricWrapper<R> {
     fn new(
         inner: R,
         op: Operation,
+        path: &str,
         handle: Arc<MetricsHandler>,
-        bytes_counter: Counter,
-        requests_duration_seconds: Histogram,
-        start: Option<Instant>,
+        start: Instant,
     ) -> Self {
+        // The path is only needed to build the path label.
+        let path = if handle.config.path_label_enabled() {
+            path.to_string()
+        } else {
+            String::new()
+        };
         Self {
             inner,
             op,
+            path,
             handle,
-            bytes_counter,
-            requests_duration_seconds,
             start,
             bytes: 0,
         }
@@ -764,11 +580,10 @@ impl<R> MetricWrapper<R> {
 
 impl<R> Drop for MetricWrapper<R> {
     fn drop(&mut self) {
-        self.bytes_counter.increment(self.bytes);
-        if let Some(instant) = self.start {
-            let dur = instant.elapsed().as_secs_f64();
-            self.requests_duration_seconds.record(dur);
-        }
+        self.handle
+            .increment_bytes_total(self.op, &self.path, self.bytes);
+        self.handle
+            .observe_requests_duration(self.op, &self.path, self.start);
     }
 }
 
@@ -780,7 +595,8 @@ impl<R: oio::Read> oio::Read for MetricWrapper<R> {
                 Ok(bytes)
             }
             Err(e) => {
-                self.handle.increment_errors_total(self.op, e.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -790,7 +606,8 @@ impl<R: oio::Read> oio::Read for MetricWrapper<R> {
         self.inner.poll_seek(cx, pos).map(|res| match res {
             Ok(n) => Ok(n),
             Err(e) => {
-                self.handle.increment_errors_total(self.op, e.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -803,7 +620,8 @@ impl<R: oio::Read> oio::Read for MetricWrapper<R> {
                 Some(Ok(bytes))
             }
             Some(Err(e)) => {
-                self.handle.increment_errors_total(self.op, e.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, e.kind());
                 Some(Err(e))
             }
             None => None,
@@ -820,14 +638,16 @@ impl<R: oio::BlockingRead> oio::BlockingRead for MetricWrapper<R> {
                 n
             })
             .map_err(|e| {
-                self.handle.increment_errors_total(self.op, e.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, e.kind());
                 e
             })
     }
 
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
         self.inner.seek(pos).map_err(|err| {
-            self.handle.increment_errors_total(self.op, err.kind());
+            self.handle
+                .increment_errors_total(self.op, &self.path, err.kind());
             err
         })
     }
@@ -839,7 +659,8 @@ impl<R: oio::BlockingRead> oio::BlockingRead for MetricWrapper<R> {
                 Ok(bytes)
             }
             Err(e) => {
-                self.handle.increment_errors_total(self.op, e.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -855,21 +676,24 @@ impl<R: oio::Write> oio::Write for MetricWrapper<R> {
                 n
             })
             .map_err(|err| {
-                self.handle.increment_errors_total(self.op, err.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, err.kind());
                 err
             })
     }
 
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_abort(cx).map_err(|err| {
-            self.handle.increment_errors_total(self.op, err.kind());
+            self.handle
+                .increment_errors_total(self.op, &self.path, err.kind());
             err
         })
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_close(cx).map_err(|err| {
-            self.handle.increment_errors_total(self.op, err.kind());
+            self.handle
+                .increment_errors_total(self.op, &self.path, err.kind());
             err
         })
     }
@@ -884,14 +708,16 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for MetricWrapper<R> {
                 n
             })
             .map_err(|err| {
-                self.handle.increment_errors_total(self.op, err.kind());
+                self.handle
+                    .increment_errors_total(self.op, &self.path, err.kind());
                 err
             })
     }
 
     fn close(&mut self) -> Result<()> {
         self.inner.close().map_err(|err| {
-            self.handle.increment_errors_total(self.op, err.kind());
+            self.handle
+                .increment_errors_total(self.op, &self.path, err.kind());
             err
         })
     }
@@ -0,0 +1,222 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use regex::Regex;
+
+use crate::*;
+
+/// Label key of the path label.
+pub(crate) const LABEL_PATH: &str = "path";
+
+/// MetricsConfig is the config shared by [`MetricsLayer`], [`PrometheusLayer`]
+/// and [`PrometheusClientLayer`].
+///
+/// # Path Label
+///
+/// Labeling by full path will explode the cardinality of metrics, so only
+/// the prefix of path will be used as the `path` label:
+///
+/// - [`MetricsConfig::with_path_segments`]: Use the first N segments of path.
+/// - [`MetricsConfig::with_path_regex`]: Use the first capture group of given
+///   regex, or the whole match if there is no capture group. Paths that don't
+///   match will be labeled as `""`.
+///
+/// The `path` label is disabled by default.
+///
+/// # Histogram Buckets
+///
+/// - `duration_buckets`: buckets in seconds for request duration, default to
+///   `exponential_buckets(0.01, 2.0, 16)`.
+/// - `bytes_buckets`: buckets in bytes for read/write size, default to
+///   `exponential_buckets(1.0, 2.0, 16)`.
+///
+/// [`MetricsLayer`]: crate::layers::MetricsLayer
+/// [`PrometheusLayer`]: crate::layers::PrometheusLayer
+/// [`PrometheusClientLayer`]: crate::layers::PrometheusClientLayer
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::MetricsConfig;
+///
+/// # fn main() -> Result<()> {
+/// // Label `datasets/2024/01/a.parquet` as `datasets/2024`.
+/// let _ = MetricsConfig::default().with_path_segments(2);
+///
+/// // Label `tenants/abc/data/a.parquet` as `abc`.
+/// let _ = MetricsConfig::default()
+///     .with_path_regex(r"^tenants/([^/]+)/")?
+///     .with_duration_buckets(vec![0.005, 0.05, 0.5, 5.0]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    path_label: PathLabel,
+    duration_buckets: Vec<f64>,
+    bytes_buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            path_label: PathLabel::Disabled,
+            duration_buckets: exponential_buckets(0.01, 2.0, 16),
+            bytes_buckets: exponential_buckets(1.0, 2.0, 16),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PathLabel {
+    Disabled,
+    Segments(usize),
+    Regex(Regex),
+}
+
+impl MetricsConfig {
+    /// Use the first `n` segments of path as the `path` label.
+    ///
+    /// `0` means disable the `path` label.
+    pub fn with_path_segments(mut self, n: usize) -> Self {
+        self.path_label = if n == 0 {
+            PathLabel::Disabled
+        } else {
+            PathLabel::Segments(n)
+        };
+        self
+    }
+
+    /// Use the capture of given regex as the `path` label.
+    pub fn with_path_regex(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|err| {
+            Error::new(ErrorKind::ConfigInvalid, "path label regex is invalid")
+                .with_context("pattern", pattern)
+                .set_source(err)
+        })?;
+        self.path_label = PathLabel::Regex(regex);
+        Ok(self)
+    }
+
+    /// Set buckets in seconds for request duration histograms.
+    ///
+    /// Empty buckets will be ignored.
+    pub fn with_duration_buckets(mut self, buckets: Vec<f64>) -> Self {
+        if !buckets.is_empty() {
+            self.duration_buckets = buckets;
+        }
+        self
+    }
+
+    /// Set buckets in bytes for read/write size histograms.
+    ///
+    /// Empty buckets will be ignored.
+    pub fn with_bytes_buckets(mut self, buckets: Vec<f64>) -> Self {
+        if !buckets.is_empty() {
+            self.bytes_buckets = buckets;
+        }
+        self
+    }
+
+    #[cfg(any(feature = "layers-prometheus", feature = "layers-prometheus-client"))]
+    pub(crate) fn duration_buckets(&self) -> &[f64] {
+        &self.duration_buckets
+    }
+
+    #[cfg(any(feature = "layers-prometheus", feature = "layers-prometheus-client"))]
+    pub(crate) fn bytes_buckets(&self) -> &[f64] {
+        &self.bytes_buckets
+    }
+
+    /// Check if the `path` label is enabled.
+    #[cfg(any(feature = "layers-metrics", feature = "layers-prometheus"))]
+    pub(crate) fn path_label_enabled(&self) -> bool {
+        !matches!(self.path_label, PathLabel::Disabled)
+    }
+
+    /// The count of path segments used as the `path` label, `0` if the
+    /// `path` label is disabled or extracted by regex.
+    #[cfg(feature = "layers-prometheus")]
+    pub(crate) fn path_segments(&self) -> usize {
+        match self.path_label {
+            PathLabel::Segments(n) => n,
+            _ => 0,
+        }
+    }
+
+    /// Extract the `path` label from given path.
+    ///
+    /// Returns `None` if the `path` label is disabled.
+    pub(crate) fn path_label<'a>(&self, path: &'a str) -> Option<&'a str> {
+        match &self.path_label {
+            PathLabel::Disabled => None,
+            PathLabel::Segments(n) => Some(
+                path.char_indices()
+                    .filter(|&(_, c)| c == '/')
+                    .nth(n - 1)
+                    .map_or(path, |(i, _)| &path[..i]),
+            ),
+            PathLabel::Regex(regex) => Some(
+                regex
+                    .captures(path)
+                    .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                    .map_or("", |m| m.as_str()),
+            ),
+        }
+    }
+}
+
+fn exponential_buckets(start: f64, factor: f64, count: usize) -> Vec<f64> {
+    let mut buckets = Vec::with_capacity(count);
+    let mut next = start;
+    for _ in 0..count {
+        buckets.push(next);
+        next *= factor;
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_label() {
+        let path = "abc/def/ghi";
+
+        let config = MetricsConfig::default();
+        assert_eq!(config.path_label(path), None);
+
+        let config = MetricsConfig::default().with_path_segments(1);
+        assert_eq!(config.path_label(path), Some("abc"));
+        let config = MetricsConfig::default().with_path_segments(2);
+        assert_eq!(config.path_label(path), Some("abc/def"));
+        let config = MetricsConfig::default().with_path_segments(usize::MAX);
+        assert_eq!(config.path_label(path), Some("abc/def/ghi"));
+        assert_eq!(config.path_label(""), Some(""));
+
+        let config = MetricsConfig::default()
+            .with_path_regex(r"^abc/([^/]+)/")
+            .unwrap();
+        assert_eq!(config.path_label(path), Some("def"));
+        assert_eq!(config.path_label("xyz/def/ghi"), Some(""));
+
+        let config = MetricsConfig::default().with_path_regex("^abc").unwrap();
+        assert_eq!(config.path_label(path), Some("abc"));
+    }
+}
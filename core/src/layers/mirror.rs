@@ -0,0 +1,753 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+
+use crate::raw::oio::WriteExt;
+use crate::raw::oio::WriteOperation;
+use crate::raw::*;
+use crate::*;
+
+/// Mirror all mutations to a secondary storage.
+///
+/// `MirrorLayer` is designed for storage migrations: all reads are served by
+/// the primary storage, while `write`, `delete`, `copy`, `rename`, `create_dir`
+/// and `batch` will also be applied to the secondary [`Operator`].
+///
+/// Writers are mirrored at [`oio::Write`] level, so streaming and multipart
+/// writes will land in both storages with the same content.
+///
+/// # Mode
+///
+/// - [`MirrorMode::Strict`]: Mirror failures will be returned to users.
+/// - [`MirrorMode::BestEffort`]: Mirror failures will be ignored.
+///
+/// No matter which mode is used, the failures on secondary storage will be
+/// recorded as [`MirrorDivergence`], users can fetch them via
+/// [`MirrorLayer::divergences`] and repair them later. At most
+/// [`MirrorLayer::with_max_divergences`] records will be kept, the oldest ones
+/// will be dropped and counted by [`MirrorLayer::dropped_divergences`].
+///
+/// # Notes
+///
+/// Operations are always applied to the primary storage first. So in strict
+/// mode, a failed mirror means the primary storage has been changed while
+/// the secondary storage has not.
+///
+/// Writers are the exception: a writer that failed to open on secondary
+/// storage will not leave content on primary storage. Async writers are
+/// aborted on primary storage, while blocking writers that can't be aborted
+/// are opened on secondary storage first. Once the content has been written,
+/// writers are closed on primary storage first too.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::MirrorLayer;
+/// use opendal::layers::MirrorMode;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # fn main() -> Result<()> {
+/// let secondary = Operator::new(services::Memory::default())?.finish();
+///
+/// let layer = MirrorLayer::new(secondary).with_mode(MirrorMode::BestEffort);
+/// let _ = Operator::new(services::Memory::default())?
+///     .layer(layer.clone())
+///     .finish();
+///
+/// for d in layer.divergences() {
+///     println!("{} on {} diverged: {}", d.operation(), d.path(), d.message());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MirrorLayer {
+    secondary: Operator,
+    mode: MirrorMode,
+    divergences: Arc<Mutex<Divergences>>,
+}
+
+impl MirrorLayer {
+    /// Create a new `MirrorLayer` that mirrors all mutations to given operator.
+    pub fn new(secondary: Operator) -> Self {
+        Self {
+            secondary,
+            mode: MirrorMode::default(),
+            divergences: Arc::new(Mutex::new(Divergences::new(DEFAULT_MAX_DIVERGENCES))),
+        }
+    }
+
+    /// Set the mode to handle mirror failures.
+    ///
+    /// Default to [`MirrorMode::Strict`].
+    pub fn with_mode(mut self, mode: MirrorMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the max count of divergences to keep, the oldest ones will be
+    /// dropped once exceeded.
+    ///
+    /// Default to 1024.
+    pub fn with_max_divergences(self, max: usize) -> Self {
+        let mut divergences = self.divergences.lock().expect("lock must be acquired");
+        divergences.max = max;
+        while divergences.records.len() > max {
+            divergences.records.pop_front();
+            divergences.dropped += 1;
+        }
+        drop(divergences);
+        self
+    }
+
+    /// Get the divergences between primary and secondary storage recorded so far.
+    ///
+    /// Divergences are shared by all clones of this layer.
+    pub fn divergences(&self) -> Vec<MirrorDivergence> {
+        let divergences = self.divergences.lock().expect("lock must be acquired");
+        divergences.records.iter().cloned().collect()
+    }
+
+    /// Get the count of divergences that have been dropped since the report
+    /// is full.
+    pub fn dropped_divergences(&self) -> u64 {
+        self.divergences
+            .lock()
+            .expect("lock must be acquired")
+            .dropped
+    }
+
+    /// Take the divergences recorded so far and reset the report, including
+    /// the count of dropped ones.
+    pub fn take_divergences(&self) -> Vec<MirrorDivergence> {
+        let mut divergences = self.divergences.lock().expect("lock must be acquired");
+        divergences.dropped = 0;
+        std::mem::take(&mut divergences.records).into()
+    }
+}
+
+const DEFAULT_MAX_DIVERGENCES: usize = 1024;
+
+/// Divergences keeps the latest records in a ring buffer.
+#[derive(Debug)]
+struct Divergences {
+    records: VecDeque<MirrorDivergence>,
+    max: usize,
+    dropped: u64,
+}
+
+impl Divergences {
+    fn new(max: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            max,
+            dropped: 0,
+        }
+    }
+
+    fn push(&mut self, record: MirrorDivergence) {
+        if self.max == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.records.len() >= self.max {
+            self.records.pop_front();
+            self.dropped += 1;
+        }
+        self.records.push_back(record);
+    }
+}
+
+impl<A: Accessor> Layer<A> for MirrorLayer {
+    type LayeredAccessor = MirrorAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        MirrorAccessor {
+            inner,
+            ctx: Arc::new(MirrorContext {
+                secondary: self.secondary.inner().clone(),
+                mode: self.mode,
+                divergences: self.divergences.clone(),
+            }),
+        }
+    }
+}
+
+/// MirrorMode controls how [`MirrorLayer`] handles failures on secondary storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MirrorMode {
+    /// Return the mirror failure to users.
+    #[default]
+    Strict,
+    /// Ignore the mirror failure, only record it as a divergence.
+    BestEffort,
+}
+
+/// MirrorDivergence records an operation that succeeded on the primary storage
+/// but failed on the secondary storage.
+#[derive(Debug, Clone)]
+pub struct MirrorDivergence {
+    operation: &'static str,
+    path: String,
+    kind: ErrorKind,
+    message: String,
+}
+
+impl MirrorDivergence {
+    /// The operation that failed on secondary storage.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// The path that failed on secondary storage.
+    ///
+    /// For `copy` and `rename`, this is the `from` path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The error kind returned by secondary storage.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The error message returned by secondary storage.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug)]
+struct MirrorContext {
+    secondary: FusedAccessor,
+    mode: MirrorMode,
+    divergences: Arc<Mutex<Divergences>>,
+}
+
+impl MirrorContext {
+    /// Handle the result returned by secondary storage.
+    ///
+    /// Returns `Ok(None)` if the mirror failed but we are in best-effort mode.
+    fn handle<T>(&self, op: &'static str, path: &str, res: Result<T>) -> Result<Option<T>> {
+        let err = match res {
+            Ok(v) => return Ok(Some(v)),
+            Err(err) => err,
+        };
+
+        self.divergences
+            .lock()
+            .expect("lock must be acquired")
+            .push(MirrorDivergence {
+                operation: op,
+                path: path.to_string(),
+                kind: err.kind(),
+                message: err.to_string(),
+            });
+
+        match self.mode {
+            MirrorMode::Strict => Err(err
+                .with_operation(op)
+                .with_context("path", path)
+                .with_context("mirror", "secondary")),
+            MirrorMode::BestEffort => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MirrorAccessor<A: Accessor> {
+    inner: A,
+    ctx: Arc<MirrorContext>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for MirrorAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = MirrorWriter<A::Writer, oio::Writer>;
+    type BlockingWriter = MirrorWriter<A::BlockingWriter, oio::BlockingWriter>;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let rp = self.inner.create_dir(path, args.clone()).await?;
+
+        let res = self.ctx.secondary.create_dir(path, args).await;
+        self.ctx
+            .handle(Operation::CreateDir.into_static(), path, res)?;
+        Ok(rp)
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let (rp, mut w) = self.inner.write(path, args.clone()).await?;
+
+        let res = self.ctx.secondary.write(path, args).await;
+        let secondary = match self.ctx.handle(Operation::Write.into_static(), path, res) {
+            Ok(secondary) => secondary.map(|(_, w)| w),
+            Err(err) => {
+                // Abort the primary writer so that it won't leave partial
+                // content behind, the mirror error is more relevant to users.
+                let _ = w.abort().await;
+                return Err(err);
+            }
+        };
+        Ok((rp, MirrorWriter::new(w, secondary, path, self.ctx.clone())))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let rp = self.inner.copy(from, to, args.clone()).await?;
+
+        let res = self.ctx.secondary.copy(from, to, args).await;
+        self.ctx.handle(Operation::Copy.into_static(), from, res)?;
+        Ok(rp)
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let rp = self.inner.rename(from, to, args.clone()).await?;
+
+        let res = self.ctx.secondary.rename(from, to, args).await;
+        self.ctx
+            .handle(Operation::Rename.into_static(), from, res)?;
+        Ok(rp)
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let rp = self.inner.delete(path, args.clone()).await?;
+
+        let res = self.ctx.secondary.delete(path, args).await;
+        self.ctx
+            .handle(Operation::Delete.into_static(), path, res)?;
+        Ok(rp)
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let rp = self.inner.batch(args.clone()).await?;
+
+        let res = self.ctx.secondary.batch(args).await;
+        if let Some(rp) = self.ctx.handle(Operation::Batch.into_static(), "", res)? {
+            for (path, res) in rp.into_results() {
+                self.ctx
+                    .handle(Operation::Batch.into_static(), &path, res)?;
+            }
+        }
+        Ok(rp)
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let rp = self.inner.blocking_create_dir(path, args.clone())?;
+
+        let res = self.ctx.secondary.blocking_create_dir(path, args);
+        self.ctx
+            .handle(Operation::BlockingCreateDir.into_static(), path, res)?;
+        Ok(rp)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        // Blocking writers can't be aborted, open the secondary writer first
+        // so that primary storage won't be touched if it failed.
+        let res = self.ctx.secondary.blocking_write(path, args.clone());
+        let secondary = self
+            .ctx
+            .handle(Operation::BlockingWrite.into_static(), path, res)?
+            .map(|(_, w)| w);
+
+        let (rp, w) = self.inner.blocking_write(path, args)?;
+        Ok((rp, MirrorWriter::new(w, secondary, path, self.ctx.clone())))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let rp = self.inner.blocking_copy(from, to, args.clone())?;
+
+        let res = self.ctx.secondary.blocking_copy(from, to, args);
+        self.ctx
+            .handle(Operation::BlockingCopy.into_static(), from, res)?;
+        Ok(rp)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let rp = self.inner.blocking_rename(from, to, args.clone())?;
+
+        let res = self.ctx.secondary.blocking_rename(from, to, args);
+        self.ctx
+            .handle(Operation::BlockingRename.into_static(), from, res)?;
+        Ok(rp)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let rp = self.inner.blocking_delete(path, args.clone())?;
+
+        let res = self.ctx.secondary.blocking_delete(path, args);
+        self.ctx
+            .handle(Operation::BlockingDelete.into_static(), path, res)?;
+        Ok(rp)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+/// MirrorWriter will write the same content into both primary and secondary writer.
+///
+/// Bytes accepted by the primary writer will be buffered and flushed into the
+/// secondary writer before next write or close.
+pub struct MirrorWriter<W, S> {
+    inner: W,
+    /// secondary will be `None` if mirror failed in best-effort mode.
+    secondary: Option<S>,
+    buf: Bytes,
+    inner_closed: bool,
+
+    path: String,
+    ctx: Arc<MirrorContext>,
+}
+
+impl<W, S> MirrorWriter<W, S> {
+    fn new(inner: W, secondary: Option<S>, path: &str, ctx: Arc<MirrorContext>) -> Self {
+        Self {
+            inner,
+            secondary,
+            buf: Bytes::new(),
+            inner_closed: false,
+
+            path: path.to_string(),
+            ctx,
+        }
+    }
+
+    fn handle<T>(&mut self, op: WriteOperation, res: Result<T>) -> Result<Option<T>> {
+        let res = self.ctx.handle(op.into_static(), &self.path, res);
+        if !matches!(res, Ok(Some(_))) {
+            // Stop mirroring once secondary writer failed.
+            self.secondary = None;
+            self.buf = Bytes::new();
+        }
+        res
+    }
+}
+
+impl<W: oio::Write, S: oio::Write> MirrorWriter<W, S> {
+    fn poll_flush_secondary(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.buf.is_empty() {
+            let Some(secondary) = self.secondary.as_mut() else {
+                break;
+            };
+
+            let res = ready!(secondary.poll_write(cx, &self.buf));
+            if let Some(n) = self.handle(WriteOperation::Write, res)? {
+                self.buf.advance(n);
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: oio::Write, S: oio::Write> oio::Write for MirrorWriter<W, S> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        ready!(self.poll_flush_secondary(cx))?;
+
+        let n = ready!(self.inner.poll_write(cx, bs))?;
+        if self.secondary.is_some() {
+            self.buf = bs.bytes(n);
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_flush_secondary(cx))?;
+
+        if !self.inner_closed {
+            ready!(self.inner.poll_close(cx))?;
+            self.inner_closed = true;
+        }
+
+        if let Some(secondary) = self.secondary.as_mut() {
+            let res = ready!(secondary.poll_close(cx));
+            self.handle(WriteOperation::Close, res)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if !self.inner_closed {
+            ready!(self.inner.poll_abort(cx))?;
+            self.inner_closed = true;
+        }
+
+        if let Some(secondary) = self.secondary.as_mut() {
+            let res = ready!(secondary.poll_abort(cx));
+            self.handle(WriteOperation::Abort, res)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: oio::BlockingWrite, S: oio::BlockingWrite> oio::BlockingWrite for MirrorWriter<W, S> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let n = self.inner.write(bs)?;
+
+        let mut buf = bs.bytes(n);
+        while !buf.is_empty() {
+            let Some(secondary) = self.secondary.as_mut() else {
+                break;
+            };
+
+            let res = secondary.write(&buf);
+            if let Some(n) = self.handle(WriteOperation::BlockingWrite, res)? {
+                buf.advance(n);
+            }
+        }
+        Ok(n)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inner.close()?;
+
+        if let Some(secondary) = self.secondary.as_mut() {
+            let res = secondary.close();
+            self.handle(WriteOperation::BlockingClose, res)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::services::Fs;
+
+    struct Fixture {
+        op: Operator,
+        secondary: Operator,
+        layer: MirrorLayer,
+        // Keep the temp dirs alive until the test finishes, they will be
+        // removed on drop.
+        _dirs: (TempDir, TempDir),
+    }
+
+    fn new_fs(dir: &TempDir) -> Operator {
+        let mut builder = Fs::default();
+        builder.root(&dir.path().join("data").to_string_lossy());
+        // Writes will be staged in tmp dir so that they can be aborted.
+        builder.atomic_write_dir(&dir.path().join("tmp").to_string_lossy());
+        Operator::new(builder).unwrap().finish()
+    }
+
+    fn new_fixture(mode: MirrorMode) -> Fixture {
+        let dirs = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let secondary = new_fs(&dirs.1);
+        let layer = MirrorLayer::new(secondary.clone()).with_mode(mode);
+        let op = new_fs(&dirs.0).layer(layer.clone());
+        Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs: dirs,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mirror_write() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::Strict);
+
+        let mut w = op.writer("test").await.unwrap();
+        w.write("hello, ").await.unwrap();
+        w.write("world!").await.unwrap();
+        w.close().await.unwrap();
+
+        assert_eq!(op.read("test").await.unwrap(), b"hello, world!");
+        assert_eq!(secondary.read("test").await.unwrap(), b"hello, world!");
+
+        op.copy("test", "copied").await.unwrap();
+        op.rename("copied", "renamed").await.unwrap();
+        assert_eq!(secondary.read("renamed").await.unwrap(), b"hello, world!");
+
+        op.create_dir("dir/").await.unwrap();
+        assert!(secondary.is_exist("dir/").await.unwrap());
+
+        op.delete("test").await.unwrap();
+        assert!(!secondary.is_exist("test").await.unwrap());
+        assert!(layer.divergences().is_empty());
+    }
+
+    #[test]
+    fn test_mirror_blocking_write() {
+        let Fixture {
+            op,
+            secondary,
+            _dirs,
+            ..
+        } = new_fixture(MirrorMode::Strict);
+        let op = op.blocking();
+
+        let mut w = op.writer("test").unwrap();
+        w.write("hello, ").unwrap();
+        w.write("world!").unwrap();
+        w.close().unwrap();
+
+        assert_eq!(secondary.blocking().read("test").unwrap(), b"hello, world!");
+    }
+
+    #[tokio::test]
+    async fn test_mirror_best_effort() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::BestEffort);
+
+        op.write("test", "hello").await.unwrap();
+        // Remove the file from secondary so that rename will fail on it.
+        secondary.delete("test").await.unwrap();
+
+        op.rename("test", "renamed").await.unwrap();
+        assert_eq!(op.read("renamed").await.unwrap(), b"hello");
+
+        let divergences = layer.take_divergences();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].operation(), "rename");
+        assert_eq!(divergences[0].path(), "test");
+        assert_eq!(divergences[0].kind(), ErrorKind::NotFound);
+        assert!(layer.divergences().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mirror_strict() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::Strict);
+
+        op.write("test", "hello").await.unwrap();
+        secondary.delete("test").await.unwrap();
+
+        let err = op.rename("test", "renamed").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(layer.divergences().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mirror_strict_abort_write() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::Strict);
+
+        // Secondary can't open writer since its parent is a file.
+        secondary.write("dir", "").await.unwrap();
+
+        assert!(op.write("dir/test", "hello").await.is_err());
+        assert!(!op.is_exist("dir/test").await.unwrap());
+        // Staged content in primary should have been cleaned up.
+        let tmp = _dirs.0.path().join("tmp");
+        assert_eq!(std::fs::read_dir(tmp).unwrap().count(), 0);
+        assert_eq!(layer.divergences().len(), 1);
+        assert_eq!(layer.divergences()[0].operation(), "write");
+    }
+
+    #[test]
+    fn test_mirror_strict_blocking_write() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::Strict);
+        let op = op.blocking();
+
+        secondary.blocking().write("dir", "").unwrap();
+
+        assert!(op.write("dir/test", "hello").is_err());
+        assert!(!op.is_exist("dir/test").unwrap());
+        let tmp = _dirs.0.path().join("tmp");
+        assert!(!tmp.exists() || std::fs::read_dir(tmp).unwrap().count() == 0);
+        assert_eq!(layer.divergences()[0].operation(), "blocking_write");
+    }
+
+    #[tokio::test]
+    async fn test_mirror_max_divergences() {
+        let Fixture {
+            op,
+            secondary,
+            layer,
+            _dirs,
+        } = new_fixture(MirrorMode::BestEffort);
+        let layer = layer.with_max_divergences(2);
+
+        for path in ["a", "b", "c"] {
+            op.write(path, "hello").await.unwrap();
+            secondary.delete(path).await.unwrap();
+            op.rename(path, &format!("{path}.renamed")).await.unwrap();
+        }
+
+        // Only the latest divergences are kept.
+        let paths: Vec<_> = layer
+            .divergences()
+            .iter()
+            .map(|d| d.path().to_string())
+            .collect();
+        assert_eq!(paths, ["b", "c"]);
+        assert_eq!(layer.dropped_divergences(), 1);
+
+        assert_eq!(layer.take_divergences().len(), 2);
+        assert_eq!(layer.dropped_divergences(), 0);
+    }
+}
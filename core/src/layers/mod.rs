@@ -26,14 +26,51 @@ pub(crate) use error_context::ErrorContextLayer;
 mod complete;
 pub(crate) use complete::CompleteLayer;
 
+mod path_pattern;
+pub(crate) use path_pattern::PathPattern;
+
+mod audit;
+pub use audit::AuditLayer;
+pub use audit::AuditOverflow;
+pub use audit::AuditRecord;
+pub use audit::AuditSink;
+pub use audit::ChannelAuditSink;
+pub use audit::LogAuditSink;
+pub use audit::OperatorAuditSink;
+
 mod concurrent_limit;
 pub use concurrent_limit::ConcurrentLimitLayer;
 
+mod deadline;
+pub use deadline::CancellationToken;
+pub use deadline::DeadlineLayer;
+pub use deadline::OperationContext;
+
 mod immutable_index;
 pub use immutable_index::ImmutableIndexLayer;
 
 mod logging;
 pub use logging::LoggingLayer;
+pub use logging::LoggingRetryInterceptor;
+
+mod mirror;
+pub use mirror::MirrorDivergence;
+pub use mirror::MirrorLayer;
+pub use mirror::MirrorMode;
+
+mod policy;
+pub use policy::PolicyAction;
+pub use policy::PolicyEffect;
+pub use policy::PolicyLayer;
+
+mod record;
+pub use record::RecordLayer;
+
+mod stats;
+pub use stats::LatencySnapshot;
+pub use stats::OperationStats;
+pub use stats::StatsLayer;
+pub use stats::StatsSnapshot;
 
 mod timeout;
 pub use timeout::TimeoutLayer;
@@ -46,11 +83,57 @@ pub use blocking::BlockingLayer;
 #[cfg(feature = "layers-chaos")]
 mod chaos;
 #[cfg(feature = "layers-chaos")]
+pub use chaos::ChaosFault;
+#[cfg(feature = "layers-chaos")]
 pub use chaos::ChaosLayer;
+#[cfg(feature = "layers-chaos")]
+pub use chaos::ChaosOperation;
+#[cfg(feature = "layers-chaos")]
+pub use chaos::ChaosRule;
+
+#[cfg(feature = "layers-compression")]
+mod compression;
+#[cfg(feature = "layers-compression")]
+pub use self::compression::CompressionCodec;
+#[cfg(feature = "layers-compression")]
+pub use self::compression::CompressionLayer;
+
+#[cfg(feature = "layers-dedup")]
+mod dedup;
+#[cfg(feature = "layers-dedup")]
+pub use self::dedup::DedupLayer;
+
+#[cfg(feature = "layers-encryption")]
+mod encryption;
+#[cfg(feature = "layers-encryption")]
+pub use self::encryption::EncryptionAlgorithm;
+#[cfg(feature = "layers-encryption")]
+pub use self::encryption::EncryptionLayer;
+#[cfg(feature = "layers-encryption")]
+pub use self::encryption::KeyProvider;
+#[cfg(feature = "layers-encryption")]
+pub use self::encryption::StaticKeyProvider;
+
+#[cfg(any(
+    feature = "layers-metrics",
+    feature = "layers-prometheus",
+    feature = "layers-prometheus-client",
+    feature = "layers-otel-metrics"
+))]
+mod metrics_config;
+#[cfg(any(
+    feature = "layers-metrics",
+    feature = "layers-prometheus",
+    feature = "layers-prometheus-client",
+    feature = "layers-otel-metrics"
+))]
+pub use self::metrics_config::MetricsConfig;
 
 #[cfg(feature = "layers-metrics")]
 mod metrics;
 #[cfg(feature = "layers-metrics")]
+pub use self::metrics::ConfiguredMetricsLayer;
+#[cfg(feature = "layers-metrics")]
 pub use self::metrics::MetricsLayer;
 
 #[cfg(feature = "layers-prometheus")]
@@ -89,6 +172,11 @@ mod oteltrace;
 #[cfg(feature = "layers-otel-trace")]
 pub use self::oteltrace::OtelTraceLayer;
 
+#[cfg(feature = "layers-otel-metrics")]
+mod otelmetrics;
+#[cfg(feature = "layers-otel-metrics")]
+pub use self::otelmetrics::OtelMetricsLayer;
+
 #[cfg(feature = "layers-throttle")]
 mod throttle;
 #[cfg(feature = "layers-throttle")]
@@ -0,0 +1,744 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::FutureExt;
+use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use opentelemetry::metrics::Histogram;
+use opentelemetry::metrics::Meter;
+use opentelemetry::metrics::Unit;
+use opentelemetry::KeyValue;
+
+use super::metrics_config::LABEL_PATH;
+use crate::layers::MetricsConfig;
+use crate::raw::*;
+use crate::*;
+
+/// The metric name of requests total.
+static METRIC_REQUESTS_TOTAL: &str = "opendal.requests";
+/// The metric name of requests duration seconds.
+static METRIC_REQUESTS_DURATION_SECONDS: &str = "opendal.requests.duration";
+/// The metric name of errors total.
+static METRIC_ERRORS_TOTAL: &str = "opendal.errors";
+/// The metric name of bytes.
+static METRIC_BYTES_TOTAL: &str = "opendal.bytes";
+
+static LABEL_SCHEME: &str = "scheme";
+static LABEL_OPERATION: &str = "operation";
+static LABEL_ERROR: &str = "error";
+
+/// Add [opentelemetry::metrics](https://docs.rs/opentelemetry/latest/opentelemetry/metrics/index.html) for every operations.
+///
+/// # Metrics
+///
+/// This layer shares the same semantics and labels with [`PrometheusLayer`]:
+///
+/// | Metric Name               | Type      | Unit | Description                              | Labels                   |
+/// |---------------------------|-----------|------|------------------------------------------|--------------------------|
+/// | opendal.requests          | Counter   |      | Total times of operation being called    | scheme, operation        |
+/// | opendal.errors            | Counter   |      | Total times of operation failed          | scheme, operation, error |
+/// | opendal.requests.duration | Histogram | s    | Time spent on specific operation         | scheme, operation        |
+/// | opendal.bytes             | Histogram | By   | Size of every read or written chunk      | scheme, operation        |
+///
+/// All metrics will carry an extra `path` label if it's enabled via [`MetricsConfig`].
+///
+/// Histogram buckets are decided by the views of OpenTelemetry SDK, so the buckets in
+/// [`MetricsConfig`] will not take effect for this layer.
+///
+/// [`PrometheusLayer`]: crate::layers::PrometheusLayer
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::MetricsConfig;
+/// use opendal::layers::OtelMetricsLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// // Use the global meter provider.
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(OtelMetricsLayer::default())
+///     .finish();
+///
+/// // Use given meter and label by the first path segment.
+/// let meter = opentelemetry::global::meter("my-app");
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         OtelMetricsLayer::new(meter)
+///             .with_config(MetricsConfig::default().with_path_segments(1)),
+///     )
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OtelMetricsLayer {
+    meter: Meter,
+    config: MetricsConfig,
+}
+
+impl Default for OtelMetricsLayer {
+    fn default() -> Self {
+        Self::new(global::meter("opendal"))
+    }
+}
+
+impl OtelMetricsLayer {
+    /// Create a new OtelMetricsLayer with given meter.
+    pub fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            config: MetricsConfig::default(),
+        }
+    }
+
+    /// Set the [`MetricsConfig`] which controls the path label.
+    pub fn with_config(mut self, config: MetricsConfig) -> Self {
+        self.config = config;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for OtelMetricsLayer {
+    type LayeredAccessor = OtelMetricsAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let scheme = inner.info().scheme();
+
+        OtelMetricsAccessor {
+            inner,
+            metrics: Arc::new(OtelMetrics::new(
+                &self.meter,
+                scheme.into_static(),
+                self.config.clone(),
+            )),
+        }
+    }
+}
+
+/// OtelMetrics holds all instruments created from the meter.
+struct OtelMetrics {
+    scheme: &'static str,
+    config: MetricsConfig,
+
+    requests_total: Counter<u64>,
+    errors_total: Counter<u64>,
+    requests_duration_seconds: Histogram<f64>,
+    bytes_total: Histogram<u64>,
+}
+
+impl OtelMetrics {
+    fn new(meter: &Meter, scheme: &'static str, config: MetricsConfig) -> Self {
+        let requests_total = meter
+            .u64_counter(METRIC_REQUESTS_TOTAL)
+            .with_description("Total times of operation being called")
+            .init();
+        let errors_total = meter
+            .u64_counter(METRIC_ERRORS_TOTAL)
+            .with_description("Total times of operation failed")
+            .init();
+        let requests_duration_seconds = meter
+            .f64_histogram(METRIC_REQUESTS_DURATION_SECONDS)
+            .with_description("Histogram of the time spent on specific operation")
+            .with_unit(Unit::new("s"))
+            .init();
+        let bytes_total = meter
+            .u64_histogram(METRIC_BYTES_TOTAL)
+            .with_description("Histogram of the size of read or written chunks")
+            .with_unit(Unit::new("By"))
+            .init();
+
+        Self {
+            scheme,
+            config,
+            requests_total,
+            errors_total,
+            requests_duration_seconds,
+            bytes_total,
+        }
+    }
+
+    fn labels(&self, op: Operation, path: &str) -> Vec<KeyValue> {
+        let mut labels = vec![
+            KeyValue::new(LABEL_SCHEME, self.scheme),
+            KeyValue::new(LABEL_OPERATION, op.into_static()),
+        ];
+        if let Some(path) = self.config.path_label(path) {
+            labels.push(KeyValue::new(LABEL_PATH, path.to_string()));
+        }
+        labels
+    }
+
+    fn increment_requests_total(&self, op: Operation, path: &str) {
+        self.requests_total.add(1, &self.labels(op, path));
+    }
+
+    fn observe_bytes_total(&self, op: Operation, path: &str, bytes: usize) {
+        self.bytes_total
+            .record(bytes as u64, &self.labels(op, path));
+    }
+
+    /// error handling is the cold path, so we will build error labels
+    /// only when needed.
+    #[inline]
+    fn increment_errors_total(&self, op: Operation, path: &str, kind: ErrorKind) {
+        let mut labels = self.labels(op, path);
+        labels.push(KeyValue::new(LABEL_ERROR, kind.into_static()));
+        self.errors_total.add(1, &labels);
+    }
+
+    /// Record the duration of request, and the error if failed.
+    fn observe<T>(&self, op: Operation, path: &str, start: Instant, res: &Result<T>) {
+        self.requests_duration_seconds
+            .record(start.elapsed().as_secs_f64(), &self.labels(op, path));
+        if let Err(err) = res {
+            self.increment_errors_total(op, path, err.kind());
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OtelMetricsAccessor<A: Accessor> {
+    inner: A,
+    metrics: Arc<OtelMetrics>,
+}
+
+impl<A: Accessor> Debug for OtelMetricsAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelMetricsAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for OtelMetricsAccessor<A> {
+    type Inner = A;
+    type Reader = OtelMetricsWrapper<A::Reader>;
+    type BlockingReader = OtelMetricsWrapper<A::BlockingReader>;
+    type Writer = OtelMetricsWrapper<A::Writer>;
+    type BlockingWriter = OtelMetricsWrapper<A::BlockingWriter>;
+    type Lister = A::Lister;
+    type BlockingLister = A::BlockingLister;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.metrics
+            .increment_requests_total(Operation::CreateDir, path);
+
+        let start = Instant::now();
+        let result = self.inner.create_dir(path, args).await;
+        self.metrics
+            .observe(Operation::CreateDir, path, start, &result);
+        result
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.metrics.increment_requests_total(Operation::Read, path);
+
+        let start = Instant::now();
+        let result = self
+            .inner
+            .read(path, args)
+            .map(|v| {
+                v.map(|(rp, r)| {
+                    (
+                        rp,
+                        OtelMetricsWrapper::new(r, Operation::Read, path, self.metrics.clone()),
+                    )
+                })
+            })
+            .await;
+        self.metrics.observe(Operation::Read, path, start, &result);
+        result
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.metrics
+            .increment_requests_total(Operation::Write, path);
+
+        let start = Instant::now();
+        let result = self
+            .inner
+            .write(path, args)
+            .map(|v| {
+                v.map(|(rp, w)| {
+                    (
+                        rp,
+                        OtelMetricsWrapper::new(w, Operation::Write, path, self.metrics.clone()),
+                    )
+                })
+            })
+            .await;
+        self.metrics.observe(Operation::Write, path, start, &result);
+        result
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.metrics.increment_requests_total(Operation::Stat, path);
+
+        let start = Instant::now();
+        let result = self.inner.stat(path, args).await;
+        self.metrics.observe(Operation::Stat, path, start, &result);
+        result
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.metrics
+            .increment_requests_total(Operation::Delete, path);
+
+        let start = Instant::now();
+        let result = self.inner.delete(path, args).await;
+        self.metrics
+            .observe(Operation::Delete, path, start, &result);
+        result
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.metrics.increment_requests_total(Operation::List, path);
+
+        let start = Instant::now();
+        let result = self.inner.list(path, args).await;
+        self.metrics.observe(Operation::List, path, start, &result);
+        result
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.metrics.increment_requests_total(Operation::Batch, "");
+
+        let start = Instant::now();
+        let result = self.inner.batch(args).await;
+        self.metrics.observe(Operation::Batch, "", start, &result);
+        result
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.metrics
+            .increment_requests_total(Operation::Presign, path);
+
+        let start = Instant::now();
+        let result = self.inner.presign(path, args).await;
+        self.metrics
+            .observe(Operation::Presign, path, start, &result);
+        result
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingCreateDir, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_create_dir(path, args);
+        self.metrics
+            .observe(Operation::BlockingCreateDir, path, start, &result);
+        result
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingRead, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_read(path, args).map(|(rp, r)| {
+            (
+                rp,
+                OtelMetricsWrapper::new(r, Operation::BlockingRead, path, self.metrics.clone()),
+            )
+        });
+        self.metrics
+            .observe(Operation::BlockingRead, path, start, &result);
+        result
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingWrite, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_write(path, args).map(|(rp, w)| {
+            (
+                rp,
+                OtelMetricsWrapper::new(w, Operation::BlockingWrite, path, self.metrics.clone()),
+            )
+        });
+        self.metrics
+            .observe(Operation::BlockingWrite, path, start, &result);
+        result
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingStat, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_stat(path, args);
+        self.metrics
+            .observe(Operation::BlockingStat, path, start, &result);
+        result
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingDelete, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_delete(path, args);
+        self.metrics
+            .observe(Operation::BlockingDelete, path, start, &result);
+        result
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.metrics
+            .increment_requests_total(Operation::BlockingList, path);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_list(path, args);
+        self.metrics
+            .observe(Operation::BlockingList, path, start, &result);
+        result
+    }
+}
+
+pub struct OtelMetricsWrapper<R> {
+    inner: R,
+
+    op: Operation,
+    path: String,
+    metrics: Arc<OtelMetrics>,
+}
+
+impl<R> OtelMetricsWrapper<R> {
+    fn new(inner: R, op: Operation, path: &str, metrics: Arc<OtelMetrics>) -> Self {
+        Self {
+            inner,
+            op,
+            path: path.to_string(),
+            metrics,
+        }
+    }
+}
+
+impl<R: oio::Read> oio::Read for OtelMetricsWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.inner.poll_read(cx, buf).map(|res| match res {
+            Ok(n) => {
+                self.metrics.observe_bytes_total(self.op, &self.path, n);
+                Ok(n)
+            }
+            Err(e) => {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, e.kind());
+                Err(e)
+            }
+        })
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos).map(|res| match res {
+            Ok(n) => Ok(n),
+            Err(e) => {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, e.kind());
+                Err(e)
+            }
+        })
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        self.inner.poll_next(cx).map(|res| match res {
+            Some(Ok(bytes)) => {
+                self.metrics
+                    .observe_bytes_total(self.op, &self.path, bytes.len());
+                Some(Ok(bytes))
+            }
+            Some(Err(e)) => {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, e.kind());
+                Some(Err(e))
+            }
+            None => None,
+        })
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for OtelMetricsWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner
+            .read(buf)
+            .map(|n| {
+                self.metrics.observe_bytes_total(self.op, &self.path, n);
+                n
+            })
+            .map_err(|e| {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, e.kind());
+                e
+            })
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.inner.seek(pos).map_err(|err| {
+            self.metrics
+                .increment_errors_total(self.op, &self.path, err.kind());
+            err
+        })
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        self.inner.next().map(|res| match res {
+            Ok(bytes) => {
+                self.metrics
+                    .observe_bytes_total(self.op, &self.path, bytes.len());
+                Ok(bytes)
+            }
+            Err(e) => {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, e.kind());
+                Err(e)
+            }
+        })
+    }
+}
+
+impl<R: oio::Write> oio::Write for OtelMetricsWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner
+            .poll_write(cx, bs)
+            .map_ok(|n| {
+                self.metrics.observe_bytes_total(self.op, &self.path, n);
+                n
+            })
+            .map_err(|err| {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, err.kind());
+                err
+            })
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx).map_err(|err| {
+            self.metrics
+                .increment_errors_total(self.op, &self.path, err.kind());
+            err
+        })
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_close(cx).map_err(|err| {
+            self.metrics
+                .increment_errors_total(self.op, &self.path, err.kind());
+            err
+        })
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for OtelMetricsWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.inner
+            .write(bs)
+            .map(|n| {
+                self.metrics.observe_bytes_total(self.op, &self.path, n);
+                n
+            })
+            .map_err(|err| {
+                self.metrics
+                    .increment_errors_total(self.op, &self.path, err.kind());
+                err
+            })
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inner.close().map_err(|err| {
+            self.metrics
+                .increment_errors_total(self.op, &self.path, err.kind());
+            err
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::borrow::Cow;
+    use std::sync::Mutex;
+
+    use opentelemetry::metrics::CallbackRegistration;
+    use opentelemetry::metrics::InstrumentProvider;
+    use opentelemetry::metrics::MetricsError;
+    use opentelemetry::metrics::Observer;
+    use opentelemetry::metrics::SyncCounter;
+    use opentelemetry::metrics::SyncHistogram;
+
+    use super::*;
+    use crate::services::Memory;
+
+    type Records = Arc<Mutex<Vec<(String, f64, Vec<KeyValue>)>>>;
+
+    /// A provider that records every measurement in memory.
+    #[derive(Default)]
+    struct MockProvider {
+        records: Records,
+    }
+
+    struct MockInstrument {
+        name: String,
+        records: Records,
+    }
+
+    impl SyncCounter<u64> for MockInstrument {
+        fn add(&self, value: u64, attributes: &[KeyValue]) {
+            self.records.lock().unwrap().push((
+                self.name.clone(),
+                value as f64,
+                attributes.to_vec(),
+            ));
+        }
+    }
+
+    impl SyncHistogram<f64> for MockInstrument {
+        fn record(&self, value: f64, attributes: &[KeyValue]) {
+            self.records
+                .lock()
+                .unwrap()
+                .push((self.name.clone(), value, attributes.to_vec()));
+        }
+    }
+
+    impl SyncHistogram<u64> for MockInstrument {
+        fn record(&self, value: u64, attributes: &[KeyValue]) {
+            self.records.lock().unwrap().push((
+                self.name.clone(),
+                value as f64,
+                attributes.to_vec(),
+            ));
+        }
+    }
+
+    impl MockProvider {
+        fn instrument(&self, name: Cow<'static, str>) -> Arc<MockInstrument> {
+            Arc::new(MockInstrument {
+                name: name.to_string(),
+                records: self.records.clone(),
+            })
+        }
+    }
+
+    impl InstrumentProvider for MockProvider {
+        fn u64_counter(
+            &self,
+            name: Cow<'static, str>,
+            _: Option<Cow<'static, str>>,
+            _: Option<Unit>,
+        ) -> opentelemetry::metrics::Result<Counter<u64>> {
+            Ok(Counter::new(self.instrument(name)))
+        }
+
+        fn f64_histogram(
+            &self,
+            name: Cow<'static, str>,
+            _: Option<Cow<'static, str>>,
+            _: Option<Unit>,
+        ) -> opentelemetry::metrics::Result<Histogram<f64>> {
+            Ok(Histogram::new(self.instrument(name)))
+        }
+
+        fn u64_histogram(
+            &self,
+            name: Cow<'static, str>,
+            _: Option<Cow<'static, str>>,
+            _: Option<Unit>,
+        ) -> opentelemetry::metrics::Result<Histogram<u64>> {
+            Ok(Histogram::new(self.instrument(name)))
+        }
+
+        fn register_callback(
+            &self,
+            _: &[Arc<dyn Any>],
+            _: Box<dyn Fn(&dyn Observer) + Send + Sync>,
+        ) -> opentelemetry::metrics::Result<Box<dyn CallbackRegistration>> {
+            Err(MetricsError::Other("callback is not supported".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_otel_metrics() {
+        let provider = MockProvider::default();
+        let records = provider.records.clone();
+        let meter = Meter::new(Arc::new(provider));
+
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(
+                OtelMetricsLayer::new(meter)
+                    .with_config(MetricsConfig::default().with_path_segments(1)),
+            )
+            .finish();
+
+        op.write("dataset/a/b", "Hello, World!").await.unwrap();
+        op.stat("other/not_exist").await.unwrap_err();
+
+        let records = records.lock().unwrap();
+        let has = |name: &str, kvs: &[(&str, &str)]| {
+            records.iter().any(|(n, _, attrs)| {
+                n == name
+                    && kvs.iter().all(|(k, v)| {
+                        attrs
+                            .iter()
+                            .any(|kv| kv.key.as_str() == *k && kv.value.as_str() == *v)
+                    })
+            })
+        };
+
+        assert!(has(
+            METRIC_REQUESTS_TOTAL,
+            &[
+                ("scheme", "memory"),
+                ("operation", "write"),
+                ("path", "dataset")
+            ]
+        ));
+        assert!(has(
+            METRIC_BYTES_TOTAL,
+            &[("operation", "write"), ("path", "dataset")]
+        ));
+        assert!(has(
+            METRIC_ERRORS_TOTAL,
+            &[
+                ("operation", "stat"),
+                ("path", "other"),
+                ("error", "NotFound")
+            ]
+        ));
+        assert!(!has(METRIC_ERRORS_TOTAL, &[("operation", "write")]));
+    }
+}
@@ -0,0 +1,138 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+/// PathPattern matches paths by prefix or glob.
+///
+/// Patterns contain `*` or `?` are globs, others are prefixes which only match
+/// on whole path segments.
+#[derive(Debug, Clone)]
+pub(crate) enum PathPattern {
+    Prefix(String),
+    Glob(String),
+}
+
+impl PathPattern {
+    pub(crate) fn new(pattern: &str) -> Self {
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.contains(['*', '?']) {
+            PathPattern::Glob(pattern.to_string())
+        } else {
+            PathPattern::Prefix(pattern.to_string())
+        }
+    }
+
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        match self {
+            PathPattern::Prefix(prefix) => match path.strip_prefix(prefix.as_str()) {
+                // `tenant-a` should not match `tenant-ab/x`.
+                Some(rest) => {
+                    prefix.is_empty()
+                        || prefix.ends_with('/')
+                        || rest.is_empty()
+                        || rest.starts_with('/')
+                }
+                None => false,
+            },
+            PathPattern::Glob(glob) => glob_match(glob.as_bytes(), path.as_bytes()),
+        }
+    }
+}
+
+/// Match path with glob pattern.
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` could also match nothing, so that `a/**/b` matches `a/b`.
+            if let [b'/', after @ ..] = rest {
+                if glob_match(after, path) {
+                    return true;
+                }
+            }
+            (0..=path.len()).any(|i| glob_match(rest, &path[i..]))
+        }
+        [b'*', rest @ ..] => {
+            for i in 0..=path.len() {
+                if glob_match(rest, &path[i..]) {
+                    return true;
+                }
+                if path.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        [b'?', rest @ ..] => match path {
+            [c, tail @ ..] if *c != b'/' => glob_match(rest, tail),
+            _ => false,
+        },
+        [p, rest @ ..] => match path {
+            [c, tail @ ..] if c == p => glob_match(rest, tail),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases = [
+            ("*.json", "a.json", true),
+            ("*.json", "dir/a.json", false),
+            ("**/*.json", "dir/a.json", true),
+            ("**/*.json", "a.json", true),
+            ("logs/**", "logs/2024/01/a.log", true),
+            ("logs/**", "other/a.log", false),
+            ("a/**/b", "a/b", true),
+            ("a/**/b", "a/x/y/b", true),
+            ("file-?.txt", "file-1.txt", true),
+            ("file-?.txt", "file-10.txt", false),
+        ];
+
+        for (pattern, path, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), path.as_bytes()),
+                expected,
+                "{pattern} {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_pattern() {
+        let cases = [
+            ("tenant-a", "tenant-a", true),
+            ("tenant-a", "tenant-a/x", true),
+            ("tenant-a", "tenant-ab/x", false),
+            ("tenant-a/", "tenant-a/x", true),
+            ("tenant-a/", "tenant-ab/x", false),
+            ("", "any/x", true),
+            ("*.json", "a.json", true),
+        ];
+
+        for (pattern, path, expected) in cases {
+            assert_eq!(
+                PathPattern::new(pattern).matches(path),
+                expected,
+                "{pattern} {path}"
+            );
+        }
+    }
+}
@@ -0,0 +1,530 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use flagset::flags;
+use flagset::FlagSet;
+
+use crate::layers::PathPattern;
+use crate::raw::*;
+use crate::*;
+
+flags! {
+    /// PolicyAction is the kind of access checked by [`PolicyLayer`].
+    pub enum PolicyAction: u8 {
+        /// Read content or metadata, used by `read` and `stat`.
+        Read,
+        /// Write content, used by `write` and `create_dir`.
+        Write,
+        /// Delete content, used by `delete` and `batch`.
+        Delete,
+        /// List entries, used by `list`.
+        List,
+        /// Presign requests, used by `presign`.
+        ///
+        /// The presigned operation will be checked too.
+        Presign,
+    }
+}
+
+/// PolicyEffect is the result of a matched rule in [`PolicyLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PolicyEffect {
+    /// Allow the access.
+    #[default]
+    Allow,
+    /// Deny the access.
+    Deny,
+}
+
+/// Add per-path access control for underlying storage services.
+///
+/// `PolicyLayer` checks every call against a list of rules. Each rule is made of
+/// a pattern, a set of [`PolicyAction`] and a [`PolicyEffect`]:
+///
+/// - Patterns contain `*`, `?` are globs: `*` and `?` match any characters
+///   except `/`, `**` matches any characters including `/`.
+/// - Other patterns are prefixes matched on whole path segments, for example,
+///   both `tenant-a` and `tenant-a/` match everything under `tenant-a/` but not
+///   `tenant-ab/`, and the empty pattern matches everything.
+///
+/// A deny rule always wins over allow rules. If no rule matches, the default
+/// effect will be used, which is [`PolicyEffect::Allow`] by default.
+///
+/// # Notes
+///
+/// - Denied calls will return [`ErrorKind::PermissionDenied`].
+/// - Paths contain `..` segments are always denied, since they are not resolved
+///   before matching.
+/// - `copy` requires `Read` on source and `Write` on target, `rename` also
+///   requires `Delete` on source.
+/// - `list` requires `List` on the listed path, and only entries that are
+///   allowed to `List` will be returned.
+/// - [`PolicyLayer::read_only`] denies all `Write` and `Delete`, and downgrades
+///   the capability of operator so that users can check it in advance.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::PolicyAction;
+/// use opendal::layers::PolicyEffect;
+/// use opendal::layers::PolicyLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         PolicyLayer::new()
+///             .with_default(PolicyEffect::Deny)
+///             .allow("tenant-a/", PolicyAction::Read | PolicyAction::List)
+///             .allow("tenant-a/tmp/**", PolicyAction::Write | PolicyAction::Delete)
+///             .deny("**/*.secret", PolicyAction::Read),
+///     )
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PolicyLayer {
+    rules: Vec<PolicyRule>,
+    default: PolicyEffect,
+    read_only: bool,
+}
+
+impl PolicyLayer {
+    /// Create a new `PolicyLayer` which allows everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the effect used when no rule matches.
+    pub fn with_default(mut self, effect: PolicyEffect) -> Self {
+        self.default = effect;
+        self
+    }
+
+    /// Allow given actions on paths matching the pattern.
+    pub fn allow(mut self, pattern: &str, actions: impl Into<FlagSet<PolicyAction>>) -> Self {
+        self.rules.push(PolicyRule::new(
+            PolicyEffect::Allow,
+            pattern,
+            actions.into(),
+        ));
+        self
+    }
+
+    /// Deny given actions on paths matching the pattern.
+    pub fn deny(mut self, pattern: &str, actions: impl Into<FlagSet<PolicyAction>>) -> Self {
+        self.rules
+            .push(PolicyRule::new(PolicyEffect::Deny, pattern, actions.into()));
+        self
+    }
+
+    /// Make the operator read-only.
+    ///
+    /// All `Write` and `Delete` will be denied, and the capabilities for them
+    /// will be disabled.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Check whether the action on path is allowed.
+    ///
+    /// Paths contain `..` segments are never allowed.
+    pub fn is_allowed(&self, path: &str, action: PolicyAction) -> bool {
+        if has_parent_segment(path) {
+            return false;
+        }
+        if self.read_only && matches!(action, PolicyAction::Write | PolicyAction::Delete) {
+            return false;
+        }
+
+        let mut effect = None;
+        for rule in &self.rules {
+            if !rule.actions.contains(action) || !rule.pattern.matches(path) {
+                continue;
+            }
+            match rule.effect {
+                PolicyEffect::Deny => return false,
+                PolicyEffect::Allow => effect = Some(PolicyEffect::Allow),
+            }
+        }
+        effect.unwrap_or(self.default) == PolicyEffect::Allow
+    }
+}
+
+/// Check whether path contains `..` segments.
+fn has_parent_segment(path: &str) -> bool {
+    path.split('/').any(|v| v == "..")
+}
+
+impl<A: Accessor> Layer<A> for PolicyLayer {
+    type LayeredAccessor = PolicyAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        PolicyAccessor {
+            inner,
+            policy: Arc::new(self.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PolicyRule {
+    effect: PolicyEffect,
+    pattern: PathPattern,
+    actions: FlagSet<PolicyAction>,
+}
+
+impl PolicyRule {
+    fn new(effect: PolicyEffect, pattern: &str, actions: FlagSet<PolicyAction>) -> Self {
+        Self {
+            effect,
+            pattern: PathPattern::new(pattern),
+            actions,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PolicyAccessor<A: Accessor> {
+    inner: A,
+    policy: Arc<PolicyLayer>,
+}
+
+impl<A: Accessor> PolicyAccessor<A> {
+    fn check(&self, op: Operation, path: &str, action: PolicyAction) -> Result<()> {
+        if has_parent_segment(path) {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "path contains `..` is denied by policy",
+            )
+            .with_operation(op)
+            .with_context("path", path));
+        }
+        if self.policy.is_allowed(path, action) {
+            return Ok(());
+        }
+
+        Err(
+            Error::new(ErrorKind::PermissionDenied, "access is denied by policy")
+                .with_operation(op)
+                .with_context("path", path)
+                .with_context("action", format!("{action:?}")),
+        )
+    }
+
+    fn check_presign(&self, path: &str, args: &OpPresign) -> Result<()> {
+        self.check(Operation::Presign, path, PolicyAction::Presign)?;
+        let action = match args.operation() {
+            PresignOperation::Stat(_) | PresignOperation::Read(_) => PolicyAction::Read,
+            PresignOperation::Write(_) => PolicyAction::Write,
+        };
+        self.check(Operation::Presign, path, action)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for PolicyAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Lister = PolicyLister<A::Lister>;
+    type BlockingLister = PolicyLister<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.inner.info();
+
+        if self.policy.read_only {
+            let cap = meta.full_capability_mut();
+            cap.write = false;
+            cap.write_can_multi = false;
+            cap.write_can_empty = false;
+            cap.write_can_append = false;
+            cap.write_with_content_type = false;
+            cap.write_with_content_disposition = false;
+            cap.write_with_cache_control = false;
+            cap.create_dir = false;
+            cap.delete = false;
+            cap.copy = false;
+            cap.rename = false;
+            cap.presign_write = false;
+            cap.batch = false;
+            cap.batch_delete = false;
+        }
+
+        meta
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.check(Operation::CreateDir, path, PolicyAction::Write)?;
+        self.inner.create_dir(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.check(Operation::Read, path, PolicyAction::Read)?;
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.check(Operation::Write, path, PolicyAction::Write)?;
+        self.inner.write(path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.check(Operation::Copy, from, PolicyAction::Read)?;
+        self.check(Operation::Copy, to, PolicyAction::Write)?;
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.check(Operation::Rename, from, PolicyAction::Read)?;
+        self.check(Operation::Rename, from, PolicyAction::Delete)?;
+        self.check(Operation::Rename, to, PolicyAction::Write)?;
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.check(Operation::Stat, path, PolicyAction::Read)?;
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.check(Operation::Delete, path, PolicyAction::Delete)?;
+        self.inner.delete(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.check(Operation::List, path, PolicyAction::List)?;
+        let (rp, l) = self.inner.list(path, args).await?;
+        Ok((rp, PolicyLister::new(l, self.policy.clone())))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let mut allowed = Vec::new();
+        let mut results = Vec::new();
+        for (path, op) in args.into_operation() {
+            let action = match &op {
+                BatchOperation::Delete(_) => PolicyAction::Delete,
+            };
+            match self.check(Operation::Batch, &path, action) {
+                Ok(()) => allowed.push((path, op)),
+                Err(err) => results.push((path, Err(err))),
+            }
+        }
+
+        if !allowed.is_empty() {
+            let rp = self.inner.batch(OpBatch::new(allowed)).await?;
+            results.extend(rp.into_results());
+        }
+        Ok(RpBatch::new(results))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.check_presign(path, &args)?;
+        self.inner.presign(path, args).await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.check(Operation::BlockingCreateDir, path, PolicyAction::Write)?;
+        self.inner.blocking_create_dir(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.check(Operation::BlockingRead, path, PolicyAction::Read)?;
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.check(Operation::BlockingWrite, path, PolicyAction::Write)?;
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.check(Operation::BlockingCopy, from, PolicyAction::Read)?;
+        self.check(Operation::BlockingCopy, to, PolicyAction::Write)?;
+        self.inner.blocking_copy(from, to, args)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.check(Operation::BlockingRename, from, PolicyAction::Read)?;
+        self.check(Operation::BlockingRename, from, PolicyAction::Delete)?;
+        self.check(Operation::BlockingRename, to, PolicyAction::Write)?;
+        self.inner.blocking_rename(from, to, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.check(Operation::BlockingStat, path, PolicyAction::Read)?;
+        self.inner.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.check(Operation::BlockingDelete, path, PolicyAction::Delete)?;
+        self.inner.blocking_delete(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.check(Operation::BlockingList, path, PolicyAction::List)?;
+        let (rp, l) = self.inner.blocking_list(path, args)?;
+        Ok((rp, PolicyLister::new(l, self.policy.clone())))
+    }
+}
+
+/// PolicyLister filters out entries that are not allowed to list.
+pub struct PolicyLister<L> {
+    inner: L,
+    policy: Arc<PolicyLayer>,
+}
+
+impl<L> PolicyLister<L> {
+    fn new(inner: L, policy: Arc<PolicyLayer>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<L: oio::List> oio::List for PolicyLister<L> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        loop {
+            match ready!(self.inner.poll_next(cx))? {
+                Some(entry) if !self.policy.is_allowed(entry.path(), PolicyAction::List) => {}
+                entry => return Poll::Ready(Ok(entry)),
+            }
+        }
+    }
+}
+
+impl<L: oio::BlockingList> oio::BlockingList for PolicyLister<L> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        loop {
+            match self.inner.next()? {
+                Some(entry) if !self.policy.is_allowed(entry.path(), PolicyAction::List) => {}
+                entry => return Ok(entry),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_policy_parent_segment() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        inner.write("tenant-b/y", "y").await.unwrap();
+
+        let op = inner.clone().layer(
+            PolicyLayer::new()
+                .with_default(PolicyEffect::Deny)
+                .allow("tenant-a", PolicyAction::Read | PolicyAction::Write),
+        );
+
+        let err = op.read("tenant-a/../tenant-b/y").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = op
+            .copy("tenant-a/x", "tenant-a/../tenant-b/z")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        let layer = PolicyLayer::new();
+        assert!(!layer.is_allowed("tenant-a/..", PolicyAction::Read));
+        let acc = layer.layer(Memory::default().build().unwrap());
+        let rp = LayeredAccessor::batch(
+            &acc,
+            OpBatch::new(vec![(
+                "tenant-a/../tenant-b/y".to_string(),
+                OpDelete::new().into(),
+            )]),
+        )
+        .await
+        .unwrap();
+        let (_, res) = &rp.results()[0];
+        assert!(
+            matches!(res, Err(err) if err.kind() == ErrorKind::PermissionDenied),
+            "batch path with `..` must be denied"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_policy() {
+        let inner = Operator::new(Memory::default()).unwrap().finish();
+        inner.write("tenant-a/x", "x").await.unwrap();
+        inner.write("tenant-a/key.secret", "secret").await.unwrap();
+        inner.write("tenant-b/y", "y").await.unwrap();
+
+        let op = inner.clone().layer(
+            PolicyLayer::new()
+                .with_default(PolicyEffect::Deny)
+                .allow("tenant-a/", PolicyAction::Read | PolicyAction::List)
+                .allow(
+                    "tenant-a/tmp/**",
+                    PolicyAction::Write | PolicyAction::Delete,
+                )
+                .deny("**/*.secret", PolicyAction::Read | PolicyAction::List),
+        );
+
+        assert_eq!(op.read("tenant-a/x").await.unwrap(), b"x");
+        let err = op.read("tenant-b/y").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = op.read("tenant-a/key.secret").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        let err = op.write("tenant-a/x", "new").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        op.write("tenant-a/tmp/z", "z").await.unwrap();
+        op.delete("tenant-a/tmp/z").await.unwrap();
+
+        let entries = op.list("tenant-a/").await.unwrap();
+        let paths: Vec<_> = entries.iter().map(|v| v.path()).collect();
+        assert_eq!(paths, vec!["tenant-a/x"]);
+        let err = op.list("").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_policy_read_only() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(PolicyLayer::new().read_only())
+            .finish();
+
+        let cap = op.info().full_capability();
+        assert!(cap.read);
+        assert!(!cap.write);
+        assert!(!cap.delete);
+
+        let err = op.blocking().write("a", "a").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = op.blocking().delete("a").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
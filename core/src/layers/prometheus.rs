@@ -25,17 +25,17 @@ use std::task::Poll;
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::FutureExt;
-use futures::TryFutureExt;
 use log::debug;
 use prometheus::core::AtomicU64;
 use prometheus::core::GenericCounterVec;
-use prometheus::exponential_buckets;
 use prometheus::histogram_opts;
 use prometheus::register_histogram_vec_with_registry;
 use prometheus::register_int_counter_vec_with_registry;
 use prometheus::HistogramVec;
 use prometheus::Registry;
 
+use super::metrics_config::LABEL_PATH;
+use crate::layers::MetricsConfig;
 use crate::raw::Accessor;
 use crate::raw::*;
 use crate::*;
@@ -50,14 +50,19 @@ use crate::*;
 /// | Metric Name             | Type     | Description                                       | Labels              |
 /// |-------------------------|----------|---------------------------------------------------|---------------------|
 /// | requests_total          | Counter  | Total times of 'create' operation being called   | scheme, operation   |
+/// | errors_total            | Counter  | Total times of operation failed                   | scheme, operation, error |
 /// | requests_duration_seconds | Histogram | Histogram of the time spent on specific operation | scheme, operation   |
 /// | bytes_total             | Histogram | Total size                                        | scheme, operation   |
 ///
+/// All metrics will carry an extra `path` label if it's enabled via [`MetricsConfig`].
+///
 /// For a more detailed explanation of these metrics and how they are used, please refer to the [Prometheus documentation](https://prometheus.io/docs/introduction/overview/).
 ///
 /// # Histogram Configuration
 ///
-/// The metric buckets for these histograms are automatically generated based on the `exponential_buckets(0.01, 2.0, 16)` configuration.
+/// The metric buckets for these histograms can be configured via [`MetricsConfig`]. By default,
+/// `requests_duration_seconds` uses `exponential_buckets(0.01, 2.0, 16)` and `bytes_total` uses
+/// `exponential_buckets(1.0, 2.0, 16)`.
 ///
 /// # Examples
 ///
@@ -106,9 +111,7 @@ use crate::*;
 #[derive(Default, Debug, Clone)]
 pub struct PrometheusLayer {
     registry: Registry,
-    requests_duration_seconds_buckets: Vec<f64>,
-    bytes_total_buckets: Vec<f64>,
-    path_label_level: usize,
+    config: MetricsConfig,
 }
 
 impl PrometheusLayer {
@@ -116,25 +119,25 @@ impl PrometheusLayer {
     pub fn with_registry(registry: Registry) -> Self {
         Self {
             registry,
-            requests_duration_seconds_buckets: exponential_buckets(0.01, 2.0, 16).unwrap(),
-            bytes_total_buckets: exponential_buckets(0.01, 2.0, 16).unwrap(),
-            path_label_level: 0,
+            config: MetricsConfig::default(),
         }
     }
 
+    /// set the [`MetricsConfig`] which controls path label and histogram buckets.
+    pub fn with_config(mut self, config: MetricsConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// set buckets for requests_duration_seconds
     pub fn requests_duration_seconds_buckets(mut self, buckets: Vec<f64>) -> Self {
-        if !buckets.is_empty() {
-            self.requests_duration_seconds_buckets = buckets;
-        }
+        self.config = self.config.with_duration_buckets(buckets);
         self
     }
 
     /// set buckets for bytes_total
     pub fn bytes_total_buckets(mut self, buckets: Vec<f64>) -> Self {
-        if !buckets.is_empty() {
-            self.bytes_total_buckets = buckets;
-        }
+        self.config = self.config.with_bytes_buckets(buckets);
         self
     }
 
@@ -142,7 +145,7 @@ impl PrometheusLayer {
     /// 0: no path label, the path label will be the ""
     /// >0: the path label will be the path split by "/" and get the last n level, like "/abc/def/ghi", if n=1, the path label will be "/abc"
     pub fn enable_path_label(mut self, level: usize) -> Self {
-        self.path_label_level = level;
+        self.config = self.config.with_path_segments(level);
         self
     }
 }
@@ -156,11 +159,9 @@ impl<A: Accessor> Layer<A> for PrometheusLayer {
 
         PrometheusAccessor {
             inner,
-            stats: Arc::new(PrometheusMetrics::new(
+            stats: Arc::new(PrometheusMetrics::with_config(
                 self.registry.clone(),
-                self.requests_duration_seconds_buckets.clone(),
-                self.bytes_total_buckets.clone(),
-                self.path_label_level,
+                self.config.clone(),
             )),
             scheme,
         }
@@ -172,27 +173,43 @@ impl<A: Accessor> Layer<A> for PrometheusLayer {
 pub struct PrometheusMetrics {
     /// Total times of the specific operation be called.
     pub requests_total: GenericCounterVec<AtomicU64>,
+    /// Total times of the specific operation failed.
+    pub errors_total: GenericCounterVec<AtomicU64>,
     /// Latency of the specific operation be called.
     pub requests_duration_seconds: HistogramVec,
     /// Size of the specific metrics.
     pub bytes_total: HistogramVec,
-    /// The Path Level we will keep in the path label.
+    /// The Path Level we will keep in the path label, `0` if the path label is
+    /// disabled or extracted by regex.
     pub path_label_level: usize,
+    /// The config to extract path label.
+    config: MetricsConfig,
 }
 
 impl PrometheusMetrics {
     /// new with prometheus register.
+    ///
+    /// Kept for compatibility, [`PrometheusLayer`] uses [`PrometheusMetrics::with_config`].
+    #[allow(dead_code)]
     pub fn new(
         registry: Registry,
         requests_duration_seconds_buckets: Vec<f64>,
         bytes_total_buckets: Vec<f64>,
         path_label_level: usize,
     ) -> Self {
-        let labels = if path_label_level > 0 {
-            vec!["scheme", "operation", "path"]
-        } else {
-            vec!["scheme", "operation"]
-        };
+        let config = MetricsConfig::default()
+            .with_duration_buckets(requests_duration_seconds_buckets)
+            .with_bytes_buckets(bytes_total_buckets)
+            .with_path_segments(path_label_level);
+        Self::with_config(registry, config)
+    }
+
+    /// new with prometheus register and given [`MetricsConfig`].
+    pub fn with_config(registry: Registry, config: MetricsConfig) -> Self {
+        let mut labels = vec!["scheme", "operation"];
+        if config.path_label_enabled() {
+            labels.push(LABEL_PATH);
+        }
         let requests_total = register_int_counter_vec_with_registry!(
             "requests_total",
             "Total times of create be called",
@@ -200,35 +217,56 @@ impl PrometheusMetrics {
             registry
         )
         .unwrap();
+
+        let mut error_labels = labels.clone();
+        error_labels.push("error");
+        let errors_total = register_int_counter_vec_with_registry!(
+            "errors_total",
+            "Total times of operation failed",
+            &error_labels,
+            registry
+        )
+        .unwrap();
+
         let opts = histogram_opts!(
             "requests_duration_seconds",
             "Histogram of the time spent on specific operation",
-            requests_duration_seconds_buckets
+            config.duration_buckets().to_vec()
         );
 
         let requests_duration_seconds =
             register_histogram_vec_with_registry!(opts, &labels, registry).unwrap();
 
-        let opts = histogram_opts!("bytes_total", "Total size of ", bytes_total_buckets);
+        let opts = histogram_opts!(
+            "bytes_total",
+            "Total size of ",
+            config.bytes_buckets().to_vec()
+        );
         let bytes_total = register_histogram_vec_with_registry!(opts, &labels, registry).unwrap();
 
         Self {
             requests_total,
+            errors_total,
             requests_duration_seconds,
             bytes_total,
-            path_label_level,
+            path_label_level: config.path_segments(),
+            config,
         }
     }
 
     /// error handling is the cold path, so we will not init error counters
     /// in advance.
     #[inline]
-    fn increment_errors_total(&self, op: Operation, kind: ErrorKind) {
+    fn increment_errors_total(&self, scheme: Scheme, op: Operation, path: &str, kind: ErrorKind) {
         debug!(
             "Prometheus statistics metrics error, operation {} error {}",
             op.into_static(),
             kind.into_static()
         );
+
+        let mut labels = self.generate_metric_label(scheme.into_static(), op.into_static(), path);
+        labels.push(kind.into_static());
+        self.errors_total.with_label_values(&labels).inc();
     }
 
     /// generate metric label
@@ -236,19 +274,11 @@ impl PrometheusMetrics {
         &self,
         scheme: &'a str,
         operation: &'a str,
-        path_label: &'a str,
+        path: &'a str,
     ) -> Vec<&'a str> {
-        match self.path_label_level {
-            0 => {
-                vec![scheme, operation]
-            }
-            n if n > 0 => {
-                let path_value = get_path_label(path_label, self.path_label_level);
-                vec![scheme, operation, path_value]
-            }
-            _ => {
-                vec![scheme, operation]
-            }
+        match self.config.path_label(path) {
+            Some(path_label) => vec![scheme, operation, path_label],
+            None => vec![scheme, operation],
         }
     }
 }
@@ -302,7 +332,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         create_res.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::CreateDir, e.kind());
+                .increment_errors_total(self.scheme, Operation::CreateDir, path, e.kind());
             e
         })
     }
@@ -335,7 +365,8 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         });
         timer.observe_duration();
         read_res.map_err(|e| {
-            self.stats.increment_errors_total(Operation::Read, e.kind());
+            self.stats
+                .increment_errors_total(self.scheme, Operation::Read, path, e.kind());
             e
         })
     }
@@ -375,7 +406,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         write_res.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::Write, e.kind());
+                .increment_errors_total(self.scheme, Operation::Write, path, e.kind());
             e
         })
     }
@@ -393,16 +424,11 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
             .with_label_values(&labels)
             .start_timer();
 
-        let stat_res = self
-            .inner
-            .stat(path, args)
-            .inspect_err(|e| {
-                self.stats.increment_errors_total(Operation::Stat, e.kind());
-            })
-            .await;
+        let stat_res = self.inner.stat(path, args).await;
         timer.observe_duration();
         stat_res.map_err(|e| {
-            self.stats.increment_errors_total(Operation::Stat, e.kind());
+            self.stats
+                .increment_errors_total(self.scheme, Operation::Stat, path, e.kind());
             e
         })
     }
@@ -425,7 +451,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         delete_res.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::Delete, e.kind());
+                .increment_errors_total(self.scheme, Operation::Delete, path, e.kind());
             e
         })
     }
@@ -448,7 +474,8 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
         timer.observe_duration();
         list_res.map_err(|e| {
-            self.stats.increment_errors_total(Operation::List, e.kind());
+            self.stats
+                .increment_errors_total(self.scheme, Operation::List, path, e.kind());
             e
         })
     }
@@ -471,7 +498,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         result.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::Batch, e.kind());
+                .increment_errors_total(self.scheme, Operation::Batch, "", e.kind());
             e
         })
     }
@@ -494,7 +521,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
         result.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::Presign, e.kind());
+                .increment_errors_total(self.scheme, Operation::Presign, path, e.kind());
             e
         })
     }
@@ -517,8 +544,12 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
 
         result.map_err(|e| {
-            self.stats
-                .increment_errors_total(Operation::BlockingCreateDir, e.kind());
+            self.stats.increment_errors_total(
+                self.scheme,
+                Operation::BlockingCreateDir,
+                path,
+                e.kind(),
+            );
             e
         })
     }
@@ -551,7 +582,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         result.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::BlockingRead, e.kind());
+                .increment_errors_total(self.scheme, Operation::BlockingRead, path, e.kind());
             e
         })
     }
@@ -583,8 +614,12 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         });
         timer.observe_duration();
         result.map_err(|e| {
-            self.stats
-                .increment_errors_total(Operation::BlockingWrite, e.kind());
+            self.stats.increment_errors_total(
+                self.scheme,
+                Operation::BlockingWrite,
+                path,
+                e.kind(),
+            );
             e
         })
     }
@@ -606,7 +641,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
         result.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::BlockingStat, e.kind());
+                .increment_errors_total(self.scheme, Operation::BlockingStat, path, e.kind());
             e
         })
     }
@@ -628,8 +663,12 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         timer.observe_duration();
 
         result.map_err(|e| {
-            self.stats
-                .increment_errors_total(Operation::BlockingDelete, e.kind());
+            self.stats.increment_errors_total(
+                self.scheme,
+                Operation::BlockingDelete,
+                path,
+                e.kind(),
+            );
             e
         })
     }
@@ -652,7 +691,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
         result.map_err(|e| {
             self.stats
-                .increment_errors_total(Operation::BlockingList, e.kind());
+                .increment_errors_total(self.scheme, Operation::BlockingList, path, e.kind());
             e
         })
     }
@@ -701,7 +740,8 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
                 Ok(bytes)
             }
             Err(e) => {
-                self.stats.increment_errors_total(self.op, e.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -711,7 +751,8 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
         self.inner.poll_seek(cx, pos).map(|res| match res {
             Ok(n) => Ok(n),
             Err(e) => {
-                self.stats.increment_errors_total(self.op, e.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -732,7 +773,8 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
                 Some(Ok(bytes))
             }
             Some(Err(e)) => {
-                self.stats.increment_errors_total(self.op, e.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Some(Err(e))
             }
             None => None,
@@ -757,14 +799,16 @@ impl<R: oio::BlockingRead> oio::BlockingRead for PrometheusMetricWrapper<R> {
                 n
             })
             .map_err(|e| {
-                self.stats.increment_errors_total(self.op, e.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 e
             })
     }
 
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
         self.inner.seek(pos).map_err(|err| {
-            self.stats.increment_errors_total(self.op, err.kind());
+            self.stats
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -784,7 +828,8 @@ impl<R: oio::BlockingRead> oio::BlockingRead for PrometheusMetricWrapper<R> {
                 Ok(bytes)
             }
             Err(e) => {
-                self.stats.increment_errors_total(self.op, e.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -808,21 +853,24 @@ impl<R: oio::Write> oio::Write for PrometheusMetricWrapper<R> {
                 n
             })
             .map_err(|err| {
-                self.stats.increment_errors_total(self.op, err.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
                 err
             })
     }
 
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_abort(cx).map_err(|err| {
-            self.stats.increment_errors_total(self.op, err.kind());
+            self.stats
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_close(cx).map_err(|err| {
-            self.stats.increment_errors_total(self.op, err.kind());
+            self.stats
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -845,43 +893,79 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for PrometheusMetricWrapper<R> {
                 n
             })
             .map_err(|err| {
-                self.stats.increment_errors_total(self.op, err.kind());
+                self.stats
+                    .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
                 err
             })
     }
 
     fn close(&mut self) -> Result<()> {
         self.inner.close().map_err(|err| {
-            self.stats.increment_errors_total(self.op, err.kind());
+            self.stats
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
 }
 
-fn get_path_label(path: &str, path_level: usize) -> &str {
-    if path_level > 0 {
-        return path
-            .char_indices()
-            .filter(|&(_, c)| c == '/')
-            .nth(path_level - 1)
-            .map_or(path, |(i, _)| &path[..i]);
-    }
-    ""
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_prometheus_path_label() {
+        let registry = Registry::new();
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(
+                PrometheusLayer::with_registry(registry.clone())
+                    .with_config(MetricsConfig::default().with_path_segments(1)),
+            )
+            .finish();
+
+        op.write("dataset/a/b", "Hello, World!").await.unwrap();
+        op.stat("other/not_exist").await.unwrap_err();
+
+        let families = registry.gather();
+        let errors = families
+            .iter()
+            .find(|v| v.get_name() == "errors_total")
+            .unwrap();
+        let metric = &errors.get_metric()[0];
+        let labels = metric
+            .get_label()
+            .iter()
+            .map(|v| (v.get_name(), v.get_value()))
+            .collect::<Vec<_>>();
+        assert!(labels.contains(&("path", "other")));
+        assert!(labels.contains(&("error", "NotFound")));
+        assert_eq!(metric.get_counter().get_value(), 1.0);
+
+        let requests = families
+            .iter()
+            .find(|v| v.get_name() == "requests_total")
+            .unwrap();
+        assert!(requests.get_metric().iter().any(|m| m
+            .get_label()
+            .iter()
+            .any(|l| l.get_name() == "path" && l.get_value() == "dataset")));
+    }
 
     #[test]
-    fn test_get_path_label() {
-        let path = "abc/def/ghi";
-        assert_eq!(get_path_label(path, 0), "");
-        assert_eq!(get_path_label(path, 1), "abc");
-        assert_eq!(get_path_label(path, 2), "abc/def");
-        assert_eq!(get_path_label(path, 3), "abc/def/ghi");
-        assert_eq!(get_path_label(path, usize::MAX), "abc/def/ghi");
-
-        assert_eq!(get_path_label("", 0), "");
+    fn test_prometheus_metrics_new() {
+        let metrics = PrometheusMetrics::new(Registry::new(), vec![0.1, 1.0], vec![], 2);
+        assert_eq!(metrics.path_label_level, 2);
+        assert_eq!(
+            metrics.generate_metric_label("memory", "read", "a/b/c"),
+            vec!["memory", "read", "a/b"]
+        );
+
+        let metrics = PrometheusMetrics::new(Registry::new(), vec![], vec![], 0);
+        assert_eq!(metrics.path_label_level, 0);
+        assert_eq!(
+            metrics.generate_metric_label("memory", "read", "a/b/c"),
+            vec!["memory", "read"]
+        );
     }
 }
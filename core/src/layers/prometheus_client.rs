@@ -27,13 +27,14 @@ use std::time::Instant;
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::FutureExt;
-use futures::TryFutureExt;
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
-use prometheus_client::metrics::histogram;
+use prometheus_client::metrics::family::MetricConstructor;
 use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
 
+use super::metrics_config::LABEL_PATH;
+use crate::layers::MetricsConfig;
 use crate::raw::Accessor;
 use crate::raw::*;
 use crate::*;
@@ -60,7 +61,7 @@ use crate::*;
 ///
 ///     let op = Operator::new(builder)
 ///         .expect("must init")
-///         .layer(PrometheusClientLayer::with_registry(&mut registry))
+///         .layer(PrometheusClientLayer::new(&mut registry))
 ///         .finish();
 ///     debug!("operator: {op:?}");
 ///
@@ -92,7 +93,15 @@ impl PrometheusClientLayer {
     /// that do NOT call this method multiple times with a same registry. If you want initialize multiple
     /// [`PrometheusClientLayer`] with a single registry, you should use [`clone`] instead.
     pub fn new(registry: &mut Registry) -> Self {
-        let metrics = PrometheusClientMetrics::register(registry);
+        Self::with_config(registry, MetricsConfig::default())
+    }
+
+    /// Create PrometheusClientLayer with given [`MetricsConfig`] which controls path label and
+    /// histogram buckets.
+    ///
+    /// The same caution as [`PrometheusClientLayer::new`] applies.
+    pub fn with_config(registry: &mut Registry, config: MetricsConfig) -> Self {
+        let metrics = PrometheusClientMetrics::register(registry, config);
         Self { metrics }
     }
 }
@@ -113,36 +122,47 @@ impl<A: Accessor> Layer<A> for PrometheusClientLayer {
     }
 }
 
-type OperationLabels = [(&'static str, &'static str); 2];
-type ErrorLabels = [(&'static str, &'static str); 3];
+type Labels = Vec<(&'static str, String)>;
+
+/// Construct histograms with configured buckets.
+#[derive(Debug, Clone)]
+struct HistogramConstructor {
+    buckets: Vec<f64>,
+}
+
+impl MetricConstructor<Histogram> for HistogramConstructor {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new(self.buckets.iter().copied())
+    }
+}
 
 /// [`PrometheusClientMetrics`] provide the performance and IO metrics with the `prometheus-client` crate.
 #[derive(Debug, Clone)]
 struct PrometheusClientMetrics {
     /// Total counter of the specific operation be called.
-    requests_total: Family<OperationLabels, Counter>,
+    requests_total: Family<Labels, Counter>,
     /// Total counter of the errors.
-    errors_total: Family<ErrorLabels, Counter>,
+    errors_total: Family<Labels, Counter>,
     /// Latency of the specific operation be called.
-    request_duration_seconds: Family<OperationLabels, Histogram>,
+    request_duration_seconds: Family<Labels, Histogram, HistogramConstructor>,
     /// The histogram of bytes
-    bytes_histogram: Family<OperationLabels, Histogram>,
+    bytes_histogram: Family<Labels, Histogram, HistogramConstructor>,
     /// The counter of bytes
-    bytes_total: Family<OperationLabels, Counter>,
+    bytes_total: Family<Labels, Counter>,
+    /// The config to extract path label.
+    config: Arc<MetricsConfig>,
 }
 
 impl PrometheusClientMetrics {
-    pub fn register(registry: &mut Registry) -> Self {
+    pub fn register(registry: &mut Registry, config: MetricsConfig) -> Self {
         let requests_total = Family::default();
         let errors_total = Family::default();
         let bytes_total = Family::default();
-        let request_duration_seconds = Family::<OperationLabels, _>::new_with_constructor(|| {
-            let buckets = histogram::exponential_buckets(0.01, 2.0, 16);
-            Histogram::new(buckets)
+        let request_duration_seconds = Family::new_with_constructor(HistogramConstructor {
+            buckets: config.duration_buckets().to_vec(),
         });
-        let bytes_histogram = Family::<OperationLabels, _>::new_with_constructor(|| {
-            let buckets = histogram::exponential_buckets(1.0, 2.0, 16);
-            Histogram::new(buckets)
+        let bytes_histogram = Family::new_with_constructor(HistogramConstructor {
+            buckets: config.bytes_buckets().to_vec(),
         });
 
         registry.register("opendal_requests", "", requests_total.clone());
@@ -160,33 +180,48 @@ impl PrometheusClientMetrics {
             request_duration_seconds,
             bytes_histogram,
             bytes_total,
+            config: Arc::new(config),
         }
     }
 
-    fn increment_errors_total(&self, scheme: Scheme, op: Operation, err: ErrorKind) {
-        let labels = [
-            ("scheme", scheme.into_static()),
-            ("op", op.into_static()),
-            ("err", err.into_static()),
+    fn labels(&self, scheme: Scheme, op: Operation, path: &str) -> Labels {
+        let mut labels = vec![
+            ("scheme", scheme.into_static().to_string()),
+            ("op", op.into_static().to_string()),
         ];
+        if let Some(path) = self.config.path_label(path) {
+            labels.push((LABEL_PATH, path.to_string()));
+        }
+        labels
+    }
+
+    fn increment_errors_total(&self, scheme: Scheme, op: Operation, path: &str, err: ErrorKind) {
+        let mut labels = self.labels(scheme, op, path);
+        labels.push(("err", err.into_static().to_string()));
         self.errors_total.get_or_create(&labels).inc();
     }
 
-    fn increment_request_total(&self, scheme: Scheme, op: Operation) {
-        let labels = [("scheme", scheme.into_static()), ("op", op.into_static())];
+    fn increment_request_total(&self, scheme: Scheme, op: Operation, path: &str) {
+        let labels = self.labels(scheme, op, path);
         self.requests_total.get_or_create(&labels).inc();
     }
 
-    fn observe_bytes_total(&self, scheme: Scheme, op: Operation, bytes: usize) {
-        let labels = [("scheme", scheme.into_static()), ("op", op.into_static())];
+    fn observe_bytes_total(&self, scheme: Scheme, op: Operation, path: &str, bytes: usize) {
+        let labels = self.labels(scheme, op, path);
         self.bytes_histogram
             .get_or_create(&labels)
             .observe(bytes as f64);
         self.bytes_total.get_or_create(&labels).inc_by(bytes as u64);
     }
 
-    fn observe_request_duration(&self, scheme: Scheme, op: Operation, duration: Duration) {
-        let labels = [("scheme", scheme.into_static()), ("op", op.into_static())];
+    fn observe_request_duration(
+        &self,
+        scheme: Scheme,
+        op: Operation,
+        path: &str,
+        duration: Duration,
+    ) {
+        let labels = self.labels(scheme, op, path);
         self.request_duration_seconds
             .get_or_create(&labels)
             .observe(duration.as_secs_f64());
@@ -225,7 +260,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
     async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::CreateDir);
+            .increment_request_total(self.scheme, Operation::CreateDir, path);
 
         let start_time = Instant::now();
         let create_res = self.inner.create_dir(path, args).await;
@@ -233,18 +268,19 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::CreateDir,
+            path,
             start_time.elapsed(),
         );
         create_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::CreateDir, e.kind());
+                .increment_errors_total(self.scheme, Operation::CreateDir, path, e.kind());
             e
         })
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Read);
+            .increment_request_total(self.scheme, Operation::Read, path);
 
         let read_res = self
             .inner
@@ -258,6 +294,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
                             Operation::Read,
                             self.metrics.clone(),
                             self.scheme,
+                            path,
                         ),
                     )
                 })
@@ -265,14 +302,14 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
             .await;
         read_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Read, e.kind());
+                .increment_errors_total(self.scheme, Operation::Read, path, e.kind());
             e
         })
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Write);
+            .increment_request_total(self.scheme, Operation::Write, path);
 
         let write_res = self
             .inner
@@ -286,6 +323,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
                             Operation::Write,
                             self.metrics.clone(),
                             self.scheme,
+                            path,
                         ),
                     )
                 })
@@ -294,85 +332,94 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
         write_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Write, e.kind());
+                .increment_errors_total(self.scheme, Operation::Write, path, e.kind());
             e
         })
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Stat);
+            .increment_request_total(self.scheme, Operation::Stat, path);
         let start_time = Instant::now();
 
-        let stat_res = self
-            .inner
-            .stat(path, args)
-            .inspect_err(|e| {
-                self.metrics
-                    .increment_errors_total(self.scheme, Operation::Stat, e.kind());
-            })
-            .await;
+        let stat_res = self.inner.stat(path, args).await;
 
-        self.metrics
-            .observe_request_duration(self.scheme, Operation::Stat, start_time.elapsed());
+        self.metrics.observe_request_duration(
+            self.scheme,
+            Operation::Stat,
+            path,
+            start_time.elapsed(),
+        );
         stat_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Stat, e.kind());
+                .increment_errors_total(self.scheme, Operation::Stat, path, e.kind());
             e
         })
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Delete);
+            .increment_request_total(self.scheme, Operation::Delete, path);
         let start_time = Instant::now();
 
         let delete_res = self.inner.delete(path, args).await;
 
-        self.metrics
-            .observe_request_duration(self.scheme, Operation::Delete, start_time.elapsed());
+        self.metrics.observe_request_duration(
+            self.scheme,
+            Operation::Delete,
+            path,
+            start_time.elapsed(),
+        );
         delete_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Delete, e.kind());
+                .increment_errors_total(self.scheme, Operation::Delete, path, e.kind());
             e
         })
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::List);
+            .increment_request_total(self.scheme, Operation::List, path);
         let start_time = Instant::now();
 
         let list_res = self.inner.list(path, args).await;
 
-        self.metrics
-            .observe_request_duration(self.scheme, Operation::List, start_time.elapsed());
+        self.metrics.observe_request_duration(
+            self.scheme,
+            Operation::List,
+            path,
+            start_time.elapsed(),
+        );
         list_res.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::List, e.kind());
+                .increment_errors_total(self.scheme, Operation::List, path, e.kind());
             e
         })
     }
 
     async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Batch);
+            .increment_request_total(self.scheme, Operation::Batch, "");
         let start_time = Instant::now();
 
         let result = self.inner.batch(args).await;
 
-        self.metrics
-            .observe_request_duration(self.scheme, Operation::Batch, start_time.elapsed());
+        self.metrics.observe_request_duration(
+            self.scheme,
+            Operation::Batch,
+            "",
+            start_time.elapsed(),
+        );
         result.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Batch, e.kind());
+                .increment_errors_total(self.scheme, Operation::Batch, "", e.kind());
             e
         })
     }
 
     async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::Presign);
+            .increment_request_total(self.scheme, Operation::Presign, path);
         let start_time = Instant::now();
 
         let result = self.inner.presign(path, args).await;
@@ -380,18 +427,19 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::Presign,
+            path,
             start_time.elapsed(),
         );
         result.map_err(|e| {
             self.metrics
-                .increment_errors_total(self.scheme, Operation::Presign, e.kind());
+                .increment_errors_total(self.scheme, Operation::Presign, path, e.kind());
             e
         })
     }
 
     fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingCreateDir);
+            .increment_request_total(self.scheme, Operation::BlockingCreateDir, path);
         let start_time = Instant::now();
 
         let result = self.inner.blocking_create_dir(path, args);
@@ -399,12 +447,14 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::BlockingCreateDir,
+            path,
             start_time.elapsed(),
         );
         result.map_err(|e| {
             self.metrics.increment_errors_total(
                 self.scheme,
                 Operation::BlockingCreateDir,
+                path,
                 e.kind(),
             );
             e
@@ -413,7 +463,7 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingRead);
+            .increment_request_total(self.scheme, Operation::BlockingRead, path);
 
         let result = self.inner.blocking_read(path, args).map(|(rp, r)| {
             (
@@ -423,20 +473,25 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
                     Operation::BlockingRead,
                     self.metrics.clone(),
                     self.scheme,
+                    path,
                 ),
             )
         });
 
         result.map_err(|e| {
-            self.metrics
-                .increment_errors_total(self.scheme, Operation::BlockingRead, e.kind());
+            self.metrics.increment_errors_total(
+                self.scheme,
+                Operation::BlockingRead,
+                path,
+                e.kind(),
+            );
             e
         })
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingWrite);
+            .increment_request_total(self.scheme, Operation::BlockingWrite, path);
 
         let result = self.inner.blocking_write(path, args).map(|(rp, r)| {
             (
@@ -446,39 +501,49 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
                     Operation::BlockingWrite,
                     self.metrics.clone(),
                     self.scheme,
+                    path,
                 ),
             )
         });
 
         result.map_err(|e| {
-            self.metrics
-                .increment_errors_total(self.scheme, Operation::BlockingWrite, e.kind());
+            self.metrics.increment_errors_total(
+                self.scheme,
+                Operation::BlockingWrite,
+                path,
+                e.kind(),
+            );
             e
         })
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingStat);
+            .increment_request_total(self.scheme, Operation::BlockingStat, path);
         let start_time = Instant::now();
 
         let result = self.inner.blocking_stat(path, args);
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::BlockingStat,
+            path,
             start_time.elapsed(),
         );
 
         result.map_err(|e| {
-            self.metrics
-                .increment_errors_total(self.scheme, Operation::BlockingStat, e.kind());
+            self.metrics.increment_errors_total(
+                self.scheme,
+                Operation::BlockingStat,
+                path,
+                e.kind(),
+            );
             e
         })
     }
 
     fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingDelete);
+            .increment_request_total(self.scheme, Operation::BlockingDelete, path);
         let start_time = Instant::now();
 
         let result = self.inner.blocking_delete(path, args);
@@ -486,18 +551,23 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::BlockingDelete,
+            path,
             start_time.elapsed(),
         );
         result.map_err(|e| {
-            self.metrics
-                .increment_errors_total(self.scheme, Operation::BlockingDelete, e.kind());
+            self.metrics.increment_errors_total(
+                self.scheme,
+                Operation::BlockingDelete,
+                path,
+                e.kind(),
+            );
             e
         })
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
         self.metrics
-            .increment_request_total(self.scheme, Operation::BlockingList);
+            .increment_request_total(self.scheme, Operation::BlockingList, path);
         let start_time = Instant::now();
 
         let result = self.inner.blocking_list(path, args);
@@ -505,11 +575,16 @@ impl<A: Accessor> LayeredAccessor for PrometheusAccessor<A> {
         self.metrics.observe_request_duration(
             self.scheme,
             Operation::BlockingList,
+            path,
             start_time.elapsed(),
         );
         result.map_err(|e| {
-            self.metrics
-                .increment_errors_total(self.scheme, Operation::BlockingList, e.kind());
+            self.metrics.increment_errors_total(
+                self.scheme,
+                Operation::BlockingList,
+                path,
+                e.kind(),
+            );
             e
         })
     }
@@ -521,17 +596,25 @@ pub struct PrometheusMetricWrapper<R> {
     op: Operation,
     metrics: Arc<PrometheusClientMetrics>,
     scheme: Scheme,
+    path: String,
     bytes_total: usize,
     start_time: Instant,
 }
 
 impl<R> PrometheusMetricWrapper<R> {
-    fn new(inner: R, op: Operation, metrics: Arc<PrometheusClientMetrics>, scheme: Scheme) -> Self {
+    fn new(
+        inner: R,
+        op: Operation,
+        metrics: Arc<PrometheusClientMetrics>,
+        scheme: Scheme,
+        path: &str,
+    ) -> Self {
         Self {
             inner,
             op,
             metrics,
             scheme,
+            path: path.to_string(),
             bytes_total: 0,
             start_time: Instant::now(),
         }
@@ -547,7 +630,7 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
             }
             Err(e) => {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, e.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -558,7 +641,7 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
             Ok(n) => Ok(n),
             Err(e) => {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, e.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -572,7 +655,7 @@ impl<R: oio::Read> oio::Read for PrometheusMetricWrapper<R> {
             }
             Some(Err(e)) => {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, e.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Some(Err(e))
             }
             None => None,
@@ -590,7 +673,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for PrometheusMetricWrapper<R> {
             })
             .map_err(|e| {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, e.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 e
             })
     }
@@ -598,7 +681,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for PrometheusMetricWrapper<R> {
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
         self.inner.seek(pos).map_err(|err| {
             self.metrics
-                .increment_errors_total(self.scheme, self.op, err.kind());
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -611,7 +694,7 @@ impl<R: oio::BlockingRead> oio::BlockingRead for PrometheusMetricWrapper<R> {
             }
             Err(e) => {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, e.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, e.kind());
                 Err(e)
             }
         })
@@ -628,7 +711,7 @@ impl<R: oio::Write> oio::Write for PrometheusMetricWrapper<R> {
             })
             .map_err(|err| {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, err.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
                 err
             })
     }
@@ -636,7 +719,7 @@ impl<R: oio::Write> oio::Write for PrometheusMetricWrapper<R> {
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_abort(cx).map_err(|err| {
             self.metrics
-                .increment_errors_total(self.scheme, self.op, err.kind());
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -644,7 +727,7 @@ impl<R: oio::Write> oio::Write for PrometheusMetricWrapper<R> {
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_close(cx).map_err(|err| {
             self.metrics
-                .increment_errors_total(self.scheme, self.op, err.kind());
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -660,7 +743,7 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for PrometheusMetricWrapper<R> {
             })
             .map_err(|err| {
                 self.metrics
-                    .increment_errors_total(self.scheme, self.op, err.kind());
+                    .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
                 err
             })
     }
@@ -668,7 +751,7 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for PrometheusMetricWrapper<R> {
     fn close(&mut self) -> Result<()> {
         self.inner.close().map_err(|err| {
             self.metrics
-                .increment_errors_total(self.scheme, self.op, err.kind());
+                .increment_errors_total(self.scheme, self.op, &self.path, err.kind());
             err
         })
     }
@@ -677,8 +760,12 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for PrometheusMetricWrapper<R> {
 impl<R> Drop for PrometheusMetricWrapper<R> {
     fn drop(&mut self) {
         self.metrics
-            .observe_bytes_total(self.scheme, self.op, self.bytes_total);
-        self.metrics
-            .observe_request_duration(self.scheme, self.op, self.start_time.elapsed());
+            .observe_bytes_total(self.scheme, self.op, &self.path, self.bytes_total);
+        self.metrics.observe_request_duration(
+            self.scheme,
+            self.op,
+            &self.path,
+            self.start_time.elapsed(),
+        );
     }
 }
@@ -0,0 +1,712 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose;
+use base64::Engine;
+use bytes::Bytes;
+use serde_json::Value;
+
+use crate::raw::cassette::*;
+use crate::raw::oio::ListExt;
+use crate::raw::*;
+use crate::*;
+
+/// Record all interactions with the underlying storage into a cassette.
+///
+/// `RecordLayer` serializes every call, its arguments, its result and the
+/// streamed bytes. The cassette can be written into another [`Operator`] via
+/// [`RecordLayer::save`] and replayed later by `raw::tests::ReplayService` without
+/// touching the original storage.
+///
+/// # Notes
+///
+/// - Reads are recorded while the reader reaches the end or is dropped, only
+///   the bytes that have been consumed will be recorded.
+/// - Writes are recorded while the writer is closed, aborted writes will not
+///   be recorded.
+/// - At most [`RecordLayer::with_max_data_size`] bytes of every read and write
+///   will be kept, larger content will be marked as truncated. Truncated reads
+///   can't be replayed and truncated writes only check the recorded prefix.
+/// - Lists are consumed eagerly while recording.
+/// - Interactions are shared by all clones of this layer.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::RecordLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let cassette = Operator::new(services::Memory::default())?.finish();
+/// let layer = RecordLayer::new(cassette, "fixtures/memory.json");
+///
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(layer.clone())
+///     .finish();
+/// op.write("test", "Hello, World!").await?;
+///
+/// layer.save().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordLayer {
+    cassette: Operator,
+    path: String,
+    max_data_size: usize,
+    tape: Arc<Tape>,
+}
+
+/// The default max size of content kept for every read and write.
+const DEFAULT_MAX_DATA_SIZE: usize = 4 * 1024 * 1024;
+
+impl RecordLayer {
+    /// Create a new `RecordLayer` which saves the cassette at `path` of given operator.
+    pub fn new(cassette: Operator, path: &str) -> Self {
+        Self {
+            cassette,
+            path: path.to_string(),
+            max_data_size: DEFAULT_MAX_DATA_SIZE,
+            tape: Arc::default(),
+        }
+    }
+
+    /// Set the max size of content kept for every read and write.
+    ///
+    /// Default to 4 MiB.
+    pub fn with_max_data_size(mut self, size: usize) -> Self {
+        self.max_data_size = size;
+        self
+    }
+
+    /// Get the count of interactions recorded so far.
+    pub fn len(&self) -> usize {
+        self.tape.interactions().len()
+    }
+
+    /// Check if there are no interactions recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Save all interactions recorded so far into the cassette.
+    pub async fn save(&self) -> Result<()> {
+        let bs = self.tape.to_vec()?;
+        self.cassette.write(&self.path, bs).await
+    }
+
+    /// Save all interactions recorded so far into the cassette in blocking way.
+    pub fn blocking_save(&self) -> Result<()> {
+        let bs = self.tape.to_vec()?;
+        self.cassette.blocking().write(&self.path, bs)
+    }
+}
+
+impl<A: Accessor> Layer<A> for RecordLayer {
+    type LayeredAccessor = RecordAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let info = inner.info();
+        *self.tape.info.lock().expect("lock must be acquired") = Some(CassetteInfo {
+            scheme: info.scheme().into_static().to_string(),
+            root: info.root().to_string(),
+            name: info.name().to_string(),
+            capability: info.full_capability().into(),
+        });
+
+        RecordAccessor {
+            inner,
+            max_data_size: self.max_data_size,
+            tape: self.tape.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordAccessor<A: Accessor> {
+    inner: A,
+    max_data_size: usize,
+    tape: Arc<Tape>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for RecordAccessor<A> {
+    type Inner = A;
+    type Reader = RecordReader<A::Reader>;
+    type BlockingReader = RecordReader<A::BlockingReader>;
+    type Writer = RecordWriter<A::Writer>;
+    type BlockingWriter = RecordWriter<A::BlockingWriter>;
+    type Lister = CassetteLister;
+    type BlockingLister = CassetteLister;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.create_dir(path, args).await;
+        self.tape
+            .record(Call::new(Operation::CreateDir, path), &res, |_| Value::Null);
+        res
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let call = Call::new(Operation::Stat, path).with_args(stat_args(&args));
+        let res = self.inner.stat(path, args).await;
+        self.tape.record(call, &res, |rp| {
+            to_value(RecordedMetadata::new(rp.clone().into_metadata()))
+        });
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let call = Call::new(Operation::Read, path).with_args(read_args(&args));
+        let res = self.inner.read(path, args).await;
+        self.tape.record_read(call, res, self.max_data_size)
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let call = Call::new(Operation::Write, path).with_args(write_args(&args));
+        let res = self.inner.write(path, args).await;
+        self.tape.record_write(call, res, self.max_data_size)
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.copy(from, to, args).await;
+        self.tape.record(
+            Call::new(Operation::Copy, from).with_target(to),
+            &res,
+            |_| Value::Null,
+        );
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.rename(from, to, args).await;
+        self.tape.record(
+            Call::new(Operation::Rename, from).with_target(to),
+            &res,
+            |_| Value::Null,
+        );
+        res
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let call = Call::new(Operation::Delete, path).with_args(delete_args(&args));
+        let res = self.inner.delete(path, args).await;
+        self.tape.record(call, &res, |_| Value::Null);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        let call = Call::new(Operation::List, path).with_args(list_args(&args));
+        let res = async {
+            let (_, mut l) = self.inner.list(path, args).await?;
+            let mut entries = VecDeque::new();
+            while let Some(entry) = l.next().await? {
+                entries.push_back(entry);
+            }
+            Ok(entries)
+        }
+        .await;
+        self.tape.record_list(call, res)
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let call = Call::new(Operation::Presign, path).with_args(presign_args(&args));
+        let res = self.inner.presign(path, args).await;
+        self.tape.record(call, &res, |rp| {
+            to_value(RecordedPresign::new(rp.clone().into_presigned_request()))
+        });
+        res
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let call = Call::new(Operation::Batch, "").with_args(batch_args(&args));
+        let res = self.inner.batch(args).await;
+        self.tape.record(call, &res, |rp| {
+            to_value(
+                rp.results()
+                    .iter()
+                    .map(|(path, res)| RecordedBatchResult {
+                        path: path.clone(),
+                        error: res.as_ref().err().map(RecordedError::new),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+        res
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.blocking_create_dir(path, args);
+        self.tape
+            .record(Call::new(Operation::BlockingCreateDir, path), &res, |_| {
+                Value::Null
+            });
+        res
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let call = Call::new(Operation::BlockingStat, path).with_args(stat_args(&args));
+        let res = self.inner.blocking_stat(path, args);
+        self.tape.record(call, &res, |rp| {
+            to_value(RecordedMetadata::new(rp.clone().into_metadata()))
+        });
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let call = Call::new(Operation::BlockingRead, path).with_args(read_args(&args));
+        let res = self.inner.blocking_read(path, args);
+        self.tape.record_read(call, res, self.max_data_size)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let call = Call::new(Operation::BlockingWrite, path).with_args(write_args(&args));
+        let res = self.inner.blocking_write(path, args);
+        self.tape.record_write(call, res, self.max_data_size)
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.blocking_copy(from, to, args);
+        self.tape.record(
+            Call::new(Operation::BlockingCopy, from).with_target(to),
+            &res,
+            |_| Value::Null,
+        );
+        res
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.blocking_rename(from, to, args);
+        self.tape.record(
+            Call::new(Operation::BlockingRename, from).with_target(to),
+            &res,
+            |_| Value::Null,
+        );
+        res
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let call = Call::new(Operation::BlockingDelete, path).with_args(delete_args(&args));
+        let res = self.inner.blocking_delete(path, args);
+        self.tape.record(call, &res, |_| Value::Null);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        let call = Call::new(Operation::BlockingList, path).with_args(list_args(&args));
+        let res = self.inner.blocking_list(path, args).and_then(|(_, mut l)| {
+            let mut entries = VecDeque::new();
+            while let Some(entry) = oio::BlockingList::next(&mut l)? {
+                entries.push_back(entry);
+            }
+            Ok(entries)
+        });
+        self.tape.record_list(call, res)
+    }
+}
+
+/// Tape keeps all interactions recorded by [`RecordLayer`].
+#[derive(Debug, Default)]
+struct Tape {
+    info: Mutex<Option<CassetteInfo>>,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl Tape {
+    fn interactions(&self) -> std::sync::MutexGuard<'_, Vec<Interaction>> {
+        self.interactions.lock().expect("lock must be acquired")
+    }
+
+    fn to_vec(&self) -> Result<Vec<u8>> {
+        let info = self
+            .info
+            .lock()
+            .expect("lock must be acquired")
+            .clone()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "record layer has not been applied to any operator",
+                )
+            })?;
+
+        let cassette = Cassette {
+            version: CASSETTE_VERSION,
+            info,
+            interactions: self.interactions().clone(),
+        };
+        serde_json::to_vec_pretty(&cassette).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "serialize cassette failed").set_source(err)
+        })
+    }
+
+    fn push(
+        &self,
+        call: Call,
+        result: std::result::Result<Value, RecordedError>,
+        data: Option<&Capture>,
+    ) {
+        self.interactions().push(Interaction {
+            operation: call.operation.to_string(),
+            path: call.path,
+            target: call.target,
+            args: call.args,
+            result,
+            data: data.map(|v| general_purpose::STANDARD.encode(&v.buf)),
+            truncated: data.map(|v| v.truncated).unwrap_or_default(),
+        });
+    }
+
+    fn record<T>(&self, call: Call, res: &Result<T>, f: impl FnOnce(&T) -> Value) {
+        let result = match res {
+            Ok(v) => Ok(f(v)),
+            Err(err) => Err(RecordedError::new(err)),
+        };
+        self.push(call, result, None);
+    }
+
+    fn record_read<R>(
+        self: &Arc<Self>,
+        call: Call,
+        res: Result<(RpRead, R)>,
+        max_data_size: usize,
+    ) -> Result<(RpRead, RecordReader<R>)> {
+        match res {
+            Ok((rp, r)) => Ok((
+                rp,
+                RecordReader {
+                    inner: r,
+                    call: Some(call),
+                    capture: Capture::new(max_data_size),
+                    tape: self.clone(),
+                },
+            )),
+            Err(err) => {
+                self.push(call, Err(RecordedError::new(&err)), None);
+                Err(err)
+            }
+        }
+    }
+
+    fn record_list(
+        &self,
+        call: Call,
+        res: Result<VecDeque<oio::Entry>>,
+    ) -> Result<(RpList, CassetteLister)> {
+        self.record(call, &res, |entries| {
+            to_value(
+                entries
+                    .iter()
+                    .map(|v| RecordedEntry {
+                        path: v.path().to_string(),
+                        metadata: RecordedMetadata::new(v.metadata().clone()),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        });
+        let entries = res?;
+        Ok((RpList::default(), CassetteLister::new(entries)))
+    }
+
+    fn record_write<W>(
+        self: &Arc<Self>,
+        call: Call,
+        res: Result<(RpWrite, W)>,
+        max_data_size: usize,
+    ) -> Result<(RpWrite, RecordWriter<W>)> {
+        match res {
+            Ok((rp, w)) => Ok((
+                rp,
+                RecordWriter {
+                    inner: w,
+                    call: Some(call),
+                    capture: Capture::new(max_data_size),
+                    tape: self.clone(),
+                },
+            )),
+            Err(err) => {
+                self.push(call, Err(RecordedError::new(&err)), None);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Capture keeps at most `max` bytes of the streamed content.
+struct Capture {
+    buf: Vec<u8>,
+    max: usize,
+    /// The count of bytes that have been streamed sequentially.
+    pos: u64,
+    truncated: bool,
+}
+
+impl Capture {
+    fn new(max: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            max,
+            pos: 0,
+            truncated: false,
+        }
+    }
+
+    fn extend(&mut self, bs: &[u8]) {
+        self.pos += bs.len() as u64;
+        if self.truncated {
+            return;
+        }
+
+        let remaining = self.max - self.buf.len();
+        if bs.len() > remaining {
+            self.buf.extend_from_slice(&bs[..remaining]);
+            self.truncated = true;
+        } else {
+            self.buf.extend_from_slice(bs);
+        }
+    }
+
+    /// Content is no longer sequential if the reader has been seeked to
+    /// another position.
+    fn seek(&mut self, pos: u64) {
+        if pos != self.pos {
+            self.pos = pos;
+            self.truncated = true;
+        }
+    }
+}
+
+/// RecordReader records the bytes consumed from inner reader.
+pub struct RecordReader<R> {
+    inner: R,
+    /// call will be taken after the read has been recorded.
+    call: Option<Call>,
+    capture: Capture,
+    tape: Arc<Tape>,
+}
+
+impl<R> RecordReader<R> {
+    fn record(&mut self, res: std::result::Result<(), &Error>) {
+        if let Some(call) = self.call.take() {
+            let result = match res {
+                Ok(()) => Ok(Value::Null),
+                Err(err) => Err(RecordedError::new(err)),
+            };
+            self.tape.push(call, result, Some(&self.capture));
+        }
+    }
+
+    fn record_read(&mut self, buf: &[u8], res: &Result<usize>) {
+        match res {
+            Ok(0) if !buf.is_empty() => self.record(Ok(())),
+            Ok(n) => self.capture.extend(&buf[..*n]),
+            Err(err) => self.record(Err(err)),
+        }
+    }
+
+    fn record_seek(&mut self, res: &Result<u64>) {
+        match res {
+            Ok(pos) => self.capture.seek(*pos),
+            Err(err) => self.record(Err(err)),
+        }
+    }
+
+    fn record_next(&mut self, res: &Option<Result<Bytes>>) {
+        match res {
+            Some(Ok(bs)) => self.capture.extend(bs),
+            Some(Err(err)) => self.record(Err(err)),
+            None => self.record(Ok(())),
+        }
+    }
+}
+
+impl<R> Drop for RecordReader<R> {
+    fn drop(&mut self) {
+        self.record(Ok(()));
+    }
+}
+
+impl<R: oio::Read> oio::Read for RecordReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_read(cx, buf));
+        self.record_read(buf, &res);
+        Poll::Ready(res)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let res = ready!(self.inner.poll_seek(cx, pos));
+        self.record_seek(&res);
+        Poll::Ready(res)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = ready!(self.inner.poll_next(cx));
+        self.record_next(&res);
+        Poll::Ready(res)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for RecordReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let res = self.inner.read(buf);
+        self.record_read(buf, &res);
+        res
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let res = self.inner.seek(pos);
+        self.record_seek(&res);
+        res
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let res = self.inner.next();
+        self.record_next(&res);
+        res
+    }
+}
+
+/// RecordWriter records all bytes accepted by inner writer and the result of close.
+pub struct RecordWriter<W> {
+    inner: W,
+    /// call will be taken after the write has been recorded.
+    call: Option<Call>,
+    capture: Capture,
+    tape: Arc<Tape>,
+}
+
+impl<W> RecordWriter<W> {
+    fn record<T>(&mut self, res: &Result<T>) {
+        if let Some(call) = self.call.take() {
+            let result = match res {
+                Ok(_) => Ok(Value::Null),
+                Err(err) => Err(RecordedError::new(err)),
+            };
+            self.tape.push(call, result, Some(&self.capture));
+        }
+    }
+}
+
+impl<W: oio::Write> oio::Write for RecordWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_write(cx, bs));
+        match &res {
+            Ok(n) => self.capture.extend(&bs.bytes(*n)),
+            Err(_) => self.record(&res),
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_close(cx));
+        self.record(&res);
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.call = None;
+        self.inner.poll_abort(cx)
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for RecordWriter<W> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let res = self.inner.write(bs);
+        match &res {
+            Ok(n) => self.capture.extend(&bs.bytes(*n)),
+            Err(_) => self.record(&res),
+        }
+        res
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close();
+        self.record(&res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_record_save() {
+        let cassette = Operator::new(Memory::default()).unwrap().finish();
+        let layer = RecordLayer::new(cassette.clone(), "cassette.json");
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        op.write("test", "Hello, World!").await.unwrap();
+        op.stat("not_exist").await.unwrap_err();
+        assert_eq!(layer.len(), 2);
+
+        layer.save().await.unwrap();
+        let bs = cassette.read("cassette.json").await.unwrap();
+        let saved: Cassette = serde_json::from_slice(&bs).unwrap();
+        assert_eq!(saved.version, CASSETTE_VERSION);
+        assert_eq!(saved.info.scheme, "memory");
+        assert!(Capability::from(saved.info.capability).write);
+        assert_eq!(saved.interactions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_max_data_size() {
+        let cassette = Operator::new(Memory::default()).unwrap().finish();
+        let layer = RecordLayer::new(cassette.clone(), "cassette.json").with_max_data_size(5);
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        op.write("test", "Hello, World!").await.unwrap();
+        assert_eq!(op.read("test").await.unwrap(), b"Hello, World!");
+        assert_eq!(op.read_with("test").range(0..5).await.unwrap(), b"Hello");
+
+        let interactions = layer.tape.interactions().clone();
+        let (write, reads): (Vec<_>, Vec<_>) = interactions
+            .into_iter()
+            .filter(|v| v.data.is_some())
+            .partition(|v| v.operation == "write");
+        assert!(write[0].truncated);
+        assert_eq!(write[0].data().unwrap().unwrap(), "Hello");
+        assert!(reads[0].truncated);
+        assert_eq!(reads[0].data().unwrap().unwrap(), "Hello");
+        assert!(!reads[1].truncated);
+        assert_eq!(reads[1].data().unwrap().unwrap(), "Hello");
+    }
+}
@@ -49,6 +49,12 @@ use crate::*;
 /// returns true. If operation still failed, this layer will set error to
 /// `Persistent` which means error has been retried.
 ///
+/// Interrupted reads (for example, a reset connection in the middle of a long
+/// streamed read) will be resumed from the consumed offset by a new ranged read
+/// instead of being returned to the caller. The etag captured while opening the
+/// reader will be sent as `if_match`, so a changed file will be reported as
+/// [`ErrorKind::ConditionNotMatch`] instead of being silently stitched together.
+///
 /// `write` and `blocking_write` don't support retry so far, visit [this issue](https://github.com/apache/opendal/issues/1223) for more details.
 ///
 /// # Examples
@@ -1129,6 +1135,7 @@ mod tests {
     use std::task::Poll;
 
     use async_trait::async_trait;
+    use bytes::Buf;
     use bytes::Bytes;
     use futures::AsyncReadExt;
     use futures::TryStreamExt;
@@ -1377,6 +1384,209 @@ mod tests {
         assert_eq!(*builder.attempt.lock().unwrap(), 5);
     }
 
+    #[derive(Default, Clone)]
+    struct ResumableBuilder {
+        service: ResumableService,
+    }
+
+    impl Builder for ResumableBuilder {
+        const SCHEME: Scheme = Scheme::Custom("resumable");
+        type Accessor = ResumableService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(self.service.clone())
+        }
+    }
+
+    /// The range and if_match of a read request.
+    type ResumableRequest = (BytesRange, Option<String>);
+
+    #[derive(Debug, Clone)]
+    struct ResumableService {
+        /// The content and etag of the file.
+        file: Arc<Mutex<(Bytes, String)>>,
+        /// All read requests sent to this service.
+        requests: Arc<Mutex<Vec<ResumableRequest>>>,
+    }
+
+    impl Default for ResumableService {
+        fn default() -> Self {
+            Self {
+                file: Arc::new(Mutex::new((
+                    Bytes::from("Hello, World!"),
+                    "etag-1".to_string(),
+                ))),
+                requests: Arc::default(),
+            }
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+    #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+    impl Accessor for ResumableService {
+        type Reader = ResumableReader;
+        type Writer = ();
+        type Lister = ();
+        type BlockingReader = ();
+        type BlockingWriter = ();
+        type BlockingLister = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_native_capability(Capability {
+                read: true,
+                read_with_range: true,
+                read_with_if_match: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn read(&self, _: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+            let (content, etag) = self.file.lock().unwrap().clone();
+
+            let mut requests = self.requests.lock().unwrap();
+            requests.push((args.range(), args.if_match().map(|v| v.to_string())));
+
+            if let Some(v) = args.if_match() {
+                if v != etag {
+                    return Err(Error::new(ErrorKind::ConditionNotMatch, "etag not match"));
+                }
+            }
+
+            let offset = args.range().offset().unwrap_or_default() as usize;
+            let size = args
+                .range()
+                .size()
+                .map(|v| v as usize)
+                .unwrap_or(content.len() - offset);
+            let data = content.slice(offset..offset + size);
+
+            Ok((
+                RpRead::new()
+                    .with_size(Some(data.len() as u64))
+                    .with_etag(Some(etag)),
+                ResumableReader {
+                    data,
+                    // Only the first request will be interrupted.
+                    interrupt: requests.len() == 1,
+                    started: false,
+                },
+            ))
+        }
+    }
+
+    /// ResumableReader returns at most 7 bytes every read and will be
+    /// interrupted after the first read if `interrupt` is set.
+    struct ResumableReader {
+        data: Bytes,
+        interrupt: bool,
+        started: bool,
+    }
+
+    impl oio::Read for ResumableReader {
+        fn poll_read(&mut self, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+            if self.interrupt && self.started {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "connection reset by peer",
+                )
+                .set_temporary()));
+            }
+
+            let n = self.data.len().min(buf.len()).min(7);
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data.advance(n);
+            self.started = true;
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_seek(&mut self, _: &mut Context<'_>, _: io::SeekFrom) -> Poll<Result<u64>> {
+            Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "output reader doesn't support seeking",
+            )))
+        }
+
+        fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+            let mut bs = vec![0; 7];
+            match ready!(self.poll_read(cx, &mut bs)) {
+                Ok(0) => Poll::Ready(None),
+                Ok(v) => Poll::Ready(Some(Ok(Bytes::from(bs[..v].to_vec())))),
+                Err(err) => Poll::Ready(Some(Err(err))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_read_resume() {
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
+
+        let builder = ResumableBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(RetryLayer::new().with_min_delay(Duration::from_millis(10)))
+            .finish();
+
+        let mut r = op.reader("resumable").await.unwrap();
+        let mut content = Vec::new();
+        r.read_to_end(&mut content)
+            .await
+            .expect("read must succeed");
+        assert_eq!(content, "Hello, World!".as_bytes());
+
+        // The interrupted read should be resumed from the consumed offset
+        // with the etag captured while opening.
+        let requests = builder.service.requests.lock().unwrap().clone();
+        assert_eq!(
+            requests,
+            vec![
+                (BytesRange::new(Some(0), None), None),
+                (
+                    BytesRange::new(Some(7), Some(6)),
+                    Some("etag-1".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_read_resume_changed() {
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
+
+        let builder = ResumableBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(RetryLayer::new().with_min_delay(Duration::from_millis(10)))
+            .finish();
+
+        let mut r = op.reader("resumable").await.unwrap();
+        let mut buf = vec![0; 7];
+        r.read_exact(&mut buf).await.expect("read must succeed");
+        assert_eq!(buf, "Hello, ".as_bytes());
+
+        // File has been changed while reading.
+        *builder.service.file.lock().unwrap() =
+            (Bytes::from("Hello, Rust!!"), "etag-2".to_string());
+
+        let mut content = Vec::new();
+        let err = r
+            .read_to_end(&mut content)
+            .await
+            .expect_err("read must fail");
+        assert_eq!(
+            err.into_inner()
+                .and_then(|e| e.downcast::<Error>().ok())
+                .map(|e| e.kind()),
+            Some(ErrorKind::ConditionNotMatch)
+        );
+    }
+
     #[tokio::test]
     async fn test_retry_list() {
         let _ = tracing_subscriber::fmt().with_test_writer().try_init();
@@ -0,0 +1,1041 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::FutureExt;
+
+use crate::raw::*;
+use crate::*;
+
+/// All operations that tracked by [`StatsLayer`], others will be counted as
+/// unknown.
+const OPERATIONS: [Operation; 19] = [
+    Operation::Info,
+    Operation::CreateDir,
+    Operation::Read,
+    Operation::Write,
+    Operation::Copy,
+    Operation::Rename,
+    Operation::Stat,
+    Operation::Delete,
+    Operation::List,
+    Operation::Batch,
+    Operation::Presign,
+    Operation::BlockingCreateDir,
+    Operation::BlockingRead,
+    Operation::BlockingWrite,
+    Operation::BlockingCopy,
+    Operation::BlockingRename,
+    Operation::BlockingStat,
+    Operation::BlockingDelete,
+    Operation::BlockingList,
+];
+
+/// All error kinds that tracked by [`StatsLayer`], others will be counted as
+/// [`ErrorKind::Unexpected`].
+const ERROR_KINDS: [ErrorKind; 14] = [
+    ErrorKind::Unexpected,
+    ErrorKind::Unsupported,
+    ErrorKind::ConfigInvalid,
+    ErrorKind::NotFound,
+    ErrorKind::PermissionDenied,
+    ErrorKind::IsADirectory,
+    ErrorKind::NotADirectory,
+    ErrorKind::AlreadyExists,
+    ErrorKind::RateLimited,
+    ErrorKind::IsSameFile,
+    ErrorKind::ConditionNotMatch,
+    ErrorKind::ContentTruncated,
+    ErrorKind::ContentIncomplete,
+    ErrorKind::InvalidInput,
+];
+
+/// Index of the operation's counter, operations that are not tracked share
+/// the last one.
+fn operation_index(op: Operation) -> usize {
+    OPERATIONS
+        .iter()
+        .position(|v| *v == op)
+        .unwrap_or(OPERATIONS.len())
+}
+
+/// Index of the error kind's counter, error kinds that are not tracked share
+/// the last one.
+fn error_kind_index(kind: ErrorKind) -> usize {
+    ERROR_KINDS
+        .iter()
+        .position(|v| *v == kind)
+        .unwrap_or(ERROR_KINDS.len())
+}
+
+/// Latency buckets in power of 2 microseconds, the last bucket covers
+/// about 9 days which is large enough.
+const LATENCY_BUCKETS: usize = 40;
+
+/// Add in-process statistics for every operations.
+///
+/// StatsLayer keeps lock-free counters for requests, errors (per [`ErrorKind`]),
+/// latency sketches per [`Operation`] and total bytes read and written. It's
+/// useful for debug endpoints and tests that want to know "what has the
+/// operator done" without running a metrics backend.
+///
+/// # Notes
+///
+/// - `StatsLayer` is cheap to clone, all clones share the same statistics.
+/// - Latency of `read` and `write` only covers the call itself, not the
+///   following IO on the returned reader or writer.
+/// - Errors returned by readers, writers and listers are counted into the
+///   operation that created them.
+/// - Requests are counted where this layer is placed. Layers added by
+///   [`Operator::layer`] sit above the operator's own completion, so a
+///   recursive list emulated by the operator is counted as one request.
+/// - [`StatsLayer::reset`] clears counters one by one, requests happened
+///   during reset could be partially recorded.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::StatsLayer;
+/// use opendal::raw::Operation;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let stats = StatsLayer::new();
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(stats.clone())
+///     .finish();
+///
+/// op.write("test", "Hello, World!").await?;
+///
+/// let snapshot = stats.snapshot();
+/// assert_eq!(snapshot.operation(Operation::Write).map(|v| v.requests()), Some(1));
+/// assert_eq!(snapshot.bytes_written(), 13);
+/// println!("{snapshot}");
+///
+/// stats.reset();
+/// assert_eq!(stats.snapshot().requests(), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StatsLayer {
+    stats: Arc<Stats>,
+}
+
+impl StatsLayer {
+    /// Create a new StatsLayer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a snapshot of current statistics.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    /// Reset all statistics to zero.
+    pub fn reset(&self) {
+        self.stats.reset()
+    }
+}
+
+impl<A: Accessor> Layer<A> for StatsLayer {
+    type LayeredAccessor = StatsAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        StatsAccessor {
+            inner,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Stats {
+    /// The last one is shared by operations that are not tracked.
+    operations: [OperationCounter; OPERATIONS.len() + 1],
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            operations: std::array::from_fn(|_| OperationCounter::default()),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Stats {
+    fn counter(&self, op: Operation) -> &OperationCounter {
+        &self.operations[operation_index(op)]
+    }
+
+    fn increment_requests(&self, op: Operation) {
+        self.counter(op).requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn increment_errors(&self, op: Operation, kind: ErrorKind) {
+        self.counter(op).errors[error_kind_index(kind)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the latency of request, and the error if failed.
+    fn observe<T>(&self, op: Operation, start: Instant, res: &Result<T>) {
+        self.counter(op).latency.observe(start.elapsed());
+        if let Err(err) = res {
+            self.increment_errors(op, err.kind());
+        }
+    }
+
+    fn add_bytes_read(&self, n: usize) {
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn add_bytes_written(&self, n: usize) {
+        self.bytes_written.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> StatsSnapshot {
+        let unknown = self.operations[OPERATIONS.len()].snapshot(Operation::default());
+        StatsSnapshot {
+            operations: OPERATIONS
+                .iter()
+                .zip(self.operations.iter())
+                .map(|(op, counter)| counter.snapshot(*op))
+                .collect(),
+            unknown_requests: unknown.requests,
+            unknown_errors: unknown.errors(),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in self.operations.iter() {
+            counter.reset();
+        }
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.bytes_written.store(0, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct OperationCounter {
+    requests: AtomicU64,
+    /// The last one is shared by error kinds that are not tracked.
+    errors: [AtomicU64; ERROR_KINDS.len() + 1],
+    latency: LatencySketch,
+}
+
+impl Default for OperationCounter {
+    fn default() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            errors: std::array::from_fn(|_| AtomicU64::new(0)),
+            latency: LatencySketch::default(),
+        }
+    }
+}
+
+impl OperationCounter {
+    fn snapshot(&self, operation: Operation) -> OperationStats {
+        let mut errors: Vec<_> = ERROR_KINDS
+            .iter()
+            .zip(self.errors.iter())
+            .map(|(kind, v)| (*kind, v.load(Ordering::Relaxed)))
+            .collect();
+        let unknown = self.errors[ERROR_KINDS.len()].load(Ordering::Relaxed);
+        if let Some((_, v)) = errors.iter_mut().find(|(k, _)| *k == ErrorKind::Unexpected) {
+            *v += unknown;
+        }
+        errors.retain(|(_, v)| *v > 0);
+
+        OperationStats {
+            operation,
+            requests: self.requests.load(Ordering::Relaxed),
+            errors,
+            latency: self.latency.snapshot(),
+        }
+    }
+
+    fn reset(&self) {
+        self.requests.store(0, Ordering::Relaxed);
+        for v in self.errors.iter() {
+            v.store(0, Ordering::Relaxed);
+        }
+        self.latency.reset();
+    }
+}
+
+/// LatencySketch is a lock-free histogram with power of 2 microseconds buckets.
+#[derive(Debug)]
+struct LatencySketch {
+    count: AtomicU64,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS],
+}
+
+impl Default for LatencySketch {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl LatencySketch {
+    fn observe(&self, dur: Duration) {
+        let us = u64::try_from(dur.as_micros()).unwrap_or(u64::MAX);
+
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+        self.buckets[latency_bucket(us)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            count: self.count.load(Ordering::Relaxed),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+            max_us: self.max_us.load(Ordering::Relaxed),
+            buckets: self
+                .buckets
+                .iter()
+                .map(|v| v.load(Ordering::Relaxed))
+                .collect(),
+        }
+    }
+
+    fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.sum_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+        for v in self.buckets.iter() {
+            v.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Bucket `0` holds latency less than 1us, bucket `i` holds latency in
+/// `[2^(i-1), 2^i)` us.
+fn latency_bucket(us: u64) -> usize {
+    let idx = (u64::BITS - us.leading_zeros()) as usize;
+    idx.min(LATENCY_BUCKETS - 1)
+}
+
+/// StatsSnapshot is a point-in-time copy of the statistics of [`StatsLayer`].
+#[derive(Debug, Clone)]
+pub struct StatsSnapshot {
+    operations: Vec<OperationStats>,
+    /// Requests and errors of operations that are not tracked.
+    unknown_requests: u64,
+    unknown_errors: u64,
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+impl StatsSnapshot {
+    /// Get the statistics of given operation, returns `None` if the operation
+    /// is not tracked by [`StatsLayer`].
+    pub fn operation(&self, op: Operation) -> Option<&OperationStats> {
+        self.operations.get(operation_index(op))
+    }
+
+    /// Iterate the statistics of operations that have been called.
+    pub fn operations(&self) -> impl Iterator<Item = &OperationStats> {
+        self.operations.iter().filter(|v| v.requests > 0)
+    }
+
+    /// Total requests of all operations.
+    pub fn requests(&self) -> u64 {
+        self.operations.iter().map(|v| v.requests).sum::<u64>() + self.unknown_requests
+    }
+
+    /// Total errors of all operations.
+    pub fn errors(&self) -> u64 {
+        self.operations.iter().map(|v| v.errors()).sum::<u64>() + self.unknown_errors
+    }
+
+    /// Total bytes read from the underlying service.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Total bytes written into the underlying service.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+impl Display for StatsSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "bytes_read={} bytes_written={}",
+            self.bytes_read, self.bytes_written
+        )?;
+        for v in self.operations() {
+            writeln!(
+                f,
+                "{}: requests={} errors={} p50={:?} p99={:?} max={:?}",
+                v.operation,
+                v.requests,
+                v.errors(),
+                v.latency.quantile(0.5),
+                v.latency.quantile(0.99),
+                v.latency.max()
+            )?;
+        }
+        if self.unknown_requests > 0 {
+            writeln!(
+                f,
+                "unknown: requests={} errors={}",
+                self.unknown_requests, self.unknown_errors
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// OperationStats is the statistics of a specific [`Operation`].
+#[derive(Debug, Clone)]
+pub struct OperationStats {
+    operation: Operation,
+    requests: u64,
+    errors: Vec<(ErrorKind, u64)>,
+    latency: LatencySnapshot,
+}
+
+impl OperationStats {
+    /// The operation of this statistics.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Total requests of this operation.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Total errors of this operation.
+    pub fn errors(&self) -> u64 {
+        self.errors.iter().map(|(_, v)| v).sum()
+    }
+
+    /// Errors of this operation with given kind.
+    pub fn errors_of(&self, kind: ErrorKind) -> u64 {
+        self.errors
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map_or(0, |(_, v)| *v)
+    }
+
+    /// Errors of this operation grouped by [`ErrorKind`], only kinds that
+    /// happened will be returned.
+    pub fn error_kinds(&self) -> &[(ErrorKind, u64)] {
+        &self.errors
+    }
+
+    /// Latency of this operation.
+    pub fn latency(&self) -> &LatencySnapshot {
+        &self.latency
+    }
+}
+
+/// LatencySnapshot is a point-in-time copy of a latency sketch.
+///
+/// Latency is recorded into power of 2 microseconds buckets, so quantiles
+/// are estimated by the upper bound of the bucket.
+#[derive(Debug, Clone)]
+pub struct LatencySnapshot {
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+    buckets: Vec<u64>,
+}
+
+impl LatencySnapshot {
+    /// Total count of observed latency.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean of observed latency.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_micros(self.sum_us / self.count)
+    }
+
+    /// Max of observed latency.
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max_us)
+    }
+
+    /// Estimate the latency at given quantile which should be in `[0.0, 1.0]`.
+    pub fn quantile(&self, q: f64) -> Duration {
+        let total: u64 = self.buckets.iter().sum();
+        if total == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((total as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, v) in self.buckets.iter().enumerate() {
+            seen += v;
+            if seen >= rank {
+                let upper = if idx == 0 { 1 } else { 1u64 << idx };
+                return Duration::from_micros(upper.min(self.max_us));
+            }
+        }
+        self.max()
+    }
+}
+
+#[derive(Clone)]
+pub struct StatsAccessor<A: Accessor> {
+    inner: A,
+    stats: Arc<Stats>,
+}
+
+impl<A: Accessor> Debug for StatsAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatsAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A: Accessor> LayeredAccessor for StatsAccessor<A> {
+    type Inner = A;
+    type Reader = StatsWrapper<A::Reader>;
+    type BlockingReader = StatsWrapper<A::BlockingReader>;
+    type Writer = StatsWrapper<A::Writer>;
+    type BlockingWriter = StatsWrapper<A::BlockingWriter>;
+    type Lister = StatsWrapper<A::Lister>;
+    type BlockingLister = StatsWrapper<A::BlockingLister>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        self.stats.increment_requests(Operation::Info);
+
+        let start = Instant::now();
+        let result = self.inner.info();
+        self.stats
+            .counter(Operation::Info)
+            .latency
+            .observe(start.elapsed());
+        result
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.stats.increment_requests(Operation::CreateDir);
+
+        let start = Instant::now();
+        let result = self.inner.create_dir(path, args).await;
+        self.stats.observe(Operation::CreateDir, start, &result);
+        result
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.stats.increment_requests(Operation::Read);
+
+        let start = Instant::now();
+        let result = self
+            .inner
+            .read(path, args)
+            .map(|v| {
+                v.map(|(rp, r)| {
+                    (
+                        rp,
+                        StatsWrapper::new(r, Operation::Read, self.stats.clone()),
+                    )
+                })
+            })
+            .await;
+        self.stats.observe(Operation::Read, start, &result);
+        result
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.stats.increment_requests(Operation::Write);
+
+        let start = Instant::now();
+        let result = self
+            .inner
+            .write(path, args)
+            .map(|v| {
+                v.map(|(rp, w)| {
+                    (
+                        rp,
+                        StatsWrapper::new(w, Operation::Write, self.stats.clone()),
+                    )
+                })
+            })
+            .await;
+        self.stats.observe(Operation::Write, start, &result);
+        result
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.stats.increment_requests(Operation::Copy);
+
+        let start = Instant::now();
+        let result = self.inner.copy(from, to, args).await;
+        self.stats.observe(Operation::Copy, start, &result);
+        result
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.stats.increment_requests(Operation::Rename);
+
+        let start = Instant::now();
+        let result = self.inner.rename(from, to, args).await;
+        self.stats.observe(Operation::Rename, start, &result);
+        result
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.stats.increment_requests(Operation::Stat);
+
+        let start = Instant::now();
+        let result = self.inner.stat(path, args).await;
+        self.stats.observe(Operation::Stat, start, &result);
+        result
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.stats.increment_requests(Operation::Delete);
+
+        let start = Instant::now();
+        let result = self.inner.delete(path, args).await;
+        self.stats.observe(Operation::Delete, start, &result);
+        result
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.stats.increment_requests(Operation::List);
+
+        let start = Instant::now();
+        let result = self.inner.list(path, args).await;
+        self.stats.observe(Operation::List, start, &result);
+        result.map(|(rp, lister)| {
+            (
+                rp,
+                StatsWrapper::new(lister, Operation::List, self.stats.clone()),
+            )
+        })
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.stats.increment_requests(Operation::Batch);
+
+        let start = Instant::now();
+        let result = self.inner.batch(args).await;
+        self.stats.observe(Operation::Batch, start, &result);
+        result
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.stats.increment_requests(Operation::Presign);
+
+        let start = Instant::now();
+        let result = self.inner.presign(path, args).await;
+        self.stats.observe(Operation::Presign, start, &result);
+        result
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.stats.increment_requests(Operation::BlockingCreateDir);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_create_dir(path, args);
+        self.stats
+            .observe(Operation::BlockingCreateDir, start, &result);
+        result
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.stats.increment_requests(Operation::BlockingRead);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_read(path, args).map(|(rp, r)| {
+            (
+                rp,
+                StatsWrapper::new(r, Operation::BlockingRead, self.stats.clone()),
+            )
+        });
+        self.stats.observe(Operation::BlockingRead, start, &result);
+        result
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.stats.increment_requests(Operation::BlockingWrite);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_write(path, args).map(|(rp, w)| {
+            (
+                rp,
+                StatsWrapper::new(w, Operation::BlockingWrite, self.stats.clone()),
+            )
+        });
+        self.stats.observe(Operation::BlockingWrite, start, &result);
+        result
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.stats.increment_requests(Operation::BlockingCopy);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_copy(from, to, args);
+        self.stats.observe(Operation::BlockingCopy, start, &result);
+        result
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.stats.increment_requests(Operation::BlockingRename);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_rename(from, to, args);
+        self.stats
+            .observe(Operation::BlockingRename, start, &result);
+        result
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.stats.increment_requests(Operation::BlockingStat);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_stat(path, args);
+        self.stats.observe(Operation::BlockingStat, start, &result);
+        result
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.stats.increment_requests(Operation::BlockingDelete);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_delete(path, args);
+        self.stats
+            .observe(Operation::BlockingDelete, start, &result);
+        result
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.stats.increment_requests(Operation::BlockingList);
+
+        let start = Instant::now();
+        let result = self.inner.blocking_list(path, args);
+        self.stats.observe(Operation::BlockingList, start, &result);
+        result.map(|(rp, lister)| {
+            (
+                rp,
+                StatsWrapper::new(lister, Operation::BlockingList, self.stats.clone()),
+            )
+        })
+    }
+}
+
+pub struct StatsWrapper<R> {
+    inner: R,
+    op: Operation,
+    stats: Arc<Stats>,
+}
+
+impl<R> StatsWrapper<R> {
+    fn new(inner: R, op: Operation, stats: Arc<Stats>) -> Self {
+        Self { inner, op, stats }
+    }
+}
+
+impl<R: oio::Read> oio::Read for StatsWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.inner.poll_read(cx, buf).map(|res| match res {
+            Ok(n) => {
+                self.stats.add_bytes_read(n);
+                Ok(n)
+            }
+            Err(e) => {
+                self.stats.increment_errors(self.op, e.kind());
+                Err(e)
+            }
+        })
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos).map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        self.inner.poll_next(cx).map(|res| match res {
+            Some(Ok(bytes)) => {
+                self.stats.add_bytes_read(bytes.len());
+                Some(Ok(bytes))
+            }
+            Some(Err(e)) => {
+                self.stats.increment_errors(self.op, e.kind());
+                Some(Err(e))
+            }
+            None => None,
+        })
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for StatsWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner
+            .read(buf)
+            .map(|n| {
+                self.stats.add_bytes_read(n);
+                n
+            })
+            .map_err(|e| {
+                self.stats.increment_errors(self.op, e.kind());
+                e
+            })
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.inner.seek(pos).map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        self.inner.next().map(|res| match res {
+            Ok(bytes) => {
+                self.stats.add_bytes_read(bytes.len());
+                Ok(bytes)
+            }
+            Err(e) => {
+                self.stats.increment_errors(self.op, e.kind());
+                Err(e)
+            }
+        })
+    }
+}
+
+impl<R: oio::Write> oio::Write for StatsWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner
+            .poll_write(cx, bs)
+            .map_ok(|n| {
+                self.stats.add_bytes_written(n);
+                n
+            })
+            .map_err(|e| {
+                self.stats.increment_errors(self.op, e.kind());
+                e
+            })
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx).map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_close(cx).map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for StatsWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.inner
+            .write(bs)
+            .map(|n| {
+                self.stats.add_bytes_written(n);
+                n
+            })
+            .map_err(|e| {
+                self.stats.increment_errors(self.op, e.kind());
+                e
+            })
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inner.close().map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+}
+
+impl<R: oio::List> oio::List for StatsWrapper<R> {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        self.inner.poll_next(cx).map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+}
+
+impl<R: oio::BlockingList> oio::BlockingList for StatsWrapper<R> {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        self.inner.next().map_err(|e| {
+            self.stats.increment_errors(self.op, e.kind());
+            e
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[test]
+    fn test_operation_and_error_kind_index() {
+        for (idx, op) in OPERATIONS.iter().enumerate() {
+            assert_eq!(operation_index(*op), idx);
+        }
+        for (idx, kind) in ERROR_KINDS.iter().enumerate() {
+            assert_eq!(error_kind_index(*kind), idx);
+        }
+    }
+
+    #[test]
+    fn test_unknown_counters() {
+        let stats = Stats::default();
+        let unknown = &stats.operations[OPERATIONS.len()];
+        unknown.requests.fetch_add(2, Ordering::Relaxed);
+        unknown.errors[ERROR_KINDS.len()].fetch_add(1, Ordering::Relaxed);
+        stats.operations[0].errors[ERROR_KINDS.len()].fetch_add(1, Ordering::Relaxed);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.requests(), 2);
+        assert_eq!(snapshot.errors(), 2);
+        let info = snapshot.operation(OPERATIONS[0]).unwrap();
+        assert_eq!(info.errors_of(ErrorKind::Unexpected), 1);
+        assert!(snapshot
+            .to_string()
+            .contains("unknown: requests=2 errors=1"));
+    }
+
+    #[test]
+    fn test_latency_quantile() {
+        let sketch = LatencySketch::default();
+        for us in [0, 3, 5, 100, 1000] {
+            sketch.observe(Duration::from_micros(us));
+        }
+        let snapshot = sketch.snapshot();
+        assert_eq!(snapshot.count(), 5);
+        assert_eq!(snapshot.max(), Duration::from_micros(1000));
+        assert_eq!(snapshot.quantile(0.0), Duration::from_micros(1));
+        assert_eq!(snapshot.quantile(0.5), Duration::from_micros(8));
+        assert_eq!(snapshot.quantile(1.0), Duration::from_micros(1000));
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let stats = StatsLayer::new();
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(stats.clone())
+            .finish();
+
+        op.write("test", "Hello, World!").await.unwrap();
+        let bs = op.read("test").await.unwrap();
+        assert_eq!(bs.len(), 13);
+        op.stat("not_exist").await.unwrap_err();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.operation(Operation::Write).unwrap().requests(), 1);
+        assert_eq!(snapshot.operation(Operation::Read).unwrap().requests(), 1);
+        assert_eq!(snapshot.bytes_written(), 13);
+        assert_eq!(snapshot.bytes_read(), 13);
+        let stat = snapshot.operation(Operation::Stat).unwrap();
+        assert!(stat.requests() >= 2);
+        assert_eq!(stat.errors_of(ErrorKind::NotFound), 1);
+        assert_eq!(snapshot.errors(), 1);
+        assert_eq!(stat.latency().count(), stat.requests());
+
+        stats.reset();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.requests(), 0);
+        assert_eq!(snapshot.bytes_read(), 0);
+        assert_eq!(snapshot.operations().count(), 0);
+    }
+
+    /// Place StatsLayer under the operator's completion to count the list
+    /// requests actually sent to the service.
+    async fn list_recursive_requests<B: Builder>(mut builder: B) -> u64 {
+        let stats = StatsLayer::new();
+        let op = OperatorBuilder::new(stats.layer(builder.build().unwrap())).finish();
+        for path in ["x/y", "x/x/y", "x/x/x/y"] {
+            op.write(path, "test").await.unwrap();
+        }
+
+        stats.reset();
+        let entries = op.list_with("x/").recursive(true).await.unwrap();
+        assert!(entries.len() >= 3);
+        stats
+            .snapshot()
+            .operation(Operation::List)
+            .unwrap()
+            .requests()
+    }
+
+    #[tokio::test]
+    async fn test_list_recursive_requests() {
+        // Memory supports list with recursive natively.
+        assert_eq!(list_recursive_requests(Memory::default()).await, 1);
+
+        // Fs doesn't, every dir will be listed once.
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = crate::services::Fs::default();
+        builder.root(dir.path().to_str().unwrap());
+        assert_eq!(list_recursive_requests(builder).await, 3);
+    }
+}
@@ -15,12 +15,18 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
+use std::future::Future;
 use std::io::SeekFrom;
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -35,7 +41,7 @@ use governor::RateLimiter;
 use crate::raw::*;
 use crate::*;
 
-/// Add a bandwidth rate limiter to the underlying services.
+/// Add a bandwidth and request rate limiter to the underlying services.
 ///
 /// # Throttle
 ///
@@ -44,6 +50,27 @@ use crate::*;
 /// [Governor](https://docs.rs/governor/latest/governor/index.html).
 /// By setting the `bandwidth` and `burst`, we can control the byte flow rate of underlying services.
 ///
+/// # Request Quotas
+///
+/// Services like s3 throttle requests by count per prefix and per operation class,
+/// for example, 3,500 `PUT` and 5,500 `GET` requests per second per prefix.
+///
+/// - [`ThrottleLayer::with_quota`] limits the requests per second of given operations.
+/// - [`ThrottleLayer::with_prefix_quota`] does the same but only for paths under the prefix.
+/// - [`ThrottleLayer::with_partition_depth`] gives every partition (the first `depth`
+///   segments of path) its own quota, which matches how s3 partitions prefixes.
+/// - [`ThrottleLayer::with_adaptive`] enables AIMD back-off: the rate will be halved
+///   when [`ErrorKind::RateLimited`] is returned, and increased additively while
+///   requests succeed, until it reaches the configured quota again.
+///
+/// Every call to the underlying service like `stat`, `read` and `write` counts as
+/// one request. Writers count one more `Write` request while closing, and one for
+/// every part if they are created with `buffer`, since every buffered part will be
+/// uploaded by a request. Blocking operations share the same quotas with async ones.
+///
+/// Partitions that have been idle for a while will be evicted, so that the
+/// memory used by quotas won't grow with the number of partitions.
+///
 /// # Note
 ///
 /// When setting the ThrottleLayer, always consider the largest possible operation size as the burst size,
@@ -66,10 +93,35 @@ use crate::*;
 ///     .layer(ThrottleLayer::new(10 * 1024, 10000 * 1024))
 ///     .finish();
 /// ```
-#[derive(Clone)]
+///
+/// This example limits requests like s3 does, without limiting bandwidth.
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::ThrottleLayer;
+/// use opendal::raw::Operation;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         ThrottleLayer::default()
+///             .with_quota(
+///                 [Operation::Write, Operation::Copy, Operation::List],
+///                 3500,
+///             )
+///             .with_quota([Operation::Read, Operation::Stat], 5500)
+///             .with_partition_depth(1)
+///             .with_adaptive(true),
+///     )
+///     .finish();
+/// ```
+#[derive(Clone, Default)]
 pub struct ThrottleLayer {
-    bandwidth: NonZeroU32,
-    burst: NonZeroU32,
+    bandwidth: Option<(NonZeroU32, NonZeroU32)>,
+    quotas: Vec<(String, Vec<Operation>, NonZeroU32)>,
+    partition_depth: usize,
+    adaptive: bool,
 }
 
 impl ThrottleLayer {
@@ -77,26 +129,98 @@ impl ThrottleLayer {
     ///
     /// - bandwidth: the maximum number of bytes allowed to pass through per second.
     /// - burst: the maximum number of bytes allowed to pass through at once.
+    ///
+    /// Use [`ThrottleLayer::default`] to create a `ThrottleLayer` without bandwidth limit.
     pub fn new(bandwidth: u32, burst: u32) -> Self {
         assert!(bandwidth > 0);
         assert!(burst > 0);
         Self {
-            bandwidth: NonZeroU32::new(bandwidth).unwrap(),
-            burst: NonZeroU32::new(burst).unwrap(),
+            bandwidth: Some((
+                NonZeroU32::new(bandwidth).unwrap(),
+                NonZeroU32::new(burst).unwrap(),
+            )),
+            ..Default::default()
         }
     }
+
+    /// Limit the requests per second of given operations.
+    ///
+    /// All given operations share the same quota.
+    pub fn with_quota(
+        self,
+        operations: impl IntoIterator<Item = Operation>,
+        ops_per_second: u32,
+    ) -> Self {
+        self.with_prefix_quota("", operations, ops_per_second)
+    }
+
+    /// Limit the requests per second of given operations on paths under the prefix.
+    ///
+    /// All given operations share the same quota.
+    pub fn with_prefix_quota(
+        mut self,
+        prefix: &str,
+        operations: impl IntoIterator<Item = Operation>,
+        ops_per_second: u32,
+    ) -> Self {
+        assert!(ops_per_second > 0);
+        self.quotas.push((
+            prefix.trim_start_matches('/').to_string(),
+            operations.into_iter().map(normalize_operation).collect(),
+            NonZeroU32::new(ops_per_second).unwrap(),
+        ));
+        self
+    }
+
+    /// Apply quotas to every partition instead of the whole operator.
+    ///
+    /// A partition is the first `depth` segments of path, for example, with depth `1`,
+    /// `a/b/c` and `a/d` are in the partition `a/`, and `e/f` is in `e/`.
+    ///
+    /// Default to `0`, which means all paths share the same quota.
+    pub fn with_partition_depth(mut self, depth: usize) -> Self {
+        self.partition_depth = depth;
+        self
+    }
+
+    /// Enable AIMD back-off for request quotas.
+    ///
+    /// The rate will be halved when [`ErrorKind::RateLimited`] is returned, and increased
+    /// additively while requests succeed.
+    pub fn with_adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
 }
 
 impl<A: Accessor> Layer<A> for ThrottleLayer {
     type LayeredAccessor = ThrottleAccessor<A>;
 
     fn layer(&self, accessor: A) -> Self::LayeredAccessor {
-        let rate_limiter = Arc::new(RateLimiter::direct(
-            Quota::per_second(self.bandwidth).allow_burst(self.burst),
-        ));
+        let rate_limiter = self.bandwidth.map(|(bandwidth, burst)| {
+            Arc::new(RateLimiter::direct(
+                Quota::per_second(bandwidth).allow_burst(burst),
+            ))
+        });
+        let request_limiter = Arc::new(RequestLimiter {
+            quotas: self
+                .quotas
+                .iter()
+                .map(|(prefix, operations, rate)| RequestQuota {
+                    prefix: prefix.clone(),
+                    operations: operations.clone(),
+                    rate: *rate,
+                    buckets: Mutex::default(),
+                })
+                .collect(),
+            partition_depth: self.partition_depth,
+            adaptive: self.adaptive,
+        });
+
         ThrottleAccessor {
             inner: accessor,
             rate_limiter,
+            request_limiter,
         }
     }
 }
@@ -106,10 +230,232 @@ impl<A: Accessor> Layer<A> for ThrottleLayer {
 /// Read more about [Middleware](https://docs.rs/governor/latest/governor/middleware/index.html)
 type SharedRateLimiter = Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>>;
 
+/// Start to evict idle buckets once a quota has this many partitions.
+const MAX_IDLE_BUCKETS: usize = 1024;
+/// Buckets that are not used for this long are considered idle.
+const BUCKET_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Blocking operations share the same quotas with async ones.
+fn normalize_operation(op: Operation) -> Operation {
+    match op {
+        Operation::BlockingCreateDir => Operation::CreateDir,
+        Operation::BlockingRead => Operation::Read,
+        Operation::BlockingWrite => Operation::Write,
+        Operation::BlockingCopy => Operation::Copy,
+        Operation::BlockingRename => Operation::Rename,
+        Operation::BlockingStat => Operation::Stat,
+        Operation::BlockingDelete => Operation::Delete,
+        Operation::BlockingList => Operation::List,
+        op => op,
+    }
+}
+
+/// RequestLimiter limits the request rate by quotas.
+#[derive(Debug)]
+struct RequestLimiter {
+    quotas: Vec<RequestQuota>,
+    partition_depth: usize,
+    adaptive: bool,
+}
+
+#[derive(Debug)]
+struct RequestQuota {
+    prefix: String,
+    operations: Vec<Operation>,
+    rate: NonZeroU32,
+    /// buckets of every partition.
+    buckets: Mutex<HashMap<String, Arc<RequestBucket>>>,
+}
+
+impl RequestLimiter {
+    /// Find the buckets that the request should acquire.
+    fn buckets(&self, op: Operation, path: &str) -> Vec<Arc<RequestBucket>> {
+        if self.quotas.is_empty() {
+            return Vec::new();
+        }
+
+        let partition: String = path
+            .split_inclusive('/')
+            .take(self.partition_depth)
+            .collect();
+        self.quotas
+            .iter()
+            .filter(|q| q.operations.contains(&op) && path.starts_with(&q.prefix))
+            .map(|q| {
+                let mut buckets = q.buckets.lock().expect("lock must succeed");
+                if buckets.len() >= MAX_IDLE_BUCKETS && !buckets.contains_key(&partition) {
+                    evict_idle_buckets(&mut buckets, Instant::now());
+                }
+                buckets
+                    .entry(partition.clone())
+                    .or_insert_with(|| Arc::new(RequestBucket::new(q.rate)))
+                    .clone()
+            })
+            .collect()
+    }
+
+    async fn acquire(buckets: &[Arc<RequestBucket>]) {
+        for bucket in buckets {
+            bucket.limiter().until_ready().await;
+        }
+    }
+
+    fn blocking_acquire(buckets: &[Arc<RequestBucket>]) {
+        for bucket in buckets {
+            let limiter = bucket.limiter();
+            while let Err(not_until) = limiter.check() {
+                thread::sleep(not_until.wait_time_from(DefaultClock::default().now()));
+            }
+        }
+    }
+
+    async fn throttle<T>(
+        &self,
+        op: Operation,
+        path: &str,
+        fut: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let buckets = self.buckets(op, path);
+        Self::acquire(&buckets).await;
+
+        let res = fut.await;
+        self.feedback(&buckets, &res);
+        res
+    }
+
+    fn blocking_throttle<T>(
+        &self,
+        op: Operation,
+        path: &str,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let buckets = self.buckets(normalize_operation(op), path);
+        Self::blocking_acquire(&buckets);
+
+        let res = f();
+        self.feedback(&buckets, &res);
+        res
+    }
+
+    fn feedback<T>(&self, buckets: &[Arc<RequestBucket>], res: &Result<T>) {
+        if !self.adaptive {
+            return;
+        }
+
+        let rate_limited = matches!(res, Err(err) if err.kind() == ErrorKind::RateLimited);
+        for bucket in buckets {
+            if rate_limited {
+                bucket.decrease();
+            } else {
+                bucket.increase();
+            }
+        }
+    }
+}
+
+/// Remove the buckets that are idle and not used by any writer.
+fn evict_idle_buckets(buckets: &mut HashMap<String, Arc<RequestBucket>>, now: Instant) {
+    buckets.retain(|_, bucket| {
+        Arc::strong_count(bucket) > 1
+            || now.saturating_duration_since(bucket.last_used()) < BUCKET_IDLE_TIMEOUT
+    });
+}
+
+/// RequestBucket holds the rate limiter of a partition, and adjusts its rate by AIMD.
+#[derive(Debug)]
+struct RequestBucket {
+    max: NonZeroU32,
+    state: Mutex<RequestBucketState>,
+}
+
+#[derive(Debug)]
+struct RequestBucketState {
+    rate: NonZeroU32,
+    successes: u32,
+    limiter: SharedRateLimiter,
+    last_used: Instant,
+}
+
+impl RequestBucket {
+    fn new(rate: NonZeroU32) -> Self {
+        Self {
+            max: rate,
+            state: Mutex::new(RequestBucketState {
+                rate,
+                successes: 0,
+                limiter: Arc::new(RateLimiter::direct(Quota::per_second(rate))),
+                last_used: Instant::now(),
+            }),
+        }
+    }
+
+    fn limiter(&self) -> SharedRateLimiter {
+        let mut state = self.state.lock().expect("lock must succeed");
+        state.last_used = Instant::now();
+        state.limiter.clone()
+    }
+
+    fn last_used(&self) -> Instant {
+        self.state.lock().expect("lock must succeed").last_used
+    }
+
+    #[cfg(test)]
+    fn rate(&self) -> u32 {
+        self.state.lock().expect("lock must succeed").rate.get()
+    }
+
+    /// Multiplicative decrease: halve the rate.
+    fn decrease(&self) {
+        let mut state = self.state.lock().expect("lock must succeed");
+        let rate = NonZeroU32::new(state.rate.get() / 2).unwrap_or(NonZeroU32::MIN);
+        state.successes = 0;
+        if rate != state.rate {
+            state.rate = rate;
+            state.limiter = Arc::new(RateLimiter::direct(Quota::per_second(rate)));
+        }
+    }
+
+    /// Additive increase: raise the rate by a tenth of the quota after a second
+    /// worth of requests succeeded.
+    fn increase(&self) {
+        let mut state = self.state.lock().expect("lock must succeed");
+        if state.rate == self.max {
+            return;
+        }
+
+        state.successes += 1;
+        if state.successes >= state.rate.get() {
+            let step = (self.max.get() / 10).max(1);
+            let rate = state.rate.saturating_add(step).min(self.max);
+            state.rate = rate;
+            state.successes = 0;
+            state.limiter = Arc::new(RateLimiter::direct(Quota::per_second(rate)));
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ThrottleAccessor<A: Accessor> {
     inner: A,
-    rate_limiter: SharedRateLimiter,
+    rate_limiter: Option<SharedRateLimiter>,
+    request_limiter: Arc<RequestLimiter>,
+}
+
+impl<A: Accessor> ThrottleAccessor<A> {
+    /// The size of parts that will be uploaded by the writer, the same as
+    /// the buffer size used by `CompleteLayer`.
+    fn part_size(&self, args: &OpWrite) -> Option<usize> {
+        let capability = self.inner.info().full_capability();
+        args.buffer().map(|mut size| {
+            if let Some(v) = capability.write_multi_max_size {
+                size = size.min(v);
+            }
+            if let Some(v) = capability.write_multi_min_size {
+                size = size.max(v);
+            }
+            size
+        })
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -127,59 +473,229 @@ impl<A: Accessor> LayeredAccessor for ThrottleAccessor<A> {
         &self.inner
     }
 
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.request_limiter
+            .throttle(
+                Operation::CreateDir,
+                path,
+                self.inner.create_dir(path, args),
+            )
+            .await
+    }
+
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
         let limiter = self.rate_limiter.clone();
 
-        self.inner
-            .read(path, args)
+        self.request_limiter
+            .throttle(Operation::Read, path, self.inner.read(path, args))
             .await
-            .map(|(rp, r)| (rp, ThrottleWrapper::new(r, limiter)))
+            .map(|(rp, r)| (rp, ThrottleWrapper::with_limiter(r, limiter)))
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
         let limiter = self.rate_limiter.clone();
+        let part_size = self.part_size(&args);
+
+        self.request_limiter
+            .throttle(Operation::Write, path, self.inner.write(path, args))
+            .await
+            .map(|(rp, w)| {
+                let w = ThrottleWrapper::with_limiter(w, limiter).with_requests(
+                    self.request_limiter.clone(),
+                    path,
+                    part_size,
+                );
+                (rp, w)
+            })
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.request_limiter
+            .throttle(Operation::Copy, to, self.inner.copy(from, to, args))
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.request_limiter
+            .throttle(Operation::Rename, to, self.inner.rename(from, to, args))
+            .await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.request_limiter
+            .throttle(Operation::Stat, path, self.inner.stat(path, args))
+            .await
+    }
 
-        self.inner
-            .write(path, args)
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.request_limiter
+            .throttle(Operation::Delete, path, self.inner.delete(path, args))
             .await
-            .map(|(rp, w)| (rp, ThrottleWrapper::new(w, limiter)))
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
-        self.inner.list(path, args).await
+        self.request_limiter
+            .throttle(Operation::List, path, self.inner.list(path, args))
+            .await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.request_limiter
+            .throttle(Operation::Batch, "", self.inner.batch(args))
+            .await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.request_limiter
+            .throttle(Operation::Presign, path, self.inner.presign(path, args))
+            .await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingCreateDir, path, || {
+                self.inner.blocking_create_dir(path, args)
+            })
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
         let limiter = self.rate_limiter.clone();
 
-        self.inner
-            .blocking_read(path, args)
-            .map(|(rp, r)| (rp, ThrottleWrapper::new(r, limiter)))
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingRead, path, || {
+                self.inner.blocking_read(path, args)
+            })
+            .map(|(rp, r)| (rp, ThrottleWrapper::with_limiter(r, limiter)))
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
         let limiter = self.rate_limiter.clone();
+        let part_size = self.part_size(&args);
+
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingWrite, path, || {
+                self.inner.blocking_write(path, args)
+            })
+            .map(|(rp, w)| {
+                let w = ThrottleWrapper::with_limiter(w, limiter).with_requests(
+                    self.request_limiter.clone(),
+                    path,
+                    part_size,
+                );
+                (rp, w)
+            })
+    }
 
-        self.inner
-            .blocking_write(path, args)
-            .map(|(rp, w)| (rp, ThrottleWrapper::new(w, limiter)))
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingCopy, to, || {
+                self.inner.blocking_copy(from, to, args)
+            })
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingRename, to, || {
+                self.inner.blocking_rename(from, to, args)
+            })
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingStat, path, || {
+                self.inner.blocking_stat(path, args)
+            })
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingDelete, path, || {
+                self.inner.blocking_delete(path, args)
+            })
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
-        self.inner.blocking_list(path, args)
+        self.request_limiter
+            .blocking_throttle(Operation::BlockingList, path, || {
+                self.inner.blocking_list(path, args)
+            })
     }
 }
 
 pub struct ThrottleWrapper<R> {
     inner: R,
-    limiter: SharedRateLimiter,
+    limiter: Option<SharedRateLimiter>,
+
+    /// Request quotas that every part and close should acquire.
+    request_limiter: Option<Arc<RequestLimiter>>,
+    buckets: Vec<Arc<RequestBucket>>,
+    acquire: Option<BoxedFuture<()>>,
+    acquired: bool,
+    /// Size of parts, and the bytes written since the last counted part.
+    part_size: Option<usize>,
+    part_written: usize,
 }
 
+/// # Safety
+///
+/// We will only take `&mut Self` reference for ThrottleWrapper.
+unsafe impl<R: Sync> Sync for ThrottleWrapper<R> {}
+
 impl<R> ThrottleWrapper<R> {
     pub fn new(inner: R, rate_limiter: SharedRateLimiter) -> Self {
+        Self::with_limiter(inner, Some(rate_limiter))
+    }
+
+    fn with_limiter(inner: R, rate_limiter: Option<SharedRateLimiter>) -> Self {
         Self {
             inner,
             limiter: rate_limiter,
+
+            request_limiter: None,
+            buckets: Vec::new(),
+            acquire: None,
+            acquired: false,
+            part_size: None,
+            part_written: 0,
+        }
+    }
+
+    /// Count every part and close as a `Write` request on path.
+    fn with_requests(
+        mut self,
+        request_limiter: Arc<RequestLimiter>,
+        path: &str,
+        part_size: Option<usize>,
+    ) -> Self {
+        self.buckets = request_limiter.buckets(Operation::Write, path);
+        self.request_limiter = Some(request_limiter);
+        self.part_size = part_size;
+        self
+    }
+
+    /// Whether a full part has been written, so that the next write will
+    /// upload it.
+    fn part_ready(&self) -> bool {
+        matches!(self.part_size, Some(size) if self.part_written >= size)
+    }
+
+    /// Update the written bytes after writing, `counted` means a part request
+    /// has been acquired for this write.
+    fn finish_write(&mut self, counted: bool, res: &Result<usize>) {
+        if counted {
+            if let Some(size) = self.part_size {
+                self.part_written -= size;
+            }
+            self.feedback(res);
+        }
+        if let Ok(n) = res {
+            self.part_written += n;
+        }
+    }
+
+    fn feedback<T>(&self, res: &Result<T>) {
+        if let Some(request_limiter) = &self.request_limiter {
+            request_limiter.feedback(&self.buckets, res);
         }
     }
 }
@@ -214,12 +730,34 @@ impl<R: oio::BlockingRead> oio::BlockingRead for ThrottleWrapper<R> {
     }
 }
 
-impl<R: oio::Write> oio::Write for ThrottleWrapper<R> {
-    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+impl<R: oio::Write> ThrottleWrapper<R> {
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.buckets.is_empty() || self.acquired {
+            return Poll::Ready(());
+        }
+
+        let buckets = self.buckets.clone();
+        let fut = self.acquire.get_or_insert_with(|| {
+            Box::pin(async move { RequestLimiter::acquire(&buckets).await })
+        });
+        ready!(fut.as_mut().poll(cx));
+        self.acquire = None;
+        self.acquired = true;
+        Poll::Ready(())
+    }
+
+    fn poll_write_inner(
+        &mut self,
+        cx: &mut Context<'_>,
+        bs: &dyn oio::WriteBuf,
+    ) -> Poll<Result<usize>> {
+        let Some(limiter) = &self.limiter else {
+            return self.inner.poll_write(cx, bs);
+        };
         let buf_length = NonZeroU32::new(bs.remaining() as u32).unwrap();
 
         loop {
-            match self.limiter.check_n(buf_length) {
+            match limiter.check_n(buf_length) {
                 Ok(res) => match res {
                     Ok(_) => return self.inner.poll_write(cx, bs),
                     // the query is valid but the Decider can not accommodate them.
@@ -239,22 +777,44 @@ impl<R: oio::Write> oio::Write for ThrottleWrapper<R> {
             }
         }
     }
+}
+
+impl<R: oio::Write> oio::Write for ThrottleWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let counted = self.acquired || self.part_ready();
+        if counted {
+            ready!(self.poll_acquire(cx));
+        }
+
+        let res = ready!(self.poll_write_inner(cx, bs));
+        self.acquired = false;
+        self.finish_write(counted, &res);
+        Poll::Ready(res)
+    }
 
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_abort(cx)
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        self.inner.poll_close(cx)
+        ready!(self.poll_acquire(cx));
+
+        let res = ready!(self.inner.poll_close(cx));
+        self.acquired = false;
+        self.feedback(&res);
+        Poll::Ready(res)
     }
 }
 
-impl<R: oio::BlockingWrite> oio::BlockingWrite for ThrottleWrapper<R> {
-    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+impl<R: oio::BlockingWrite> ThrottleWrapper<R> {
+    fn write_inner(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let Some(limiter) = &self.limiter else {
+            return self.inner.write(bs);
+        };
         let buf_length = NonZeroU32::new(bs.remaining() as u32).unwrap();
 
         loop {
-            match self.limiter.check_n(buf_length) {
+            match limiter.check_n(buf_length) {
                 Ok(res) => match res {
                     Ok(_) => return self.inner.write(bs),
                     // the query is valid but the Decider can not accommodate them.
@@ -271,8 +831,133 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for ThrottleWrapper<R> {
             }
         }
     }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for ThrottleWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let counted = self.part_ready();
+        if counted {
+            RequestLimiter::blocking_acquire(&self.buckets);
+        }
+
+        let res = self.write_inner(bs);
+        self.finish_write(counted, &res);
+        res
+    }
 
     fn close(&mut self) -> Result<()> {
-        self.inner.close()
+        RequestLimiter::blocking_acquire(&self.buckets);
+
+        let res = self.inner.close();
+        self.feedback(&res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_request_quota() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(
+                ThrottleLayer::default()
+                    .with_prefix_quota("slow/", [Operation::Stat], 10)
+                    .with_partition_depth(2),
+            )
+            .finish();
+
+        // The burst of quota is its rate, the next 10 requests need about a second.
+        let now = Instant::now();
+        for _ in 0..20 {
+            let _ = op.stat("slow/a/file").await;
+        }
+        assert!(now.elapsed() >= Duration::from_millis(800));
+
+        // Paths in other partitions or without quota are not limited.
+        let now = Instant::now();
+        for _ in 0..10 {
+            let _ = op.stat("slow/b/file").await;
+        }
+        for _ in 0..100 {
+            let _ = op.stat("fast/file").await;
+            let _ = op.list("slow/a/").await;
+        }
+        assert!(now.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_request_quota_writer() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(ThrottleLayer::default().with_quota([Operation::Write], 10))
+            .finish();
+
+        // Opening and closing the writer cost one request each, writes
+        // without buffer are not counted.
+        let now = Instant::now();
+        let mut w = op.writer("file").await.unwrap();
+        for _ in 0..100 {
+            w.write("x").await.unwrap();
+        }
+        w.close().await.unwrap();
+        assert!(now.elapsed() < Duration::from_millis(500));
+
+        // Every buffered part costs one request.
+        let now = Instant::now();
+        let mut w = op.writer_with("file").buffer(2).await.unwrap();
+        for _ in 0..40 {
+            w.write("x").await.unwrap();
+        }
+        w.close().await.unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_evict_idle_buckets() {
+        let rate = NonZeroU32::new(10).unwrap();
+        let mut buckets: HashMap<_, _> = (0..10)
+            .map(|i| (i.to_string(), Arc::new(RequestBucket::new(rate))))
+            .collect();
+        let in_use = buckets["0"].clone();
+
+        evict_idle_buckets(&mut buckets, Instant::now());
+        assert_eq!(buckets.len(), 10);
+
+        // Buckets used by writers are kept.
+        evict_idle_buckets(&mut buckets, Instant::now() + BUCKET_IDLE_TIMEOUT);
+        assert_eq!(buckets.len(), 1);
+        assert!(Arc::ptr_eq(&buckets["0"], &in_use));
+    }
+
+    #[test]
+    fn test_request_bucket_aimd() {
+        let bucket = RequestBucket::new(NonZeroU32::new(100).unwrap());
+
+        bucket.decrease();
+        assert_eq!(bucket.rate(), 50);
+        bucket.decrease();
+        assert_eq!(bucket.rate(), 25);
+
+        // Increase by a tenth of quota after a second worth of successes.
+        for _ in 0..24 {
+            bucket.increase();
+        }
+        assert_eq!(bucket.rate(), 25);
+        bucket.increase();
+        assert_eq!(bucket.rate(), 35);
+
+        for _ in 0..1000 {
+            bucket.increase();
+        }
+        assert_eq!(bucket.rate(), 100);
+
+        for _ in 0..10 {
+            bucket.decrease();
+        }
+        assert_eq!(bucket.rate(), 1);
     }
 }
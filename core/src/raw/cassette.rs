@@ -0,0 +1,640 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Cassette format shared by `RecordLayer` and `ReplayService`.
+
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::task::Context;
+use std::task::Poll;
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use bytes::Bytes;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::raw::*;
+use crate::*;
+
+/// The version of cassette format written by `RecordLayer`.
+pub(crate) const CASSETTE_VERSION: u32 = 1;
+
+/// Call is the key used to match interactions.
+#[derive(Debug, Clone)]
+pub(crate) struct Call {
+    pub(crate) operation: &'static str,
+    pub(crate) path: String,
+    pub(crate) target: Option<String>,
+    pub(crate) args: Value,
+}
+
+impl Call {
+    pub(crate) fn new(op: Operation, path: &str) -> Self {
+        Self {
+            operation: op.into_static(),
+            path: path.to_string(),
+            target: None,
+            args: Value::Null,
+        }
+    }
+
+    pub(crate) fn with_target(mut self, target: &str) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    pub(crate) fn with_args(mut self, args: Value) -> Self {
+        self.args = args;
+        self
+    }
+
+    pub(crate) fn matches(&self, interaction: &Interaction) -> bool {
+        self.operation == interaction.operation
+            && self.path == interaction.path
+            && self.target == interaction.target
+            && self.args == interaction.args
+    }
+
+    pub(crate) fn mismatch(&self, message: &str) -> Error {
+        let mut err = Error::new(ErrorKind::Unexpected, message)
+            .with_operation(self.operation)
+            .with_context("path", &self.path);
+        if let Some(target) = &self.target {
+            err = err.with_context("target", target);
+        }
+        if !self.args.is_null() {
+            err = err.with_context("args", self.args.to_string());
+        }
+        err
+    }
+}
+
+/// Build args in json object, `None` values will be skipped.
+#[derive(Default)]
+pub(crate) struct Args(Map<String, Value>);
+
+impl Args {
+    pub(crate) fn with(mut self, key: &str, value: Option<impl Into<Value>>) -> Self {
+        if let Some(v) = value {
+            self.0.insert(key.to_string(), v.into());
+        }
+        self
+    }
+
+    pub(crate) fn build(self) -> Value {
+        if self.0.is_empty() {
+            Value::Null
+        } else {
+            Value::Object(self.0)
+        }
+    }
+}
+
+pub(crate) fn stat_args(args: &OpStat) -> Value {
+    Args::default()
+        .with("if_match", args.if_match())
+        .with("if_none_match", args.if_none_match())
+        .with("version", args.version())
+        .with(
+            "override_content_disposition",
+            args.override_content_disposition(),
+        )
+        .with("override_cache_control", args.override_cache_control())
+        .with("override_content_type", args.override_content_type())
+        .build()
+}
+
+pub(crate) fn read_args(args: &OpRead) -> Value {
+    let range = args.range();
+    Args::default()
+        .with("offset", range.offset())
+        .with("size", range.size())
+        .with("if_match", args.if_match())
+        .with("if_none_match", args.if_none_match())
+        .with("version", args.version())
+        .with(
+            "override_content_disposition",
+            args.override_content_disposition(),
+        )
+        .with("override_cache_control", args.override_cache_control())
+        .with("override_content_type", args.override_content_type())
+        .build()
+}
+
+pub(crate) fn write_args(args: &OpWrite) -> Value {
+    Args::default()
+        .with("append", args.append().then_some(true))
+        .with("content_type", args.content_type())
+        .with("content_disposition", args.content_disposition())
+        .with("cache_control", args.cache_control())
+        .build()
+}
+
+pub(crate) fn delete_args(args: &OpDelete) -> Value {
+    Args::default().with("version", args.version()).build()
+}
+
+pub(crate) fn list_args(args: &OpList) -> Value {
+    Args::default()
+        .with("recursive", args.recursive().then_some(true))
+        .with("limit", args.limit())
+        .with("start_after", args.start_after())
+        .build()
+}
+
+pub(crate) fn batch_args(args: &OpBatch) -> Value {
+    let ops = args
+        .operation()
+        .iter()
+        .map(|(path, op)| {
+            let args = match op {
+                BatchOperation::Delete(args) => delete_args(args),
+            };
+            serde_json::json!({
+                "operation": op.operation().into_static(),
+                "path": path,
+                "args": args,
+            })
+        })
+        .collect::<Vec<_>>();
+    Args::default().with("operations", Some(ops)).build()
+}
+
+pub(crate) fn presign_args(args: &OpPresign) -> Value {
+    let (kind, op_args) = match args.operation() {
+        PresignOperation::Stat(v) => ("stat", stat_args(v)),
+        PresignOperation::Read(v) => ("read", read_args(v)),
+        PresignOperation::Write(v) => ("write", write_args(v)),
+    };
+    Args::default()
+        .with("kind", Some(kind))
+        .with("expire", Some(args.expire().as_secs()))
+        .with("args", (!op_args.is_null()).then_some(op_args))
+        .build()
+}
+
+pub(crate) fn to_value<T: Serialize>(v: T) -> Value {
+    serde_json::to_value(v).expect("recorded value must be serializable")
+}
+
+pub(crate) fn from_value<T: for<'de> Deserialize<'de>>(v: Value) -> Result<T> {
+    serde_json::from_value(v).map_err(|err| {
+        Error::new(ErrorKind::Unexpected, "recorded result is invalid").set_source(err)
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Cassette {
+    pub(crate) version: u32,
+    pub(crate) info: CassetteInfo,
+    pub(crate) interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct CassetteInfo {
+    pub(crate) scheme: String,
+    pub(crate) root: String,
+    pub(crate) name: String,
+    pub(crate) capability: RecordedCapability,
+}
+
+impl Debug for CassetteInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CassetteInfo")
+            .field("scheme", &self.scheme)
+            .field("root", &self.root)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// RecordedCapability is the serializable form of [`Capability`].
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct RecordedCapability {
+    stat: bool,
+    stat_with_if_match: bool,
+    stat_with_if_none_match: bool,
+    stat_with_override_cache_control: bool,
+    stat_with_override_content_disposition: bool,
+    stat_with_override_content_type: bool,
+    read: bool,
+    read_can_seek: bool,
+    read_can_next: bool,
+    read_with_range: bool,
+    read_with_if_match: bool,
+    read_with_if_none_match: bool,
+    read_with_override_cache_control: bool,
+    read_with_override_content_disposition: bool,
+    read_with_override_content_type: bool,
+    write: bool,
+    write_can_multi: bool,
+    write_can_empty: bool,
+    write_can_append: bool,
+    write_with_content_type: bool,
+    write_with_content_disposition: bool,
+    write_with_cache_control: bool,
+    write_multi_max_size: Option<usize>,
+    write_multi_min_size: Option<usize>,
+    write_multi_align_size: Option<usize>,
+    write_total_max_size: Option<usize>,
+    create_dir: bool,
+    delete: bool,
+    copy: bool,
+    rename: bool,
+    list: bool,
+    list_with_limit: bool,
+    list_with_start_after: bool,
+    list_with_recursive: bool,
+    presign: bool,
+    presign_read: bool,
+    presign_stat: bool,
+    presign_write: bool,
+    batch: bool,
+    batch_delete: bool,
+    batch_max_operations: Option<usize>,
+    blocking: bool,
+}
+
+impl From<Capability> for RecordedCapability {
+    fn from(v: Capability) -> Self {
+        Self {
+            stat: v.stat,
+            stat_with_if_match: v.stat_with_if_match,
+            stat_with_if_none_match: v.stat_with_if_none_match,
+            stat_with_override_cache_control: v.stat_with_override_cache_control,
+            stat_with_override_content_disposition: v.stat_with_override_content_disposition,
+            stat_with_override_content_type: v.stat_with_override_content_type,
+            read: v.read,
+            read_can_seek: v.read_can_seek,
+            read_can_next: v.read_can_next,
+            read_with_range: v.read_with_range,
+            read_with_if_match: v.read_with_if_match,
+            read_with_if_none_match: v.read_with_if_none_match,
+            read_with_override_cache_control: v.read_with_override_cache_control,
+            read_with_override_content_disposition: v.read_with_override_content_disposition,
+            read_with_override_content_type: v.read_with_override_content_type,
+            write: v.write,
+            write_can_multi: v.write_can_multi,
+            write_can_empty: v.write_can_empty,
+            write_can_append: v.write_can_append,
+            write_with_content_type: v.write_with_content_type,
+            write_with_content_disposition: v.write_with_content_disposition,
+            write_with_cache_control: v.write_with_cache_control,
+            write_multi_max_size: v.write_multi_max_size,
+            write_multi_min_size: v.write_multi_min_size,
+            write_multi_align_size: v.write_multi_align_size,
+            write_total_max_size: v.write_total_max_size,
+            create_dir: v.create_dir,
+            delete: v.delete,
+            copy: v.copy,
+            rename: v.rename,
+            list: v.list,
+            list_with_limit: v.list_with_limit,
+            list_with_start_after: v.list_with_start_after,
+            list_with_recursive: v.list_with_recursive,
+            presign: v.presign,
+            presign_read: v.presign_read,
+            presign_stat: v.presign_stat,
+            presign_write: v.presign_write,
+            batch: v.batch,
+            batch_delete: v.batch_delete,
+            batch_max_operations: v.batch_max_operations,
+            blocking: v.blocking,
+        }
+    }
+}
+
+impl From<RecordedCapability> for Capability {
+    fn from(v: RecordedCapability) -> Self {
+        Capability {
+            stat: v.stat,
+            stat_with_if_match: v.stat_with_if_match,
+            stat_with_if_none_match: v.stat_with_if_none_match,
+            stat_with_override_cache_control: v.stat_with_override_cache_control,
+            stat_with_override_content_disposition: v.stat_with_override_content_disposition,
+            stat_with_override_content_type: v.stat_with_override_content_type,
+            read: v.read,
+            read_can_seek: v.read_can_seek,
+            read_can_next: v.read_can_next,
+            read_with_range: v.read_with_range,
+            read_with_if_match: v.read_with_if_match,
+            read_with_if_none_match: v.read_with_if_none_match,
+            read_with_override_cache_control: v.read_with_override_cache_control,
+            read_with_override_content_disposition: v.read_with_override_content_disposition,
+            read_with_override_content_type: v.read_with_override_content_type,
+            write: v.write,
+            write_can_multi: v.write_can_multi,
+            write_can_empty: v.write_can_empty,
+            write_can_append: v.write_can_append,
+            write_with_content_type: v.write_with_content_type,
+            write_with_content_disposition: v.write_with_content_disposition,
+            write_with_cache_control: v.write_with_cache_control,
+            write_multi_max_size: v.write_multi_max_size,
+            write_multi_min_size: v.write_multi_min_size,
+            write_multi_align_size: v.write_multi_align_size,
+            write_total_max_size: v.write_total_max_size,
+            create_dir: v.create_dir,
+            delete: v.delete,
+            copy: v.copy,
+            rename: v.rename,
+            list: v.list,
+            list_with_limit: v.list_with_limit,
+            list_with_start_after: v.list_with_start_after,
+            list_with_recursive: v.list_with_recursive,
+            presign: v.presign,
+            presign_read: v.presign_read,
+            presign_stat: v.presign_stat,
+            presign_write: v.presign_write,
+            batch: v.batch,
+            batch_delete: v.batch_delete,
+            batch_max_operations: v.batch_max_operations,
+            blocking: v.blocking,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Interaction {
+    pub(crate) operation: String,
+    pub(crate) path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) target: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub(crate) args: Value,
+    pub(crate) result: std::result::Result<Value, RecordedError>,
+    /// Base64 encoded content of read and write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data: Option<String>,
+    /// Whether `data` only keeps a part of the content.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) truncated: bool,
+}
+
+impl Interaction {
+    pub(crate) fn data(&self) -> Result<Option<Bytes>> {
+        let Some(data) = &self.data else {
+            return Ok(None);
+        };
+        let bs = general_purpose::STANDARD.decode(data).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "recorded data is invalid").set_source(err)
+        })?;
+        Ok(Some(Bytes::from(bs)))
+    }
+
+    pub(crate) fn into_result(self) -> Result<Value> {
+        self.result.map_err(RecordedError::into_error)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RecordedError {
+    pub(crate) kind: String,
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) temporary: bool,
+}
+
+impl RecordedError {
+    pub(crate) fn new(err: &Error) -> Self {
+        Self {
+            kind: err.kind().into_static().to_string(),
+            message: err.to_string(),
+            temporary: err.is_temporary(),
+        }
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        let kind = match self.kind.as_str() {
+            "Unsupported" => ErrorKind::Unsupported,
+            "ConfigInvalid" => ErrorKind::ConfigInvalid,
+            "NotFound" => ErrorKind::NotFound,
+            "PermissionDenied" => ErrorKind::PermissionDenied,
+            "IsADirectory" => ErrorKind::IsADirectory,
+            "NotADirectory" => ErrorKind::NotADirectory,
+            "AlreadyExists" => ErrorKind::AlreadyExists,
+            "RateLimited" => ErrorKind::RateLimited,
+            "IsSameFile" => ErrorKind::IsSameFile,
+            "ConditionNotMatch" => ErrorKind::ConditionNotMatch,
+            "ContentTruncated" => ErrorKind::ContentTruncated,
+            "ContentIncomplete" => ErrorKind::ContentIncomplete,
+            "InvalidInput" => ErrorKind::InvalidInput,
+            _ => ErrorKind::Unexpected,
+        };
+
+        let err = Error::new(kind, &self.message);
+        if self.temporary {
+            err.set_temporary()
+        } else {
+            err
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct RecordedMetadata {
+    pub(crate) mode: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) complete: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_md5: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cache_control: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_disposition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
+}
+
+impl RecordedMetadata {
+    pub(crate) fn new(meta: Metadata) -> Self {
+        let mode = match meta.mode() {
+            EntryMode::FILE => "file",
+            EntryMode::DIR => "dir",
+            EntryMode::Unknown => "unknown",
+        };
+
+        let mut v = RecordedMetadata {
+            mode: mode.to_string(),
+            complete: meta.metakey().contains(Metakey::Complete),
+            ..Default::default()
+        };
+        if meta.contains_metakey(Metakey::ContentLength) {
+            v.content_length = Some(meta.content_length());
+        }
+        if meta.contains_metakey(Metakey::ContentType) {
+            v.content_type = meta.content_type().map(|v| v.to_string());
+        }
+        if meta.contains_metakey(Metakey::ContentMd5) {
+            v.content_md5 = meta.content_md5().map(|v| v.to_string());
+        }
+        if meta.contains_metakey(Metakey::Etag) {
+            v.etag = meta.etag().map(|v| v.to_string());
+        }
+        if meta.contains_metakey(Metakey::LastModified) {
+            v.last_modified = meta.last_modified().map(|v| v.to_rfc3339());
+        }
+        if meta.contains_metakey(Metakey::CacheControl) {
+            v.cache_control = meta.cache_control().map(|v| v.to_string());
+        }
+        if meta.contains_metakey(Metakey::ContentDisposition) {
+            v.content_disposition = meta.content_disposition().map(|v| v.to_string());
+        }
+        if meta.contains_metakey(Metakey::Version) {
+            v.version = meta.version().map(|v| v.to_string());
+        }
+        v
+    }
+
+    pub(crate) fn into_metadata(self) -> Result<Metadata> {
+        let mode = match self.mode.as_str() {
+            "file" => EntryMode::FILE,
+            "dir" => EntryMode::DIR,
+            _ => EntryMode::Unknown,
+        };
+
+        let mut meta = Metadata::new(mode);
+        if let Some(v) = self.content_length {
+            meta.set_content_length(v);
+        }
+        if let Some(v) = &self.content_type {
+            meta.set_content_type(v);
+        }
+        if let Some(v) = &self.content_md5 {
+            meta.set_content_md5(v);
+        }
+        if let Some(v) = &self.etag {
+            meta.set_etag(v);
+        }
+        if let Some(v) = &self.last_modified {
+            let v = DateTime::parse_from_rfc3339(v).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "recorded last modified is invalid")
+                    .set_source(err)
+            })?;
+            meta.set_last_modified(v.with_timezone(&Utc));
+        }
+        if let Some(v) = &self.cache_control {
+            meta.set_cache_control(v);
+        }
+        if let Some(v) = &self.content_disposition {
+            meta.set_content_disposition(v);
+        }
+        if let Some(v) = &self.version {
+            meta.set_version(v);
+        }
+
+        if self.complete {
+            meta = meta.with_metakey(Metakey::Complete);
+        }
+        Ok(meta)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RecordedEntry {
+    pub(crate) path: String,
+    pub(crate) metadata: RecordedMetadata,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RecordedBatchResult {
+    pub(crate) path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<RecordedError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RecordedPresign {
+    pub(crate) method: String,
+    pub(crate) uri: String,
+    #[serde(default)]
+    pub(crate) headers: BTreeMap<String, String>,
+}
+
+impl RecordedPresign {
+    pub(crate) fn new(req: PresignedRequest) -> Self {
+        Self {
+            method: req.method().to_string(),
+            uri: req.uri().to_string(),
+            headers: req
+                .header()
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.to_string(),
+                        String::from_utf8_lossy(v.as_bytes()).to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn into_presigned_request(self) -> Result<PresignedRequest> {
+        let invalid = |err: http::Error| {
+            Error::new(ErrorKind::Unexpected, "recorded presign is invalid").set_source(err)
+        };
+
+        let method = http::Method::from_str(&self.method).map_err(|err| invalid(err.into()))?;
+        let uri = http::Uri::from_str(&self.uri).map_err(|err| invalid(err.into()))?;
+        let mut headers = http::HeaderMap::new();
+        for (k, v) in self.headers {
+            headers.insert(
+                http::HeaderName::from_str(&k).map_err(|err| invalid(err.into()))?,
+                http::HeaderValue::from_str(&v).map_err(|err| invalid(err.into()))?,
+            );
+        }
+        Ok(PresignedRequest::new(method, uri, headers))
+    }
+}
+
+/// CassetteLister returns entries that have been collected in advance.
+pub struct CassetteLister {
+    entries: VecDeque<oio::Entry>,
+}
+
+impl CassetteLister {
+    pub(crate) fn new(entries: VecDeque<oio::Entry>) -> Self {
+        Self { entries }
+    }
+}
+
+impl oio::List for CassetteLister {
+    fn poll_next(&mut self, _: &mut Context<'_>) -> Poll<Result<Option<oio::Entry>>> {
+        Poll::Ready(Ok(self.entries.pop_front()))
+    }
+}
+
+impl oio::BlockingList for CassetteLister {
+    fn next(&mut self) -> Result<Option<oio::Entry>> {
+        Ok(self.entries.pop_front())
+    }
+}
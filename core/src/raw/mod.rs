@@ -76,6 +76,10 @@ pub use futures_util::ConcurrentFutures;
 mod enum_utils;
 pub use enum_utils::*;
 
+// Replay related items are only used with `tests` feature.
+#[cfg_attr(not(feature = "tests"), allow(dead_code))]
+pub(crate) mod cassette;
+
 // Expose as a pub mod to avoid confusing.
 pub mod adapters;
 pub mod oio;
@@ -75,6 +75,16 @@ impl Entry {
         self.meta.mode()
     }
 
+    /// Get the metadata of entry.
+    pub fn metadata(&self) -> &Metadata {
+        &self.meta
+    }
+
+    /// Get the mutable metadata of entry.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.meta
+    }
+
     /// Consume self to convert into an Entry.
     ///
     /// NOTE: implement this by hand to avoid leaking raw entry to end-users.
@@ -37,6 +37,12 @@ use crate::*;
 /// The `seek` operation on `RangeReader` is zero cost and purely in-memory. But calling `seek`
 /// while there is a pending read request will cancel the request and start a new one. This could
 /// add extra cost to the read operation.
+///
+/// The strong etag returned by the first read request will be captured and sent as `if_match`
+/// while resuming read from current position (for example, after an interrupted read has been
+/// retried) if the service supports `read_with_if_match`. So we will return `ConditionNotMatch`
+/// instead of mixing the content of different versions. Weak etags (`W/"..."`) are skipped
+/// since they can't be used to compare byte ranges.
 pub struct RangeReader<A: Accessor, R> {
     acc: Arc<A>,
     path: Arc<String>,
@@ -45,6 +51,10 @@ pub struct RangeReader<A: Accessor, R> {
     offset: Option<u64>,
     size: Option<u64>,
     cur: u64,
+    /// The strong etag captured from the service, `None` if the service
+    /// doesn't support `read_with_if_match`.
+    etag: Option<String>,
+    if_match_supported: bool,
     state: State<R>,
 }
 
@@ -81,6 +91,7 @@ where
     /// This operation is not zero cost. If the accessor already returns a
     /// seekable reader, please don't use this.
     pub fn new(acc: Arc<A>, path: &str, op: OpRead) -> RangeReader<A, R> {
+        let if_match_supported = acc.info().full_capability().read_with_if_match;
         // Normalize range like `..` into `0..` to make sure offset is valid.
         let (offset, size) = match (op.range().offset(), op.range().size()) {
             (None, None) => (Some(0), None),
@@ -95,6 +106,8 @@ where
             offset,
             size,
             cur: 0,
+            etag: None,
+            if_match_supported,
             state: State::<R>::Idle,
         }
     }
@@ -173,6 +186,17 @@ where
         }
     }
 
+    /// Ensure etag will use the strong etag returned by the first `RpRead`.
+    ///
+    /// The etag will be used as `if_match` while resuming read so that we can
+    /// make sure the file is not changed.
+    fn ensure_etag(&mut self, etag: Option<&str>) {
+        if !self.if_match_supported || self.etag.is_some() {
+            return;
+        }
+        self.etag = etag.filter(|v| !v.starts_with("W/")).map(|v| v.to_string());
+    }
+
     /// Calculate the current range, maybe sent as next read request.
     ///
     /// # Panics
@@ -204,6 +228,11 @@ where
         // the op into deterministic to avoid ETag changes.
         if self.cur != 0 {
             op = op.into_deterministic();
+            // Resume read only if the file is still the one we have read.
+            // Users' if_match will be kept if it has been set.
+            if let (None, Some(etag)) = (self.op.if_match(), &self.etag) {
+                op = op.with_if_match(etag);
+            }
         }
         // Alter OpRead with correct calculated range.
         op = op.with_range(self.calculate_range());
@@ -245,6 +274,11 @@ where
         // the op into deterministic to avoid ETag changes.
         if self.cur != 0 {
             op = op.into_deterministic();
+            // Resume read only if the file is still the one we have read.
+            // Users' if_match will be kept if it has been set.
+            if let (None, Some(etag)) = (self.op.if_match(), &self.etag) {
+                op = op.with_if_match(etag);
+            }
         }
         // Alter OpRead with correct calculated range.
         op = op.with_range(self.calculate_range());
@@ -318,6 +352,7 @@ where
                 })?;
 
                 self.ensure_size(rp.range().unwrap_or_default().size(), rp.size());
+                self.ensure_etag(rp.etag());
 
                 self.state = State::Read(r);
                 self.poll_read(cx, buf)
@@ -445,6 +480,7 @@ where
 
                 // Set size if read returns size hint.
                 self.ensure_size(rp.range().unwrap_or_default().size(), rp.size());
+                self.ensure_etag(rp.etag());
 
                 self.state = State::Read(r);
                 self.poll_next(cx)
@@ -493,6 +529,7 @@ where
 
                 // Set size if read returns size hint.
                 self.ensure_size(rp.range().unwrap_or_default().size(), rp.size());
+                self.ensure_etag(rp.etag());
 
                 self.state = State::Read(r);
                 self.read(buf)
@@ -601,6 +638,7 @@ where
                 let r = match self.read_action() {
                     Ok((rp, r)) => {
                         self.ensure_size(rp.range().unwrap_or_default().size(), rp.size());
+                        self.ensure_etag(rp.etag());
                         r
                     }
                     Err(err) => return Some(Err(err)),
@@ -661,11 +699,26 @@ mod tests {
     #[derive(Debug, Clone, Default)]
     struct MockReadService {
         data: Bytes,
+        etag: Option<String>,
+        /// The if_match of every read request.
+        if_matches: Arc<std::sync::Mutex<Vec<Option<String>>>>,
     }
 
     impl MockReadService {
         fn new(data: Bytes) -> Self {
-            Self { data }
+            Self {
+                data,
+                ..Default::default()
+            }
+        }
+
+        fn with_etag(mut self, etag: &str) -> Self {
+            self.etag = Some(etag.to_string());
+            self
+        }
+
+        fn if_matches(&self) -> Vec<Option<String>> {
+            self.if_matches.lock().unwrap().clone()
         }
     }
 
@@ -682,6 +735,7 @@ mod tests {
             let mut am = AccessorInfo::default();
             am.set_native_capability(Capability {
                 read: true,
+                read_with_if_match: true,
                 ..Default::default()
             });
 
@@ -689,10 +743,14 @@ mod tests {
         }
 
         async fn read(&self, _: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+            self.if_matches
+                .lock()
+                .unwrap()
+                .push(args.if_match().map(|v| v.to_string()));
             let bs = args.range().apply_on_bytes(self.data.clone());
 
             Ok((
-                RpRead::new(),
+                RpRead::new().with_etag(self.etag.clone()),
                 MockReader {
                     inner: futures::io::Cursor::new(bs.into()),
                 },
@@ -827,4 +885,39 @@ mod tests {
 
         Ok(())
     }
+
+    async fn read_after_seek(acc: MockReadService) -> anyhow::Result<Vec<Option<String>>> {
+        let acc = Arc::new(acc);
+        let mut r = Box::new(RangeReader::new(
+            acc.clone(),
+            "x",
+            OpRead::default().with_range(BytesRange::from(..)),
+        )) as oio::Reader;
+
+        let mut buf = vec![0; 1024];
+        r.read_exact(&mut buf).await?;
+        r.seek(SeekFrom::Start(4096)).await?;
+        r.read_exact(&mut buf).await?;
+        Ok(acc.if_matches())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_strong_etag() -> anyhow::Result<()> {
+        let (bs, _) = gen_bytes();
+        let acc = MockReadService::new(bs).with_etag("\"abc\"");
+
+        let if_matches = read_after_seek(acc).await?;
+        assert_eq!(if_matches, vec![None, Some("\"abc\"".to_string())]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_weak_etag() -> anyhow::Result<()> {
+        let (bs, _) = gen_bytes();
+        let acc = MockReadService::new(bs).with_etag("W/\"abc\"");
+
+        let if_matches = read_after_seek(acc).await?;
+        assert_eq!(if_matches, vec![None, None]);
+        Ok(())
+    }
 }
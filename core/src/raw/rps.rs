@@ -116,6 +116,11 @@ pub struct RpRead {
     /// It's ok to leave range as empty, but it's recommended to set range if possible. We will use
     /// this range as hint to do some optimization like avoid an extra stat or read.
     range: Option<BytesContentRange>,
+    /// ETag is the etag of the file returned by this read operation.
+    ///
+    /// It's ok to leave etag as empty, but it's recommended to set etag if possible. We will use
+    /// this etag as `if_match` to make sure the file is not changed while resuming the read.
+    etag: Option<String>,
 }
 
 impl RpRead {
@@ -151,6 +156,17 @@ impl RpRead {
         self.range = range;
         self
     }
+
+    /// Got the etag of the file returned by this read operation.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Set the etag of the file returned by this read operation.
+    pub fn with_etag(mut self, etag: Option<String>) -> Self {
+        self.etag = etag;
+        self
+    }
 }
 
 /// Reply for `batch` operation.
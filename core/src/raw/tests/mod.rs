@@ -25,6 +25,11 @@ mod write;
 pub use write::WriteAction;
 pub use write::WriteChecker;
 
+mod replay;
+pub use replay::ReplayService;
+
 mod utils;
+pub use utils::init_record_layer;
+pub use utils::init_replay_service;
 pub use utils::init_test_service;
 pub use utils::TEST_RUNTIME;
@@ -0,0 +1,498 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::Value;
+
+use crate::raw::cassette::*;
+use crate::raw::*;
+use crate::*;
+
+/// Serve calls from a cassette recorded by [`RecordLayer`](crate::layers::RecordLayer).
+///
+/// Every call will be matched against the first unused interaction with the
+/// same operation, path and arguments. Calls without a matching interaction,
+/// reads whose content has been truncated while recording and writes with
+/// different content will fail with [`ErrorKind::Unexpected`].
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::raw::tests::ReplayService;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let fixtures = Operator::new(services::Memory::default())?.finish();
+/// # let layer = opendal::layers::RecordLayer::new(fixtures.clone(), "memory.json");
+/// # let op = Operator::new(services::Memory::default())?.layer(layer.clone()).finish();
+/// # op.write("test", "Hello, World!").await?;
+/// # layer.save().await?;
+///
+/// let op = ReplayService::load(&fixtures, "memory.json")
+///     .await?
+///     .into_operator();
+/// op.write("test", "Hello, World!").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReplayService {
+    info: AccessorInfo,
+    interactions: Arc<Mutex<Vec<(bool, Interaction)>>>,
+}
+
+impl ReplayService {
+    /// Load a cassette from given bytes.
+    pub fn from_slice(bs: &[u8]) -> Result<Self> {
+        let cassette: Cassette = serde_json::from_slice(bs).map_err(|err| {
+            Error::new(ErrorKind::ConfigInvalid, "cassette is invalid").set_source(err)
+        })?;
+        if cassette.version != CASSETTE_VERSION {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "cassette version is not supported",
+            )
+            .with_context("version", cassette.version.to_string()));
+        }
+
+        let mut info = AccessorInfo::default();
+        info.set_scheme(
+            Scheme::from_str(&cassette.info.scheme).unwrap_or(Scheme::Custom("replay")),
+        )
+        .set_root(&cassette.info.root)
+        .set_name(&cassette.info.name)
+        .set_native_capability(cassette.info.capability.into());
+
+        Ok(Self {
+            info,
+            interactions: Arc::new(Mutex::new(
+                cassette
+                    .interactions
+                    .into_iter()
+                    .map(|v| (false, v))
+                    .collect(),
+            )),
+        })
+    }
+
+    /// Load a cassette from `path` of given operator.
+    pub async fn load(op: &Operator, path: &str) -> Result<Self> {
+        let bs = op.read(path).await?;
+        Self::from_slice(&bs)
+    }
+
+    /// Load a cassette from `path` of given operator in blocking way.
+    pub fn blocking_load(op: &Operator, path: &str) -> Result<Self> {
+        let bs = op.blocking().read(path)?;
+        Self::from_slice(&bs)
+    }
+
+    /// Get the count of interactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.interactions
+            .lock()
+            .expect("lock must be acquired")
+            .iter()
+            .filter(|(used, _)| !used)
+            .count()
+    }
+
+    /// Build an [`Operator`] which serves all calls from this cassette.
+    pub fn into_operator(self) -> Operator {
+        OperatorBuilder::new(self).finish()
+    }
+
+    /// Take the first unused interaction that matches given call.
+    fn take(&self, call: &Call) -> Result<Interaction> {
+        let mut interactions = self.interactions.lock().expect("lock must be acquired");
+        let Some((used, interaction)) = interactions
+            .iter_mut()
+            .find(|(used, v)| !*used && call.matches(v))
+        else {
+            return Err(call.mismatch("no recorded interaction matches this call"));
+        };
+
+        *used = true;
+        Ok(interaction.clone())
+    }
+
+    fn replay(&self, call: &Call) -> Result<Value> {
+        self.take(call)?.into_result()
+    }
+
+    fn replay_create_dir(&self, op: Operation, path: &str) -> Result<RpCreateDir> {
+        self.replay(&Call::new(op, path))?;
+        Ok(RpCreateDir::default())
+    }
+
+    fn replay_stat(&self, call: Call) -> Result<RpStat> {
+        let meta: RecordedMetadata = from_value(self.replay(&call)?)?;
+        Ok(RpStat::new(meta.into_metadata()?))
+    }
+
+    fn replay_read(&self, call: Call) -> Result<(RpRead, oio::Cursor)> {
+        let interaction = self.take(&call)?;
+        if interaction.truncated {
+            return Err(call.mismatch("recorded read content has been truncated"));
+        }
+        let data = interaction.data()?.unwrap_or_default();
+        interaction.into_result()?;
+
+        Ok((
+            RpRead::new().with_size(Some(data.len() as u64)),
+            oio::Cursor::from(data),
+        ))
+    }
+
+    fn replay_write(&self, call: Call) -> Result<(RpWrite, ReplayWriter)> {
+        let interaction = self.take(&call)?;
+        // Writes failed while opening will not have data recorded.
+        let Some(expected) = interaction.data()? else {
+            interaction.into_result()?;
+            return Err(call.mismatch("recorded write doesn't have content"));
+        };
+
+        Ok((
+            RpWrite::default(),
+            ReplayWriter {
+                call,
+                expected,
+                truncated: interaction.truncated,
+                result: interaction.result,
+                buf: Vec::new(),
+            },
+        ))
+    }
+
+    fn replay_list(&self, call: Call) -> Result<(RpList, CassetteLister)> {
+        let entries: Vec<RecordedEntry> = from_value(self.replay(&call)?)?;
+        let entries = entries
+            .into_iter()
+            .map(|v| Ok(oio::Entry::with(v.path, v.metadata.into_metadata()?)))
+            .collect::<Result<_>>()?;
+
+        Ok((RpList::default(), CassetteLister::new(entries)))
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Accessor for ReplayService {
+    type Reader = oio::Cursor;
+    type BlockingReader = oio::Cursor;
+    type Writer = ReplayWriter;
+    type BlockingWriter = ReplayWriter;
+    type Lister = CassetteLister;
+    type BlockingLister = CassetteLister;
+
+    fn info(&self) -> AccessorInfo {
+        self.info.clone()
+    }
+
+    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        self.replay_create_dir(Operation::CreateDir, path)
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.replay_stat(Call::new(Operation::Stat, path).with_args(stat_args(&args)))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.replay_read(Call::new(Operation::Read, path).with_args(read_args(&args)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.replay_write(Call::new(Operation::Write, path).with_args(write_args(&args)))
+    }
+
+    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.replay(&Call::new(Operation::Copy, from).with_target(to))?;
+        Ok(RpCopy::default())
+    }
+
+    async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.replay(&Call::new(Operation::Rename, from).with_target(to))?;
+        Ok(RpRename::default())
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.replay(&Call::new(Operation::Delete, path).with_args(delete_args(&args)))?;
+        Ok(RpDelete::default())
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.replay_list(Call::new(Operation::List, path).with_args(list_args(&args)))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let call = Call::new(Operation::Presign, path).with_args(presign_args(&args));
+        let req: RecordedPresign = from_value(self.replay(&call)?)?;
+        Ok(RpPresign::new(req.into_presigned_request()?))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let call = Call::new(Operation::Batch, "").with_args(batch_args(&args));
+        let results: Vec<RecordedBatchResult> = from_value(self.replay(&call)?)?;
+        Ok(RpBatch::new(
+            results
+                .into_iter()
+                .map(|v| {
+                    let res = match v.error {
+                        None => Ok(RpDelete::default().into()),
+                        Some(err) => Err(err.into_error()),
+                    };
+                    (v.path, res)
+                })
+                .collect(),
+        ))
+    }
+
+    fn blocking_create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        self.replay_create_dir(Operation::BlockingCreateDir, path)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.replay_stat(Call::new(Operation::BlockingStat, path).with_args(stat_args(&args)))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.replay_read(Call::new(Operation::BlockingRead, path).with_args(read_args(&args)))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.replay_write(Call::new(Operation::BlockingWrite, path).with_args(write_args(&args)))
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.replay(&Call::new(Operation::BlockingCopy, from).with_target(to))?;
+        Ok(RpCopy::default())
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.replay(&Call::new(Operation::BlockingRename, from).with_target(to))?;
+        Ok(RpRename::default())
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.replay(&Call::new(Operation::BlockingDelete, path).with_args(delete_args(&args)))?;
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingLister)> {
+        self.replay_list(Call::new(Operation::BlockingList, path).with_args(list_args(&args)))
+    }
+}
+
+/// ReplayWriter buffers all written bytes and compares them with the recorded
+/// content while closing.
+///
+/// Only the recorded prefix will be compared if the content has been truncated.
+pub struct ReplayWriter {
+    call: Call,
+    expected: Bytes,
+    truncated: bool,
+    result: std::result::Result<Value, RecordedError>,
+    buf: Vec<u8>,
+}
+
+impl ReplayWriter {
+    fn write_buf(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let chunk = bs.chunk();
+        self.buf.extend_from_slice(chunk);
+        Ok(chunk.len())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let matched = if self.truncated {
+            self.buf.starts_with(&self.expected)
+        } else {
+            self.buf == self.expected
+        };
+        if !matched {
+            return Err(self
+                .call
+                .mismatch("written content mismatches the recorded content")
+                .with_context("expected_size", self.expected.len().to_string())
+                .with_context("actual_size", self.buf.len().to_string()));
+        }
+
+        self.result
+            .clone()
+            .map(|_| ())
+            .map_err(RecordedError::into_error)
+    }
+}
+
+impl oio::Write for ReplayWriter {
+    fn poll_write(&mut self, _: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        Poll::Ready(self.write_buf(bs))
+    }
+
+    fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.finish())
+    }
+
+    fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl oio::BlockingWrite for ReplayWriter {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.write_buf(bs)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::RecordLayer;
+    use crate::services::Memory;
+
+    fn new_recorder() -> (Operator, Operator, RecordLayer) {
+        let cassette = Operator::new(Memory::default()).unwrap().finish();
+        let layer = RecordLayer::new(cassette.clone(), "cassette.json");
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+        (op, cassette, layer)
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let (op, cassette, layer) = new_recorder();
+
+        op.write("dir/test", "Hello, World!").await.unwrap();
+        let meta = op.stat("dir/test").await.unwrap();
+        assert_eq!(op.read("dir/test").await.unwrap(), b"Hello, World!");
+        assert_eq!(
+            op.read_with("dir/test").range(0..5).await.unwrap(),
+            b"Hello"
+        );
+        let entries = op.list("dir/").await.unwrap();
+        let err = op.stat("not_exist").await.unwrap_err();
+        op.delete("dir/test").await.unwrap();
+        layer.save().await.unwrap();
+
+        let replay = ReplayService::load(&cassette, "cassette.json")
+            .await
+            .unwrap();
+        let op = replay.clone().into_operator();
+
+        op.write("dir/test", "Hello, World!").await.unwrap();
+        let replayed = op.stat("dir/test").await.unwrap();
+        assert_eq!(replayed.content_length(), meta.content_length());
+        assert_eq!(replayed.mode(), meta.mode());
+        assert_eq!(op.read("dir/test").await.unwrap(), b"Hello, World!");
+        assert_eq!(
+            op.read_with("dir/test").range(0..5).await.unwrap(),
+            b"Hello"
+        );
+        let replayed = op.list("dir/").await.unwrap();
+        assert_eq!(
+            replayed.iter().map(|v| v.path()).collect::<Vec<_>>(),
+            entries.iter().map(|v| v.path()).collect::<Vec<_>>()
+        );
+        assert_eq!(op.stat("not_exist").await.unwrap_err().kind(), err.kind());
+        op.delete("dir/test").await.unwrap();
+        assert_eq!(replay.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_replay_mismatch() {
+        let (op, cassette, layer) = new_recorder();
+
+        op.write("test", "Hello, World!").await.unwrap();
+        layer.save().await.unwrap();
+
+        let op = ReplayService::load(&cassette, "cassette.json")
+            .await
+            .unwrap()
+            .into_operator();
+
+        // Written content differs from the recording.
+        let err = op.write("test", "Hello, Rust!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        // The interaction has been used.
+        let err = op.write("test", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        // No such interaction at all.
+        let err = op.delete("other").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+
+    #[test]
+    fn test_blocking_record_and_replay() {
+        let (op, cassette, layer) = new_recorder();
+        let op = op.blocking();
+
+        op.write("test", "Hello, World!").unwrap();
+        assert_eq!(op.read("test").unwrap(), b"Hello, World!");
+        layer.blocking_save().unwrap();
+
+        let op = ReplayService::blocking_load(&cassette, "cassette.json")
+            .unwrap()
+            .into_operator()
+            .blocking();
+        op.write("test", "Hello, World!").unwrap();
+        assert_eq!(op.read("test").unwrap(), b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_replay_truncated() {
+        let cassette = Operator::new(Memory::default()).unwrap().finish();
+        let layer = RecordLayer::new(cassette.clone(), "cassette.json").with_max_data_size(5);
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .layer(layer.clone());
+
+        op.write("test", "Hello, World!").await.unwrap();
+        assert_eq!(op.read("test").await.unwrap(), b"Hello, World!");
+        assert_eq!(op.read_with("test").range(0..5).await.unwrap(), b"Hello");
+        layer.save().await.unwrap();
+
+        let op = ReplayService::load(&cassette, "cassette.json")
+            .await
+            .unwrap()
+            .into_operator();
+
+        // Only the recorded prefix will be checked.
+        op.write("test", "Hello, Rust!").await.unwrap();
+        // Truncated read can't be replayed.
+        let err = op.read("test").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert_eq!(op.read_with("test").range(0..5).await.unwrap(), b"Hello");
+    }
+}
@@ -21,6 +21,8 @@ use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 
+use super::ReplayService;
+use crate::raw::*;
 use crate::*;
 
 /// TEST_RUNTIME is the runtime used for running tests.
@@ -85,3 +87,56 @@ pub fn init_test_service() -> Result<Option<Operator>> {
 
     Ok(Some(op))
 }
+
+/// Init a replay service from the cassette at `OPENDAL_TEST_REPLAY`.
+///
+/// - Load the cassette from local fs.
+/// - Else, returns a `None` if `OPENDAL_TEST_REPLAY` is not set.
+pub fn init_replay_service() -> Result<Option<Operator>> {
+    let _ = dotenvy::dotenv();
+
+    let path = if let Ok(v) = env::var("OPENDAL_TEST_REPLAY") {
+        v
+    } else {
+        return Ok(None);
+    };
+
+    let bs =
+        std::fs::read(&path).map_err(|err| new_std_io_error(err).with_context("path", &path))?;
+    let op = ReplayService::from_slice(&bs)?
+        .into_operator()
+        .layer(layers::LoggingLayer::default().with_backtrace_output(true));
+
+    Ok(Some(op))
+}
+
+/// Init a record layer which saves the cassette at `OPENDAL_TEST_RECORD`.
+///
+/// - Save the cassette into local fs.
+/// - Else, returns a `None` if `OPENDAL_TEST_RECORD` is not set.
+pub fn init_record_layer() -> Result<Option<layers::RecordLayer>> {
+    let _ = dotenvy::dotenv();
+
+    let path = if let Ok(v) = env::var("OPENDAL_TEST_RECORD") {
+        v
+    } else {
+        return Ok(None);
+    };
+
+    let path = env::current_dir().map_err(new_std_io_error)?.join(path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(
+            Error::new(ErrorKind::ConfigInvalid, "record path must be a file")
+                .with_context("path", path.to_string_lossy()),
+        );
+    };
+
+    let cassette = Operator::via_map(
+        Scheme::Fs,
+        HashMap::from([("root".to_string(), dir.to_string_lossy().to_string())]),
+    )?;
+    Ok(Some(layers::RecordLayer::new(
+        cassette,
+        &name.to_string_lossy(),
+    )))
+}
@@ -321,8 +321,12 @@ impl Accessor for CosBackend {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
                 let size = parse_content_length(resp.headers())?;
                 let range = parse_content_range(resp.headers())?;
+                let etag = parse_etag(resp.headers())?.map(|v| v.to_string());
                 Ok((
-                    RpRead::new().with_size(size).with_range(range),
+                    RpRead::new()
+                        .with_size(size)
+                        .with_range(range)
+                        .with_etag(etag),
                     resp.into_body(),
                 ))
             }
@@ -411,7 +411,11 @@ impl Accessor for GcsBackend {
 
         if resp.status().is_success() {
             let size = parse_content_length(resp.headers())?;
-            Ok((RpRead::new().with_size(size), resp.into_body()))
+            let etag = parse_etag(resp.headers())?.map(|v| v.to_string());
+            Ok((
+                RpRead::new().with_size(size).with_etag(etag),
+                resp.into_body(),
+            ))
         } else if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
             Ok((RpRead::new(), IncomingAsyncBody::empty()))
         } else {
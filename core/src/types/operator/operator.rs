@@ -75,7 +75,7 @@ pub struct Operator {
 
 /// # Operator basic API.
 impl Operator {
-    pub(super) fn inner(&self) -> &FusedAccessor {
+    pub(crate) fn inner(&self) -> &FusedAccessor {
         &self.accessor
     }
 
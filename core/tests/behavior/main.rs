@@ -42,9 +42,15 @@ mod blocking_rename;
 mod blocking_stat;
 mod blocking_write;
 
+// Replay test cases
+mod replay;
+
 // External dependencies
 use libtest_mimic::Arguments;
 use libtest_mimic::Trial;
+use opendal::layers::RecordLayer;
+use opendal::raw::tests::init_record_layer;
+use opendal::raw::tests::init_replay_service;
 use opendal::raw::tests::init_test_service;
 use opendal::raw::tests::TEST_RUNTIME;
 use opendal::*;
@@ -54,12 +60,22 @@ pub static TEST_FIXTURE: Fixture = Fixture::new();
 fn main() -> anyhow::Result<()> {
     let args = Arguments::from_args();
 
+    // Serve the replay cases from the recorded cassette.
+    if let Some(op) = init_replay_service()? {
+        return run_replay(&args, op, None);
+    }
+
     let op = if let Some(op) = init_test_service()? {
         op
     } else {
         return Ok(());
     };
 
+    // Record the replay cases into a new cassette.
+    if let Some(layer) = init_record_layer()? {
+        return run_replay(&args, op.layer(layer.clone()), Some(layer));
+    }
+
     let mut tests = Vec::new();
 
     async_copy::tests(&op, &mut tests);
@@ -82,14 +98,7 @@ fn main() -> anyhow::Result<()> {
     blocking_stat::tests(&op, &mut tests);
     blocking_write::tests(&op, &mut tests);
 
-    // Don't init logging while building operator which may break cargo
-    // nextest output
-    let _ = tracing_subscriber::fmt()
-        .pretty()
-        .with_test_writer()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .try_init();
-
+    init_logging();
     let conclusion = libtest_mimic::run(&args, tests);
 
     // Cleanup the fixtures.
@@ -97,3 +106,27 @@ fn main() -> anyhow::Result<()> {
 
     conclusion.exit()
 }
+
+fn run_replay(args: &Arguments, op: Operator, recorder: Option<RecordLayer>) -> anyhow::Result<()> {
+    let mut tests = Vec::new();
+    replay::tests(&op, &mut tests);
+
+    init_logging();
+    let conclusion = libtest_mimic::run(args, tests);
+
+    if let Some(layer) = recorder {
+        layer.blocking_save()?;
+    }
+
+    conclusion.exit()
+}
+
+/// Don't init logging while building operator which may break cargo
+/// nextest output
+fn init_logging() {
+    let _ = tracing_subscriber::fmt()
+        .pretty()
+        .with_test_writer()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init();
+}
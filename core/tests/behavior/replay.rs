@@ -0,0 +1,94 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use anyhow::Result;
+
+use crate::*;
+
+/// Cases in this module use fixed paths and content so that they can be
+/// recorded by `OPENDAL_TEST_RECORD` and replayed by `OPENDAL_TEST_REPLAY`.
+pub fn tests(op: &Operator, tests: &mut Vec<Trial>) {
+    let cap = op.info().full_capability();
+
+    if cap.read && cap.write && cap.stat && cap.delete {
+        tests.extend(async_trials!(op, test_replay_read_write));
+    }
+
+    if cap.list && cap.write && cap.create_dir && cap.delete {
+        tests.extend(async_trials!(op, test_replay_list));
+    }
+
+    if cap.read && cap.write && cap.stat && cap.delete && cap.blocking {
+        tests.extend(blocking_trials!(op, test_replay_blocking_read_write));
+    }
+}
+
+/// Write, stat, read and delete a fixed file.
+pub async fn test_replay_read_write(op: Operator) -> Result<()> {
+    let path = "replay/async_file";
+
+    op.write(path, "Hello, World!").await?;
+
+    let meta = op.stat(path).await?;
+    assert_eq!(meta.mode(), EntryMode::FILE);
+    assert_eq!(meta.content_length(), 13);
+
+    assert_eq!(op.read(path).await?, b"Hello, World!");
+    assert_eq!(op.read_with(path).range(7..12).await?, b"World");
+
+    op.delete(path).await?;
+    let err = op.stat(path).await.expect_err("stat must fail");
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    Ok(())
+}
+
+/// Create a fixed dir and list the file inside.
+pub async fn test_replay_list(op: Operator) -> Result<()> {
+    let dir = "replay/list/";
+    let path = "replay/list/file";
+
+    op.create_dir(dir).await?;
+    op.write(path, "Hello, World!").await?;
+
+    let entries = op.list(dir).await?;
+    assert_eq!(
+        entries.iter().map(|v| v.path()).collect::<Vec<_>>(),
+        vec![path]
+    );
+
+    op.delete(path).await?;
+    op.delete(dir).await?;
+    Ok(())
+}
+
+/// Write, stat, read and delete a fixed file in blocking way.
+pub fn test_replay_blocking_read_write(op: BlockingOperator) -> Result<()> {
+    let path = "replay/blocking_file";
+
+    op.write(path, "Hello, World!")?;
+
+    let meta = op.stat(path)?;
+    assert_eq!(meta.mode(), EntryMode::FILE);
+    assert_eq!(meta.content_length(), 13);
+
+    assert_eq!(op.read(path)?, b"Hello, World!");
+
+    op.delete(path)?;
+    let err = op.stat(path).expect_err("stat must fail");
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    Ok(())
+}
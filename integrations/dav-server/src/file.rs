@@ -15,40 +15,127 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::fmt::Debug;
+use std::fmt::Formatter;
 use std::io::SeekFrom;
 
+use bytes::Buf;
 use bytes::Bytes;
 use dav_server::davpath::DavPath;
 use dav_server::fs::DavFile;
 use dav_server::fs::DavMetaData;
+use dav_server::fs::FsError;
 use dav_server::fs::FsFuture;
+use dav_server::fs::FsResult;
+use dav_server::fs::OpenOptions;
 use futures::FutureExt;
 use opendal::Operator;
+use opendal::Reader;
+use opendal::Writer;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
 
 use super::metadata::WebdavMetaData;
 
-#[derive(Debug)]
+/// WebdavFile is a file opened by [`crate::OpendalFs`].
+///
+/// - Reads are served by a [`Reader`] created at the first read or seek, which
+///   will seek natively or by range reads based on the service's capability.
+/// - Writes are streamed into a [`Writer`] created while opening, which will be
+///   committed on flush.
 pub struct WebdavFile {
     op: Operator,
     path: DavPath,
+
+    /// Position of the next read or write.
+    pos: u64,
+    reader: Option<Reader>,
+    writer: Option<Writer>,
+}
+
+impl Debug for WebdavFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebdavFile")
+            .field("op", &self.op)
+            .field("path", &self.path)
+            .field("pos", &self.pos)
+            .finish_non_exhaustive()
+    }
 }
 
 impl WebdavFile {
-    pub fn new(op: Operator, path: DavPath) -> Self {
-        Self { op, path }
+    /// Open file at given path with options.
+    pub async fn open(op: Operator, path: DavPath, options: OpenOptions) -> FsResult<Self> {
+        let file_path = path.as_url_string();
+
+        if options.create_new && op.is_exist(&file_path).await.map_err(convert_error)? {
+            return Err(FsError::Exists);
+        }
+
+        let writer = if options.write {
+            let w = op
+                .writer_with(&file_path)
+                .append(options.append && !options.truncate)
+                .await
+                .map_err(convert_error)?;
+            Some(w)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            op,
+            path,
+            pos: 0,
+            reader: None,
+            writer,
+        })
+    }
+
+    async fn reader(&mut self) -> FsResult<&mut Reader> {
+        if self.reader.is_none() {
+            let r = self
+                .op
+                .reader(&self.path.as_url_string())
+                .await
+                .map_err(convert_error)?;
+            self.reader = Some(r);
+        }
+
+        Ok(self.reader.as_mut().expect("reader must be valid"))
+    }
+
+    async fn write(&mut self, buf: Bytes) -> FsResult<()> {
+        let size = buf.len() as u64;
+        let w = self.writer.as_mut().ok_or(FsError::Forbidden)?;
+        w.write(buf).await.map_err(convert_error)?;
+
+        self.pos += size;
+        Ok(())
     }
 }
 
 impl DavFile for WebdavFile {
     fn read_bytes(&mut self, count: usize) -> FsFuture<Bytes> {
         async move {
-            let file_path = self.path.as_url_string();
-            self.op
-                .read_with(&file_path)
-                .range(0..count as u64)
-                .await
-                .map(Bytes::from)
-                .map_err(convert_error)
+            let r = self.reader().await?;
+
+            let mut buf = vec![0; count];
+            let mut n = 0;
+            while n < count {
+                let size = r
+                    .read(&mut buf[n..])
+                    .await
+                    .map_err(|_| FsError::GeneralFailure)?;
+                if size == 0 {
+                    break;
+                }
+                n += size;
+            }
+            buf.truncate(n);
+
+            self.pos += n as u64;
+            Ok(Bytes::from(buf))
         }
         .boxed()
     }
@@ -66,24 +153,45 @@ impl DavFile for WebdavFile {
         .boxed()
     }
 
-    fn write_buf(&mut self, buf: Box<dyn bytes::Buf + Send>) -> FsFuture<()> {
-        self.write_bytes(Bytes::copy_from_slice(buf.chunk()))
+    fn write_buf(&mut self, mut buf: Box<dyn Buf + Send>) -> FsFuture<()> {
+        let bs = buf.copy_to_bytes(buf.remaining());
+        self.write_bytes(bs)
     }
 
     fn write_bytes(&mut self, buf: Bytes) -> FsFuture<()> {
+        async move { self.write(buf).await }.boxed()
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> FsFuture<u64> {
         async move {
-            let file_path = self.path.as_url_string();
-            self.op.write(&file_path, buf).await.map_err(convert_error)
+            // Writer can only append data at current position.
+            if self.writer.is_some() {
+                return match pos {
+                    SeekFrom::Start(n) if n == self.pos => Ok(self.pos),
+                    SeekFrom::Current(0) => Ok(self.pos),
+                    _ => Err(FsError::NotImplemented),
+                };
+            }
+
+            let r = self.reader().await?;
+            let n = r.seek(pos).await.map_err(|_| FsError::GeneralFailure)?;
+
+            self.pos = n;
+            Ok(n)
         }
         .boxed()
     }
 
-    fn seek(&mut self, _pos: SeekFrom) -> FsFuture<u64> {
-        futures_util::future::err(dav_server::fs::FsError::NotImplemented).boxed()
-    }
-
     fn flush(&mut self) -> FsFuture<()> {
-        futures_util::future::ok(()).boxed()
+        async move {
+            // Commit all written data, the file can't be written anymore after flush.
+            if let Some(mut w) = self.writer.take() {
+                w.close().await.map_err(convert_error)?;
+            }
+
+            Ok(())
+        }
+        .boxed()
     }
 }
 
@@ -93,6 +201,8 @@ pub fn convert_error(opendal_error: opendal::Error) -> dav_server::fs::FsError {
             dav_server::fs::FsError::Exists
         }
         opendal::ErrorKind::NotFound => dav_server::fs::FsError::NotFound,
+        opendal::ErrorKind::PermissionDenied => dav_server::fs::FsError::Forbidden,
+        opendal::ErrorKind::Unsupported => dav_server::fs::FsError::NotImplemented,
         _ => dav_server::fs::FsError::GeneralFailure,
     }
 }
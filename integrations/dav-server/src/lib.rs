@@ -19,5 +19,6 @@ mod dir_entry;
 mod file;
 mod metadata;
 mod opendalfs;
+mod props;
 
 pub use opendalfs::OpendalFs;
@@ -15,7 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::future::Future;
 use std::path::Path;
+use std::pin::Pin;
 use std::task::ready;
 use std::task::Poll::Ready;
 
@@ -24,7 +26,10 @@ use dav_server::fs::DavDirEntry;
 use dav_server::fs::DavFile;
 use dav_server::fs::DavFileSystem;
 use dav_server::fs::DavMetaData;
+use dav_server::fs::DavProp;
 use dav_server::fs::FsError;
+use dav_server::fs::FsFuture;
+use dav_server::fs::FsResult;
 use futures::FutureExt;
 use futures_util::Stream;
 use futures_util::StreamExt;
@@ -34,6 +39,9 @@ use opendal::Operator;
 use super::file::convert_error;
 use super::file::WebdavFile;
 use super::metadata::WebdavMetaData;
+use super::props::is_props_path;
+use super::props::DavProps;
+use super::props::PROPS_DIR;
 use crate::dir_entry::WebDAVDirEntry;
 
 #[derive(Clone)]
@@ -45,10 +53,11 @@ impl DavFileSystem for OpendalFs {
     fn open<'a>(
         &'a self,
         path: &'a dav_server::davpath::DavPath,
-        _options: dav_server::fs::OpenOptions,
+        options: dav_server::fs::OpenOptions,
     ) -> dav_server::fs::FsFuture<Box<dyn dav_server::fs::DavFile>> {
         async move {
-            let file = WebdavFile::new(self.op.clone(), path.clone());
+            check_path(path)?;
+            let file = WebdavFile::open(self.op.clone(), path.clone(), options).await?;
             Ok(Box::new(file) as Box<dyn DavFile>)
         }
         .boxed()
@@ -61,6 +70,7 @@ impl DavFileSystem for OpendalFs {
     ) -> dav_server::fs::FsFuture<dav_server::fs::FsStream<Box<dyn dav_server::fs::DavDirEntry>>>
     {
         async move {
+            check_path(path)?;
             self.op
                 .lister(path.as_url_string().as_str())
                 .await
@@ -75,6 +85,7 @@ impl DavFileSystem for OpendalFs {
         path: &'a dav_server::davpath::DavPath,
     ) -> dav_server::fs::FsFuture<Box<dyn dav_server::fs::DavMetaData>> {
         async move {
+            check_path(path)?;
             let opendal_metadata = self.op.stat(path.as_url_string().as_str()).await;
             match opendal_metadata {
                 Ok(metadata) => {
@@ -89,6 +100,7 @@ impl DavFileSystem for OpendalFs {
 
     fn create_dir<'a>(&'a self, path: &'a DavPath) -> dav_server::fs::FsFuture<()> {
         async move {
+            check_path(path)?;
             let path = path.as_url_string();
 
             // check if the parent path is exist.
@@ -128,20 +140,32 @@ impl DavFileSystem for OpendalFs {
 
     fn remove_file<'a>(&'a self, path: &'a DavPath) -> dav_server::fs::FsFuture<()> {
         async move {
+            check_path(path)?;
             self.op
                 .delete(path.as_url_string().as_str())
                 .await
-                .map_err(convert_error)
+                .map_err(convert_error)?;
+            DavProps::remove(&self.op, path).await
         }
         .boxed()
     }
 
     fn remove_dir<'a>(&'a self, path: &'a DavPath) -> dav_server::fs::FsFuture<()> {
-        self.remove_file(path)
+        async move {
+            check_path(path)?;
+            self.op
+                .delete(path.as_url_string().as_str())
+                .await
+                .map_err(convert_error)?;
+            DavProps::remove_all(&self.op, path).await
+        }
+        .boxed()
     }
 
     fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> dav_server::fs::FsFuture<()> {
         async move {
+            check_path(from)?;
+            check_path(to)?;
             let from_path = from
                 .as_rel_ospath()
                 .to_str()
@@ -150,25 +174,78 @@ impl DavFileSystem for OpendalFs {
             self.op
                 .copy(from_path, to_path)
                 .await
-                .map_err(convert_error)
+                .map_err(convert_error)?;
+            if from.is_collection() {
+                DavProps::copy_all(&self.op, from, to).await
+            } else {
+                DavProps::copy(&self.op, from, to).await
+            }
         }
         .boxed()
     }
 
     fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> dav_server::fs::FsFuture<()> {
         async move {
+            check_path(from)?;
+            check_path(to)?;
             let from_path = from
                 .as_rel_ospath()
                 .to_str()
                 .ok_or(FsError::GeneralFailure)?;
             let to_path = to.as_rel_ospath().to_str().ok_or(FsError::GeneralFailure)?;
             if from.is_collection() {
-                let _ = self.remove_file(to).await;
+                let _ = self.remove_dir(to).await;
             }
             self.op
                 .rename(from_path, to_path)
                 .await
-                .map_err(convert_error)
+                .map_err(convert_error)?;
+            if from.is_collection() {
+                DavProps::rename_all(&self.op, from, to).await
+            } else {
+                DavProps::copy(&self.op, from, to).await?;
+                DavProps::remove(&self.op, from).await
+            }
+        }
+        .boxed()
+    }
+
+    fn have_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+    ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        Box::pin(futures::future::ready(!is_props_path(path)))
+    }
+
+    fn patch_props<'a>(
+        &'a self,
+        path: &'a DavPath,
+        patch: Vec<(bool, DavProp)>,
+    ) -> FsFuture<Vec<(http::StatusCode, DavProp)>> {
+        async move {
+            check_path(path)?;
+            let mut props = DavProps::load(&self.op, path).await?;
+            let res = props.patch(patch);
+            props.save(&self.op, path).await?;
+            Ok(res)
+        }
+        .boxed()
+    }
+
+    fn get_props<'a>(&'a self, path: &'a DavPath, do_content: bool) -> FsFuture<Vec<DavProp>> {
+        async move {
+            check_path(path)?;
+            let props = DavProps::load(&self.op, path).await?;
+            Ok(props.props(do_content))
+        }
+        .boxed()
+    }
+
+    fn get_prop<'a>(&'a self, path: &'a DavPath, prop: DavProp) -> FsFuture<Vec<u8>> {
+        async move {
+            check_path(path)?;
+            let props = DavProps::load(&self.op, path).await?;
+            props.prop(&prop).ok_or(FsError::NotFound)
         }
         .boxed()
     }
@@ -180,6 +257,14 @@ impl OpendalFs {
     }
 }
 
+/// Forbid accessing the sidecar objects of dead properties.
+fn check_path(path: &DavPath) -> FsResult<()> {
+    if is_props_path(path) {
+        return Err(FsError::Forbidden);
+    }
+    Ok(())
+}
+
 struct DavStream {
     op: Operator,
     lister: Lister,
@@ -193,12 +278,20 @@ impl Stream for DavStream {
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
         let dav_stream = self.get_mut();
-        match ready!(dav_stream.lister.poll_next_unpin(cx)) {
-            Some(entry) => {
-                let webdav_entry = WebDAVDirEntry::new(entry.unwrap(), dav_stream.op.clone());
-                Ready(Some(Box::new(webdav_entry) as Box<dyn DavDirEntry>))
+        loop {
+            match ready!(dav_stream.lister.poll_next_unpin(cx)) {
+                Some(entry) => {
+                    let entry = entry.unwrap();
+                    // Skip the sidecar objects of dead properties.
+                    if entry.path().starts_with(PROPS_DIR) {
+                        continue;
+                    }
+
+                    let webdav_entry = WebDAVDirEntry::new(entry, dav_stream.op.clone());
+                    return Ready(Some(Box::new(webdav_entry) as Box<dyn DavDirEntry>));
+                }
+                None => return Ready(None),
             }
-            None => Ready(None),
         }
     }
 }
@@ -0,0 +1,215 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use dav_server::davpath::DavPath;
+use dav_server::fs::DavProp;
+use dav_server::fs::FsError;
+use dav_server::fs::FsResult;
+use futures::TryStreamExt;
+use http::StatusCode;
+use opendal::ErrorKind;
+use opendal::Operator;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::file::convert_error;
+
+/// Dead properties set by `PROPPATCH` are stored as XML in a sidecar object
+/// under this dir, for example, props of `/a/b.txt` are stored in
+/// `.davprops/a/b.txt.xml`.
+///
+/// OpenDAL can't read or write user metadata of files yet, so props can't be
+/// stored along with the files. Props of children are stored under the dir
+/// of their parent, and will be copied, moved or removed with the parent.
+///
+/// This dir is hidden from clients, any access to it will be forbidden.
+pub const PROPS_DIR: &str = ".davprops/";
+
+/// Check if given path is inside [`PROPS_DIR`].
+pub fn is_props_path(path: &DavPath) -> bool {
+    path.as_rel_ospath()
+        .components()
+        .next()
+        .map_or(false, |v| v.as_os_str() == PROPS_DIR.trim_end_matches('/'))
+}
+
+/// DavProps is all dead properties of a file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename = "props")]
+pub struct DavProps {
+    #[serde(default, rename = "prop")]
+    props: Vec<StoredProp>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredProp {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xml: Option<String>,
+}
+
+impl StoredProp {
+    fn is(&self, prop: &DavProp) -> bool {
+        self.name == prop.name && self.namespace == prop.namespace
+    }
+
+    fn to_dav_prop(&self, do_content: bool) -> DavProp {
+        DavProp {
+            name: self.name.clone(),
+            prefix: self.prefix.clone(),
+            namespace: self.namespace.clone(),
+            xml: match do_content {
+                true => self.xml.clone().map(String::into_bytes),
+                false => None,
+            },
+        }
+    }
+}
+
+impl DavProps {
+    /// Load the properties of given path, returns empty properties if not set.
+    pub async fn load(op: &Operator, path: &DavPath) -> FsResult<Self> {
+        match op.read(&props_path(path)).await {
+            Ok(bs) => {
+                let s = String::from_utf8(bs).map_err(|_| FsError::GeneralFailure)?;
+                quick_xml::de::from_str(&s).map_err(|_| FsError::GeneralFailure)
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(convert_error(err)),
+        }
+    }
+
+    /// Save the properties of given path, the sidecar object will be removed if empty.
+    pub async fn save(&self, op: &Operator, path: &DavPath) -> FsResult<()> {
+        let props_path = props_path(path);
+        if self.props.is_empty() {
+            return op.delete(&props_path).await.map_err(convert_error);
+        }
+
+        let s = quick_xml::se::to_string(self).map_err(|_| FsError::GeneralFailure)?;
+        op.write(&props_path, s).await.map_err(convert_error)
+    }
+
+    /// Copy the properties from one path to another, used while copying or renaming files.
+    pub async fn copy(op: &Operator, from: &DavPath, to: &DavPath) -> FsResult<()> {
+        Self::load(op, from).await?.save(op, to).await
+    }
+
+    /// Remove the properties of given path, used while removing files.
+    pub async fn remove(op: &Operator, path: &DavPath) -> FsResult<()> {
+        Self::default().save(op, path).await
+    }
+
+    /// Copy the properties of given dir and all its children, used while
+    /// copying dirs. Properties of the target's children will be replaced.
+    pub async fn copy_all(op: &Operator, from: &DavPath, to: &DavPath) -> FsResult<()> {
+        Self::copy(op, from, to).await?;
+
+        let (from_dir, to_dir) = (props_dir(from), props_dir(to));
+        op.remove_all(&to_dir).await.map_err(convert_error)?;
+
+        let mut lister = match op.lister_with(&from_dir).recursive(true).await {
+            Ok(v) => v,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(convert_error(err)),
+        };
+        while let Some(entry) = lister.try_next().await.map_err(convert_error)? {
+            let Some(rel) = entry.path().strip_prefix(&from_dir) else {
+                continue;
+            };
+            if rel.is_empty() || rel.ends_with('/') {
+                continue;
+            }
+
+            let bs = op.read(entry.path()).await.map_err(convert_error)?;
+            op.write(&format!("{to_dir}{rel}"), bs)
+                .await
+                .map_err(convert_error)?;
+        }
+        Ok(())
+    }
+
+    /// Move the properties of given dir and all its children, used while
+    /// renaming dirs.
+    pub async fn rename_all(op: &Operator, from: &DavPath, to: &DavPath) -> FsResult<()> {
+        Self::copy_all(op, from, to).await?;
+        Self::remove_all(op, from).await
+    }
+
+    /// Remove the properties of given dir and all its children, used while
+    /// removing dirs.
+    pub async fn remove_all(op: &Operator, path: &DavPath) -> FsResult<()> {
+        Self::remove(op, path).await?;
+        op.remove_all(&props_dir(path)).await.map_err(convert_error)
+    }
+
+    /// Apply the patch, `true` means set the property and `false` means remove it.
+    pub fn patch(&mut self, patch: Vec<(bool, DavProp)>) -> Vec<(StatusCode, DavProp)> {
+        patch
+            .into_iter()
+            .map(|(set, prop)| {
+                self.props.retain(|p| !p.is(&prop));
+                if set {
+                    self.props.push(StoredProp {
+                        name: prop.name.clone(),
+                        prefix: prop.prefix.clone(),
+                        namespace: prop.namespace.clone(),
+                        xml: prop
+                            .xml
+                            .as_ref()
+                            .map(|v| String::from_utf8_lossy(v).to_string()),
+                    });
+                }
+
+                let prop = DavProp { xml: None, ..prop };
+                (StatusCode::OK, prop)
+            })
+            .collect()
+    }
+
+    /// Get all properties, the content will be returned only if `do_content` is true.
+    pub fn props(&self, do_content: bool) -> Vec<DavProp> {
+        self.props
+            .iter()
+            .map(|p| p.to_dav_prop(do_content))
+            .collect()
+    }
+
+    /// Get the content of given property.
+    pub fn prop(&self, prop: &DavProp) -> Option<Vec<u8>> {
+        self.props
+            .iter()
+            .find(|p| p.is(prop))
+            .and_then(|p| p.xml.clone())
+            .map(String::into_bytes)
+    }
+}
+
+fn props_path(path: &DavPath) -> String {
+    let path = path.as_rel_ospath().to_string_lossy().to_string();
+    format!("{PROPS_DIR}{}.xml", path.trim_end_matches('/'))
+}
+
+/// The dir that contains the props of given dir's children.
+fn props_dir(path: &DavPath) -> String {
+    let path = path.as_rel_ospath().to_string_lossy().to_string();
+    format!("{PROPS_DIR}{}/", path.trim_end_matches('/'))
+}
@@ -15,11 +15,19 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::io::SeekFrom;
+
 use anyhow::Result;
+use bytes::Bytes;
 use dav_server::davpath::DavPath;
 use dav_server::fs::DavFileSystem;
+use dav_server::fs::DavProp;
+use dav_server::fs::OpenOptions;
+use dav_server::fs::ReadDirMeta;
 use dav_server_opendalfs::OpendalFs;
+use futures::StreamExt;
 use opendal::services::Fs;
+use opendal::services::Memory;
 use opendal::Operator;
 
 #[tokio::test]
@@ -39,3 +47,167 @@ async fn test() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_write_and_seek() -> Result<()> {
+    let op = Operator::new(Memory::default())?.finish();
+    let webdavfs = OpendalFs::new(op.clone());
+    let path = DavPath::new("/test.txt").unwrap();
+
+    let mut file = webdavfs.open(&path, OpenOptions::write()).await.unwrap();
+    file.write_bytes(Bytes::from("Hello, ")).await.unwrap();
+    file.write_bytes(Bytes::from("World!")).await.unwrap();
+    file.flush().await.unwrap();
+    assert_eq!(op.read("test.txt").await?, b"Hello, World!");
+
+    let mut file = webdavfs.open(&path, OpenOptions::read()).await.unwrap();
+    assert_eq!(file.seek(SeekFrom::Start(7)).await.unwrap(), 7);
+    assert_eq!(file.read_bytes(5).await.unwrap(), Bytes::from("World"));
+    assert_eq!(file.seek(SeekFrom::End(-6)).await.unwrap(), 7);
+    assert_eq!(file.read_bytes(16).await.unwrap(), Bytes::from("World!"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_props() -> Result<()> {
+    let op = Operator::new(Memory::default())?.finish();
+    let webdavfs = OpendalFs::new(op.clone());
+    let path = DavPath::new("/test.txt").unwrap();
+    op.write("test.txt", "Hello, World!").await?;
+
+    let prop = DavProp {
+        name: "author".to_string(),
+        prefix: Some("D".to_string()),
+        namespace: Some("DAV:".to_string()),
+        xml: Some(b"<D:author>opendal</D:author>".to_vec()),
+    };
+    let res = webdavfs
+        .patch_props(&path, vec![(true, prop.clone())])
+        .await
+        .unwrap();
+    assert_eq!(res.len(), 1);
+
+    assert_eq!(
+        webdavfs.get_prop(&path, prop.clone()).await.unwrap(),
+        b"<D:author>opendal</D:author>"
+    );
+    let props = webdavfs.get_props(&path, false).await.unwrap();
+    assert_eq!(props.len(), 1);
+    assert_eq!(props[0].name, "author");
+
+    // Sidecar objects of dead properties should not be listed.
+    let entries: Vec<_> = webdavfs
+        .read_dir(&DavPath::new("/").unwrap(), ReadDirMeta::None)
+        .await
+        .unwrap()
+        .map(|entry| String::from_utf8(entry.name()).unwrap())
+        .collect()
+        .await;
+    assert_eq!(entries, vec!["test.txt"]);
+
+    webdavfs
+        .patch_props(&path, vec![(false, prop.clone())])
+        .await
+        .unwrap();
+    assert!(webdavfs.get_prop(&path, prop).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_props_dir_is_hidden() -> Result<()> {
+    let op = Operator::new(Memory::default())?.finish();
+    let webdavfs = OpendalFs::new(op.clone());
+    let prop = DavProp {
+        name: "author".to_string(),
+        prefix: None,
+        namespace: None,
+        xml: Some(b"<author>opendal</author>".to_vec()),
+    };
+
+    op.create_dir("dir/").await?;
+    op.write("dir/test.txt", "Hello, World!").await?;
+    for path in ["/dir/", "/dir/test.txt"] {
+        webdavfs
+            .patch_props(&DavPath::new(path).unwrap(), vec![(true, prop.clone())])
+            .await
+            .unwrap();
+    }
+
+    // Sidecar objects can't be accessed by clients.
+    let props_path = DavPath::new("/.davprops/dir/test.txt.xml").unwrap();
+    assert!(webdavfs.metadata(&props_path).await.is_err());
+    assert!(webdavfs
+        .open(&props_path, OpenOptions::read())
+        .await
+        .is_err());
+    assert!(webdavfs.remove_file(&props_path).await.is_err());
+    assert!(webdavfs.get_props(&props_path, true).await.is_err());
+    assert!(!webdavfs.have_props(&props_path).await);
+    assert!(webdavfs
+        .read_dir(&DavPath::new("/.davprops/").unwrap(), ReadDirMeta::None)
+        .await
+        .is_err());
+
+    // Sidecar objects of the dir and its children will be removed with the dir.
+    webdavfs
+        .remove_file(&DavPath::new("/dir/test.txt").unwrap())
+        .await
+        .unwrap();
+    webdavfs
+        .remove_dir(&DavPath::new("/dir/").unwrap())
+        .await
+        .unwrap();
+    assert!(op.list_with(".davprops/").recursive(true).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_props_move_with_dir() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("dav-test-props-{}", std::process::id()));
+    let mut builder = Fs::default();
+    builder.root(root.to_str().unwrap());
+    let op = Operator::new(builder)?.finish();
+    let webdavfs = OpendalFs::new(op.clone());
+    let prop = DavProp {
+        name: "author".to_string(),
+        prefix: None,
+        namespace: None,
+        xml: Some(b"<author>opendal</author>".to_vec()),
+    };
+
+    op.write("dir/sub/test.txt", "Hello, World!").await?;
+    for path in ["/dir/", "/dir/sub/test.txt"] {
+        webdavfs
+            .patch_props(&DavPath::new(path).unwrap(), vec![(true, prop.clone())])
+            .await
+            .unwrap();
+    }
+
+    // Props of the dir and its children are moved along with the dir.
+    webdavfs
+        .rename(
+            &DavPath::new("/dir/").unwrap(),
+            &DavPath::new("/moved/").unwrap(),
+        )
+        .await
+        .unwrap();
+    for path in ["/moved/", "/moved/sub/test.txt"] {
+        let props = webdavfs
+            .get_props(&DavPath::new(path).unwrap(), false)
+            .await
+            .unwrap();
+        assert_eq!(props.len(), 1);
+    }
+    assert!(!op.is_exist(".davprops/dir.xml").await?);
+    assert!(op
+        .list_with(".davprops/dir/")
+        .recursive(true)
+        .await?
+        .is_empty());
+
+    std::fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
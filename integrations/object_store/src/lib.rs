@@ -15,14 +15,23 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io;
+use std::mem;
 use std::ops::Range;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::future::BoxFuture;
 use futures::stream::BoxStream;
+use futures::FutureExt;
 use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
@@ -40,17 +49,51 @@ use opendal::Metadata;
 use opendal::Metakey;
 use opendal::Operator;
 use opendal::Reader;
+use opendal::Writer;
 use tokio::io::AsyncWrite;
 
-#[derive(Debug)]
 pub struct OpendalStore {
     inner: Operator,
+
+    write_buffer: usize,
+    write_concurrent: usize,
+    next_multipart_id: AtomicU64,
 }
 
 impl OpendalStore {
     /// Create OpendalStore by given Operator.
     pub fn new(op: Operator) -> Self {
-        Self { inner: op }
+        Self {
+            inner: op,
+
+            write_buffer: 8 * 1024 * 1024,
+            write_concurrent: 8,
+            next_multipart_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the buffer size of writers returned by `put_multipart`.
+    ///
+    /// Default to 8 MiB. Services could alter the buffer size to meet their requirements.
+    pub fn with_write_buffer(mut self, size: usize) -> Self {
+        self.write_buffer = size;
+        self
+    }
+
+    /// Set the maximum concurrent write tasks of writers returned by `put_multipart`.
+    ///
+    /// Default to 8.
+    pub fn with_write_concurrent(mut self, concurrent: usize) -> Self {
+        self.write_concurrent = concurrent;
+        self
+    }
+}
+
+impl Debug for OpendalStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpendalStore")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
     }
 }
 
@@ -70,42 +113,57 @@ impl ObjectStore for OpendalStore {
             .map_err(|err| format_object_store_error(err, location.as_ref()))?)
     }
 
+    /// Start a multipart upload.
+    ///
+    /// Data will be streamed into [`Writer`] with `concurrent` enabled if the service
+    /// can write multiple chunks. Otherwise, data will be buffered in memory and written
+    /// at once while shutting down.
     async fn put_multipart(
         &self,
-        _location: &Path,
+        location: &Path,
     ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
-        Err(object_store::Error::NotSupported {
-            source: Box::new(opendal::Error::new(
-                opendal::ErrorKind::Unsupported,
-                "put_multipart is not implemented so far",
-            )),
-        })
-    }
+        let path = location.as_ref();
+        let id = self
+            .next_multipart_id
+            .fetch_add(1, Ordering::Relaxed)
+            .to_string();
+
+        let state = if self.inner.info().full_capability().write_can_multi {
+            let w = self
+                .inner
+                .writer_with(path)
+                .buffer(self.write_buffer)
+                .concurrent(self.write_concurrent)
+                .await
+                .map_err(|err| format_object_store_error(err, path))?;
+            MultipartState::Writer(w)
+        } else {
+            MultipartState::Buffer {
+                op: self.inner.clone(),
+                path: path.to_string(),
+                buf: Vec::new(),
+            }
+        };
 
-    async fn abort_multipart(&self, _location: &Path, _multipart_id: &MultipartId) -> Result<()> {
-        Err(object_store::Error::NotSupported {
-            source: Box::new(opendal::Error::new(
-                opendal::ErrorKind::Unsupported,
-                "abort_multipart is not implemented so far",
-            )),
-        })
+        Ok((id, Box::new(OpendalMultipartWriter { state })))
     }
 
-    async fn get_opts(&self, _location: &Path, _options: GetOptions) -> Result<GetResult> {
-        Err(object_store::Error::NotSupported {
-            source: Box::new(opendal::Error::new(
-                opendal::ErrorKind::Unsupported,
-                "get_opts is not implemented so far",
-            )),
-        })
+    /// Abort a multipart upload.
+    ///
+    /// It's a no-op since the upload is bound to its writer: a writer dropped
+    /// without shutdown will abort the upload in background if there is a tokio
+    /// runtime, so that no buffer is kept after the writer is gone.
+    async fn abort_multipart(&self, _: &Path, _: &MultipartId) -> Result<()> {
+        Ok(())
     }
 
-    async fn get(&self, location: &Path) -> Result<GetResult> {
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        let path = location.as_ref();
         let meta = self
             .inner
-            .stat(location.as_ref())
+            .stat(path)
             .await
-            .map_err(|err| format_object_store_error(err, location.as_ref()))?;
+            .map_err(|err| format_object_store_error(err, path))?;
 
         let meta = ObjectMeta {
             location: location.clone(),
@@ -113,19 +171,47 @@ impl ObjectStore for OpendalStore {
             size: meta.content_length() as usize,
             e_tag: meta.etag().map(|x| x.to_string()),
         };
-        let r = self
+        check_get_options(&options, &meta)?;
+
+        let range = match options.range {
+            Some(range) => range.start..range.end.min(meta.size),
+            None => 0..meta.size,
+        };
+        if range.start > range.end {
+            return Err(format_object_store_error(
+                opendal::Error::new(
+                    opendal::ErrorKind::InvalidInput,
+                    &format!("range {:?} is out of object size {}", range, meta.size),
+                ),
+                path,
+            ));
+        }
+
+        let mut fut = self
             .inner
-            .reader(location.as_ref())
+            .reader_with(path)
+            .range(range.start as u64..range.end as u64);
+        // Make sure that we are reading the object that we have checked.
+        if let Some(etag) = &meta.e_tag {
+            if self.inner.info().full_capability().read_with_if_match {
+                fut = fut.if_match(etag);
+            }
+        }
+        let r = fut
             .await
-            .map_err(|err| format_object_store_error(err, location.as_ref()))?;
+            .map_err(|err| format_object_store_error(err, path))?;
 
         Ok(GetResult {
             payload: GetResultPayload::Stream(Box::pin(OpendalReader { inner: r })),
-            range: (0..meta.size),
+            range,
             meta,
         })
     }
 
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        self.get_opts(location, GetOptions::default()).await
+    }
+
     async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
         let bs = self
             .inner
@@ -148,7 +234,7 @@ impl ObjectStore for OpendalStore {
             location: location.clone(),
             last_modified: meta.last_modified().unwrap_or_default(),
             size: meta.content_length() as usize,
-            e_tag: None,
+            e_tag: meta.etag().map(|x| x.to_string()),
         })
     }
 
@@ -243,29 +329,52 @@ impl ObjectStore for OpendalStore {
         })
     }
 
-    async fn copy(&self, _from: &Path, _to: &Path) -> Result<()> {
-        Err(object_store::Error::NotSupported {
-            source: Box::new(opendal::Error::new(
-                opendal::ErrorKind::Unsupported,
-                "copy is not implemented so far",
-            )),
-        })
+    /// Copy an object, fallback to read and write if the service doesn't support copy.
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        if self.inner.info().full_capability().copy {
+            self.inner
+                .copy(from.as_ref(), to.as_ref())
+                .await
+                .map_err(|err| format_object_store_error(err, from.as_ref()))?;
+        } else {
+            let bs = self
+                .inner
+                .read(from.as_ref())
+                .await
+                .map_err(|err| format_object_store_error(err, from.as_ref()))?;
+            self.inner
+                .write(to.as_ref(), bs)
+                .await
+                .map_err(|err| format_object_store_error(err, to.as_ref()))?;
+        }
+
+        Ok(())
     }
 
-    async fn rename(&self, _from: &Path, _to: &Path) -> Result<()> {
-        Err(object_store::Error::NotSupported {
-            source: Box::new(opendal::Error::new(
-                opendal::ErrorKind::Unsupported,
-                "rename is not implemented so far",
-            )),
-        })
+    /// Rename an object, fallback to copy and delete if the service doesn't support rename.
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        if self.inner.info().full_capability().rename {
+            self.inner
+                .rename(from.as_ref(), to.as_ref())
+                .await
+                .map_err(|err| format_object_store_error(err, from.as_ref()))?;
+        } else {
+            self.copy(from, to).await?;
+            self.delete(from).await?;
+        }
+
+        Ok(())
     }
 
-    async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> Result<()> {
+    /// Copy an object only if the destination doesn't exist.
+    ///
+    /// Not supported: OpenDAL can't copy atomically with a condition on the
+    /// destination, and checking before copying is racy.
+    async fn copy_if_not_exists(&self, _: &Path, _: &Path) -> Result<()> {
         Err(object_store::Error::NotSupported {
             source: Box::new(opendal::Error::new(
                 opendal::ErrorKind::Unsupported,
-                "copy_if_not_exists is not implemented so far",
+                "copy_if_not_exists is not supported",
             )),
         })
     }
@@ -285,6 +394,10 @@ fn format_object_store_error(err: opendal::Error, path: &str) -> object_store::E
             path: path.to_string(),
             source: Box::new(err),
         },
+        ErrorKind::ConditionNotMatch => object_store::Error::Precondition {
+            path: path.to_string(),
+            source: Box::new(err),
+        },
         kind => object_store::Error::Generic {
             store: kind.into_static(),
             source: Box::new(err),
@@ -301,6 +414,65 @@ fn format_object_meta(path: &str, meta: &Metadata) -> ObjectMeta {
     }
 }
 
+/// Check the conditions of [`GetOptions`] against the object meta.
+fn check_get_options(options: &GetOptions, meta: &ObjectMeta) -> Result<()> {
+    let path = meta.location.as_ref();
+    let precondition = |msg: &str| object_store::Error::Precondition {
+        path: path.to_string(),
+        source: Box::new(opendal::Error::new(
+            opendal::ErrorKind::ConditionNotMatch,
+            msg,
+        )),
+    };
+    let not_modified = |msg: &str| object_store::Error::NotModified {
+        path: path.to_string(),
+        source: Box::new(opendal::Error::new(
+            opendal::ErrorKind::ConditionNotMatch,
+            msg,
+        )),
+    };
+
+    if options.if_match.is_some() || options.if_none_match.is_some() {
+        let etag = meta.e_tag.as_deref().ok_or_else(|| {
+            format_object_store_error(
+                opendal::Error::new(
+                    opendal::ErrorKind::Unsupported,
+                    "etag is not returned by service, if_match and if_none_match are not supported",
+                ),
+                path,
+            )
+        })?;
+
+        if let Some(v) = &options.if_match {
+            if v != etag && v != "*" {
+                return Err(precondition("etag doesn't match if_match"));
+            }
+        }
+        if let Some(v) = &options.if_none_match {
+            if v == etag || v == "*" {
+                return Err(not_modified("etag matches if_none_match"));
+            }
+        }
+    }
+
+    if let Some(date) = options.if_unmodified_since {
+        if meta.last_modified > date {
+            return Err(precondition(
+                "object has been modified since if_unmodified_since",
+            ));
+        }
+    }
+    if let Some(date) = options.if_modified_since {
+        if meta.last_modified <= date {
+            return Err(not_modified(
+                "object has not been modified since if_modified_since",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 async fn try_format_object_meta(res: Result<Entry, opendal::Error>) -> Result<ObjectMeta> {
     let entry = res.map_err(|err| format_object_store_error(err, ""))?;
     let meta = entry.metadata();
@@ -327,6 +499,97 @@ impl Stream for OpendalReader {
     }
 }
 
+/// Abort the writer in background if we are inside a tokio runtime.
+fn abort_in_background(mut w: Writer) {
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(async move {
+            let _ = w.abort().await;
+        });
+    }
+}
+
+/// OpendalMultipartWriter is the writer returned by `put_multipart`.
+struct OpendalMultipartWriter {
+    state: MultipartState,
+}
+
+enum MultipartState {
+    /// Data will be streamed into the writer.
+    Writer(Writer),
+    /// Data will be buffered in memory and written at once while shutting down.
+    ///
+    /// Used by services that can't write multiple chunks.
+    Buffer {
+        op: Operator,
+        path: String,
+        buf: Vec<u8>,
+    },
+    Closing(BoxFuture<'static, opendal::Result<()>>),
+    Closed,
+}
+
+impl AsyncWrite for OpendalMultipartWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.state {
+            MultipartState::Writer(w) => Pin::new(w).poll_write(cx, buf),
+            MultipartState::Buffer { buf: b, .. } => {
+                b.extend_from_slice(buf);
+                Poll::Ready(Ok(buf.len()))
+            }
+            MultipartState::Closing(_) | MultipartState::Closed => Poll::Ready(Err(
+                io::Error::new(io::ErrorKind::Other, "writer has been closed"),
+            )),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.state {
+            MultipartState::Writer(w) => Pin::new(w).poll_flush(cx),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                MultipartState::Writer(w) => {
+                    ready!(Pin::new(w).poll_shutdown(cx))?;
+                    self.state = MultipartState::Closed;
+                }
+                MultipartState::Buffer { .. } => {
+                    if let MultipartState::Buffer { op, path, buf } =
+                        mem::replace(&mut self.state, MultipartState::Closed)
+                    {
+                        let fut = async move { op.write(&path, buf).await };
+                        self.state = MultipartState::Closing(fut.boxed());
+                    }
+                }
+                MultipartState::Closing(fut) => {
+                    let res = ready!(fut.poll_unpin(cx));
+                    self.state = MultipartState::Closed;
+                    return Poll::Ready(
+                        res.map_err(|err| io::Error::new(io::ErrorKind::Other, err)),
+                    );
+                }
+                MultipartState::Closed => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+impl Drop for OpendalMultipartWriter {
+    fn drop(&mut self) {
+        // Abort the unfinished upload so that its parts and buffer won't be kept.
+        if let MultipartState::Writer(w) = mem::replace(&mut self.state, MultipartState::Closed) {
+            abort_in_background(w);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -334,6 +597,7 @@ mod tests {
     use object_store::path::Path;
     use object_store::ObjectStore;
     use opendal::services;
+    use tokio::io::AsyncWriteExt;
 
     use super::*;
 
@@ -451,4 +715,101 @@ mod tests {
             "data/test.txt"
         );
     }
+
+    #[tokio::test]
+    async fn test_put_multipart() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let object_store: Arc<dyn ObjectStore> = Arc::new(OpendalStore::new(op));
+
+        let path: Path = "data/multipart.txt".into();
+        let (_, mut w) = object_store.put_multipart(&path).await.unwrap();
+        w.write_all(b"hello, ").await.unwrap();
+        w.write_all(b"world!").await.unwrap();
+        w.shutdown().await.unwrap();
+
+        assert_eq!(
+            object_store
+                .get(&path)
+                .await
+                .unwrap()
+                .bytes()
+                .await
+                .unwrap(),
+            Bytes::from_static(b"hello, world!")
+        );
+
+        // Aborting a finished upload is a no-op.
+        let (id, _) = object_store.put_multipart(&path).await.unwrap();
+        object_store.abort_multipart(&path, &id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_multipart_dropped() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let object_store: Arc<dyn ObjectStore> = Arc::new(OpendalStore::new(op));
+
+        let path: Path = "data/dropped.txt".into();
+        let (id, mut w) = object_store.put_multipart(&path).await.unwrap();
+        w.write_all(b"hello").await.unwrap();
+        drop(w);
+        object_store.abort_multipart(&path, &id).await.unwrap();
+
+        let err = object_store.head(&path).await.unwrap_err();
+        assert!(matches!(err, object_store::Error::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_get_opts() {
+        let object_store = create_test_object_store().await;
+        let path: Path = "data/test.txt".into();
+        let meta = object_store.head(&path).await.unwrap();
+
+        let result = object_store
+            .get_opts(
+                &path,
+                GetOptions {
+                    range: Some(7..12),
+                    if_unmodified_since: Some(meta.last_modified),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.range, 7..12);
+        assert_eq!(result.bytes().await.unwrap(), Bytes::from_static(b"world"));
+
+        let err = object_store
+            .get_opts(
+                &path,
+                GetOptions {
+                    if_modified_since: Some(meta.last_modified),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::NotModified { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_copy_and_rename() {
+        let object_store = create_test_object_store().await;
+        let from: Path = "data/test.txt".into();
+        let to: Path = "data/copied.txt".into();
+
+        object_store.copy(&from, &to).await.unwrap();
+        assert_eq!(object_store.head(&to).await.unwrap().size, 13);
+
+        let err = object_store
+            .copy_if_not_exists(&from, &to)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::NotSupported { .. }));
+
+        let renamed: Path = "data/renamed.txt".into();
+        object_store.rename(&to, &renamed).await.unwrap();
+        assert_eq!(object_store.head(&renamed).await.unwrap().size, 13);
+        let err = object_store.head(&to).await.unwrap_err();
+        assert!(matches!(err, object_store::Error::NotFound { .. }));
+    }
 }